@@ -1,10 +1,21 @@
 use anyhow::Result;
-use crossterm::event::{KeyCode, KeyEvent};
-use std::time::Instant;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use ratatui::layout::{Position, Rect};
+use std::cell::Cell;
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
 
 use crate::config::{AppConfig, NetworkRule, TunnelInfo};
+use crate::config::history::{self, EventKind, HistoryEvent};
+use crate::keymap::{Action, KeyMap};
+use crate::theme::{Theme, ThemeStamp};
 use crate::network::{NetworkInfo, ConnectivityStatus};
-use crate::vpn::wireguard::{WgProfile, WgStatus, VpnHealthCheck};
+use crate::notify::{self, Notice};
+use crate::vpn::fastest::EndpointLatency;
+use crate::vpn::lint::Finding;
+use crate::vpn::mtu::MtuProbe;
+use crate::vpn::quota;
+use crate::vpn::wireguard::{ProfileSource, RoutingInfo, TunnelScope, WgProfile, WgStatus, VpnHealthCheck};
 
 /// Pending configuration change that will be applied after countdown
 #[derive(Debug, Clone)]
@@ -22,12 +33,306 @@ pub enum PendingAction {
     Connect,          // Connect to tunnel
     Disconnect,       // Disconnect from tunnel
     Reconnect,        // Disconnect then connect (tunnel changed)
+    DisconnectTunnel, // Disconnect just this tunnel, others stay up
     KillSwitchOn,     // Enable kill switch
     KillSwitchOff,    // Disable kill switch
+    Undo {            // Put back the state from before the last change
+        state: Box<UndoState>,
+        take_down: Option<String>,  // Tunnel the undone change brought up
+    },
+    Restart {         // Take one tunnel down and up again so an edited config applies
+        interface: String,
+    },
+    TestConnect {     // Trial connect for a connection test
+        previous: Option<String>,   // Primary tunnel it replaces until the rollback
+    },
+    TestRollBack {    // End a connection test and bring `previous` back
+        previous: Option<String>,
+        message: String,            // Status to show once rolled back
+    },
 }
 
-/// Countdown duration in seconds before applying changes
-const COUNTDOWN_SECONDS: u64 = 4;
+/// What a network rule does when its network is connected
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RuleMode {
+    None,
+    Always,
+    Never,
+    Session,
+}
+
+impl RuleMode {
+    const ALL: [RuleMode; 4] = [RuleMode::None, RuleMode::Always, RuleMode::Never, RuleMode::Session];
+
+    fn of(rule: Option<&NetworkRule>) -> Self {
+        match rule {
+            Some(r) if r.always_vpn => RuleMode::Always,
+            Some(r) if r.never_vpn => RuleMode::Never,
+            Some(r) if r.session_vpn => RuleMode::Session,
+            _ => RuleMode::None,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            RuleMode::None => "No rule",
+            RuleMode::Always => "Always",
+            RuleMode::Never => "Never",
+            RuleMode::Session => "Session",
+        }
+    }
+
+    /// Brings a tunnel up when its network connects
+    fn connects(self) -> bool {
+        matches!(self, RuleMode::Always | RuleMode::Session)
+    }
+}
+
+/// Rows of the rule editor: rule, tunnel, priority, DNS servers, kill switch, open WiFi
+pub const RULE_FORM_FIELDS: usize = 6;
+
+/// Every setting of one network's rule, edited together (Enter in the Networks section)
+#[derive(Debug, Clone)]
+pub struct RuleForm {
+    pub network_id: String,
+    pub network_name: String,
+    pub mode: RuleMode,
+    pub tunnel: Option<String>,      // None = `default_profile`
+    pub priority: Option<u32>,       // None = lowest
+    pub dns: String,                 // Comma-separated while editing, empty = the tunnel's own DNS
+    pub kill_switch: Option<bool>,   // None = the tunnel's own setting
+    pub match_open_networks: bool,
+    pub field: usize,                // Highlighted row
+}
+
+/// The option after (or before) `current` in `options`, wrapping around
+fn cycle_option<T: Clone + PartialEq>(options: &[T], current: &T, forward: bool) -> T {
+    let index = options.iter().position(|o| o == current).unwrap_or(0);
+    let next = if forward { index + 1 } else { index + options.len() - 1 };
+    options[next % options.len()].clone()
+}
+
+/// VPN state from before the last applied change, restored with `u`
+#[derive(Debug, Clone, PartialEq)]
+pub struct UndoState {
+    pub action: PendingAction,   // The change that was applied
+    pub tunnel: Option<String>,  // Primary tunnel that was up before it
+    pub kill_switch: bool,       // Kill switch state before it
+}
+
+/// An applied change whose helper calls are still running
+struct RunningChange {
+    change: PendingChange,
+    before: UndoState,
+    warnings: Vec<String>,  // Preflight warnings to show once connected
+    started: Instant,
+    task: tokio::task::JoinHandle<ChangeOutcome>,
+}
+
+/// What the helper calls behind a change did
+struct ChangeOutcome {
+    result: Result<()>,
+    kill_switch: bool,    // Kill switch state afterwards
+    disconnected: bool,   // The previous tunnel went down
+}
+
+/// Connect, disconnect or toggle the kill switch without touching `App`
+/// `drop_kill_switch` is set when the primary interface goes away
+async fn run_change(
+    action: PendingAction,
+    tunnel: Option<String>,
+    kill_switch: bool,
+    drop_kill_switch: bool,
+    tunnel_ks: bool,
+) -> ChangeOutcome {
+    use crate::vpn::{killswitch, wireguard};
+
+    let mut outcome = ChangeOutcome { result: Ok(()), kill_switch, disconnected: false };
+    // The kill switch is bound to the current interface - drop it before switching
+    if drop_kill_switch && outcome.kill_switch {
+        let _ = killswitch::disable().await;
+        outcome.kill_switch = false;
+    }
+
+    match (action, tunnel) {
+        (action @ (PendingAction::Connect | PendingAction::Reconnect), Some(tunnel)) => {
+            if action == PendingAction::Reconnect {
+                outcome.disconnected = wireguard::disconnect().await.is_ok();
+            }
+            // start_change ran the preflight before scheduling this
+            outcome.result = wireguard::connect_checked(&tunnel).await;
+            // Apply the tunnel's kill switch setting; re-applying moves a kept one onto the new interface
+            if outcome.result.is_ok() && (tunnel_ks || outcome.kill_switch) {
+                outcome.kill_switch |= killswitch::enable().await.is_ok();
+            }
+        }
+        (PendingAction::Disconnect, _) => {
+            outcome.result = wireguard::disconnect().await;
+            outcome.disconnected = outcome.result.is_ok();
+        }
+        (PendingAction::DisconnectTunnel, Some(tunnel)) => {
+            outcome.result = wireguard::disconnect_interface(&tunnel).await;
+            outcome.disconnected = outcome.result.is_ok();
+        }
+        (PendingAction::KillSwitchOn, _) => {
+            outcome.result = killswitch::enable().await;
+            outcome.kill_switch |= outcome.result.is_ok();
+        }
+        (PendingAction::KillSwitchOff, _) => {
+            outcome.result = killswitch::disable().await;
+            outcome.kill_switch &= outcome.result.is_err();
+        }
+        // Only the undone change's tunnel goes down; ones connected alongside stay up
+        (PendingAction::Undo { take_down, .. }, tunnel) => {
+            if let Some(take_down) = take_down {
+                outcome.result = wireguard::disconnect_interface(&take_down).await;
+                outcome.disconnected = outcome.result.is_ok();
+            }
+            if let (Ok(()), Some(tunnel)) = (&outcome.result, tunnel) {
+                outcome.result = wireguard::connect_additional(&tunnel).await;
+            }
+            // Also re-applied when a kept kill switch has to follow the restored tunnel
+            if outcome.result.is_ok() && tunnel_ks {
+                outcome.result = killswitch::enable().await;
+                outcome.kill_switch |= outcome.result.is_ok();
+            }
+        }
+        // start_change ran the preflight before scheduling this
+        (PendingAction::Restart { interface }, Some(tunnel)) => {
+            outcome.result = wireguard::disconnect_interface(&interface).await;
+            if outcome.result.is_ok() {
+                outcome.result = wireguard::bring_up(&tunnel).await;
+            }
+        }
+        // A trial only swaps the primary tunnel; ones connected alongside stay up
+        (PendingAction::TestConnect { previous }, Some(tunnel)) => {
+            if let Some(previous) = &previous {
+                outcome.result = wireguard::disconnect_interface(previous).await;
+            }
+            if outcome.result.is_ok() {
+                outcome.result = wireguard::connect_additional(&tunnel).await;
+                if let (Err(_), Some(previous)) = (&outcome.result, &previous) {
+                    let _ = wireguard::connect_additional(previous).await;
+                }
+            }
+        }
+        (PendingAction::TestRollBack { previous, .. }, Some(interface)) => {
+            outcome.result = wireguard::disconnect_interface(&interface).await;
+            if let (Ok(()), Some(previous)) = (&outcome.result, previous) {
+                outcome.result = wireguard::connect_additional(&previous).await;
+            }
+        }
+        _ => {}
+    }
+    outcome
+}
+
+/// Tunnel names with whether all their peers have a PresharedKey (None = unreadable)
+type PskFindings = Vec<(String, Option<bool>)>;
+
+/// Info line spinner while a change is running
+const SPINNER: &[char] = &['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+
+/// Shown when a change is asked for while another one is running
+const OPERATION_BUSY: &str = "Another change is still running - try again when it is done";
+
+/// What the Confirm popup is asking about, beyond delete/forget
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfirmKind {
+    Overwrite(Popup),          // Save over an existing tunnel, from this editor popup
+    KillSwitchOn,              // Enable the kill switch with no VPN up
+    DisconnectAlways(String),  // Disconnect a tunnel an Always rule would bring back
+    WriteMtu(String, u32),     // Save a probed MTU into the tunnel's config
+    ReconnectEdited(String),   // Reconnect a tunnel whose config was just edited
+    RenameOverwrite(String),   // Rename over an existing tunnel (`rename_from` is the old name)
+    OverwriteBrokenConfig,     // Let saves replace a config.toml that failed to parse
+    DisconnectForeign(String), // Disconnect a tunnel another tool brought up
+}
+
+/// `managed_by` as shown to the user
+fn manager_label(manager: &str) -> &str {
+    if manager == crate::vpn::wireguard::MANAGER_UNKNOWN {
+        "another tool"
+    } else {
+        manager
+    }
+}
+
+/// How deep the file browser's find mode descends, and how many configs it lists
+const BROWSER_FIND_DEPTH: usize = 4;
+const BROWSER_FIND_LIMIT: usize = 500;
+
+/// Collect `.conf` files below `dir` (hidden entries and symlinked folders skipped)
+/// Names are paths relative to `root`
+fn find_configs(root: &std::path::Path, dir: &std::path::Path, depth: usize, found: &mut Vec<BrowserEntry>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        if found.len() >= BROWSER_FIND_LIMIT {
+            return;
+        }
+        let name = entry.file_name().to_string_lossy().to_string();
+        if name.starts_with('.') {
+            continue;
+        }
+        let path = entry.path();
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        if file_type.is_dir() {
+            if depth > 0 {
+                find_configs(root, &path, depth - 1, found);
+            }
+        } else if name.ends_with(".conf") {
+            found.push(BrowserEntry {
+                name: path.strip_prefix(root).unwrap_or(&path).to_string_lossy().to_string(),
+                is_dir: false,
+                path,
+            });
+        }
+    }
+}
+
+/// Shown when neither the editor nor the pager could open a terminal
+const NO_TERMINAL_MESSAGE: &str =
+    "No terminal emulator found (tried foot, kitty, alacritty, gnome-terminal, xterm)";
+
+/// Quote a string for `sh -c`
+fn shell_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "'\\''"))
+}
+
+/// One line of the Tunnels list
+#[derive(Debug, Clone, PartialEq)]
+pub enum TunnelRow {
+    Group { name: String, members: Vec<usize>, collapsed: bool },
+    Tunnel { index: usize, grouped: bool },  // Index into `tunnels`; grouped ones are indented
+}
+
+/// A dry-run connection that is rolled back unless the user keeps it
+#[derive(Debug, Clone)]
+pub struct ConnectionTest {
+    pub tunnel_name: String,
+    pub stage: TestStage,
+    pub stage_start: Instant,
+    previous_tunnel: Option<String>,  // Primary tunnel (profile name) to restore on rollback
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TestStage {
+    Handshake,                              // Waiting for the first handshake
+    Routing,                                // Checking routing and reachability
+    Reachable { latency_ms: Option<u32> },  // Passed - waiting for the user to keep it
+}
+
+/// Give up on a test tunnel that hasn't completed a handshake by now
+const TEST_HANDSHAKE_TIMEOUT_SECS: u64 = 10;
+/// How long a passed test stays up before it is rolled back
+pub const TEST_KEEP_SECS: u64 = 5;
+
+/// Handshake age shown amber from here (WireGuard rekeys every 2 minutes under traffic)
+pub const HANDSHAKE_WARN_SECS: u64 = 150;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Section {
@@ -36,6 +341,64 @@ pub enum Section {
     KillSwitch,    // Internet kill switch box
 }
 
+impl Section {
+    /// Name stored in config.toml to restore focus on the next launch
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Section::Networks => "networks",
+            Section::Tunnels => "tunnels",
+            Section::KillSwitch => "killswitch",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        [Section::Networks, Section::Tunnels, Section::KillSwitch]
+            .into_iter()
+            .find(|s| s.as_str() == name)
+    }
+}
+
+/// Screen areas from the last draw, used to map mouse clicks to widgets
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LayoutAreas {
+    pub networks: Rect,
+    pub tunnels: Rect,
+    pub config: Rect,
+    pub killswitch: Rect,
+}
+
+/// Public IP lookups after connecting: attempts, and the delay before the first retry
+/// (doubles each time: 5s, 10s)
+const IP_FETCH_MAX_ATTEMPTS: u32 = 3;
+const IP_FETCH_RETRY_SECS: u64 = 5;
+
+/// Two clicks on the same row within this window count as a double-click
+const DOUBLE_CLICK_MS: u64 = 400;
+
+/// Events kept in memory and shown in the History popup
+const HISTORY_CAP: usize = 200;
+
+/// How often to look for a changed Omarchy theme or [theme] section
+const THEME_CHECK_SECS: u64 = 2;
+
+/// Throughput samples averaged for the rate display (one per status refresh)
+const RATE_WINDOW: usize = 5;
+
+/// Throughput samples kept for the sparkline
+const RATE_HISTORY: usize = 60;
+
+/// Latency samples kept for the health popup
+const LATENCY_HISTORY: usize = 60;
+
+/// Network scans younger than this are reused by refresh()
+const NETWORK_CACHE_SECS: u64 = 3;
+
+/// Rescan networks in the background this often
+const NETWORK_SCAN_SECS: u64 = 15;
+
+/// How often to check the viewed config for edits made outside tonneru
+const CONFIG_CHECK_SECS: u64 = 3;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Popup {
     None,
@@ -44,19 +407,43 @@ pub enum Popup {
     ManualConfig,  // Manual config creation (name + paste content)
     Help,
     Confirm,
+    Allowlist,     // Kill switch allowlist editor (CIDRs)
+    History,       // Connection event log
+    KillSwitchPreview,  // Rules the kill switch will apply, before enabling
+    Fastest,       // Tunnels ranked by endpoint latency
+    Latency,       // Latency history and jitter of the connection
+    Label,         // Tunnel tags and note editor
+    Rename,        // New name for a tunnel (typed into input_buffer)
+    EditRule,      // All settings of the selected network's rule
+    Routing,       // Routing table and VPN interface addresses
+    Lint,          // Problems found in a tunnel's config
 }
 
 pub struct App {
     pub section: Section,
     pub popup: Popup,
+    keymap: KeyMap,  // Main-screen keys, with `[keybindings]` applied
 
     // Network state (top section)
     pub networks: Vec<NetworkInfo>,
     pub selected_network: usize,
+    network_scan: Option<tokio::task::JoinHandle<Vec<NetworkInfo>>>,  // iwctl/nmcli running off the UI loop
+    restore_network: Option<String>,  // Identifier to reselect once the first scan lands
+    last_network_scan: Option<Instant>,  // When the cached list was filled
+    mtu_probe: Option<tokio::task::JoinHandle<(String, Result<MtuProbe>)>>,  // Pings to a tunnel's endpoint
+    pub mtu_result: Option<(String, MtuProbe)>,  // Last probe, shown while that tunnel is up
+    latency_probe: Option<tokio::task::JoinHandle<Vec<EndpointLatency>>>,  // Pings to every tunnel's endpoint
+    psk_tunnels: HashMap<String, Option<bool>>,  // Whether each tunnel's peers all have a PresharedKey (None = unreadable)
+    psk_scan: Option<tokio::task::JoinHandle<PskFindings>>,  // Configs read for `psk_tunnels`
+    pub latency_ranking: Vec<EndpointLatency>,  // Fastest first, shown in the Fastest popup
+    pub latency_selected: usize,
 
     // Tunnel state (middle section) 
     pub tunnels: Vec<WgProfile>,
-    pub selected_tunnel: usize,
+    pub selected_tunnel: usize,          // Index into `tunnels` (always a visible one)
+    pub selected_group: Option<String>,  // Group header highlighted instead of a tunnel
+    pub tunnel_filter: Option<String>,   // Case-insensitive name filter ('/' to edit)
+    pub tunnel_filter_active: bool,      // True while typing into the filter
     pub vpn_status: WgStatus,
 
     // Network rules (which tunnel for which network)
@@ -70,6 +457,8 @@ pub struct App {
     pub config_preview: String,
     pub preview_name: String,
     pub preview_field: usize,  // 0 = name, 1 = save/cancel buttons
+    pub preview_from_clipboard: bool,  // Esc closes the preview instead of returning to the browser
+    pub confirm_kind: Option<ConfirmKind>,  // None = delete tunnel / forget network
 
     // Status message (shown in info line, auto-clears after timeout)
     pub status_message: Option<String>,
@@ -77,36 +466,113 @@ pub struct App {
 
     // Kill switch
     pub kill_switch_enabled: bool,
+    pub allowlist_selected: usize,       // Selected entry in allowlist popup
+    pub allowlist_dirty: bool,           // Allowlist changed while popup open
+    pub killswitch_preview: Option<crate::vpn::killswitch::Preview>,
+    pub killswitch_preview_scroll: usize,
+
+    // Tunnel label editor (uses preview_field: 0 = tags, 1 = note)
+    pub label_tunnel: String,
+    pub label_tags: String,              // Comma-separated while editing
+    pub label_note: String,
+    pub label_group: String,             // Empty = automatic (shared name prefix)
+    pub rename_from: String,             // Tunnel being renamed
 
     // File browser state
     pub browser_path: std::path::PathBuf,
     pub browser_entries: Vec<BrowserEntry>,
     pub browser_selected: usize,
+    pub browser_recursive: bool,         // Flat list of configs found in subdirectories ('F')
+    pub export_tunnel: Option<String>,   // Set when the browser is picking an export folder
+
+    // Event history (shared with the daemon via history.jsonl)
+    pub history: VecDeque<HistoryEvent>,
+    pub history_scroll: usize,          // Lines scrolled from the newest event
+    pub history_utc_offset: i64,        // Local offset for timestamps, read when opened
+
+    // Routing popup ('I'), read fresh when opened or refreshed
+    pub routing_info: RoutingInfo,
+    pub routing_scroll: usize,
+
+    // Config check ('C') of `lint_tunnel`
+    pub lint_tunnel: String,
+    pub lint_findings: Vec<Finding>,
 
     // Tunnel config viewer (right side of tunnels box)
     pub tunnel_config_content: String,
+    tunnel_config_mtime: Option<(String, u64)>,  // Tunnel and mtime of the loaded config
+    config_check: Option<tokio::task::JoinHandle<(String, Option<u64>)>>,  // mtime read off the UI loop
+    last_config_check: Instant,
+    pub tunnel_config_stale: bool,       // Config changed on disk since it was loaded ('c' reloads)
+    active_scope: Option<(String, Option<TunnelScope>)>,  // Primary tunnel's AllowedIPs scope (cached per interface)
     pub tunnel_config_scroll: usize,     // Scroll offset for display
+    pub config_viewer_height: Cell<usize>, // Visible rows, updated by the viewer each draw
+    pub config_search: Option<String>,   // Search query highlighted in the config viewer
+    pub config_search_active: bool,      // Whether the search query is being typed
+    pub show_peers: bool,                // Expand the per-peer section of the config viewer
 
     // Pending change countdown (3 second delay before applying rule/tunnel changes)
     pub pending_change: Option<PendingChange>,
+    pub rule_form: Option<RuleForm>,     // Open rule editor
     pub countdown_start: Option<Instant>,
-    pub countdown_seconds: u8,           // Current countdown value for display
+    pub countdown_seconds: u64,          // Current countdown value for display
+    pub undo: Option<UndoState>,         // One level of undo for the last applied change
+    operation: Option<RunningChange>,    // Applied change still running
+
+    // Tunnel dry run ("test connection")
+    pub connection_test: Option<ConnectionTest>,
 
     // Info line content
     pub info_message: Option<String>,    // Current info message (traffic, status, etc.)
+    pub handshake_age: Option<u64>,      // Seconds since the primary tunnel's handshake, updated each tick
+    pub banner: Option<String>,          // Persistent setup warning shown above the info line
     
     // Rate limiting for status refresh
     pub last_status_refresh: Instant,    // When we last refreshed VPN status
+
+    // Live theme reload
+    theme_stamp: ThemeStamp,             // Theme files as of the last (re)load
+    last_theme_check: Instant,
     
     // Network connectivity status
     pub connectivity: ConnectivityStatus, // Current internet connectivity
     pub last_connectivity_check: Instant, // When we last checked connectivity
     pub vpn_health: VpnHealthCheck,       // Detailed VPN health status
     pub last_health_check: Instant,       // When we last did a full health check
+    pub latency_samples: VecDeque<Option<u32>>,  // Recent pings through the tunnel, newest last (None = lost)
+    latency_sample: Option<tokio::task::JoinHandle<Option<u32>>>,  // Ping running off the UI loop
+    last_latency_sample: Instant,
+    leak_probe: Option<tokio::task::JoinHandle<bool>>,  // Request past the kill switch (`leak_check`)
     
     // Public IP tracking
-    pub public_ip: Option<String>,        // Current public IP address
+    pub public_ip: Option<String>,        // Current public IPv4 address
+    pub public_ipv6: Option<String>,      // Current public IPv6 address
     pub ip_fetch_pending: bool,           // Whether we're waiting to fetch IP
+    ip_fetch_attempts: u32,               // Lookups tried since the VPN connected
+    ip_fetch_next: Instant,               // Earliest time for the next lookup
+    ip_fetch: Option<tokio::task::JoinHandle<(Option<String>, Option<String>)>>,  // IPv4/IPv6 lookup off the UI loop
+
+    // Mouse support
+    pub layout: Cell<LayoutAreas>,        // Widget areas, updated by the UI each draw
+    last_click: Option<(Instant, Position)>, // For double-click detection
+
+    // Transfer accounting across reconnects
+    last_transfer: Option<(String, u64, u64)>, // Last-seen (interface, rx, tx) counters
+    last_transfer_at: Option<Instant>,    // When `last_transfer` was read
+    rate_samples: VecDeque<(f64, f64)>,   // Recent (rx, tx) bytes/sec, newest last
+    pub rate_history: VecDeque<u64>,      // Combined bytes/sec for the sparkline, newest last
+    session_rx_bytes: u64,                // Received on connections completed since launch
+    session_tx_bytes: u64,                // Sent on connections completed since launch
+    tunnel_counters: HashMap<String, TransferCounters>,  // Every up interface, for lifetime and quota totals
+    utc_offset: i64,                      // Local offset for quota periods, read at launch
+}
+
+/// Last-seen `wg show` counters of one up interface
+#[derive(Debug, Clone, Copy, Default)]
+struct TransferCounters {
+    rx: u64,
+    tx: u64,
+    quota_baseline: u64,  // Bytes of this connection counted in the previous quota period
 }
 
 #[derive(Debug, Clone)]
@@ -119,23 +585,42 @@ pub struct BrowserEntry {
 impl App {
     pub async fn new() -> Result<Self> {
         let config = AppConfig::load().unwrap_or_default();
+        let keymap = KeyMap::new(&config.keybindings);
         let tunnels = crate::vpn::wireguard::list_profiles().await.unwrap_or_default();
-        let vpn_status = crate::vpn::wireguard::get_status().await.unwrap_or_default();
-        let networks = crate::network::get_networks().await.unwrap_or_default();
         
-        // Get initial connectivity status
-        let connectivity = crate::network::check_connectivity().await;
-        let vpn_health = crate::vpn::wireguard::health_check().await;
+        // Initial status: reuse the daemon's results when it is running
+        let (vpn_status, connectivity, vpn_health) = match crate::network::status_socket::query().await {
+            Some(snapshot) => (snapshot.vpn_status, snapshot.connectivity, snapshot.vpn_health),
+            None => {
+                let status = crate::vpn::wireguard::get_status(&config).await.unwrap_or_default();
+                let health = crate::vpn::wireguard::health_check(&status).await;
+                (status, crate::network::check_connectivity().await, health)
+            }
+        };
 
         let mut app = Self {
             section: Section::Networks,
+            keymap,
             popup: Popup::None,
 
-            networks,
+            networks: Vec::new(),
             selected_network: 0,
+            restore_network: None,
+            network_scan: None,
+            last_network_scan: None,
+            mtu_probe: None,
+            mtu_result: None,
+            latency_probe: None,
+            psk_tunnels: HashMap::new(),
+            psk_scan: None,
+            latency_ranking: Vec::new(),
+            latency_selected: 0,
 
             tunnels,
             selected_tunnel: 0,
+            selected_group: None,
+            tunnel_filter: None,
+            tunnel_filter_active: false,
             vpn_status,
 
             network_rules: config.network_rules.clone(),
@@ -146,34 +631,111 @@ impl App {
             config_preview: String::new(),
             preview_name: String::new(),
             preview_field: 0,
+            preview_from_clipboard: false,
+            confirm_kind: None,
 
             status_message: None,
             status_message_time: None,
             kill_switch_enabled: false,
+            allowlist_selected: 0,
+            allowlist_dirty: false,
+            killswitch_preview: None,
+            killswitch_preview_scroll: 0,
+            label_tunnel: String::new(),
+            label_tags: String::new(),
+            label_note: String::new(),
+            label_group: String::new(),
+            rename_from: String::new(),
 
             browser_path: dirs::home_dir().unwrap_or_else(|| std::path::PathBuf::from("/")),
             browser_entries: Vec::new(),
             browser_selected: 0,
+            browser_recursive: false,
+            export_tunnel: None,
+            history: VecDeque::new(),
+            history_scroll: 0,
+            history_utc_offset: 0,
+            routing_info: RoutingInfo::default(),
+            routing_scroll: 0,
+            lint_tunnel: String::new(),
+            lint_findings: Vec::new(),
 
             tunnel_config_content: String::new(),
+            tunnel_config_mtime: None,
+            config_check: None,
+            last_config_check: Instant::now(),
+            tunnel_config_stale: false,
+            active_scope: None,
             tunnel_config_scroll: 0,
+            config_viewer_height: Cell::new(0),
+            config_search: None,
+            config_search_active: false,
+            show_peers: false,
 
             pending_change: None,
+            rule_form: None,
+            undo: None,
+            operation: None,
             countdown_start: None,
             countdown_seconds: 0,
+            connection_test: None,
             info_message: None,
+            handshake_age: None,
+            banner: None,
 
             last_status_refresh: Instant::now(),
+            theme_stamp: Theme::source_stamp(),
+            last_theme_check: Instant::now(),
             
             connectivity,
             last_connectivity_check: Instant::now(),
             vpn_health,
             last_health_check: Instant::now(),
+            latency_samples: VecDeque::new(),
+            latency_sample: None,
+            last_latency_sample: Instant::now(),
+            leak_probe: None,
             
             public_ip: None,
+            public_ipv6: None,
             ip_fetch_pending: false,
+            ip_fetch_attempts: 0,
+            ip_fetch_next: Instant::now(),
+            ip_fetch: None,
+
+            layout: Cell::new(LayoutAreas::default()),
+            last_click: None,
+
+            last_transfer: None,
+            last_transfer_at: None,
+            rate_samples: VecDeque::new(),
+            rate_history: VecDeque::new(),
+            session_rx_bytes: 0,
+            session_tx_bytes: 0,
+            tunnel_counters: HashMap::new(),
+            utc_offset: history::local_utc_offset(),
         };
 
+        // Without the helper every privileged call fails and lists come back empty
+        if !crate::vpn::helper_installed() {
+            app.banner = Some(format!(
+                "⚠ {} not installed - run: sudo install -Dm755 packaging/usr/lib/tonneru/tonneru-sudo {}",
+                crate::vpn::HELPER_PATH, crate::vpn::HELPER_PATH
+            ));
+            tracing::warn!("Helper script missing: {}", crate::vpn::HELPER_PATH);
+        }
+
+        // Nothing is saved over a broken config.toml until the user says so
+        if let Some(error) = app.config.parse_error.clone() {
+            app.banner = Some("⚠ config.toml failed to parse - using defaults, your file was NOT overwritten (copy in config.toml.bak)".to_string());
+            app.set_status(format!(
+                "config.toml {} - fix it and restart, or let tonneru overwrite it with new settings? (y/n)",
+                error
+            ));
+            app.confirm_kind = Some(ConfirmKind::OverwriteBrokenConfig);
+            app.popup = Popup::Confirm;
+        }
+
         // Check if kill switch is already enabled (from previous session)
         if crate::vpn::killswitch::is_enabled().await.unwrap_or(false) {
             app.kill_switch_enabled = true;
@@ -193,42 +755,80 @@ impl App {
             }
         }
 
-        // Auto-reconnect to last tunnel if enabled and not already connected
-        if !app.vpn_status.connected && app.config.auto_reconnect {
-            if let Some(ref last_tunnel) = app.config.last_connected {
-                // Check if this tunnel still exists
-                if app.tunnels.iter().any(|t| &t.name == last_tunnel) {
-                    tracing::info!("Auto-reconnecting to last tunnel: {}", last_tunnel);
-                    if let Ok(_) = crate::vpn::wireguard::connect(last_tunnel).await {
-                        // Refresh status after connecting
-                        app.vpn_status = crate::vpn::wireguard::get_status().await.unwrap_or_default();
-                        
-                        // Enable kill switch if tunnel has it configured
-                        let tunnel_ks = app.get_tunnel_info(last_tunnel)
-                            .map(|t| t.kill_switch)
-                            .unwrap_or(false);
-                        if tunnel_ks {
-                            if crate::vpn::killswitch::enable().await.is_ok() {
-                                app.kill_switch_enabled = true;
-                            }
-                        }
+        // Connect the startup profile, else auto-reconnect to the last tunnel, unless already connected
+        if !app.vpn_status.connected {
+            // Only tunnels that still exist
+            let exists = |name: &String| app.tunnels.iter().any(|t| &t.name == name);
+            let startup = app.config.startup_profile.clone().filter(exists);
+            let last = app.config.last_connected.clone().filter(|name| app.config.auto_reconnect && exists(name));
+            if let Some(tunnel) = startup.as_ref().or(last.as_ref()) {
+                if startup.is_some() {
+                    tracing::info!("Connecting startup profile: {}", tunnel);
+                } else {
+                    tracing::info!("Auto-reconnecting to last tunnel: {}", tunnel);
+                }
+                if crate::vpn::wireguard::connect(tunnel).await.is_ok() {
+                    // Refresh status after connecting
+                    app.refresh_vpn_status().await;
+
+                    // Enable kill switch if tunnel has it configured
+                    let tunnel_ks = app.get_tunnel_info(tunnel)
+                        .map(|t| t.kill_switch)
+                        .unwrap_or(false);
+                    if tunnel_ks && crate::vpn::killswitch::enable().await.is_ok() {
+                        app.kill_switch_enabled = true;
                     }
                 }
             }
         }
 
+        // Come back where the last session left off; names survive reordering
+        if let Some(section) = app.config.last_section.as_deref().and_then(Section::from_name) {
+            app.section = section;
+        }
+        if let Some(name) = app.config.last_selected_tunnel.as_deref() {
+            app.selected_tunnel = app.tunnels.iter().position(|t| t.name == name).unwrap_or(0);
+        }
+        app.clamp_tunnel_row();
+        app.restore_network = app.config.last_selected_network.clone();
+
+        // Networks arrive a tick later so a slow iwctl doesn't delay startup
+        app.start_network_scan();
+        app.start_psk_scan();
+
         // Load config for the initially selected tunnel
         app.load_selected_tunnel_config().await;
 
         Ok(app)
     }
 
-    /// Set a status message (auto-clears after 3 seconds)
+    /// Remember section and selection for the next launch
+    /// Reloads config.toml first so changes the daemon made meanwhile survive
+    pub fn save_ui_state(&self) -> Result<()> {
+        let mut config = AppConfig::load().unwrap_or_else(|_| self.config.clone());
+        config.last_section = Some(self.section.as_str().to_string());
+        config.last_selected_tunnel = self.tunnels.get(self.selected_tunnel).map(|t| t.name.clone());
+        config.last_selected_network = self.networks.get(self.selected_network).map(|n| n.identifier())
+            .or_else(|| self.restore_network.clone());
+        config.save()
+    }
+
+    /// Set a status message (auto-clears after `status_message_secs`)
     fn set_status(&mut self, msg: impl Into<String>) {
         self.status_message = Some(msg.into());
         self.status_message_time = Some(Instant::now());
     }
     
+    /// Interface a tunnel runs on; differs from its name for some NetworkManager connections
+    fn tunnel_interface<'a>(&'a self, name: &'a str) -> &'a str {
+        self.tunnels.iter().find(|t| t.name == name).map_or(name, |t| t.interface())
+    }
+
+    /// Tunnel running on an interface - the inverse of `tunnel_interface`
+    fn interface_tunnel<'a>(&'a self, interface: &'a str) -> &'a str {
+        self.tunnels.iter().find(|t| t.interface() == interface).map_or(interface, |t| t.name.as_str())
+    }
+
     /// Get TunnelInfo for a tunnel by name
     fn get_tunnel_info(&self, name: &str) -> Option<&TunnelInfo> {
         self.config.known_tunnels.iter().find(|t| t.name == name)
@@ -240,7 +840,7 @@ impl App {
             self.config.known_tunnels.push(TunnelInfo {
                 name: name.to_string(),
                 protocol: "wireguard".to_string(),
-                kill_switch: false,
+                ..Default::default()
             });
         }
         self.config.known_tunnels.iter_mut().find(|t| t.name == name).unwrap()
@@ -258,19 +858,87 @@ impl App {
         if let Some(tunnel) = self.tunnels.get(self.selected_tunnel) {
             let tunnel_name = tunnel.name.clone();
             
-            // Use the helper to read config (passwordless sudo)
-            match crate::vpn::run_helper(&["config-read", &tunnel_name]).await {
-                Ok(output) if output.status.success() => {
-                    self.tunnel_config_content = String::from_utf8_lossy(&output.stdout).to_string();
+            // Read through the helper (passwordless sudo), or directly from config_dir
+            match crate::vpn::wireguard::read_config(&tunnel_name).await {
+                Ok(content) => {
+                    self.tunnel_config_mtime = crate::vpn::wireguard::config_mtime(&tunnel_name).await
+                        .map(|mtime| (tunnel_name.clone(), mtime));
+                    self.tunnel_config_stale = false;
+                    let psk = crate::vpn::wireguard::config_uses_psk(&content);
+                    self.psk_tunnels.insert(tunnel_name.clone(), psk);
+                    self.tunnel_config_content = content;
                     self.tunnel_config_scroll = 0;
+                    // Picks up edits to the connected tunnel's AllowedIPs
+                    if self.vpn_status.interface.as_deref() == Some(self.tunnel_interface(&tunnel_name)) {
+                        self.active_scope = Some((tunnel_name, TunnelScope::from_config(&self.tunnel_config_content)));
+                    }
                 }
-                _ => {
+                Err(_) => {
                     self.tunnel_config_content = "# Unable to load config\n# Check permissions".to_string();
+                    self.tunnel_config_mtime = None;
+                    self.tunnel_config_stale = false;
                 }
             }
         } else {
             self.tunnel_config_content.clear();
+            self.tunnel_config_mtime = None;
+            self.tunnel_config_stale = false;
+        }
+    }
+
+    /// Read the viewed config's mtime in the background
+    fn start_config_check(&mut self) {
+        if self.config_check.is_some() || self.tunnel_config_stale {
+            return;
+        }
+        let Some((name, _)) = self.tunnel_config_mtime.clone() else {
+            return;
+        };
+        self.last_config_check = Instant::now();
+        self.config_check = Some(tokio::spawn(async move {
+            let mtime = crate::vpn::wireguard::config_mtime(&name).await;
+            (name, mtime)
+        }));
+    }
+
+    /// Flag the viewer stale when the config it shows was modified since loading
+    async fn poll_config_check(&mut self) {
+        if !self.config_check.as_ref().is_some_and(|task| task.is_finished()) {
+            return;
+        }
+        let Some(task) = self.config_check.take() else {
+            return;
+        };
+        if let Ok((name, Some(mtime))) = task.await {
+            if self.tunnel_config_mtime.as_ref().is_some_and(|(loaded, at)| *loaded == name && *at != mtime) {
+                self.tunnel_config_stale = true;
+            }
+        }
+    }
+
+    /// Reload the selected tunnel's config; a changed config of a connected tunnel
+    /// offers a reconnect like an edit does, so the tunnel doesn't keep running the old one
+    async fn reload_tunnel_config(&mut self) -> Result<()> {
+        let Some(tunnel) = self.selected_visible_tunnel() else {
+            return Ok(());
+        };
+        let tunnel_name = tunnel.name.clone();
+        let connected = self.vpn_status.is_up(tunnel.interface());
+        let before = std::mem::take(&mut self.tunnel_config_content);
+
+        self.load_selected_tunnel_config().await;
+        if self.tunnel_config_content == before {
+            self.set_status(format!("{} config unchanged", tunnel_name));
+        } else if !connected {
+            self.set_status(format!("Config reloaded for {}", tunnel_name));
+        } else if self.config.reconnect_on_edit {
+            self.reconnect_edited(&tunnel_name).await?;
+        } else {
+            self.set_status(format!("{} changed on disk - reconnect now to apply? (y/n)", tunnel_name));
+            self.confirm_kind = Some(ConfirmKind::ReconnectEdited(tunnel_name));
+            self.popup = Popup::Confirm;
         }
+        Ok(())
     }
 
     pub async fn handle_key(&mut self, key: KeyEvent) -> Result<()> {
@@ -279,10 +947,82 @@ impl App {
             return self.handle_popup_key(key).await;
         }
 
+        // A running connection test only answers keep/cancel
+        if self.connection_test.is_some() {
+            return self.handle_test_key(key).await;
+        }
+
+        // Inline tunnel filter captures typing while active
+        if self.tunnel_filter_active {
+            return self.handle_filter_key(key).await;
+        }
+
+        // Config viewer search captures typing while active
+        if self.config_search_active {
+            self.handle_config_search_key(key);
+            return Ok(());
+        }
+
         // Handle normal key input
         self.handle_normal_key(key).await
     }
 
+    pub async fn handle_mouse(&mut self, mouse: MouseEvent) -> Result<()> {
+        // Popups and text input own the screen
+        if self.popup != Popup::None || self.tunnel_filter_active || self.config_search_active {
+            return Ok(());
+        }
+
+        let pos = Position::new(mouse.column, mouse.row);
+        let layout = self.layout.get();
+
+        match mouse.kind {
+            MouseEventKind::ScrollDown if layout.config.contains(pos) => self.scroll_config(3),
+            MouseEventKind::ScrollUp if layout.config.contains(pos) => self.scroll_config(-3),
+            MouseEventKind::Down(MouseButton::Left) => {
+                let double = self.last_click
+                    .is_some_and(|(at, last)| last == pos && at.elapsed() < Duration::from_millis(DOUBLE_CLICK_MS));
+                self.last_click = if double { None } else { Some((Instant::now(), pos)) };
+
+                if layout.networks.contains(pos) {
+                    self.section = Section::Networks;
+                    if let Some(row) = Self::table_row(layout.networks, pos) {
+                        if row < self.networks.len() {
+                            self.selected_network = row;
+                        }
+                    }
+                } else if layout.tunnels.contains(pos) {
+                    self.section = Section::Tunnels;
+                    let rows = self.tunnel_rows();
+                    if let Some(row) = Self::table_row(layout.tunnels, pos).and_then(|row| rows.get(row)) {
+                        if self.select_row(row) {
+                            self.load_selected_tunnel_config().await;
+                        }
+                        match row {
+                            TunnelRow::Group { .. } if double => self.toggle_selected_group()?,
+                            TunnelRow::Tunnel { .. } if double => self.use_tunnel_now().await?,
+                            _ => {}
+                        }
+                    }
+                } else if layout.config.contains(pos) {
+                    self.section = Section::Tunnels;
+                } else if layout.killswitch.contains(pos) {
+                    self.section = Section::KillSwitch;
+                    self.toggle_kill_switch().await?;
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Map a click inside a bordered table with a header row to a row index
+    fn table_row(area: Rect, pos: Position) -> Option<usize> {
+        let first_row = area.y + 2; // Top border + header
+        let last_row = area.bottom().saturating_sub(1); // Bottom border
+        (pos.y >= first_row && pos.y < last_row).then(|| (pos.y - first_row) as usize)
+    }
+
     async fn handle_normal_key(&mut self, key: KeyEvent) -> Result<()> {
         // Escape cancels pending change
         if key.code == KeyCode::Esc && self.pending_change.is_some() {
@@ -291,16 +1031,56 @@ impl App {
             return Ok(());
         }
 
-        match key.code {
+        // Enter applies a pending change without waiting out the countdown
+        if key.code == KeyCode::Enter && self.pending_change.is_some() {
+            return self.apply_pending_change().await;
+        }
+
+        // Escape clears a kept tunnel filter
+        if key.code == KeyCode::Esc && self.section == Section::Tunnels && self.tunnel_filter.is_some() {
+            self.tunnel_filter = None;
+            self.clamp_tunnel_selection().await;
+            return Ok(());
+        }
+
+        // Escape clears a kept config search
+        if key.code == KeyCode::Esc && self.section == Section::Tunnels && self.config_search.is_some() {
+            self.config_search = None;
+            return Ok(());
+        }
+
+        // Config viewer scrolling (Tunnels section)
+        if self.section == Section::Tunnels {
+            let page = self.config_page() as isize;
+            let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+            let delta = match key.code {
+                KeyCode::PageDown => Some(page),
+                KeyCode::PageUp => Some(-page),
+                KeyCode::Char('d') if ctrl => Some(page / 2),
+                KeyCode::Char('u') if ctrl => Some(-page / 2),
+                KeyCode::Home => Some(isize::MIN),
+                KeyCode::End => Some(isize::MAX),
+                _ => None,
+            };
+            if let Some(delta) = delta {
+                self.scroll_config(delta);
+                return Ok(());
+            }
+        }
+
+        let Some(action) = self.keymap.action(&key) else {
+            return Ok(());
+        };
+        match action {
             // Navigation between sections (Networks ↔ Tunnels ↔ KillSwitch)
-            KeyCode::Tab => {
+            Action::NextSection => {
                 self.section = match self.section {
                     Section::Networks => Section::Tunnels,
                     Section::Tunnels => Section::KillSwitch,
                     Section::KillSwitch => Section::Networks,
                 };
             }
-            KeyCode::BackTab => {
+            Action::PrevSection => {
                 self.section = match self.section {
                     Section::Networks => Section::KillSwitch,
                     Section::Tunnels => Section::Networks,
@@ -309,12 +1089,13 @@ impl App {
             }
 
             // Vertical navigation (j/down, up only - 'k' is for kill switch)
-            KeyCode::Char('j') | KeyCode::Down => self.move_down().await,
-            KeyCode::Up => self.move_up().await,
+            Action::MoveDown => self.move_down().await,
+            Action::MoveUp => self.move_up().await,
 
             // Actions based on section
-            KeyCode::Char(' ') | KeyCode::Enter => {
+            Action::Activate => {
                 match self.section {
+                        Section::Tunnels if self.selected_group.is_some() => self.toggle_selected_group()?,
                     Section::Tunnels => {
                 // Space/Enter = connect/use tunnel now
                 self.use_tunnel_now().await?;
@@ -323,50 +1104,160 @@ impl App {
                         // Space/Enter = toggle kill switch
                         self.toggle_kill_switch().await?;
                     }
-                    _ => {}
+                    Section::Networks => self.start_rule_editor(),
                 }
             }
 
             // Edit config in external editor (only in Tunnels section)
-            KeyCode::Char('e') => {
-                if self.section == Section::Tunnels && !self.tunnels.is_empty() {
-                    self.edit_tunnel_config_external().await?;
-                }
+            Action::Edit if self.section == Section::Tunnels && !self.tunnels.is_empty() => {
+                self.edit_tunnel_config_external().await?;
+            }
+
+            // View config read-only in a pager (only in Tunnels section)
+            Action::View if self.section == Section::Tunnels && !self.tunnels.is_empty() => {
+                self.view_tunnel_config_external();
+            }
+
+            // Dry-run the selected tunnel, rolling back afterwards (only in Tunnels section)
+            Action::Test if self.section == Section::Tunnels => {
+                self.start_connection_test().await?;
+            }
+
+            // Probe the path MTU of the selected (connected) tunnel
+            Action::MtuProbe if self.section == Section::Tunnels => self.start_mtu_probe(),
+
+            // Rank tunnels by endpoint latency and offer the fastest
+            Action::Fastest if self.section == Section::Tunnels => self.start_latency_ranking(),
+
+            // Connect alongside already-active tunnels (only in Tunnels section)
+            Action::ConnectAlongside if self.section == Section::Tunnels => {
+                self.connect_alongside().await?;
             }
 
             // New manual config creation (only in Tunnels section)
-            KeyCode::Char('n') => {
-                if self.section == Section::Tunnels {
-                    self.start_manual_config();
+            Action::NewTunnel if self.section == Section::Tunnels => {
+                self.start_manual_config();
+            }
+
+            // Expand/collapse the peer list of a multi-peer tunnel (only in Tunnels section)
+            Action::Peers if self.section == Section::Tunnels => {
+                self.show_peers = !self.show_peers;
+            }
+
+            // Edit tags and note for the selected tunnel (only in Tunnels section)
+            Action::Labels if self.section == Section::Tunnels => self.start_label_editor(),
+
+            // Rename the selected tunnel (only in Tunnels section)
+            Action::Rename if self.section == Section::Tunnels => self.start_rename(),
+
+            // Filter tunnels by name or tag (only in Tunnels section)
+            Action::Filter if self.section == Section::Tunnels => {
+                self.tunnel_filter_active = true;
+                if self.tunnel_filter.is_none() {
+                    self.tunnel_filter = Some(String::new());
+                }
+            }
+
+            // Search the config viewer (only in Tunnels section)
+            Action::Search if self.section == Section::Tunnels && !self.tunnels.is_empty() => {
+                self.config_search_active = true;
+                if self.config_search.is_none() {
+                    self.config_search = Some(String::new());
                 }
             }
 
             // Import config from file browser
-            KeyCode::Char('i') => self.start_file_browser(),
+            Action::Import => self.start_file_browser(),
+
+            // Export the selected tunnel's config (only in Tunnels section)
+            Action::Export if self.section == Section::Tunnels => self.start_export_browser(),
+
+            // Import config from clipboard (only in Tunnels section)
+            Action::Paste if self.section == Section::Tunnels => self.import_from_clipboard(),
             
             // Delete/remove
-            KeyCode::Char('d') | KeyCode::Delete | KeyCode::Backspace => {
+            Action::Delete => {
                 self.delete_selection().await?;
             }
             
+            // Open the captive portal sign-in page
+            Action::CaptivePortal if self.connectivity.captive_portal_url.is_some() => {
+                self.open_captive_portal();
+            }
+
             // Refresh
-            KeyCode::Char('R') => self.refresh().await?,
+            Action::Refresh => {
+                // Always rescan, even if the cached list is fresh
+                self.start_network_scan();
+                self.refresh().await?;
+            }
             
+            // Reload theme colors
+            Action::ReloadTheme => {
+                self.reload_theme();
+                self.set_status("Theme reloaded");
+            }
+
             // Toggle rule (cycle through: none -> always -> never -> none)
-            KeyCode::Char('r') => self.cycle_tunnel_rule().await?,
+            Action::CycleRule => self.cycle_tunnel_rule().await?,
             
             // Cycle through tunnels for selected network
-            KeyCode::Char('t') => self.cycle_network_tunnel().await?,
+            Action::CycleTunnel => self.cycle_network_tunnel().await?,
+            
+            // Move the selected tunnel up/down in the Tunnels list
+            Action::MoveTunnelUp if self.section == Section::Tunnels => self.move_tunnel(false)?,
+            Action::MoveTunnelDown if self.section == Section::Tunnels => self.move_tunnel(true)?,
+
+            // Nudge rule priority up/down for selected network
+            Action::PriorityUp => self.adjust_rule_priority(true)?,
+            Action::PriorityDown => self.adjust_rule_priority(false)?,
             
             // Kill switch toggle (only when KillSwitch section is active)
-            KeyCode::Char('k') => {
-                if self.section == Section::KillSwitch {
-                    self.toggle_kill_switch().await?;
-                }
+            Action::KillSwitch if self.section == Section::KillSwitch => {
+                self.toggle_kill_switch().await?;
+            }
+            
+            // Edit kill switch allowlist (only when KillSwitch section is active)
+            Action::Allowlist if self.section == Section::KillSwitch => {
+                self.start_allowlist_editor();
             }
             
             // Help (? or h)
-            KeyCode::Char('?') | KeyCode::Char('h') => self.popup = Popup::Help,
+            Action::Help => self.popup = Popup::Help,
+
+            // Event history
+            Action::History => self.open_history(),
+
+            // Latency history and jitter
+            Action::Latency => self.popup = Popup::Latency,
+
+            // Routing table and addresses, for when routing_ok is false
+            Action::Routing => {
+                self.routing_scroll = 0;
+                self.refresh_routing_info().await;
+                self.popup = Popup::Routing;
+            }
+
+            // Pause/resume the daemon's automatic actions
+            Action::PauseDaemon => self.toggle_daemon_pause()?,
+
+            // Undo the last applied change (tunnel switch, connect, kill switch)
+            Action::Undo => self.undo_last_change().await?,
+
+            // Emergency: drop every tunnel and the kill switch, no countdown
+            Action::PanicDisconnect => self.panic_disconnect().await?,
+
+            // Copy public IP (or tunnel endpoint) to clipboard
+            Action::CopyIp => self.copy_public_ip(),
+
+            // Copy the selected tunnel's config, keys redacted unless configured otherwise
+            Action::CopyConfig if self.section == Section::Tunnels => self.copy_tunnel_config().await,
+
+            // Check the selected tunnel's config for common mistakes
+            Action::Lint if self.section == Section::Tunnels => self.lint_selected_tunnel().await,
+
+            // Reload the viewed config after it changed on disk
+            Action::ReloadConfig if self.section == Section::Tunnels => self.reload_tunnel_config().await?,
 
             _ => {}
         }
@@ -378,25 +1269,165 @@ impl App {
             Popup::FileBrowser => self.handle_browser_key(key).await,
             Popup::ConfigPreview => self.handle_preview_key(key).await,
             Popup::ManualConfig => self.handle_manual_config_key(key).await,
+            Popup::Allowlist => self.handle_allowlist_key(key).await,
+            Popup::Label => self.handle_label_key(key),
+            Popup::Rename => self.handle_rename_key(key).await,
+            Popup::EditRule => self.handle_rule_form_key(key),
             Popup::Help => {
                 if matches!(key.code, KeyCode::Esc | KeyCode::Char('?') | KeyCode::Char('h') | KeyCode::Enter | KeyCode::Char('q')) {
                     self.popup = Popup::None;
                 }
                 Ok(())
             }
-            Popup::Confirm => {
+            Popup::KillSwitchPreview => self.handle_killswitch_preview_key(key),
+            Popup::Fastest => self.handle_fastest_key(key).await,
+            Popup::Lint => {
+                if matches!(key.code, KeyCode::Esc | KeyCode::Enter | KeyCode::Char('q'))
+                    || self.keymap.action(&key) == Some(Action::Lint)
+                {
+                    self.popup = Popup::None;
+                }
+                Ok(())
+            }
+            Popup::Latency => {
+                // The key that opened it closes it, whatever it is bound to
+                if matches!(key.code, KeyCode::Esc | KeyCode::Enter | KeyCode::Char('q'))
+                    || self.keymap.action(&key) == Some(Action::Latency)
+                {
+                    self.popup = Popup::None;
+                }
+                Ok(())
+            }
+            Popup::Routing => {
+                let lines = self.routing_lines();
                 match key.code {
-                    KeyCode::Char('y') | KeyCode::Enter => {
-                        self.confirm_action().await?;
-                        self.popup = Popup::None;
+                    KeyCode::Esc | KeyCode::Char('q') => self.popup = Popup::None,
+                    _ if self.keymap.action(&key) == Some(Action::Routing) => self.popup = Popup::None,
+                    KeyCode::Char('j') | KeyCode::Down => {
+                        self.routing_scroll = (self.routing_scroll + 1).min(lines.saturating_sub(1));
+                    }
+                    KeyCode::Char('k') | KeyCode::Up => {
+                        self.routing_scroll = self.routing_scroll.saturating_sub(1);
+                    }
+                    KeyCode::PageDown => {
+                        self.routing_scroll = (self.routing_scroll + 10).min(lines.saturating_sub(1));
+                    }
+                    KeyCode::PageUp => {
+                        self.routing_scroll = self.routing_scroll.saturating_sub(10);
                     }
-                    KeyCode::Char('n') | KeyCode::Esc => {
-                        self.popup = Popup::None;
+                    KeyCode::Char('r') | KeyCode::Char('R') => {
+                        self.refresh_routing_info().await;
+                        self.routing_scroll = self.routing_scroll.min(self.routing_lines().saturating_sub(1));
                     }
                     _ => {}
                 }
                 Ok(())
             }
+            Popup::History => {
+                match key.code {
+                    KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('H') => self.popup = Popup::None,
+                    KeyCode::Char('j') | KeyCode::Down => {
+                        self.history_scroll = (self.history_scroll + 1).min(self.history.len().saturating_sub(1));
+                    }
+                    KeyCode::Char('k') | KeyCode::Up => {
+                        self.history_scroll = self.history_scroll.saturating_sub(1);
+                    }
+                    _ => {}
+                }
+                Ok(())
+            }
+            Popup::Confirm => {
+                match key.code {
+                    KeyCode::Char('y') | KeyCode::Enter => match self.confirm_kind.take() {
+                        Some(ConfirmKind::Overwrite(origin)) => {
+                            // Back to the editor first so a failed save keeps it open
+                            self.popup = origin;
+                            if origin == Popup::ManualConfig {
+                                self.save_manual_config(true).await?;
+                            } else {
+                                self.save_imported_config(true).await?;
+                            }
+                        }
+                        Some(ConfirmKind::KillSwitchOn) => {
+                            self.popup = Popup::None;
+                            self.schedule_kill_switch(true);
+                        }
+                        Some(ConfirmKind::WriteMtu(tunnel_name, mtu)) => {
+                            self.popup = Popup::None;
+                            self.write_tunnel_mtu(&tunnel_name, mtu).await;
+                        }
+                        Some(ConfirmKind::ReconnectEdited(tunnel_name)) => {
+                            self.popup = Popup::None;
+                            self.reconnect_edited(&tunnel_name).await?;
+                        }
+                        Some(ConfirmKind::RenameOverwrite(new_name)) => {
+                            self.rename_tunnel(&new_name).await?;
+                        }
+                        Some(ConfirmKind::DisconnectForeign(tunnel_name)) => {
+                            self.popup = Popup::None;
+                            self.start_tunnel_change(PendingAction::DisconnectTunnel, tunnel_name).await?;
+                        }
+                        Some(ConfirmKind::OverwriteBrokenConfig) => {
+                            AppConfig::allow_overwrite();
+                            self.banner = None;
+                            self.set_status("config.toml will be rewritten on the next change (old file in config.toml.bak)");
+                            self.popup = Popup::None;
+                        }
+                        Some(ConfirmKind::DisconnectAlways(_)) if self.operation_in_progress() => {
+                            self.popup = Popup::None;
+                            self.set_status(OPERATION_BUSY);
+                        }
+                        Some(ConfirmKind::DisconnectAlways(tunnel_name)) => {
+                            self.popup = Popup::None;
+                            self.suspend_always_rule()?;
+                            let paused = format!("Disconnecting {} - Always rule paused until the network changes...", tunnel_name);
+                            self.start_tunnel_change(PendingAction::DisconnectTunnel, tunnel_name).await?;
+                            self.set_status(paused);
+                        }
+                        None => {
+                            self.confirm_action().await?;
+                            self.popup = Popup::None;
+                        }
+                    },
+                    KeyCode::Char('n') | KeyCode::Esc => match self.confirm_kind.take() {
+                        Some(ConfirmKind::Overwrite(origin)) => {
+                            self.set_status("Not saved - choose another name");
+                            self.popup = origin;
+                        }
+                        Some(ConfirmKind::KillSwitchOn) => {
+                            self.set_status("Kill switch unchanged");
+                            self.popup = Popup::None;
+                        }
+                        Some(ConfirmKind::WriteMtu(tunnel_name, _)) => {
+                            self.set_status(format!("{} config unchanged", tunnel_name));
+                            self.popup = Popup::None;
+                        }
+                        Some(ConfirmKind::ReconnectEdited(tunnel_name)) => {
+                            self.set_status(format!("Config saved - changes apply the next time {} connects", tunnel_name));
+                            self.popup = Popup::None;
+                        }
+                        Some(ConfirmKind::RenameOverwrite(_)) => {
+                            self.set_status("Not renamed - choose another name");
+                            self.popup = Popup::Rename;
+                        }
+                        Some(ConfirmKind::DisconnectForeign(tunnel_name)) => {
+                            self.set_status(format!("{} left alone", tunnel_name));
+                            self.popup = Popup::None;
+                        }
+                        Some(ConfirmKind::OverwriteBrokenConfig) => {
+                            self.set_status("Changes won't be saved until config.toml is fixed");
+                            self.popup = Popup::None;
+                        }
+                        Some(ConfirmKind::DisconnectAlways(tunnel_name)) => {
+                            self.set_status(format!("{} stays connected", tunnel_name));
+                            self.popup = Popup::None;
+                        }
+                        None => self.popup = Popup::None,
+                    },
+                    _ => {}
+                }
+                Ok(())
+            }
             _ => Ok(()),
         }
     }
@@ -409,11 +1440,12 @@ impl App {
                 }
             }
             Section::Tunnels => {
-                if !self.tunnels.is_empty() {
-                    let old_selection = self.selected_tunnel;
-                    self.selected_tunnel = (self.selected_tunnel + 1) % self.tunnels.len();
+                // Tunnels inside collapsed groups have no row, so they're skipped
+                let rows = self.tunnel_rows();
+                if !rows.is_empty() {
+                    let pos = self.selected_row(&rows).unwrap_or(0);
                     // Load config if selection changed
-                    if old_selection != self.selected_tunnel {
+                    if self.select_row(&rows[(pos + 1) % rows.len()]) {
                         self.load_selected_tunnel_config().await;
                     }
                 }
@@ -432,11 +1464,11 @@ impl App {
                 }
             }
             Section::Tunnels => {
-                if !self.tunnels.is_empty() {
-                    let old_selection = self.selected_tunnel;
-                    self.selected_tunnel = self.selected_tunnel.checked_sub(1).unwrap_or(self.tunnels.len() - 1);
+                let rows = self.tunnel_rows();
+                if !rows.is_empty() {
+                    let pos = self.selected_row(&rows).unwrap_or(0);
                     // Load config if selection changed
-                    if old_selection != self.selected_tunnel {
+                    if self.select_row(&rows[pos.checked_sub(1).unwrap_or(rows.len() - 1)]) {
                         self.load_selected_tunnel_config().await;
                     }
                 }
@@ -447,125 +1479,732 @@ impl App {
         }
     }
 
+    /// Indices into `tunnels` that pass the current name/tag filter
+    pub fn visible_tunnel_indices(&self) -> Vec<usize> {
+        let filter = self.tunnel_filter.as_deref().unwrap_or("").to_lowercase();
+        self.tunnels
+            .iter()
+            .enumerate()
+            .filter(|(_, t)| {
+                filter.is_empty()
+                    || t.name.to_lowercase().contains(&filter)
+                    || self.tunnel_tags(&t.name).iter().any(|tag| tag.to_lowercase().contains(&filter))
+            })
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /// Visible tunnels as list rows, each group's members under its header
+    /// A group sits where its first tunnel was; a filter shows collapsed groups' matches too
+    pub fn tunnel_rows(&self) -> Vec<TunnelRow> {
+        let visible = self.visible_tunnel_indices();
+        let names: Vec<&str> = visible.iter().map(|&i| self.tunnels[i].name.as_str()).collect();
+        let groups = self.config.tunnel_groups(&names);
+
+        let mut order: Vec<(Option<String>, Vec<usize>)> = Vec::new();
+        for (&index, group) in visible.iter().zip(groups) {
+            let existing = group.as_ref().and_then(|g| order.iter().position(|(k, _)| k.as_ref() == Some(g)));
+            match existing {
+                Some(pos) => order[pos].1.push(index),
+                None => order.push((group, vec![index])),
+            }
+        }
+
+        let mut rows = Vec::new();
+        for (group, members) in order {
+            let Some(name) = group else {
+                rows.extend(members.into_iter().map(|index| TunnelRow::Tunnel { index, grouped: false }));
+                continue;
+            };
+            let collapsed = self.tunnel_filter.is_none() && self.config.collapsed_groups.contains(&name);
+            let tunnels: Vec<TunnelRow> = if collapsed {
+                Vec::new()
+            } else {
+                members.iter().map(|&index| TunnelRow::Tunnel { index, grouped: true }).collect()
+            };
+            rows.push(TunnelRow::Group { name, members, collapsed });
+            rows.extend(tunnels);
+        }
+        rows
+    }
+
+    /// Position of the highlighted group header or tunnel in `rows`
+    fn selected_row(&self, rows: &[TunnelRow]) -> Option<usize> {
+        rows.iter().position(|row| match row {
+            TunnelRow::Group { name, .. } => self.selected_group.as_ref() == Some(name),
+            TunnelRow::Tunnel { index, .. } => self.selected_group.is_none() && *index == self.selected_tunnel,
+        })
+    }
+
+    /// Highlight a row; true when another tunnel's config needs loading
+    fn select_row(&mut self, row: &TunnelRow) -> bool {
+        match row {
+            TunnelRow::Group { name, .. } => {
+                self.selected_group = Some(name.clone());
+                false
+            }
+            TunnelRow::Tunnel { index, .. } => {
+                self.selected_group = None;
+                let changed = *index != self.selected_tunnel;
+                self.selected_tunnel = *index;
+                changed
+            }
+        }
+    }
+
+    /// Move the highlighted tunnel past its neighbour in the list and store the order
+    /// Grouped tunnels move within their group; others move past whole groups
+    fn move_tunnel(&mut self, down: bool) -> Result<()> {
+        let rows = self.tunnel_rows();
+        let Some(pos) = self.selected_row(&rows) else {
+            return Ok(());
+        };
+        let TunnelRow::Tunnel { index: current, grouped } = rows[pos] else {
+            return Ok(());
+        };
+        let neighbour = if down { rows.get(pos + 1) } else { pos.checked_sub(1).map(|p| &rows[p]) };
+
+        // (index in `tunnels`, whether to swap with it) - a group sits where its first
+        // member is, so passing a group means moving next to that member instead
+        let target = match neighbour {
+            Some(TunnelRow::Tunnel { index, grouped: true }) if grouped => Some((*index, true)),
+            Some(TunnelRow::Tunnel { grouped: true, .. }) if !down => {
+                // Moving up out from under an expanded group: pass its header
+                rows[..pos].iter().rev().find_map(|row| match row {
+                    TunnelRow::Group { members, .. } => members.first().map(|&first| (first, false)),
+                    _ => None,
+                })
+            }
+            Some(TunnelRow::Tunnel { index, grouped: false }) if !grouped => Some((*index, true)),
+            Some(TunnelRow::Group { members, .. }) if !grouped => members.first().map(|&first| (first, false)),
+            _ => None,
+        };
+        let Some((target, swap)) = target else {
+            self.set_status(match (grouped, down) {
+                (true, true) => "Already last in its group",
+                (true, false) => "Already first in its group",
+                (false, true) => "Already at the bottom",
+                (false, false) => "Already at the top",
+            });
+            return Ok(());
+        };
+
+        // Number every tunnel in the new order
+        let mut names: Vec<String> = self.tunnels.iter().map(|t| t.name.clone()).collect();
+        if swap {
+            names.swap(current, target);
+        } else {
+            let name = names.remove(current);
+            let at = names.iter().position(|n| *n == self.tunnels[target].name).unwrap_or(0);
+            names.insert(if down { at + 1 } else { at }, name);
+        }
+        for (order, name) in names.iter().enumerate() {
+            if !self.config.known_tunnels.iter().any(|t| t.name == *name) {
+                let protocol = self.tunnels.iter().find(|t| t.name == *name).map(|t| t.protocol.clone()).unwrap_or_default();
+                self.config.known_tunnels.push(TunnelInfo { name: name.clone(), protocol, ..Default::default() });
+            }
+            if let Some(tunnel) = self.config.known_tunnels.iter_mut().find(|t| t.name == *name) {
+                tunnel.order = Some(order as u32);
+            }
+        }
+        self.config.save()?;
+
+        let selected = self.tunnels[current].name.clone();
+        crate::vpn::wireguard::sort_profiles(&mut self.tunnels, &self.config);
+        self.selected_tunnel = self.tunnels.iter().position(|t| t.name == selected).unwrap_or(0);
+        Ok(())
+    }
+
+    /// Collapse or expand the highlighted group; remembered in config.toml
+    fn toggle_selected_group(&mut self) -> Result<()> {
+        let Some(group) = self.selected_group.clone() else {
+            return Ok(());
+        };
+        match self.config.collapsed_groups.iter().position(|g| *g == group) {
+            Some(pos) => {
+                self.config.collapsed_groups.remove(pos);
+            }
+            None => self.config.collapsed_groups.push(group),
+        }
+        self.config.save()
+    }
+
+    /// Tags set on a tunnel (empty if none)
+    pub fn tunnel_tags(&self, name: &str) -> &[String] {
+        self.get_tunnel_info(name).map(|t| t.tags.as_slice()).unwrap_or(&[])
+    }
+
+    /// Note set on a tunnel
+    pub fn tunnel_note(&self, name: &str) -> Option<&str> {
+        self.get_tunnel_info(name).and_then(|t| t.note.as_deref())
+    }
+
+    /// The highlighted tunnel, if it is visible under the current filter
+    /// None while a group header is highlighted
+    fn selected_visible_tunnel(&self) -> Option<&WgProfile> {
+        if self.selected_group.is_none() && self.visible_tunnel_indices().contains(&self.selected_tunnel) {
+            self.tunnels.get(self.selected_tunnel)
+        } else {
+            None
+        }
+    }
+
+    /// Keep the selection on a row after the filter, list or groups change
+    async fn clamp_tunnel_selection(&mut self) {
+        if self.clamp_tunnel_row() {
+            self.load_selected_tunnel_config().await;
+        }
+    }
+
+    /// A tunnel hidden in a collapsed group hands the highlight to its header,
+    /// anything else gone falls back to the first row
+    /// Returns true when another tunnel's config needs loading
+    fn clamp_tunnel_row(&mut self) -> bool {
+        let rows = self.tunnel_rows();
+        if rows.is_empty() || self.selected_row(&rows).is_some() {
+            return false;
+        }
+        let folded = rows.iter().find(|row| matches!(
+            row,
+            TunnelRow::Group { members, collapsed: true, .. }
+                if self.selected_group.is_none() && members.contains(&self.selected_tunnel)
+        ));
+        let target = folded.unwrap_or(&rows[0]).clone();
+        self.select_row(&target)
+    }
+
+    /// Handle key input while the inline tunnel filter is being edited
+    async fn handle_filter_key(&mut self, key: KeyEvent) -> Result<()> {
+        match key.code {
+            KeyCode::Esc => {
+                // Clear filter and return to normal navigation
+                self.tunnel_filter = None;
+                self.tunnel_filter_active = false;
+            }
+            KeyCode::Enter => {
+                // Keep filter, return to normal navigation
+                self.tunnel_filter_active = false;
+                if self.tunnel_filter.as_deref() == Some("") {
+                    self.tunnel_filter = None;
+                }
+            }
+            KeyCode::Down => {
+                self.move_down().await;
+                return Ok(());
+            }
+            KeyCode::Up => {
+                self.move_up().await;
+                return Ok(());
+            }
+            KeyCode::Backspace => {
+                if let Some(filter) = self.tunnel_filter.as_mut() {
+                    filter.pop();
+                }
+            }
+            KeyCode::Char(c) => {
+                self.tunnel_filter.get_or_insert_with(String::new).push(c);
+            }
+            _ => return Ok(()),
+        }
+        self.clamp_tunnel_selection().await;
+        Ok(())
+    }
+
+    /// Handle typing in the config viewer search
+    fn handle_config_search_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Esc => {
+                self.config_search = None;
+                self.config_search_active = false;
+            }
+            KeyCode::Enter => {
+                // Keep the highlight, return to normal navigation
+                self.config_search_active = false;
+                if self.config_search.as_deref() == Some("") {
+                    self.config_search = None;
+                }
+            }
+            KeyCode::Down => self.jump_to_config_match(true),
+            KeyCode::Up => self.jump_to_config_match(false),
+            KeyCode::Backspace => {
+                if let Some(query) = self.config_search.as_mut() {
+                    query.pop();
+                }
+                self.jump_to_first_config_match();
+            }
+            KeyCode::Char(c) => {
+                self.config_search.get_or_insert_with(String::new).push(c);
+                self.jump_to_first_config_match();
+            }
+            _ => {}
+        }
+    }
+
+    /// Whether a config line matches the current search (case-insensitive)
+    pub fn config_line_matches(&self, line: &str) -> bool {
+        match self.config_search.as_deref() {
+            Some(query) if !query.is_empty() => line.to_lowercase().contains(&query.to_lowercase()),
+            _ => false,
+        }
+    }
+
+    /// Rows per page in the config viewer (before the first draw, assume a small page)
+    fn config_page(&self) -> usize {
+        match self.config_viewer_height.get() {
+            0 => 10,
+            height => height,
+        }
+    }
+
+    /// Largest scroll offset that still fills the viewer
+    fn max_config_scroll(&self) -> usize {
+        self.tunnel_config_content.lines().count().saturating_sub(self.config_page())
+    }
+
+    /// Scroll the config viewer by `delta` lines, clamped to the content
+    fn scroll_config(&mut self, delta: isize) {
+        let target = self.tunnel_config_scroll.saturating_add_signed(delta);
+        self.tunnel_config_scroll = target.min(self.max_config_scroll());
+    }
+
+    /// Scroll so the first matching line is at the top of the viewer
+    fn jump_to_first_config_match(&mut self) {
+        if let Some(index) = self.tunnel_config_content.lines().position(|l| self.config_line_matches(l)) {
+            self.tunnel_config_scroll = index.min(self.max_config_scroll());
+        }
+    }
+
+    /// Scroll to the next (or previous) matching line, wrapping around
+    fn jump_to_config_match(&mut self, forward: bool) {
+        let matches: Vec<usize> = self.tunnel_config_content
+            .lines()
+            .enumerate()
+            .filter(|(_, l)| self.config_line_matches(l))
+            .map(|(i, _)| i)
+            .collect();
+        if matches.is_empty() {
+            return;
+        }
+
+        let current = self.tunnel_config_scroll;
+        let target = if forward {
+            matches.iter().copied().find(|&i| i > current).unwrap_or(matches[0])
+        } else {
+            matches.iter().rev().copied().find(|&i| i < current).unwrap_or(matches[matches.len() - 1])
+        };
+        self.tunnel_config_scroll = target.min(self.max_config_scroll());
+    }
+
     /// Edit tunnel config in external editor (opens new terminal window)
     async fn edit_tunnel_config_external(&mut self) -> Result<()> {
-        if let Some(tunnel) = self.tunnels.get(self.selected_tunnel) {
+        if let Some(tunnel) = self.selected_visible_tunnel() {
             let tunnel_name = tunnel.name.clone();
             let was_connected = self.vpn_status.connected 
-                && self.vpn_status.interface.as_deref() == Some(&tunnel_name);
-            let config_path = format!("/etc/wireguard/{}.conf", tunnel_name);
+                && self.vpn_status.interface.as_deref() == Some(tunnel.interface());
             
             self.set_status(format!("Opening {} in editor...", tunnel_name));
             
             // Open a new terminal window for editing
             // This keeps the TUI intact and gives a clean prompt for sudo password
-            let edit_cmd = format!("sudoedit '{}'", config_path);
+            // (configs in the user's config_dir need no sudo)
+            let edit_cmd = match crate::vpn::wireguard::user_config_path(&tunnel_name) {
+                Some(path) => format!("${{EDITOR:-vi}} {}", shell_quote(&path.to_string_lossy())),
+                None => format!("sudoedit '/etc/wireguard/{}.conf'", tunnel_name),
+            };
             let title = format!("Edit {}", tunnel_name);
+            let before = crate::vpn::wireguard::read_config(&tunnel_name).await.ok();
             
-            // Try different terminal emulators (foot is common on Wayland/Omarchy)
-            let terminals = [
-                ("foot", vec!["--title", &title, "-W", "80x24", "-e", "sh", "-c", &edit_cmd]),
-                ("kitty", vec!["--title", &title, "-e", "sh", "-c", &edit_cmd]),
-                ("alacritty", vec!["--title", &title, "-e", "sh", "-c", &edit_cmd]),
-                ("gnome-terminal", vec!["--title", &title, "--geometry=80x24", "--", "sh", "-c", &edit_cmd]),
-                ("xterm", vec!["-title", &title, "-geometry", "80x24", "-e", "sh", "-c", &edit_cmd]),
-            ];
-            
-            let mut spawned = false;
-            for (term, args) in &terminals {
-                if let Ok(mut child) = std::process::Command::new(term)
-                    .args(args)
-                    .spawn()
-                {
-                    // Wait for the terminal/editor to close
-                    let _ = child.wait();
-                    spawned = true;
-                    break;
-                }
-            }
-            
-            if spawned {
+            if Self::run_in_terminal(&title, &edit_cmd) {
                 // Reload the config content
                 self.load_selected_tunnel_config().await;
+
+                // Closing the editor without saving shouldn't drop the connection
+                let after = crate::vpn::wireguard::read_config(&tunnel_name).await.ok();
+                if before.is_some() && before == after {
+                    self.set_status(format!("No changes to {}", tunnel_name));
+                    return Ok(());
+                }
                 
-                // If tunnel was connected, reconnect to apply changes
-                if was_connected {
-                    self.set_status(format!("Reconnecting {} to apply changes...", tunnel_name));
-                    let _ = crate::vpn::wireguard::disconnect().await;
-                    match crate::vpn::wireguard::connect(&tunnel_name).await {
-                        Ok(_) => {
-                            self.set_status(format!("Config updated & {} reconnected", tunnel_name));
-                        }
-                        Err(e) => {
-                            self.set_status(format!("Reconnect failed: {}", e));
-                        }
-                    }
-                    self.refresh().await?;
-                } else {
+                // If tunnel was connected, reconnect to apply changes (or ask first)
+                if !was_connected {
                     self.set_status(format!("Config reloaded for {}", tunnel_name));
+                } else if self.config.reconnect_on_edit {
+                    self.reconnect_edited(&tunnel_name).await?;
+                } else {
+                    self.set_status(format!("{} changed - reconnect now to apply? (y/n)", tunnel_name));
+                    self.confirm_kind = Some(ConfirmKind::ReconnectEdited(tunnel_name));
+                    self.popup = Popup::Confirm;
                 }
             } else {
-                self.set_status("No terminal emulator found (tried foot, kitty, alacritty, gnome-terminal, xterm)");
+                self.set_status(NO_TERMINAL_MESSAGE);
             }
         }
         Ok(())
     }
 
+    /// Reconnect a tunnel so an edited config takes effect; other tunnels stay up
+    async fn reconnect_edited(&mut self, tunnel_name: &str) -> Result<()> {
+        let interface = self.tunnel_interface(tunnel_name).to_string();
+        self.start_change(PendingChange {
+            network_id: String::new(),
+            network_name: String::new(),
+            tunnel_name: Some(tunnel_name.to_string()),
+            action: PendingAction::Restart { interface },
+        }).await
+    }
+
+    /// Show the selected tunnel's config read-only in $PAGER (less by default)
+    /// Nothing is written and the tunnel is left alone
+    fn view_tunnel_config_external(&mut self) {
+        let Some(tunnel) = self.selected_visible_tunnel() else {
+            return;
+        };
+        let tunnel_name = tunnel.name.clone();
+
+        // Read through the helper so the private key never touches disk
+        let view_cmd = match crate::vpn::wireguard::user_config_path(&tunnel_name) {
+            Some(path) => format!("${{PAGER:-less}} {}", shell_quote(&path.to_string_lossy())),
+            None => format!(
+                "sudo {} config-read '{}' | ${{PAGER:-less}}",
+                crate::vpn::HELPER_PATH, tunnel_name
+            ),
+        };
+        let title = format!("View {}", tunnel_name);
+
+        if Self::run_in_terminal(&title, &view_cmd) {
+            self.set_status(format!("Closed {}", tunnel_name));
+        } else {
+            self.set_status(NO_TERMINAL_MESSAGE);
+        }
+    }
+
+    /// Run a shell command in a new terminal window and wait for it to close
+    /// Returns false when no known terminal emulator could be started
+    fn run_in_terminal(title: &str, cmd: &str) -> bool {
+        // Try different terminal emulators (foot is common on Wayland/Omarchy)
+        let terminals = [
+            ("foot", vec!["--title", title, "-W", "80x24", "-e", "sh", "-c", cmd]),
+            ("kitty", vec!["--title", title, "-e", "sh", "-c", cmd]),
+            ("alacritty", vec!["--title", title, "-e", "sh", "-c", cmd]),
+            ("gnome-terminal", vec!["--title", title, "--geometry=80x24", "--", "sh", "-c", cmd]),
+            ("xterm", vec!["-title", title, "-geometry", "80x24", "-e", "sh", "-c", cmd]),
+        ];
+
+        for (term, args) in &terminals {
+            if let Ok(mut child) = std::process::Command::new(term)
+                .args(args)
+                .spawn()
+            {
+                // Wait for the terminal to close
+                let _ = child.wait();
+                return true;
+            }
+        }
+        false
+    }
+
     /// Connect to the selected tunnel now (one-time)
     async fn use_tunnel_now(&mut self) -> Result<()> {
-        if self.section != Section::Tunnels {
+        // The status line already shows the running change
+        if self.section != Section::Tunnels || self.operation_in_progress() {
             return Ok(());
         }
 
-        if let Some(tunnel) = self.tunnels.get(self.selected_tunnel) {
+        if let Some(tunnel) = self.selected_visible_tunnel() {
             let tunnel_name = tunnel.name.clone();
-            if self.vpn_status.connected && self.vpn_status.interface.as_deref() == Some(&tunnel_name) {
-                // Already connected, disconnect
-                // Disable kill switch when disconnecting
-                if self.kill_switch_enabled {
-                    let _ = crate::vpn::killswitch::disable().await;
-                    self.kill_switch_enabled = false;
+            // NetworkManager plugin VPNs don't show up in `wg show`
+            if tunnel.connected || self.vpn_status.is_up(tunnel.interface()) {
+                // Tearing down another tool's interface makes the two fight over it
+                if let Some(manager) = self.foreign_manager(&tunnel_name) {
+                    self.set_status(format!(
+                        "{} was brought up by {}, not tonneru - disconnecting may conflict with it. Disconnect anyway? (y/n)",
+                        tunnel_name, manager_label(&manager)
+                    ));
+                    self.confirm_kind = Some(ConfirmKind::DisconnectForeign(tunnel_name));
+                    self.popup = Popup::Confirm;
+                    return Ok(());
                 }
-                crate::vpn::wireguard::disconnect().await?;
-                self.set_status("Disconnected");
-            } else {
-                // Disconnect any existing first (and their kill switch)
-                if self.vpn_status.connected {
-                    if self.kill_switch_enabled {
-                        let _ = crate::vpn::killswitch::disable().await;
-                        self.kill_switch_enabled = false;
-                    }
-                    crate::vpn::wireguard::disconnect().await?;
+
+                // The daemon would reconnect it right away - offer to pause the rule instead
+                if let Some(network_name) = self.always_rule_network(&tunnel_name) {
+                    self.set_status(format!(
+                        "'{}' has an Always rule that reconnects {}. Disconnect and pause the rule until the network changes? (y/n)",
+                        network_name, tunnel_name
+                    ));
+                    self.confirm_kind = Some(ConfirmKind::DisconnectAlways(tunnel_name));
+                    self.popup = Popup::Confirm;
+                    return Ok(());
                 }
-                crate::vpn::wireguard::connect(&tunnel_name).await?;
-                
-                // Save last connected tunnel for auto-reconnect
-                self.config.last_connected = Some(tunnel_name.clone());
+
+                // Already connected, disconnect just this tunnel
+                self.start_tunnel_change(PendingAction::DisconnectTunnel, tunnel_name).await?;
+            } else {
+                // Switching drops any existing tunnel first (and its kill switch)
+                let action = if self.vpn_status.connected { PendingAction::Reconnect } else { PendingAction::Connect };
+                self.start_tunnel_change(action, tunnel_name).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Start a change picked in the Tunnels list right away, without a countdown
+    async fn start_tunnel_change(&mut self, action: PendingAction, tunnel_name: String) -> Result<()> {
+        let network = self.networks.iter().find(|n| n.connected);
+        let change = PendingChange {
+            network_id: network.map(|n| n.identifier()).unwrap_or_default(),
+            network_name: network.map(|n| n.name.clone()).unwrap_or_default(),
+            tunnel_name: Some(tunnel_name),
+            action,
+        };
+        self.start_change(change).await
+    }
+
+    /// Disconnect one tunnel, dropping the kill switch if it was the primary
+    async fn disconnect_tunnel_now(&mut self, tunnel_name: &str) -> Result<()> {
+        let is_primary = self.vpn_status.interface.as_deref() == Some(self.tunnel_interface(tunnel_name));
+        if is_primary && self.kill_switch_enabled && !self.config.persistent_kill_switch {
+            let _ = crate::vpn::killswitch::disable().await;
+            self.kill_switch_enabled = false;
+        }
+        crate::vpn::wireguard::disconnect_interface(tunnel_name).await?;
+        self.run_tunnel_hook(tunnel_name, false).await;
+        Ok(())
+    }
+
+    /// Run the tunnel's `on_connect` or `on_disconnect` command, if set
+    /// A failure replaces the status message and goes to the history
+    async fn run_tunnel_hook(&mut self, tunnel_name: &str, connected: bool) {
+        let (event, command) = match self.get_tunnel_info(tunnel_name) {
+            Some(info) if connected => ("connect", info.on_connect.clone()),
+            Some(info) => ("disconnect", info.on_disconnect.clone()),
+            None => return,
+        };
+        let Some(command) = command else {
+            return;
+        };
+        if let Err(e) = crate::vpn::run_hook(&command, tunnel_name, event).await {
+            let message = format!("{} on_{} hook failed: {}", tunnel_name, event, e);
+            self.log_event(EventKind::Error, message.clone());
+            self.set_status(message);
+        }
+    }
+
+    /// Tool managing `tunnel`'s interface when tonneru would take it down another way
+    fn foreign_manager(&self, tunnel_name: &str) -> Option<String> {
+        let tunnel = self.tunnels.iter().find(|t| t.name == tunnel_name)?;
+        let manager = self.vpn_status.interface_status(tunnel.interface())?.managed_by.clone()?;
+        crate::vpn::wireguard::is_foreign_manager(tunnel.source, &manager).then_some(manager)
+    }
+
+    /// Name of the current network when its Always rule would make the daemon reconnect `tunnel`
+    fn always_rule_network(&self, tunnel: &str) -> Option<String> {
+        if self.config.paused {
+            return None;
+        }
+        let network = self.networks.iter().find(|n| n.connected)?;
+        let rule = self.get_network_rule(network).filter(|r| r.always_vpn && !r.suspended)?;
+        let rule_tunnel = rule.tunnel_name.as_ref().or(self.config.default_profile.as_ref());
+        (rule_tunnel.map(String::as_str) == Some(tunnel)).then(|| network.name.clone())
+    }
+
+    /// Stop the current network's Always rule from acting until the network changes
+    fn suspend_always_rule(&mut self) -> Result<()> {
+        let Some(network) = self.networks.iter().find(|n| n.connected) else {
+            return Ok(());
+        };
+        let Some(identifier) = self.get_network_rule(network).map(|r| r.identifier.clone()) else {
+            return Ok(());
+        };
+
+        if let Some(rule) = self.network_rules.iter_mut().find(|r| r.identifier == identifier) {
+            rule.suspended = true;
+        }
+        self.config.network_rules = self.network_rules.clone();
+        self.config.save()
+    }
+
+    /// Check a tunnel's config before touching the current connection
+    /// Returns warnings to show once connected, or None after reporting why it can't connect
+    async fn preflight_tunnel(&mut self, tunnel: &str) -> Option<Vec<String>> {
+        match crate::vpn::wireguard::preflight(tunnel).await {
+            Ok(warnings) => Some(warnings),
+            Err(e) => {
+                self.log_event(EventKind::Error, format!("{}: {}", tunnel, e));
+                self.set_status(format!("Can't connect {}: {}", tunnel, e));
+                None
+            }
+        }
+    }
+
+    /// Follow up a "Connected" message with the first preflight warning
+    fn show_preflight_warning(&mut self, tunnel: &str, warnings: &[String]) {
+        if let Some(warning) = warnings.first() {
+            self.set_status(format!("Connected to {} - ⚠ {}", tunnel, warning));
+        }
+    }
+
+    /// Connect to the selected tunnel for a test; `tick` verifies and rolls it back
+    async fn start_connection_test(&mut self) -> Result<()> {
+        if self.pending_change.is_some() || self.operation_in_progress() {
+            return Ok(());
+        }
+        let Some(tunnel_name) = self.selected_visible_tunnel().map(|t| t.name.clone()) else {
+            return Ok(());
+        };
+        if self.vpn_status.is_up(self.tunnel_interface(&tunnel_name)) {
+            self.set_status(format!("{} is already connected", tunnel_name));
+            return Ok(());
+        }
+        // The kill switch is bound to the current interface and would block the test
+        if self.kill_switch_enabled {
+            self.set_status("Disable the kill switch before testing a tunnel");
+            return Ok(());
+        }
+
+        let previous = self.vpn_status.interface.as_deref()
+            .filter(|_| self.vpn_status.connected)
+            .map(|iface| self.interface_tunnel(iface).to_string());
+        // Not recorded as last_connected - this is only a trial
+        self.start_change(PendingChange {
+            network_id: String::new(),
+            network_name: String::new(),
+            tunnel_name: Some(tunnel_name),
+            action: PendingAction::TestConnect { previous },
+        }).await
+    }
+
+    /// Keys while a connection test runs: Enter/y keeps a passed test, Esc aborts
+    async fn handle_test_key(&mut self, key: KeyEvent) -> Result<()> {
+        let Some(test) = self.connection_test.clone() else {
+            return Ok(());
+        };
+        match key.code {
+            KeyCode::Enter | KeyCode::Char('y') if matches!(test.stage, TestStage::Reachable { .. }) => {
+                self.connection_test = None;
+                self.config.last_connected = Some(test.tunnel_name.clone());
                 let _ = self.config.save();
-                
-                // Apply the tunnel's kill switch setting
-                let tunnel_ks = self.get_tunnel_info(&tunnel_name)
+                self.set_status(format!("Keeping {}", test.tunnel_name));
+
+                // Now a regular connection - apply the tunnel's kill switch setting
+                let tunnel_ks = self.get_tunnel_info(&test.tunnel_name)
                     .map(|t| t.kill_switch)
                     .unwrap_or(false);
-                if tunnel_ks {
-                    if let Ok(_) = crate::vpn::killswitch::enable().await {
-                        self.kill_switch_enabled = true;
-                        self.set_status(format!("Connected to {} (kill switch on)", tunnel_name));
-                    } else {
-                        self.set_status(format!("Connected to {}", tunnel_name));
+                if tunnel_ks && crate::vpn::killswitch::enable().await.is_ok() {
+                    self.kill_switch_enabled = true;
+                    self.set_status(format!("Keeping {} (kill switch on)", test.tunnel_name));
+                }
+            }
+            KeyCode::Esc => {
+                self.roll_back_connection_test("Test cancelled").await?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Advance the connection test: handshake → routing → reachable → roll back
+    async fn advance_connection_test(&mut self) -> Result<()> {
+        let iface = self.connection_test.as_ref().map(|t| self.tunnel_interface(&t.tunnel_name).to_string());
+        let (Some(test), Some(iface)) = (self.connection_test.as_mut(), iface) else {
+            return Ok(());
+        };
+        let elapsed = test.stage_start.elapsed().as_secs();
+
+        match test.stage {
+            TestStage::Handshake => {
+                let handshake_done = self.vpn_status
+                    .interface_status(&iface)
+                    .is_some_and(|s| s.latest_handshake.is_some() && !s.handshake_stale);
+                if handshake_done {
+                    test.stage = TestStage::Routing;
+                    test.stage_start = Instant::now();
+                } else if elapsed >= TEST_HANDSHAKE_TIMEOUT_SECS {
+                    let msg = format!("Test failed: no handshake after {}s", TEST_HANDSHAKE_TIMEOUT_SECS);
+                    self.roll_back_connection_test(&msg).await?;
+                }
+            }
+            TestStage::Routing => {
+                let health = crate::vpn::wireguard::health_check(&self.vpn_status).await;
+                if health.can_reach_internet {
+                    if let Some(test) = self.connection_test.as_mut() {
+                        test.stage = TestStage::Reachable { latency_ms: health.latency_ms };
+                        test.stage_start = Instant::now();
                     }
                 } else {
-                    self.set_status(format!("Connected to {}", tunnel_name));
+                    let msg = if health.routing_configured {
+                        "Test failed: handshake OK but internet unreachable"
+                    } else {
+                        "Test failed: traffic is not routed through the tunnel"
+                    };
+                    self.roll_back_connection_test(msg).await?;
+                }
+                self.vpn_health = health;
+                self.last_health_check = Instant::now();
+            }
+            TestStage::Reachable { latency_ms } => {
+                if elapsed >= TEST_KEEP_SECS {
+                    let latency = latency_ms.map(|ms| format!(" ({} ms)", ms)).unwrap_or_default();
+                    let msg = format!("Test passed{} - rolled back", latency);
+                    self.roll_back_connection_test(&msg).await?;
                 }
             }
-            self.refresh().await?;
         }
         Ok(())
     }
 
+    /// Undo a connection test nobody kept when quitting in the middle of it,
+    /// waiting for the rollback (and a test tunnel still coming up) to finish
+    pub async fn cancel_connection_test(&mut self) -> Result<()> {
+        self.finish_operation().await?;
+        self.roll_back_connection_test("Test cancelled").await?;
+        self.finish_operation().await
+    }
+
+    /// Tear down the test tunnel and bring back whatever was connected before
+    async fn roll_back_connection_test(&mut self, msg: &str) -> Result<()> {
+        if self.operation_in_progress() {
+            return Ok(());
+        }
+        let Some(test) = self.connection_test.take() else {
+            return Ok(());
+        };
+        let interface = self.tunnel_interface(&test.tunnel_name).to_string();
+        self.start_change(PendingChange {
+            network_id: String::new(),
+            network_name: String::new(),
+            tunnel_name: Some(interface),
+            action: PendingAction::TestRollBack { previous: test.previous_tunnel, message: msg.to_string() },
+        }).await
+    }
+
+    /// Bring up the selected tunnel without tearing down the ones already up
+    async fn connect_alongside(&mut self) -> Result<()> {
+        if self.operation_in_progress() {
+            return Ok(());
+        }
+        let Some(tunnel_name) = self.selected_visible_tunnel().map(|t| t.name.clone()) else {
+            return Ok(());
+        };
+
+        if self.vpn_status.is_up(self.tunnel_interface(&tunnel_name)) {
+            self.set_status(format!("{} is already up", tunnel_name));
+            return Ok(());
+        }
+        // The kill switch only lets the primary interface through
+        if self.kill_switch_enabled {
+            self.set_status("Disable the kill switch to run tunnels side by side");
+            return Ok(());
+        }
+
+        crate::vpn::wireguard::connect_additional(&tunnel_name).await?;
+        self.set_status(format!("Connected to {} alongside existing tunnels", tunnel_name));
+        self.refresh().await?;
+        Ok(())
+    }
+
     /// Cycle through tunnel rules: none -> always -> never -> session -> none
     /// Works from Networks section, preserves tunnel selection
-    /// For active networks, schedules a pending change with 3-second countdown
+    /// For active networks, schedules a pending change with a countdown
     async fn cycle_tunnel_rule(&mut self) -> Result<()> {
         // Only works in Networks section
         if self.section != Section::Networks {
@@ -589,8 +2228,12 @@ impl App {
         // Remove old rule
         self.network_rules.retain(|rule| rule.identifier != identifier);
 
-        // Determine the current tunnel (preserve it across rule changes)
+        // Determine the current tunnel and priority (preserve them across rule changes)
         let current_tunnel = current_rule.as_ref().and_then(|r| r.tunnel_name.clone());
+        let current_priority = current_rule.as_ref().and_then(|r| r.priority);
+        let current_dns = current_rule.as_ref().and_then(|r| r.dns_servers.clone());
+        let current_open = current_rule.as_ref().is_some_and(|r| r.match_open_networks);
+        let current_kill_switch = current_rule.as_ref().and_then(|r| r.kill_switch);
 
         // Determine new rule and what action to take
         let (new_rule, action, status_text) = match current_rule {
@@ -605,6 +2248,11 @@ impl App {
                     always_vpn: true,
                     never_vpn: false,
                     session_vpn: false,
+                    priority: current_priority,
+                    dns_servers: current_dns,
+                    kill_switch: current_kill_switch,
+                    match_open_networks: current_open,
+                    suspended: false,
                 };
                 let action = if tunnel_name.is_some() { Some(PendingAction::Connect) } else { None };
                 (Some(rule), action, format!("{}: Always", network.name))
@@ -617,6 +2265,11 @@ impl App {
                     always_vpn: false,
                     never_vpn: true,
                     session_vpn: false,
+                    priority: current_priority,
+                    dns_servers: current_dns,
+                    kill_switch: current_kill_switch,
+                    match_open_networks: current_open,
+                    suspended: false,
                 };
                 (Some(rule), Some(PendingAction::Disconnect), format!("{}: Never", network.name))
             }
@@ -629,6 +2282,11 @@ impl App {
                     always_vpn: false,
                     never_vpn: false,
                     session_vpn: true,
+                    priority: current_priority,
+                    dns_servers: current_dns,
+                    kill_switch: current_kill_switch,
+                    match_open_networks: current_open,
+                    suspended: false,
                 };
                 let action = if tunnel.is_some() { Some(PendingAction::Connect) } else { None };
                 (Some(rule), action, format!("{}: Session", network.name))
@@ -715,6 +2373,10 @@ impl App {
             .as_ref()
             .map(|r| (r.always_vpn, r.never_vpn, r.session_vpn))
             .unwrap_or((true, false, false)); // Default to Always when first selecting tunnel
+        let priority = current_rule.as_ref().and_then(|r| r.priority);
+        let dns_servers = current_rule.as_ref().and_then(|r| r.dns_servers.clone());
+        let match_open_networks = current_rule.as_ref().is_some_and(|r| r.match_open_networks);
+        let kill_switch = current_rule.as_ref().and_then(|r| r.kill_switch);
 
         // Remove old rule and add new one
         self.network_rules.retain(|r| r.identifier != identifier);
@@ -724,6 +2386,11 @@ impl App {
             always_vpn,
             never_vpn,
             session_vpn,
+            priority,
+            dns_servers,
+            kill_switch,
+            match_open_networks,
+            suspended: false,
         });
 
         let rule_text = if always_vpn { "Always" } else if session_vpn { "Session" } else if never_vpn { "Never" } else { "-" };
@@ -745,13 +2412,227 @@ impl App {
         Ok(())
     }
 
+    /// Open the rule editor for the selected network
+    fn start_rule_editor(&mut self) {
+        let Some(network) = self.networks.get(self.selected_network) else {
+            return;
+        };
+        let network_id = network.identifier();
+        let rule = self.network_rules.iter().find(|r| r.identifier == network_id);
+        self.rule_form = Some(RuleForm {
+            network_name: network.name.clone(),
+            mode: RuleMode::of(rule),
+            tunnel: rule.and_then(|r| r.tunnel_name.clone()),
+            priority: rule.and_then(|r| r.priority),
+            dns: rule.and_then(|r| r.dns_servers.as_ref()).map(|s| s.join(", ")).unwrap_or_default(),
+            kill_switch: rule.and_then(|r| r.kill_switch),
+            match_open_networks: rule.is_some_and(|r| r.match_open_networks),
+            network_id,
+            field: 0,
+        });
+        self.popup = Popup::EditRule;
+    }
+
+    /// Handle key input for the rule editor popup
+    /// ↑↓ pick a field, ←→ change it, typing edits DNS servers, Enter saves
+    fn handle_rule_form_key(&mut self, key: KeyEvent) -> Result<()> {
+        let Some(form) = self.rule_form.as_mut() else {
+            self.popup = Popup::None;
+            return Ok(());
+        };
+        match key.code {
+            KeyCode::Esc => {
+                self.rule_form = None;
+                self.popup = Popup::None;
+            }
+            KeyCode::Enter => self.save_rule_form()?,
+            KeyCode::Up | KeyCode::BackTab => form.field = (form.field + RULE_FORM_FIELDS - 1) % RULE_FORM_FIELDS,
+            KeyCode::Down | KeyCode::Tab => form.field = (form.field + 1) % RULE_FORM_FIELDS,
+            KeyCode::Char(c) if form.field == 3 => form.dns.push(c),
+            KeyCode::Backspace if form.field == 3 => {
+                form.dns.pop();
+            }
+            KeyCode::Left | KeyCode::Right | KeyCode::Char(' ') => {
+                let forward = key.code != KeyCode::Left;
+                match form.field {
+                    0 => form.mode = cycle_option(&RuleMode::ALL, &form.mode, forward),
+                    1 => {
+                        let options: Vec<Option<String>> = std::iter::once(None)
+                            .chain(self.tunnels.iter().map(|t| Some(t.name.clone())))
+                            .collect();
+                        form.tunnel = cycle_option(&options, &form.tunnel, forward);
+                    }
+                    // None is the lowest priority, below 1
+                    2 => form.priority = match (form.priority, forward) {
+                        (None, true) => Some(1),
+                        (Some(p), true) => Some(p.saturating_add(1)),
+                        (Some(p), false) if p > 1 => Some(p - 1),
+                        _ => None,
+                    },
+                    4 => form.kill_switch = cycle_option(&[None, Some(true), Some(false)], &form.kill_switch, forward),
+                    5 => form.match_open_networks = !form.match_open_networks,
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Store the rule editor's settings; on the connected network the VPN
+    /// follows the new rule after the usual countdown
+    fn save_rule_form(&mut self) -> Result<()> {
+        let Some(form) = self.rule_form.clone() else {
+            return Ok(());
+        };
+
+        let mut dns_servers = Vec::new();
+        for server in form.dns.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+            if server.parse::<std::net::IpAddr>().is_err() {
+                self.set_status(format!("Invalid DNS server: {} (e.g. 10.0.0.53)", server));
+                return Ok(());
+            }
+            dns_servers.push(server.to_string());
+        }
+
+        let old_mode = RuleMode::of(self.network_rules.iter().find(|r| r.identifier == form.network_id));
+        self.network_rules.retain(|r| r.identifier != form.network_id);
+        // A rule with nothing set is the same as no rule
+        let empty = form.mode == RuleMode::None && form.tunnel.is_none() && form.priority.is_none()
+            && dns_servers.is_empty() && form.kill_switch.is_none() && !form.match_open_networks;
+        if !empty {
+            self.network_rules.push(NetworkRule {
+                identifier: form.network_id.clone(),
+                tunnel_name: form.tunnel.clone(),
+                always_vpn: form.mode == RuleMode::Always,
+                never_vpn: form.mode == RuleMode::Never,
+                session_vpn: form.mode == RuleMode::Session,
+                priority: form.priority,
+                dns_servers: (!dns_servers.is_empty()).then_some(dns_servers),
+                kill_switch: form.kill_switch,
+                match_open_networks: form.match_open_networks,
+                suspended: false,
+            });
+        }
+        self.config.network_rules = self.network_rules.clone();
+        self.config.save()?;
+
+        self.rule_form = None;
+        self.popup = Popup::None;
+        self.set_status(format!("{}: {}", form.network_name, form.mode.label()));
+
+        let is_active = self.networks.iter().any(|n| n.connected && n.identifier() == form.network_id);
+        if !is_active {
+            return Ok(());
+        }
+        let target = form.tunnel.clone().or_else(|| self.config.default_profile.clone());
+        let current = self.vpn_status.interface.clone().filter(|_| self.vpn_status.connected);
+        let action = match (form.mode, &target, &current) {
+            (mode, Some(_), None) if mode.connects() => Some(PendingAction::Connect),
+            (mode, Some(tunnel), Some(iface)) if mode.connects() && self.tunnel_interface(tunnel) != iface => {
+                Some(PendingAction::Reconnect)
+            }
+            (RuleMode::Never, _, Some(_)) => Some(PendingAction::Disconnect),
+            (RuleMode::None, _, Some(_)) if old_mode.connects() => Some(PendingAction::Disconnect),
+            _ => None,
+        };
+        if let Some(action) = action {
+            self.schedule_change(PendingChange {
+                network_id: form.network_id,
+                network_name: form.network_name,
+                tunnel_name: target,
+                action,
+            });
+        }
+        Ok(())
+    }
+
+    /// Raise or lower the priority of the selected network's rule
+    /// Priority decides which rule wins when several match (e.g. SSID vs device rule)
+    fn adjust_rule_priority(&mut self, raise: bool) -> Result<()> {
+        // Only works in Networks section
+        if self.section != Section::Networks {
+            return Ok(());
+        }
+
+        let network = match self.networks.get(self.selected_network) {
+            Some(n) => n.clone(),
+            None => return Ok(()),
+        };
+
+        let identifier = network.identifier();
+        let rule = match self.network_rules.iter_mut().find(|r| r.identifier == identifier) {
+            Some(r) => r,
+            None => {
+                self.set_status("No rule to prioritize. Press 'r' first.");
+                return Ok(());
+            }
+        };
+
+        // None is the lowest priority; lowering 1 goes back to None
+        rule.priority = match (rule.priority, raise) {
+            (None, true) => Some(1),
+            (Some(p), true) => Some(p.saturating_add(1)),
+            (Some(p), false) if p > 1 => Some(p - 1),
+            _ => None,
+        };
+
+        let priority_text = match rule.priority {
+            Some(p) => format!("priority {}", p),
+            None => "default priority".to_string(),
+        };
+        self.set_status(format!("{}: {}", network.name, priority_text));
+
+        self.config.network_rules = self.network_rules.clone();
+        self.config.save()?;
+        Ok(())
+    }
+
     fn start_file_browser(&mut self) {
+        self.export_tunnel = None;
         self.popup = Popup::FileBrowser;
         self.browser_path = dirs::home_dir().unwrap_or_else(|| std::path::PathBuf::from("/"));
         self.browser_selected = 0;
+        self.browser_recursive = false;
         self.refresh_browser();
     }
 
+    /// Open the file browser to pick a folder for exporting the selected tunnel
+    fn start_export_browser(&mut self) {
+        let Some(name) = self.selected_visible_tunnel().map(|t| t.name.clone()) else {
+            return;
+        };
+        let export_dir = crate::vpn::export::default_export_dir();
+        // Start in ~/tonneru-backup when possible, else home
+        self.browser_path = if std::fs::create_dir_all(&export_dir).is_ok() {
+            export_dir
+        } else {
+            dirs::home_dir().unwrap_or_else(|| std::path::PathBuf::from("/"))
+        };
+        self.export_tunnel = Some(name);
+        self.popup = Popup::FileBrowser;
+        self.browser_selected = 0;
+        self.browser_recursive = false;
+        self.refresh_browser();
+    }
+
+    /// Export the pending tunnel into the browser's current directory
+    async fn export_to_current_dir(&mut self) {
+        let Some(name) = self.export_tunnel.take() else {
+            return;
+        };
+        match crate::vpn::export::export_profile(&name, &self.browser_path).await {
+            Ok(path) => {
+                self.set_status(format!("Exported to {} - contains the private key, keep it safe", path.display()));
+                self.popup = Popup::None;
+            }
+            Err(e) => {
+                self.set_status(format!("Export failed: {:#}", e));
+                self.export_tunnel = Some(name); // Stay in the browser to pick another folder
+            }
+        }
+    }
+
     /// Start manual config creation popup
     fn start_manual_config(&mut self) {
         self.popup = Popup::ManualConfig;
@@ -769,44 +2650,312 @@ impl App {
                 self.input_buffer.clear();
                 self.config_preview.clear();
             }
-            KeyCode::Tab | KeyCode::BackTab => {
-                // Toggle between name field (0) and content field (1)
-                self.preview_field = if self.preview_field == 0 { 1 } else { 0 };
+            KeyCode::Tab | KeyCode::BackTab => {
+                // Toggle between name field (0) and content field (1)
+                self.preview_field = if self.preview_field == 0 { 1 } else { 0 };
+            }
+            KeyCode::F(2) => {
+                // F2 to save (when content is entered)
+                if !self.input_buffer.is_empty() && !self.config_preview.is_empty() {
+                    self.save_manual_config(false).await?;
+                } else {
+                    self.set_status("Enter name and config content first");
+                }
+            }
+            KeyCode::F(3) => self.insert_new_keypair(),
+            KeyCode::Enter => {
+                if self.preview_field == 0 {
+                    // Move from name to content field
+                    self.preview_field = 1;
+                } else {
+                    // In content field, Enter adds newline
+                    self.config_preview.push('\n');
+                }
+            }
+            KeyCode::Backspace => {
+                if self.preview_field == 0 {
+                    self.input_buffer.pop();
+                } else {
+                    self.config_preview.pop();
+                }
+            }
+            KeyCode::Char(c) => {
+                if self.preview_field == 0 {
+                    // Name field: only valid filename characters
+                    if c.is_alphanumeric() || c == '-' || c == '_' {
+                        self.input_buffer.push(c);
+                    }
+                } else {
+                    // Content field: any character
+                    self.config_preview.push(c);
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Put a fresh PrivateKey into the config being typed and show (and copy) its public key
+    fn insert_new_keypair(&mut self) {
+        match crate::vpn::wireguard::generate_keypair() {
+            Ok((private_key, public_key)) => {
+                self.config_preview = crate::vpn::wireguard::set_private_key(&self.config_preview, &private_key);
+                self.preview_field = 1;
+                let copied = if Self::write_clipboard(&public_key) { " (copied)" } else { "" };
+                self.set_status(format!("Public key for the server: {}{}", public_key, copied));
+            }
+            Err(e) => self.set_status(format!("Cannot generate keys: {:#}", e)),
+        }
+    }
+
+    /// Suspend or resume the daemon's rule-based connects/disconnects
+    /// The daemon picks the flag up from config.toml on its next cycle
+    fn toggle_daemon_pause(&mut self) -> Result<()> {
+        self.config.paused = !self.config.paused;
+        self.config.save()?;
+        self.set_status(if self.config.paused {
+            "Daemon paused - no automatic connects/disconnects (D resumes)"
+        } else {
+            "Daemon resumed"
+        });
+        Ok(())
+    }
+
+    /// Open the tags/note editor for the selected tunnel
+    fn start_label_editor(&mut self) {
+        let Some(name) = self.selected_visible_tunnel().map(|t| t.name.clone()) else {
+            return;
+        };
+        self.label_tags = self.tunnel_tags(&name).join(", ");
+        self.label_note = self.tunnel_note(&name).unwrap_or("").to_string();
+        self.label_group = self.get_tunnel_info(&name).and_then(|t| t.group.clone()).unwrap_or_default();
+        self.label_tunnel = name;
+        self.preview_field = 0;
+        self.popup = Popup::Label;
+    }
+
+    /// Handle key input for the label editor popup
+    /// Tab switches between tags, note and group, Enter saves, Esc cancels
+    fn handle_label_key(&mut self, key: KeyEvent) -> Result<()> {
+        let field = match self.preview_field {
+            0 => &mut self.label_tags,
+            1 => &mut self.label_note,
+            _ => &mut self.label_group,
+        };
+        match key.code {
+            KeyCode::Esc => self.popup = Popup::None,
+            KeyCode::Tab => self.preview_field = (self.preview_field + 1) % 3,
+            KeyCode::BackTab => self.preview_field = (self.preview_field + 2) % 3,
+            KeyCode::Backspace => {
+                field.pop();
+            }
+            KeyCode::Enter => {
+                let mut tags: Vec<String> = Vec::new();
+                for tag in self.label_tags.split(',').map(str::trim).filter(|t| !t.is_empty()) {
+                    if !tags.iter().any(|t| t == tag) {
+                        tags.push(tag.to_string());
+                    }
+                }
+                let note = self.label_note.trim();
+                let note = (!note.is_empty()).then(|| note.to_string());
+                let group = self.label_group.trim();
+                let group = (!group.is_empty()).then(|| group.to_string());
+
+                let name = self.label_tunnel.clone();
+                let tunnel = self.ensure_tunnel_info(&name);
+                tunnel.tags = tags;
+                tunnel.note = note;
+                tunnel.group = group;
+                self.config.save()?;
+                self.popup = Popup::None;
+                self.clamp_tunnel_row();
+                self.set_status(format!("Updated labels for {}", name));
+            }
+            KeyCode::Char(c) => field.push(c),
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Open the rename popup for the selected tunnel
+    fn start_rename(&mut self) {
+        let Some((name, source)) = self.selected_visible_tunnel().map(|t| (t.name.clone(), t.source)) else {
+            return;
+        };
+        if source == ProfileSource::NetworkManager {
+            self.set_status(format!("{} is a NetworkManager connection - rename it there", name));
+            return;
+        }
+        self.input_buffer = name.clone();
+        self.rename_from = name;
+        self.popup = Popup::Rename;
+    }
+
+    /// Handle key input for the rename popup
+    /// Enter renames (asking first if the name is taken), Esc cancels
+    async fn handle_rename_key(&mut self, key: KeyEvent) -> Result<()> {
+        match key.code {
+            KeyCode::Esc => {
+                self.popup = Popup::None;
+                self.input_buffer.clear();
+            }
+            KeyCode::Backspace => {
+                self.input_buffer.pop();
+            }
+            KeyCode::Enter => {
+                let new_name = crate::vpn::wireguard::sanitize_profile_name(&self.input_buffer);
+                if new_name.is_empty() {
+                    self.set_status("Enter a name (letters, digits, '-' and '_')");
+                } else if new_name == self.rename_from {
+                    self.popup = Popup::None;
+                    self.input_buffer.clear();
+                } else if crate::vpn::wireguard::profile_exists(&new_name).await {
+                    self.set_status(format!(
+                        "Tunnel '{}' already exists. Replace it with '{}'? (y/n)",
+                        new_name, self.rename_from
+                    ));
+                    self.confirm_kind = Some(ConfirmKind::RenameOverwrite(new_name));
+                    self.popup = Popup::Confirm;
+                } else {
+                    self.rename_tunnel(&new_name).await?;
+                }
+            }
+            KeyCode::Char(c) => self.input_buffer.push(c),
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Save `rename_from`'s config as `new_name`, delete the old one and repoint
+    /// rules and settings; a connected tunnel comes back up under the new interface
+    async fn rename_tunnel(&mut self, new_name: &str) -> Result<()> {
+        let old_name = std::mem::take(&mut self.rename_from);
+        self.popup = Popup::None;
+        self.input_buffer.clear();
+
+        let content = match crate::vpn::wireguard::read_config(&old_name).await {
+            Ok(content) => content,
+            Err(e) => {
+                self.set_status(format!("Error: {}", e));
+                return Ok(());
+            }
+        };
+
+        // wg-quick can't rename a live interface, so take it down first
+        let was_up = self.vpn_status.is_up(&old_name);
+        let was_primary = was_up && self.vpn_status.interface.as_deref() == Some(old_name.as_str());
+        let kill_switch = was_primary && self.kill_switch_enabled;
+        if was_up {
+            self.disconnect_tunnel_now(&old_name).await?;
+        }
+
+        if let Err(e) = crate::vpn::wireguard::add_profile(new_name, &content).await {
+            self.set_status(format!("Rename failed: {:#}", e));
+            if was_up {
+                let _ = crate::vpn::wireguard::connect_additional(&old_name).await;
+            }
+            return self.refresh().await;
+        }
+        let removed = crate::vpn::wireguard::delete_profile(&old_name).await;
+
+        self.config.network_rules = self.network_rules.clone();
+        self.config.rename_tunnel(&old_name, new_name);
+        self.network_rules = self.config.network_rules.clone();
+        self.config.save()?;
+
+        let mut status = format!("Renamed '{}' to '{}'", old_name, new_name);
+        if let Err(e) = removed {
+            status = format!("{} - old config left behind: {}", status, e);
+        }
+        if was_up {
+            let reconnect = if was_primary {
+                crate::vpn::wireguard::connect(new_name).await
+            } else {
+                crate::vpn::wireguard::connect_additional(new_name).await
+            };
+            match reconnect {
+                Ok(_) => {
+                    if kill_switch && crate::vpn::killswitch::enable().await.is_ok() {
+                        self.kill_switch_enabled = true;
+                    }
+                    status.push_str(" and reconnected");
+                }
+                Err(e) => status = format!("{} - reconnect failed: {}", status, e),
+            }
+        }
+        self.set_status(status);
+
+        self.refresh().await?;
+        if let Some(index) = self.tunnels.iter().position(|t| t.name == new_name) {
+            self.selected_tunnel = index;
+            self.selected_group = None;
+        }
+        self.clamp_tunnel_row();
+        self.load_selected_tunnel_config().await;
+        Ok(())
+    }
+
+    /// Open the kill switch allowlist editor popup
+    fn start_allowlist_editor(&mut self) {
+        self.popup = Popup::Allowlist;
+        self.input_buffer.clear();  // Will hold the CIDR being typed
+        self.allowlist_selected = 0;
+        self.allowlist_dirty = false;
+    }
+
+    /// Handle key input for the allowlist editor popup
+    /// Typing builds a CIDR, Enter adds it, Delete removes the selected entry
+    async fn handle_allowlist_key(&mut self, key: KeyEvent) -> Result<()> {
+        let len = self.config.killswitch_allowlist.len();
+        match key.code {
+            KeyCode::Esc => {
+                self.popup = Popup::None;
+                self.input_buffer.clear();
+                
+                // Re-apply so the running kill switch picks up the new allowlist
+                if self.allowlist_dirty && self.kill_switch_enabled {
+                    match crate::vpn::killswitch::enable().await {
+                        Ok(_) => self.set_status("Kill switch allowlist applied"),
+                        Err(e) => self.set_status(format!("Error: {}", e)),
+                    }
+                }
+            }
+            KeyCode::Down if len > 0 => {
+                self.allowlist_selected = (self.allowlist_selected + 1) % len;
             }
-            KeyCode::F(2) => {
-                // F2 to save (when content is entered)
-                if !self.input_buffer.is_empty() && !self.config_preview.is_empty() {
-                    self.save_manual_config().await?;
-                } else {
-                    self.set_status("Enter name and config content first");
-                }
+            KeyCode::Up if len > 0 => {
+                self.allowlist_selected = self.allowlist_selected.checked_sub(1).unwrap_or(len - 1);
             }
             KeyCode::Enter => {
-                if self.preview_field == 0 {
-                    // Move from name to content field
-                    self.preview_field = 1;
+                let cidr = self.input_buffer.trim().to_string();
+                if !crate::config::is_valid_cidr(&cidr) {
+                    self.set_status(format!("Invalid CIDR: {} (e.g. 192.168.1.0/24)", cidr));
+                } else if self.config.killswitch_allowlist.contains(&cidr) {
+                    self.set_status(format!("{} already allowed", cidr));
                 } else {
-                    // In content field, Enter adds newline
-                    self.config_preview.push('\n');
+                    self.config.killswitch_allowlist.push(cidr.clone());
+                    self.config.save()?;
+                    self.allowlist_selected = self.config.killswitch_allowlist.len() - 1;
+                    self.allowlist_dirty = true;
+                    self.input_buffer.clear();
+                    self.set_status(format!("Allowed {}", cidr));
                 }
             }
-            KeyCode::Backspace => {
-                if self.preview_field == 0 {
-                    self.input_buffer.pop();
-                } else {
-                    self.config_preview.pop();
+            KeyCode::Delete if len > 0 => {
+                let removed = self.config.killswitch_allowlist.remove(self.allowlist_selected);
+                self.config.save()?;
+                if self.allowlist_selected >= self.config.killswitch_allowlist.len() {
+                    self.allowlist_selected = self.config.killswitch_allowlist.len().saturating_sub(1);
                 }
+                self.allowlist_dirty = true;
+                self.set_status(format!("Removed {}", removed));
             }
-            KeyCode::Char(c) => {
-                if self.preview_field == 0 {
-                    // Name field: only valid filename characters
-                    if c.is_alphanumeric() || c == '-' || c == '_' {
-                        self.input_buffer.push(c);
-                    }
-                } else {
-                    // Content field: any character
-                    self.config_preview.push(c);
-                }
+            KeyCode::Backspace => {
+                self.input_buffer.pop();
+            }
+            // CIDR characters only (IPv4/IPv6 digits, separators, prefix)
+            KeyCode::Char(c) if c.is_ascii_hexdigit() || c == '.' || c == ':' || c == '/' => {
+                self.input_buffer.push(c);
             }
             _ => {}
         }
@@ -814,10 +2963,14 @@ impl App {
     }
 
     /// Save the manually created config
-    async fn save_manual_config(&mut self) -> Result<()> {
+    async fn save_manual_config(&mut self, overwrite: bool) -> Result<()> {
         let name = self.input_buffer.clone();
         let content = self.config_preview.clone();
 
+        if !overwrite && self.confirm_overwrite(&name, Popup::ManualConfig).await {
+            return Ok(());
+        }
+
         match crate::vpn::wireguard::add_profile(&name, &content).await {
             Ok(_) => {
                 self.set_status(format!("Created tunnel: {}", name));
@@ -827,7 +2980,8 @@ impl App {
                 self.config_preview.clear();
             }
             Err(e) => {
-                self.set_status(format!("Failed: {}", e));
+                // Show the full chain so validation errors point at the bad line
+                self.set_status(format!("Failed: {:#}", e));
                 // Don't close popup on error
             }
         }
@@ -846,8 +3000,14 @@ impl App {
             });
         }
 
-        // Read directory contents
-        if let Ok(entries) = std::fs::read_dir(&self.browser_path) {
+        // Find mode: every config below this folder, as relative paths
+        if self.browser_recursive {
+            let mut found = Vec::new();
+            find_configs(&self.browser_path, &self.browser_path, BROWSER_FIND_DEPTH, &mut found);
+            found.sort_by_key(|entry| entry.name.to_lowercase());
+            self.browser_entries.extend(found);
+        } else if let Ok(entries) = std::fs::read_dir(&self.browser_path) {
+            // Read directory contents
             let mut dirs: Vec<BrowserEntry> = Vec::new();
             let mut files: Vec<BrowserEntry> = Vec::new();
 
@@ -876,8 +3036,8 @@ impl App {
             }
 
             // Sort alphabetically
-            dirs.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
-            files.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
+            dirs.sort_by_key(|a| a.name.to_lowercase());
+            files.sort_by_key(|a| a.name.to_lowercase());
 
             self.browser_entries.extend(dirs);
             self.browser_entries.extend(files);
@@ -892,17 +3052,24 @@ impl App {
         match key.code {
             KeyCode::Esc | KeyCode::Char('q') => {
                 self.popup = Popup::None;
+                self.export_tunnel = None;
             }
-            KeyCode::Char('j') | KeyCode::Down => {
-                if !self.browser_entries.is_empty() {
-                    self.browser_selected = (self.browser_selected + 1) % self.browser_entries.len();
-                }
+            // Export mode: save into the directory being shown
+            KeyCode::Char('s') if self.export_tunnel.is_some() => {
+                self.export_to_current_dir().await;
             }
-            KeyCode::Char('k') | KeyCode::Up => {
-                if !self.browser_entries.is_empty() {
-                    self.browser_selected = self.browser_selected.checked_sub(1)
-                        .unwrap_or(self.browser_entries.len() - 1);
-                }
+            // Toggle find mode: configs in subdirectories as one flat list
+            KeyCode::Char('F') if self.export_tunnel.is_none() => {
+                self.browser_recursive = !self.browser_recursive;
+                self.browser_selected = 0;
+                self.refresh_browser();
+            }
+            KeyCode::Char('j') | KeyCode::Down if !self.browser_entries.is_empty() => {
+                self.browser_selected = (self.browser_selected + 1) % self.browser_entries.len();
+            }
+            KeyCode::Char('k') | KeyCode::Up if !self.browser_entries.is_empty() => {
+                self.browser_selected = self.browser_selected.checked_sub(1)
+                    .unwrap_or(self.browser_entries.len() - 1);
             }
             KeyCode::Enter | KeyCode::Char(' ') => {
                 if let Some(entry) = self.browser_entries.get(self.browser_selected).cloned() {
@@ -910,7 +3077,7 @@ impl App {
                         self.browser_path = entry.path;
                         self.browser_selected = 0;
                         self.refresh_browser();
-                    } else {
+                    } else if self.export_tunnel.is_none() {
                         // Load file and show preview
                         self.load_config_preview(&entry.path)?;
                     }
@@ -936,19 +3103,12 @@ impl App {
     fn load_config_preview(&mut self, path: &std::path::Path) -> Result<()> {
         match std::fs::read_to_string(path) {
             Ok(content) => {
-                if content.contains("[Interface]") && content.contains("[Peer]") {
-                    self.config_preview = content;
-                    self.preview_name = path
-                        .file_stem()
-                        .and_then(|s| s.to_str())
-                        .unwrap_or("tunnel")
-                        .to_string();
-                    self.input_buffer = self.preview_name.clone();
-                    self.popup = Popup::ConfigPreview;
-                    self.preview_field = 0;  // Start on name field
-                } else {
-                    self.set_status("Not a valid WireGuard config");
-                }
+                let name = path
+                    .file_stem()
+                    .and_then(|s| s.to_str())
+                    .unwrap_or("tunnel")
+                    .to_string();
+                self.open_config_preview(content, name, false);
             }
             Err(e) => {
                 self.set_status(format!("Cannot read: {}", e));
@@ -957,10 +3117,149 @@ impl App {
         Ok(())
     }
 
+    /// Import a config from the clipboard (wl-paste, falling back to xclip)
+    fn import_from_clipboard(&mut self) {
+        match Self::read_clipboard() {
+            Some(content) if !content.trim().is_empty() => {
+                self.open_config_preview(content, "imported".to_string(), true);
+            }
+            Some(_) => self.set_status("Clipboard is empty"),
+            None => self.set_status("Cannot read clipboard (install wl-clipboard or xclip)"),
+        }
+    }
+
+    /// Read clipboard text, or None if no clipboard tool is available
+    /// A tool that runs but fails (e.g. nothing copied) counts as an empty clipboard
+    fn read_clipboard() -> Option<String> {
+        let tools: [(&str, &[&str]); 2] = [
+            ("wl-paste", &["--no-newline"]),
+            ("xclip", &["-selection", "clipboard", "-o"]),
+        ];
+        let mut tool_found = false;
+        for (cmd, args) in tools {
+            match std::process::Command::new(cmd).args(args).output() {
+                Ok(output) if output.status.success() => {
+                    return Some(String::from_utf8_lossy(&output.stdout).to_string());
+                }
+                Ok(_) => tool_found = true,
+                Err(_) => {}
+            }
+        }
+        tool_found.then(String::new)
+    }
+
+    /// Copy text with wl-copy or xclip
+    /// Returns false if no clipboard tool could copy it
+    fn write_clipboard(text: &str) -> bool {
+        use std::io::Write;
+        use std::process::{Command, Stdio};
+
+        let tools: [(&str, &[&str]); 2] = [
+            ("wl-copy", &[]),
+            ("xclip", &["-selection", "clipboard"]),
+        ];
+        for (cmd, args) in tools {
+            let Ok(mut child) = Command::new(cmd)
+                .args(args)
+                .stdin(Stdio::piped())
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .spawn()
+            else {
+                continue;
+            };
+            if let Some(mut stdin) = child.stdin.take() {
+                let _ = stdin.write_all(text.as_bytes());
+            }
+            // Both tools fork to keep serving the selection, so this returns quickly
+            if child.wait().is_ok_and(|status| status.success()) {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Copy the public IP, or the tunnel endpoint when no IP is known
+    fn copy_public_ip(&mut self) {
+        let text = self.public_ip.clone()
+            .or_else(|| self.public_ipv6.clone())
+            .or_else(|| self.vpn_status.endpoint.clone());
+        let Some(text) = text else {
+            self.set_status("No public IP or endpoint to copy");
+            return;
+        };
+
+        if Self::write_clipboard(&text) {
+            self.set_status(format!("Copied {}", text));
+        } else {
+            self.set_status("Could not copy - install wl-clipboard or xclip");
+        }
+    }
+
+    /// Copy the selected tunnel's config for sharing
+    /// Keys are replaced with <redacted> unless `redact_copied_configs = false`
+    async fn copy_tunnel_config(&mut self) {
+        let Some(name) = self.selected_visible_tunnel().map(|t| t.name.clone()) else {
+            return;
+        };
+        let config = match crate::vpn::wireguard::read_config(&name).await {
+            Ok(config) => config,
+            Err(e) => {
+                self.set_status(format!("Error: {}", e));
+                return;
+            }
+        };
+
+        let redact = self.config.redact_copied_configs;
+        let text = if redact { crate::vpn::export::redact_keys(&config) } else { config };
+        if !Self::write_clipboard(&text) {
+            self.set_status("Could not copy - install wl-clipboard or xclip");
+        } else if redact {
+            self.set_status(format!("Copied {} config (keys redacted)", name));
+        } else {
+            self.set_status(format!("Copied {} config INCLUDING its private key - don't share it", name));
+        }
+    }
+
+    /// Check the selected tunnel's config for common mistakes and list the findings
+    async fn lint_selected_tunnel(&mut self) {
+        let Some(name) = self.selected_visible_tunnel().map(|t| t.name.clone()) else {
+            return;
+        };
+        let config = match crate::vpn::wireguard::read_config(&name).await {
+            Ok(config) => config,
+            Err(e) => {
+                self.set_status(format!("Error: {}", e));
+                return;
+            }
+        };
+
+        let local_addrs = tokio::task::spawn_blocking(crate::vpn::wireguard::local_ipv4_addrs)
+            .await
+            .unwrap_or_default();
+        self.lint_findings = crate::vpn::lint::lint(&config, &local_addrs);
+        self.lint_tunnel = name;
+        self.popup = Popup::Lint;
+    }
+
+    /// Validate config content and open the import preview
+    fn open_config_preview(&mut self, content: String, name: String, from_clipboard: bool) {
+        if content.contains("[Interface]") && content.contains("[Peer]") {
+            self.config_preview = content;
+            self.preview_name = name;
+            self.input_buffer = self.preview_name.clone();
+            self.preview_from_clipboard = from_clipboard;
+            self.popup = Popup::ConfigPreview;
+            self.preview_field = 0;  // Start on name field
+        } else {
+            self.set_status("Not a valid WireGuard config");
+        }
+    }
+
     async fn handle_preview_key(&mut self, key: KeyEvent) -> Result<()> {
         match key.code {
             KeyCode::Esc => {
-                self.popup = Popup::FileBrowser;
+                self.popup = if self.preview_from_clipboard { Popup::None } else { Popup::FileBrowser };
                 self.config_preview.clear();
                 self.input_buffer.clear();
             }
@@ -971,44 +3270,42 @@ impl App {
             KeyCode::Enter => {
                 if self.preview_field == 1 {
                     // On action bar, Enter = save
-                    self.save_imported_config().await?;
+                    self.save_imported_config(false).await?;
                 } else {
                     // On name field, Enter moves to action bar
                     self.preview_field = 1;
                 }
             }
-            KeyCode::Backspace => {
-                if self.preview_field == 0 {
-                    self.input_buffer.pop();
-                }
+            KeyCode::Backspace if self.preview_field == 0 => {
+                self.input_buffer.pop();
             }
-            KeyCode::Char(c) => {
-                if self.preview_field == 0 {
-                    // Only allow valid filename characters in name field
-                    if c.is_alphanumeric() || c == '-' || c == '_' {
-                        self.input_buffer.push(c);
-                    }
-                }
+            // Only allow valid filename characters in name field
+            KeyCode::Char(c) if self.preview_field == 0 && (c.is_alphanumeric() || c == '-' || c == '_') => {
+                self.input_buffer.push(c);
             }
             _ => {}
         }
         Ok(())
     }
 
-    async fn save_imported_config(&mut self) -> Result<()> {
+    async fn save_imported_config(&mut self, overwrite: bool) -> Result<()> {
         let name = if self.input_buffer.is_empty() {
             self.preview_name.clone()
         } else {
             self.input_buffer.clone()
         };
 
+        if !overwrite && self.confirm_overwrite(&name, Popup::ConfigPreview).await {
+            return Ok(());
+        }
+
         match crate::vpn::wireguard::add_profile(&name, &self.config_preview).await {
             Ok(_) => {
                 self.set_status(format!("Saved tunnel: {}", name));
                 let _ = self.refresh().await;
             }
             Err(e) => {
-                self.set_status(format!("Failed: {}", e));
+                self.set_status(format!("Failed: {:#}", e));
                 return Ok(()); // Don't close popup on error
             }
         }
@@ -1019,12 +3316,33 @@ impl App {
         Ok(())
     }
 
+    /// Ask before replacing an existing tunnel; true if the confirm popup was opened
+    /// `origin` is the popup to return to (and save from) once answered
+    async fn confirm_overwrite(&mut self, name: &str, origin: Popup) -> bool {
+        if !crate::vpn::wireguard::profile_exists(name).await {
+            return false;
+        }
+        let safe_name = crate::vpn::wireguard::sanitize_profile_name(name);
+        self.set_status(format!("Overwrite existing tunnel '{}'? (y/n)", safe_name));
+        self.confirm_kind = Some(ConfirmKind::Overwrite(origin));
+        self.popup = Popup::Confirm;
+        true
+    }
+
     async fn delete_selection(&mut self) -> Result<()> {
         match self.section {
             Section::Tunnels => {
-                if let Some(tunnel) = self.tunnels.get(self.selected_tunnel) {
-                    self.input_buffer = tunnel.name.clone(); // Store name for confirm
-                    self.set_status(format!("Delete '{}'? (y/n)", tunnel.name));
+                if let Some(name) = self.selected_visible_tunnel().map(|t| t.name.clone()) {
+                    // Rules that auto-connect this tunnel fall back to no tunnel
+                    let rules = self.network_rules.iter()
+                        .filter(|r| r.tunnel_name.as_deref() == Some(name.as_str()))
+                        .count();
+                    match rules {
+                        0 => self.set_status(format!("Delete '{}'? (y/n)", name)),
+                        1 => self.set_status(format!("Delete '{}'? 1 network rule references it and will lose its tunnel. (y/n)", name)),
+                        n => self.set_status(format!("Delete '{}'? {} network rules reference it and will lose their tunnel. (y/n)", name, n)),
+                    }
+                    self.input_buffer = name; // Store name for confirm
                     self.popup = Popup::Confirm;
                 }
             }
@@ -1049,19 +3367,31 @@ impl App {
              let network_name = self.input_buffer.clone();
              self.input_buffer.clear();
              
-             if let Some(network) = self.networks.iter().find(|n| n.name == network_name) {
-                 // 1. Remove rules
-                 let identifier = network.identifier();
-                 self.network_rules.retain(|r| r.identifier != identifier);
+             if let Some(network) = self.networks.iter().find(|n| n.name == network_name).cloned() {
+                 // Forgets it in iwd and NetworkManager and drops its rule
+                 let result = crate::network::forget_network(&network, &mut self.network_rules).await;
                  self.config.network_rules = self.network_rules.clone();
                  self.config.save()?;
-                 
-                 // 2. Forget network from system
-                 match crate::network::forget_network(network).await {
-                     Ok(_) => {
-                         self.set_status(format!("Forgot network '{}'", network_name));
+                 crate::config::seen::update(|seen| {
+                     let before = seen.len();
+                     seen.retain(|s| s.identifier != network.identifier());
+                     seen.len() != before
+                 })?;
+                 if network.away {
+                     self.networks.retain(|n| n.identifier() != network.identifier());
+                     self.selected_network = self.selected_network.min(self.networks.len().saturating_sub(1));
+                 }
+
+                 match result {
+                     Ok(forgot) => {
+                         let rule = if forgot.rule_removed { ", rule removed" } else { "" };
+                         self.set_status(format!("Forgot network '{}' ({}{})", network_name, forgot.backends(), rule));
                          self.refresh().await?;
                      }
+                     // Out of range and unknown to iwd/NM: only the history entry was left
+                     Err(_) if network.away => {
+                         self.set_status(format!("Removed '{}' from seen networks", network_name));
+                     }
                      Err(e) => {
                          self.set_status(format!("Error: {}", e));
                      }
@@ -1105,17 +3435,416 @@ impl App {
         Ok(())
     }
 
-    async fn refresh(&mut self) -> Result<()> {
-        self.tunnels = crate::vpn::wireguard::list_profiles().await.unwrap_or_default();
-        self.vpn_status = crate::vpn::wireguard::get_status().await.unwrap_or_default();
-        self.networks = crate::network::get_networks().await.unwrap_or_default();
-        Ok(())
-    }
-
-    async fn toggle_kill_switch(&mut self) -> Result<()> {
-        // Toggle the visual state immediately for feedback
-        let new_state = !self.kill_switch_enabled;
-        
+    /// Re-read `wg show`; who manages an interface is looked up once, when it comes up
+    async fn refresh_vpn_status(&mut self) {
+        let mut status = crate::vpn::wireguard::get_status(&self.config).await.unwrap_or_default();
+        if let Err(e) = crate::vpn::wireguard::detect_managers(&mut status, &self.vpn_status).await {
+            tracing::warn!("{:#}", e);
+        }
+        self.vpn_status = status;
+    }
+
+    async fn refresh(&mut self) -> Result<()> {
+        self.tunnels = crate::vpn::wireguard::list_profiles().await.unwrap_or_default();
+        self.refresh_vpn_status().await;
+        if self.last_network_scan.is_none_or(|at| at.elapsed().as_secs() >= NETWORK_CACHE_SECS) {
+            self.start_network_scan();
+        }
+        self.start_psk_scan();
+        self.clamp_tunnel_selection().await;
+        Ok(())
+    }
+
+    /// Read the configs of tunnels not yet in `psk_tunnels` in a background task
+    fn start_psk_scan(&mut self) {
+        if self.psk_scan.is_some() {
+            return;
+        }
+        let names: Vec<String> = self.tunnels.iter()
+            .filter(|t| t.source == ProfileSource::WgQuick && !self.psk_tunnels.contains_key(&t.name))
+            .map(|t| t.name.clone())
+            .collect();
+        if names.is_empty() {
+            return;
+        }
+        self.psk_scan = Some(tokio::spawn(async move {
+            let mut found = Vec::new();
+            for name in names {
+                let psk = crate::vpn::wireguard::read_config(&name).await
+                    .ok()
+                    .and_then(|config| crate::vpn::wireguard::config_uses_psk(&config));
+                found.push((name, psk));
+            }
+            found
+        }));
+    }
+
+    /// Take the result of a finished PSK scan
+    async fn poll_psk_scan(&mut self) {
+        if !self.psk_scan.as_ref().is_some_and(|scan| scan.is_finished()) {
+            return;
+        }
+        if let Some(scan) = self.psk_scan.take() {
+            match scan.await {
+                Ok(found) => self.psk_tunnels.extend(found),
+                Err(e) => tracing::warn!("PSK scan failed: {}", e),
+            }
+        }
+    }
+
+    /// Whether the tunnel's peers all have a PresharedKey, once its config was read
+    pub fn tunnel_psk(&self, name: &str) -> Option<bool> {
+        self.psk_tunnels.get(name).copied().flatten()
+    }
+
+    /// Enumerate networks in a background task; `tick` picks up the result
+    /// iwctl/nmcli can take a second or more, which would freeze key handling
+    fn start_network_scan(&mut self) {
+        if self.network_scan.is_none() {
+            self.network_scan = Some(tokio::spawn(async {
+                crate::network::get_networks().await.unwrap_or_default()
+            }));
+        }
+    }
+
+    /// Find the selected tunnel's path MTU in the background (pings bypass the tunnel)
+    fn start_mtu_probe(&mut self) {
+        if self.mtu_probe.is_some() {
+            return;
+        }
+        let Some((tunnel_name, iface)) = self.selected_visible_tunnel().map(|t| (t.name.clone(), t.interface().to_string())) else {
+            return;
+        };
+        let Some(endpoint) = self.vpn_status.interface_status(&iface).and_then(|s| s.endpoint.clone()) else {
+            self.set_status(format!("Connect {} first to probe its MTU", tunnel_name));
+            return;
+        };
+
+        self.set_status(format!("Probing MTU to {}...", endpoint));
+        self.mtu_probe = Some(tokio::task::spawn_blocking(move || {
+            let via = crate::network::default_route_interface(Some(&iface));
+            let result = crate::vpn::mtu::probe(&endpoint, via.as_deref());
+            (tunnel_name, result)
+        }));
+    }
+
+    /// Take a finished MTU probe and offer to save the recommendation
+    async fn poll_mtu_probe(&mut self) {
+        if !self.mtu_probe.as_ref().is_some_and(|probe| probe.is_finished()) {
+            return;
+        }
+        let Some(probe) = self.mtu_probe.take() else {
+            return;
+        };
+        let (tunnel_name, result) = match probe.await {
+            Ok(done) => done,
+            Err(e) => {
+                tracing::warn!("MTU probe failed: {}", e);
+                return;
+            }
+        };
+        let probe = match result {
+            Ok(probe) => probe,
+            Err(e) => {
+                self.set_status(format!("MTU probe failed: {}", e));
+                return;
+            }
+        };
+        self.mtu_result = Some((tunnel_name.clone(), probe));
+
+        let current = crate::vpn::wireguard::read_config(&tunnel_name).await
+            .ok()
+            .and_then(|config| crate::vpn::mtu::config_mtu(&config));
+        if current == Some(probe.recommended) {
+            self.set_status(format!("{} already uses MTU {} (path MTU {})", tunnel_name, probe.recommended, probe.path_mtu));
+            return;
+        }
+        self.set_status(format!(
+            "Path MTU {} - write MTU = {} into {}? (y/n)",
+            probe.path_mtu, probe.recommended, tunnel_name
+        ));
+        self.confirm_kind = Some(ConfirmKind::WriteMtu(tunnel_name, probe.recommended));
+        self.popup = Popup::Confirm;
+    }
+
+    /// Ping every visible WireGuard tunnel's endpoint in the background
+    /// The tunnel filter narrows the comparison, e.g. to one provider's tag
+    fn start_latency_ranking(&mut self) {
+        if self.latency_probe.is_some() {
+            return;
+        }
+        let tunnels: Vec<String> = self.visible_tunnel_indices()
+            .into_iter()
+            .map(|i| &self.tunnels[i])
+            .filter(|t| t.protocol == "wireguard" && t.source == ProfileSource::WgQuick)
+            .map(|t| t.name.clone())
+            .collect();
+        if tunnels.len() < 2 {
+            self.set_status("Need at least two WireGuard tunnels to compare");
+            return;
+        }
+
+        self.set_status(format!("Pinging {} endpoints...", tunnels.len()));
+        let exclude = self.vpn_status.interface.clone();
+        self.latency_probe = Some(tokio::spawn(crate::vpn::fastest::rank(tunnels, exclude)));
+    }
+
+    /// Take a finished ranking and show it
+    async fn poll_latency_ranking(&mut self) {
+        if !self.latency_probe.as_ref().is_some_and(|probe| probe.is_finished()) {
+            return;
+        }
+        let Some(probe) = self.latency_probe.take() else {
+            return;
+        };
+        let ranked = match probe.await {
+            Ok(ranked) => ranked,
+            Err(e) => {
+                tracing::warn!("Latency ranking failed: {}", e);
+                return;
+            }
+        };
+        if ranked.iter().all(|e| e.latency_ms.is_none()) {
+            self.set_status("No tunnel endpoint answered ping");
+            return;
+        }
+        self.latency_ranking = ranked;
+        self.latency_selected = 0;
+        self.popup = Popup::Fastest;
+    }
+
+    /// Take a finished latency ping into the history
+    async fn poll_latency_sample(&mut self) {
+        if !self.latency_sample.as_ref().is_some_and(|ping| ping.is_finished()) {
+            return;
+        }
+        let Some(ping) = self.latency_sample.take() else {
+            return;
+        };
+        // A sample from before a disconnect would skew the new connection's stats
+        if self.vpn_status.connected {
+            self.push_latency_sample(ping.await.ok().flatten());
+        }
+    }
+
+    /// Take a finished public IP lookup, scheduling a retry with backoff if it found nothing
+    async fn poll_public_ip(&mut self) {
+        if !self.ip_fetch.as_ref().is_some_and(|lookup| lookup.is_finished()) {
+            return;
+        }
+        let Some(lookup) = self.ip_fetch.take() else {
+            return;
+        };
+        // Either family may be missing (IPv4-only or IPv6-only tunnel)
+        let (ipv4, ipv6) = lookup.await.unwrap_or_default();
+        if !self.vpn_status.connected {
+            return;
+        }
+        self.public_ip = ipv4;
+        self.public_ipv6 = ipv6;
+
+        let got_ip = self.public_ip.is_some() || self.public_ipv6.is_some();
+        if got_ip || self.ip_fetch_attempts >= IP_FETCH_MAX_ATTEMPTS {
+            self.ip_fetch_pending = false;
+        } else {
+            let delay = IP_FETCH_RETRY_SECS << (self.ip_fetch_attempts - 1);
+            self.ip_fetch_next = Instant::now() + Duration::from_secs(delay);
+            tracing::debug!("Public IP lookup failed, retrying in {}s", delay);
+        }
+    }
+
+    /// Take a finished leak check; a leak shows as a banner until a check passes
+    async fn poll_leak_probe(&mut self) {
+        if !self.leak_probe.as_ref().is_some_and(|probe| probe.is_finished()) {
+            return;
+        }
+        let Some(probe) = self.leak_probe.take() else {
+            return;
+        };
+        let leaked = probe.await.unwrap_or(false);
+        if leaked && !self.vpn_health.leak_detected {
+            self.log_event(EventKind::HealthFailure, "Traffic leaks past the kill switch");
+        }
+        self.vpn_health.leak_detected = leaked && self.kill_switch_enabled;
+    }
+
+    /// Banner above the info line: a kill switch leak outranks setup problems
+    pub fn banner_text(&self) -> Option<&str> {
+        if self.vpn_health.leak_detected {
+            return Some("⚠ LEAK: traffic gets out without the VPN although the kill switch is on");
+        }
+        self.banner.as_deref()
+    }
+
+    fn push_latency_sample(&mut self, sample: Option<u32>) {
+        if self.latency_samples.len() >= LATENCY_HISTORY {
+            self.latency_samples.pop_front();
+        }
+        self.latency_samples.push_back(sample);
+    }
+
+    /// Handle key input for the latency ranking popup
+    /// Enter connects the highlighted tunnel (the fastest, unless moved)
+    async fn handle_fastest_key(&mut self, key: KeyEvent) -> Result<()> {
+        let len = self.latency_ranking.len();
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('n') => {
+                self.popup = Popup::None;
+                self.latency_ranking.clear();
+            }
+            KeyCode::Char('j') | KeyCode::Down if len > 0 => {
+                self.latency_selected = (self.latency_selected + 1) % len;
+            }
+            KeyCode::Char('k') | KeyCode::Up if len > 0 => {
+                self.latency_selected = self.latency_selected.checked_sub(1).unwrap_or(len - 1);
+            }
+            KeyCode::Enter | KeyCode::Char('y') => {
+                let Some(choice) = self.latency_ranking.get(self.latency_selected).map(|e| e.tunnel.clone()) else {
+                    return Ok(());
+                };
+                self.popup = Popup::None;
+                self.latency_ranking.clear();
+                if self.vpn_status.is_up(self.tunnel_interface(&choice)) {
+                    self.set_status(format!("Already connected to {}", choice));
+                    return Ok(());
+                }
+                let Some(index) = self.tunnels.iter().position(|t| t.name == choice) else {
+                    return Ok(());
+                };
+                self.section = Section::Tunnels;
+                self.selected_tunnel = index;
+                self.selected_group = None;
+                self.use_tunnel_now().await?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Save a probed MTU into a tunnel's [Interface] section; applies on the next connect
+    async fn write_tunnel_mtu(&mut self, tunnel_name: &str, mtu: u32) {
+        let result = async {
+            let config = crate::vpn::wireguard::read_config(tunnel_name).await?;
+            crate::vpn::wireguard::add_profile(tunnel_name, &crate::vpn::mtu::set_config_mtu(&config, mtu)).await
+        }.await;
+        match result {
+            Ok(()) => {
+                self.load_selected_tunnel_config().await;
+                self.set_status(format!("MTU {} saved to {} - reconnect to apply", mtu, tunnel_name));
+            }
+            Err(e) => self.set_status(format!("Could not save MTU: {}", e)),
+        }
+    }
+
+    /// Whether a network scan is still running (shown as "scanning…")
+    pub fn is_scanning_networks(&self) -> bool {
+        self.network_scan.is_some()
+    }
+
+    /// Take the result of a finished network scan
+    async fn poll_network_scan(&mut self) {
+        if !self.network_scan.as_ref().is_some_and(|scan| scan.is_finished()) {
+            return;
+        }
+        let Some(scan) = self.network_scan.take() else {
+            return;
+        };
+        match scan.await {
+            Ok(networks) => {
+                self.networks = networks;
+                self.remember_networks();
+                // First scan after launch: reselect the network from the last session
+                if let Some(id) = self.restore_network.take() {
+                    if let Some(index) = self.networks.iter().position(|n| n.identifier() == id) {
+                        self.selected_network = index;
+                    }
+                }
+                self.selected_network = self.selected_network.min(self.networks.len().saturating_sub(1));
+            }
+            Err(e) => tracing::warn!("Network scan failed: {}", e),
+        }
+        self.last_network_scan = Some(Instant::now());
+    }
+
+    /// Add a fresh scan to the seen-networks history, then list remembered networks
+    /// that are out of range after the real ones
+    fn remember_networks(&mut self) {
+        // The daemon counts connections in the same file
+        let now = crate::network::status_socket::now_secs();
+        let networks = &self.networks;
+        let seen = crate::config::seen::update(|seen| {
+            let mut changed = false;
+            for network in networks {
+                changed |= crate::config::seen::remember(seen, network, false, now);
+            }
+            changed
+        }).unwrap_or_else(|e| {
+            tracing::warn!("Could not save seen networks: {}", e);
+            Vec::new()
+        });
+
+        let away: Vec<NetworkInfo> = seen.iter()
+            .filter(|seen| !self.networks.iter().any(|n| n.identifier() == seen.identifier))
+            .map(|seen| seen.to_network())
+            .collect();
+        self.networks.extend(away);
+    }
+
+    async fn toggle_kill_switch(&mut self) -> Result<()> {
+        let new_state = !self.kill_switch_enabled;
+
+        // Show exactly what will be blocked before turning it on
+        if new_state {
+            match crate::vpn::killswitch::preview().await {
+                Ok(preview) => {
+                    self.killswitch_preview = Some(preview);
+                    self.killswitch_preview_scroll = 0;
+                    self.popup = Popup::KillSwitchPreview;
+                    return Ok(());
+                }
+                Err(e) => tracing::warn!("Kill switch preview unavailable: {:#}", e),
+            }
+        }
+
+        // With no tunnel up, turning it on cuts off everything - ask first
+        if new_state && !self.vpn_status.connected {
+            self.set_status("No VPN is connected - this will block all internet. Continue? (y/n)");
+            self.confirm_kind = Some(ConfirmKind::KillSwitchOn);
+            self.popup = Popup::Confirm;
+            return Ok(());
+        }
+
+        self.schedule_kill_switch(new_state);
+        Ok(())
+    }
+
+    /// Handle keys in the kill switch preview: scroll, then confirm or cancel
+    fn handle_killswitch_preview_key(&mut self, key: KeyEvent) -> Result<()> {
+        match key.code {
+            KeyCode::Char('y') | KeyCode::Enter => {
+                self.killswitch_preview = None;
+                self.popup = Popup::None;
+                self.schedule_kill_switch(true);
+            }
+            KeyCode::Char('n') | KeyCode::Esc | KeyCode::Char('q') => {
+                self.killswitch_preview = None;
+                self.popup = Popup::None;
+                self.set_status("Kill switch unchanged");
+            }
+            KeyCode::Char('j') | KeyCode::Down => {
+                let lines = self.killswitch_preview.as_ref().map_or(0, |p| p.ruleset.lines().count());
+                self.killswitch_preview_scroll = (self.killswitch_preview_scroll + 1).min(lines.saturating_sub(1));
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                self.killswitch_preview_scroll = self.killswitch_preview_scroll.saturating_sub(1);
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Start the countdown to turn the kill switch on or off
+    fn schedule_kill_switch(&mut self, new_state: bool) {
         // Schedule the change with countdown
         let action = if new_state {
             PendingAction::KillSwitchOn
@@ -1134,19 +3863,34 @@ impl App {
         self.set_status(format!(
             "Kill switch → {} ({}s)",
             if new_state { "ON" } else { "OFF" },
-            COUNTDOWN_SECONDS
+            self.countdown_secs()
         ));
-        
-        Ok(())
     }
 
 
     pub async fn tick(&mut self) -> Result<()> {
+        // Pick up a finished network scan, or start the periodic one
+        self.poll_network_scan().await;
+        self.poll_mtu_probe().await;
+        self.poll_latency_ranking().await;
+        self.poll_latency_sample().await;
+        self.poll_leak_probe().await;
+        self.poll_public_ip().await;
+        self.poll_psk_scan().await;
+        self.poll_config_check().await;
+        self.poll_operation().await?;
+        if self.last_config_check.elapsed().as_secs() >= CONFIG_CHECK_SECS {
+            self.start_config_check();
+        }
+        if self.last_network_scan.is_some_and(|at| at.elapsed().as_secs() >= NETWORK_SCAN_SECS) {
+            self.start_network_scan();
+        }
+
         // Handle pending change countdown
         if let Some(start) = self.countdown_start {
             let elapsed = start.elapsed().as_secs();
-            let remaining = COUNTDOWN_SECONDS.saturating_sub(elapsed);
-            self.countdown_seconds = remaining as u8;
+            let remaining = self.countdown_secs().saturating_sub(elapsed);
+            self.countdown_seconds = remaining;
 
             if remaining == 0 {
                 // Time's up - apply the pending change
@@ -1154,39 +3898,68 @@ impl App {
             }
         }
         
-        // Clear status message after 3 seconds
+        // Clear status message after the configured timeout
+        // (kept while the Confirm popup shows it as the question)
         if let Some(time) = self.status_message_time {
-            if time.elapsed().as_secs() >= 3 {
+            if self.popup != Popup::Confirm && self.operation.is_none() && time.elapsed().as_secs() >= self.config.status_message_secs {
                 self.status_message = None;
                 self.status_message_time = None;
             }
         }
 
+        // Follow theme switches (Omarchy theme or config.toml) without a restart
+        if self.last_theme_check.elapsed().as_secs() >= THEME_CHECK_SECS {
+            self.last_theme_check = Instant::now();
+            if Theme::source_stamp() != self.theme_stamp {
+                self.reload_theme();
+            }
+        }
+
         // Refresh VPN status for live traffic stats (every 1 second to avoid too many sudo calls)
         if self.last_status_refresh.elapsed().as_millis() >= 1000 {
             let was_connected = self.vpn_status.connected;
-            self.vpn_status = crate::vpn::wireguard::get_status().await.unwrap_or_default();
+            self.refresh_vpn_status().await;
             self.last_status_refresh = Instant::now();
             
+            self.track_transfer();
+            self.check_quota();
+            self.refresh_active_scope().await;
+
             // Trigger IP fetch when VPN just connected
             if !was_connected && self.vpn_status.connected {
                 self.ip_fetch_pending = true;
+                self.ip_fetch_attempts = 0;
+                self.ip_fetch_next = Instant::now();
             }
             
-            // Clear IP when VPN disconnects
+            // Clear IP (and stop retrying) when VPN disconnects
             if was_connected && !self.vpn_status.connected {
                 self.public_ip = None;
+                self.public_ipv6 = None;
+                self.ip_fetch_pending = false;
+                if let Some(lookup) = self.ip_fetch.take() {
+                    lookup.abort();
+                }
             }
         }
         
-        // Fetch public IP if pending (do this after a short delay to allow connection to stabilize)
+        // Step any running connection test with the fresh status
+        if self.connection_test.is_some() {
+            self.advance_connection_test().await?;
+        }
+        
+        // Fetch public IP if pending, retrying with backoff while the connection stabilizes
         // Skip if kill switch is enabled (traffic is blocked, will timeout)
-        if self.ip_fetch_pending && self.vpn_status.connected && !self.kill_switch_enabled {
-            self.ip_fetch_pending = false;
-            // Spawn IP fetch - don't block the UI
-            if let Some(ip) = crate::network::get_public_ip().await {
-                self.public_ip = Some(ip);
-            }
+        if self.ip_fetch_pending
+            && self.vpn_status.connected
+            && !self.kill_switch_enabled
+            && self.ip_fetch.is_none()
+            && Instant::now() >= self.ip_fetch_next
+        {
+            self.ip_fetch_attempts += 1;
+            self.ip_fetch = Some(tokio::spawn(async {
+                tokio::join!(crate::network::get_public_ip(), crate::network::get_public_ipv6())
+            }));
         }
         
         // Periodic connectivity check (every 10 seconds)
@@ -1199,8 +3972,37 @@ impl App {
         // Periodic VPN health check (every 30 seconds when connected)
         // Skip if kill switch is enabled (health check requires network access)
         if self.vpn_status.connected && !self.kill_switch_enabled && self.last_health_check.elapsed().as_secs() >= 30 {
-            self.vpn_health = crate::vpn::wireguard::health_check().await;
+            self.vpn_health = crate::vpn::wireguard::health_check(&self.vpn_status).await;
+            self.last_health_check = Instant::now();
+            if self.config.latency_sample_secs == 0 {
+                self.push_latency_sample(self.vpn_health.latency_ms);
+            }
+        }
+
+        // The health check is skipped under the kill switch, so the leak check runs on its own
+        if self.kill_switch_enabled
+            && self.config.leak_check
+            && self.leak_probe.is_none()
+            && self.last_health_check.elapsed().as_secs() >= 30
+        {
+            let iface = self.vpn_status.interface.clone().filter(|_| self.vpn_status.connected);
+            self.leak_probe = Some(tokio::spawn(async move {
+                crate::vpn::wireguard::detect_leak(iface.as_deref()).await
+            }));
             self.last_health_check = Instant::now();
+        } else if !self.kill_switch_enabled && self.vpn_health.leak_detected {
+            self.vpn_health.leak_detected = false;
+        }
+
+        // Dedicated latency sampling for the health popup
+        if !self.vpn_status.connected {
+            self.latency_samples.clear();
+        } else if self.config.latency_sample_secs > 0
+            && self.latency_sample.is_none()
+            && self.last_latency_sample.elapsed().as_secs() >= self.config.latency_sample_secs
+        {
+            self.latency_sample = Some(tokio::task::spawn_blocking(crate::vpn::wireguard::probe_latency));
+            self.last_latency_sample = Instant::now();
         }
 
         // Update info message with VPN traffic stats if connected
@@ -1211,31 +4013,220 @@ impl App {
         Ok(())
     }
 
-    /// Parse transfer string like "1.23 GiB" or "1.23 GiB received" to bytes
-    fn parse_transfer_to_bytes(s: &str) -> u64 {
-        let parts: Vec<&str> = s.trim().split_whitespace().collect();
-        if parts.len() < 2 {
-            return 0;
+    /// Fold wg counters into session/lifetime totals when they reset.
+    /// `wg show` counters start from zero on every connection, so the last
+    /// values seen before a disconnect (or interface switch) are added to the
+    /// running totals before they are lost. Session totals and rates follow the
+    /// primary tunnel; lifetime and quota totals cover every tunnel that is up.
+    fn track_transfer(&mut self) {
+        let current = match (&self.vpn_status.interface, &self.vpn_status.transfer_rx, &self.vpn_status.transfer_tx) {
+            (Some(iface), Some(rx), Some(tx)) if self.vpn_status.connected => Some((
+                iface.clone(),
+                crate::vpn::wireguard::parse_transfer_bytes(rx),
+                crate::vpn::wireguard::parse_transfer_bytes(tx),
+            )),
+            _ => None,
+        };
+
+        let now = Instant::now();
+        if let Some((last_iface, last_rx, last_tx)) = self.last_transfer.take() {
+            let reset = match &current {
+                Some((iface, rx, tx)) => *iface != last_iface || *rx < last_rx || *tx < last_tx,
+                None => true,
+            };
+
+            // Throughput since the previous reading; a reset starts the average over
+            // so the counter jump of a new connection never shows up as a spike
+            if reset {
+                self.rate_samples.clear();
+                self.rate_history.clear();
+            } else if let (Some((_, rx, tx)), Some(at)) = (&current, self.last_transfer_at) {
+                let elapsed = now.duration_since(at).as_secs_f64();
+                if elapsed > 0.0 {
+                    if self.rate_samples.len() >= RATE_WINDOW {
+                        self.rate_samples.pop_front();
+                    }
+                    let (rx_rate, tx_rate) = ((rx - last_rx) as f64 / elapsed, (tx - last_tx) as f64 / elapsed);
+                    self.rate_samples.push_back((rx_rate, tx_rate));
+
+                    if self.rate_history.len() >= RATE_HISTORY {
+                        self.rate_history.pop_front();
+                    }
+                    self.rate_history.push_back((rx_rate + tx_rate) as u64);
+                }
+            }
+
+            if reset {
+                self.session_rx_bytes += last_rx;
+                self.session_tx_bytes += last_tx;
+            }
+        }
+
+        self.last_transfer = current;
+        self.last_transfer_at = Some(now);
+        self.track_tunnel_totals();
+    }
+
+    /// Charge each interface's traffic to its tunnel's lifetime and quota totals
+    /// once its counters reset or it goes down
+    fn track_tunnel_totals(&mut self) {
+        let parse = |t: &Option<String>| t.as_deref().map(crate::vpn::wireguard::parse_transfer_bytes);
+        let up: HashMap<String, (u64, u64)> = self.vpn_status.all_interfaces()
+            .filter_map(|s| Some((s.interface.clone()?, (parse(&s.transfer_rx)?, parse(&s.transfer_tx)?))))
+            .collect();
+
+        let previous = std::mem::take(&mut self.tunnel_counters);
+        let continues = |iface: &str, last: &TransferCounters| {
+            up.get(iface).is_some_and(|&(rx, tx)| rx >= last.rx && tx >= last.tx)
+        };
+        for (iface, &(rx, tx)) in &up {
+            let quota_baseline = previous.get(iface)
+                .filter(|last| continues(iface, last))
+                .map_or(0, |last| last.quota_baseline);
+            self.tunnel_counters.insert(iface.clone(), TransferCounters { rx, tx, quota_baseline });
+        }
+
+        let (now, offset) = (crate::network::status_socket::now_secs(), self.utc_offset);
+        let mut changed = false;
+        for (iface, last) in previous {
+            if continues(&iface, &last) || (last.rx == 0 && last.tx == 0) {
+                continue;
+            }
+            let name = self.interface_tunnel(&iface).to_string();
+            let tunnel = self.ensure_tunnel_info(&name);
+            tunnel.lifetime_rx_bytes += last.rx;
+            tunnel.lifetime_tx_bytes += last.tx;
+            quota::roll_period(tunnel, now, offset);
+            tunnel.period_bytes += (last.rx + last.tx).saturating_sub(last.quota_baseline);
+            changed = true;
+        }
+        if changed {
+            let _ = self.config.save();
+        }
+    }
+
+    /// Roll each up tunnel's quota period and alert at 80% and 100%
+    fn check_quota(&mut self) {
+        let up: Vec<(String, String)> = self.tunnel_counters.keys()
+            .map(|iface| (iface.clone(), self.interface_tunnel(iface).to_string()))
+            .collect();
+        for (iface, name) in up {
+            self.check_tunnel_quota(&iface, &name);
         }
-        
-        let value: f64 = parts[0].parse().unwrap_or(0.0);
-        let unit = parts[1].to_lowercase();
-        
-        let multiplier: u64 = match unit.as_str() {
-            "b" => 1,
-            "kib" => 1024,
-            "mib" => 1024 * 1024,
-            "gib" => 1024 * 1024 * 1024,
-            "tib" => 1024 * 1024 * 1024 * 1024,
-            // Also handle SI units
-            "kb" => 1000,
-            "mb" => 1000 * 1000,
-            "gb" => 1000 * 1000 * 1000,
-            "tb" => 1000 * 1000 * 1000 * 1000,
-            _ => 1,
+    }
+
+    fn check_tunnel_quota(&mut self, iface: &str, name: &str) {
+        let Some(counters) = self.tunnel_counters.get_mut(iface) else {
+            return;
+        };
+        let (now, offset) = (crate::network::status_socket::now_secs(), self.utc_offset);
+        let Some(tunnel) = self.config.known_tunnels.iter_mut().find(|t| t.name == name) else {
+            return;
+        };
+        let Some(quota) = tunnel.quota_gib.map(quota::quota_bytes) else {
+            return;
+        };
+
+        let mut changed = false;
+        let moved = counters.rx + counters.tx;
+        if quota::roll_period(tunnel, now, offset) {
+            // What this connection moved so far belongs to the period that just ended
+            counters.quota_baseline = moved;
+            changed = true;
+        }
+        let used = tunnel.period_bytes + moved.saturating_sub(counters.quota_baseline);
+        let level = quota::alert_level(used, quota);
+        if level != tunnel.quota_alerted {
+            // A raised quota lowers the level, so the alert can fire again later
+            let alert = level > tunnel.quota_alerted;
+            tunnel.quota_alerted = level;
+            changed = true;
+            if alert {
+                let message = if level >= 100 {
+                    format!("{} has used its data quota ({})", name, Self::format_bytes(quota))
+                } else {
+                    format!("{} has used {}% of its data quota ({} of {})",
+                        name, level, Self::format_bytes(used), Self::format_bytes(quota))
+                };
+                let _ = Notice::new(notify::Kind::Warning, message.clone()).show(&self.config);
+                self.set_status(format!("⚠ {}", message));
+            }
+        }
+        if changed {
+            let _ = self.config.save();
+        }
+    }
+
+    /// Quota usage of a tunnel as (fraction used, summary), None without a quota
+    pub fn quota_usage(&self, name: &str) -> Option<(f64, String)> {
+        let tunnel = self.get_tunnel_info(name)?;
+        let quota = quota::quota_bytes(tunnel.quota_gib?);
+        let reset_day = tunnel.quota_reset_day.unwrap_or(1).clamp(1, quota::MAX_RESET_DAY);
+        let period = quota::current_period(crate::network::status_socket::now_secs(), self.utc_offset, reset_day);
+
+        // Stored bytes from an earlier period don't count anymore
+        let mut used = if tunnel.usage_period.as_deref() == Some(period.as_str()) { tunnel.period_bytes } else { 0 };
+        if let Some(counters) = self.tunnel_counters.get(self.tunnel_interface(name)) {
+            used += (counters.rx + counters.tx).saturating_sub(counters.quota_baseline);
+        }
+
+        let fraction = if quota == 0 { 1.0 } else { used as f64 / quota as f64 };
+        let suffix = match reset_day {
+            1 | 21 => "st",
+            2 | 22 => "nd",
+            3 | 23 => "rd",
+            _ => "th",
+        };
+        Some((fraction, format!("{:.0}% · {} of {} · resets on the {}{}",
+            fraction * 100.0, Self::format_bytes(used), Self::format_bytes(quota), reset_day, suffix)))
+    }
+
+    /// Read the primary tunnel's AllowedIPs when the primary interface changes
+    async fn refresh_active_scope(&mut self) {
+        let iface = self.vpn_status.interface.clone().filter(|_| self.vpn_status.connected);
+        if self.active_scope.as_ref().map(|(name, _)| name) == iface.as_ref() {
+            return;
+        }
+        self.active_scope = match iface {
+            Some(name) => {
+                let scope = crate::vpn::wireguard::read_config(&name)
+                    .await
+                    .ok()
+                    .and_then(|config| TunnelScope::from_config(&config));
+                Some((name, scope))
+            }
+            None => None,
         };
-        
-        (value * multiplier as f64) as u64
+    }
+
+    /// AllowedIPs scope of the primary tunnel, if its config could be read
+    pub fn active_scope(&self) -> Option<&TunnelScope> {
+        self.active_scope.as_ref().and_then(|(_, scope)| scope.as_ref())
+    }
+
+    /// Moving-average (rx, tx) throughput in bytes/sec, None until there's a sample
+    fn transfer_rate(&self) -> Option<(f64, f64)> {
+        if self.rate_samples.is_empty() {
+            return None;
+        }
+        let n = self.rate_samples.len() as f64;
+        let (rx, tx) = self.rate_samples
+            .iter()
+            .fold((0.0, 0.0), |(rx, tx), (r, t)| (rx + r, tx + t));
+        Some((rx / n, tx / n))
+    }
+
+    /// Format a rate like "1.2 MB/s" or "45 KB/s"
+    fn format_rate(bytes_per_sec: f64) -> String {
+        if bytes_per_sec >= 1_000_000_000.0 {
+            format!("{:.1} GB/s", bytes_per_sec / 1_000_000_000.0)
+        } else if bytes_per_sec >= 1_000_000.0 {
+            format!("{:.1} MB/s", bytes_per_sec / 1_000_000.0)
+        } else if bytes_per_sec >= 1_000.0 {
+            format!("{:.0} KB/s", bytes_per_sec / 1_000.0)
+        } else {
+            format!("{:.0} B/s", bytes_per_sec)
+        }
     }
 
     /// Format bytes to human-readable string
@@ -1259,7 +4250,16 @@ impl App {
     }
 
     /// Format duration to human-readable string
-    fn format_duration(secs: u64) -> String {
+    /// Format seconds as a clock: "0:42", "12:05", "1:02:03"
+    pub fn format_clock(secs: u64) -> String {
+        if secs < 3600 {
+            format!("{}:{:02}", secs / 60, secs % 60)
+        } else {
+            format!("{}:{:02}:{:02}", secs / 3600, (secs % 3600) / 60, secs % 60)
+        }
+    }
+
+    pub fn format_duration(secs: u64) -> String {
         if secs < 60 {
             format!("{}s", secs)
         } else if secs < 3600 {
@@ -1281,8 +4281,34 @@ impl App {
         }
     }
 
+    /// Open the detected captive portal in the default browser
+    fn open_captive_portal(&mut self) {
+        let Some(url) = self.connectivity.captive_portal_url.clone() else {
+            return;
+        };
+        match std::process::Command::new("xdg-open")
+            .arg(&url)
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .spawn()
+        {
+            Ok(_) => self.set_status(format!("Opening {} in browser...", url)),
+            Err(e) => self.set_status(format!("xdg-open failed: {} - visit {}", e, url)),
+        }
+    }
+
+    /// Host part of a portal URL, for a compact info line
+    fn portal_host(url: &str) -> &str {
+        let rest = url.split_once("://").map_or(url, |(_, rest)| rest);
+        rest.split(['/', '?']).next().unwrap_or(rest)
+    }
+
     /// Update the info message with current status/traffic
     fn update_info_message(&mut self) {
+        self.handshake_age = self.vpn_status.handshake_epoch
+            .filter(|_| self.vpn_status.connected)
+            .map(|epoch| crate::network::status_socket::now_secs().saturating_sub(epoch));
+
         if self.vpn_status.connected {
             let mut parts = Vec::new();
             
@@ -1295,14 +4321,30 @@ impl App {
                 "󰒍" // Connected but issues
             };
             
-            // Interface name with health indicator
+            // Interface name with health indicator, and who runs it when that isn't wg-quick
             if let Some(ref iface) = self.vpn_status.interface {
-                parts.push(format!("{} {}", health_icon, iface));
+                match self.vpn_status.managed_by.as_deref() {
+                    Some(manager) if manager != crate::vpn::wireguard::MANAGER_WG_QUICK => {
+                        parts.push(format!("{} {} (via {})", health_icon, iface, manager_label(manager)));
+                    }
+                    _ => parts.push(format!("{} {}", health_icon, iface)),
+                }
+            }
+            
+            // Other tunnels up at the same time
+            let others: Vec<&str> = self.vpn_status.additional
+                .iter()
+                .filter_map(|s| s.interface.as_deref())
+                .collect();
+            if !others.is_empty() {
+                parts.push(format!("+ {}", others.join(", ")));
             }
             
-            // Public IP address (if available)
-            if let Some(ref ip) = self.public_ip {
-                parts.push(format!("󰩟 {}", ip));
+            // Public IP address(es) - show whichever families resolved
+            match (&self.public_ip, &self.public_ipv6) {
+                (Some(v4), Some(v6)) => parts.push(format!("󰩟 {} / {}", v4, v6)),
+                (Some(ip), None) | (None, Some(ip)) => parts.push(format!("󰩟 {}", ip)),
+                (None, None) => {}
             }
             
             // Session duration - use actual interface uptime from system
@@ -1312,30 +4354,70 @@ impl App {
                 }
             }
             
-            // Cumulative session traffic (total since connection established)
+            // Traffic: session (since launch, across reconnects) and lifetime (this tunnel)
             if let (Some(ref rx), Some(ref tx)) = (&self.vpn_status.transfer_rx, &self.vpn_status.transfer_tx) {
-                let total_rx = Self::parse_transfer_to_bytes(rx);
-                let total_tx = Self::parse_transfer_to_bytes(tx);
+                let current_rx = crate::vpn::wireguard::parse_transfer_bytes(rx);
+                let current_tx = crate::vpn::wireguard::parse_transfer_bytes(tx);
+
+                if let Some((rx_rate, tx_rate)) = self.transfer_rate() {
+                    parts.push(format!("↓ {} ↑ {}", Self::format_rate(rx_rate), Self::format_rate(tx_rate)));
+                }
                 
-                parts.push(format!("↓{} ↑{}", 
-                    Self::format_bytes(total_rx), 
-                    Self::format_bytes(total_tx)
+                parts.push(format!("session ↓{} ↑{}", 
+                    Self::format_bytes(self.session_rx_bytes + current_rx), 
+                    Self::format_bytes(self.session_tx_bytes + current_tx)
+                ));
+
+                let (stored_rx, stored_tx) = self.vpn_status.interface.as_deref()
+                    .and_then(|iface| self.get_tunnel_info(self.interface_tunnel(iface)))
+                    .map(|t| (t.lifetime_rx_bytes, t.lifetime_tx_bytes))
+                    .unwrap_or((0, 0));
+                parts.push(format!("lifetime ↓{} ↑{}",
+                    Self::format_bytes(stored_rx + current_rx),
+                    Self::format_bytes(stored_tx + current_tx)
                 ));
             }
             
-            // Tunnel type indicator
-            if self.vpn_status.routing_ok {
-                parts.push("󰒘 Full".to_string());  // All traffic through VPN
-            } else {
-                parts.push("󰒙 Split".to_string()); // Only specific IPs through VPN
+            // Tunnel type from the config's AllowedIPs; fall back to the routes if unreadable
+            match self.active_scope() {
+                Some(scope) if scope.is_full() => {
+                    parts.push(format!("󰒘 {}", scope.label()));
+                    if !self.vpn_status.routing_ok {
+                        parts.push("⚠ full tunnel but default route bypasses VPN".to_string());
+                    }
+                }
+                Some(scope) => parts.push(format!("󰒙 {}", scope.label())),
+                None if self.vpn_status.routing_ok => parts.push("󰒘 Full".to_string()),  // All traffic through VPN
+                None => parts.push("󰒙 Split".to_string()), // Only specific IPs through VPN
             }
             
+            // Result of the last MTU probe ('M') for a tunnel that's up
+            if let Some((tunnel, probe)) = self.mtu_result.as_ref().filter(|(t, _)| self.vpn_status.is_up(self.tunnel_interface(t))) {
+                parts.push(format!("MTU {} recommended for {}", probe.recommended, tunnel));
+            }
+
             // Status warnings - skip when kill switch is on (expected behavior)
             if !self.kill_switch_enabled {
                 if self.vpn_status.handshake_stale {
                     parts.push("⏳ stale".to_string());
                 } else if !self.vpn_health.can_reach_internet && self.vpn_health.interface_exists {
                     parts.push("⚠ no internet".to_string());
+                } else if let Some(family) = self.vpn_health.family_label() {
+                    parts.push(family.to_string());
+                }
+                
+                // Traffic goes through the tunnel but lookups provably don't
+                if self.vpn_status.routing_ok
+                    && self.vpn_health.interface_exists
+                    && self.vpn_health.dns_ok
+                    && self.vpn_health.resolved_via_vpn == Some(false)
+                {
+                    parts.push("⚠ DNS leak".to_string());
+                }
+
+                // Dynamic-DNS endpoint moved since connecting
+                if self.vpn_health.endpoint_changed {
+                    parts.push("⚠ endpoint IP changed - reconnect recommended".to_string());
                 }
             }
             
@@ -1351,162 +4433,412 @@ impl App {
             } else if !self.connectivity.has_ip_address {
                 self.info_message = Some("󰤫 No IP address".to_string());
             } else if !self.connectivity.has_internet {
-                if self.connectivity.can_reach_gateway {
+                if let Some(ref url) = self.connectivity.captive_portal_url {
+                    self.info_message = Some(format!(
+                        "󰤩 Captive portal at {} - press o to sign in",
+                        Self::portal_host(url)
+                    ));
+                } else if self.connectivity.can_reach_gateway {
                     self.info_message = Some("󰤩 No internet (captive portal?)".to_string());
                 } else {
                     self.info_message = Some("󰤩 No internet".to_string());
                 }
-            } else if let Some(network) = self.networks.iter().find(|n| n.connected) {
-                // Online but no VPN
-                self.info_message = Some(format!("󰖩 {} (no VPN)", network.name));
             } else {
-                self.info_message = Some("󰖩 Online (no VPN)".to_string());
+                // Online but no VPN
+                let name = self.networks.iter()
+                    .find(|n| n.connected)
+                    .map_or("Online", |n| n.name.as_str());
+                self.info_message = Some(match self.connectivity.family_label() {
+                    Some(family) => format!("󰖩 {} (no VPN) │ {}", name, family),
+                    None => format!("󰖩 {} (no VPN)", name),
+                });
             }
         }
     }
 
-    /// Apply the pending configuration change
+    /// Record an event in the in-memory log and the shared history file
+    fn log_event(&mut self, kind: EventKind, detail: impl Into<String>) {
+        let event = HistoryEvent::new(history::SOURCE_TUI, kind, detail);
+        history::record(&event);
+        self.history.push_back(event);
+        while self.history.len() > HISTORY_CAP {
+            self.history.pop_front();
+        }
+    }
+
+    /// Re-read theme colors and remember which files they came from
+    fn reload_theme(&mut self) {
+        self.theme_stamp = Theme::source_stamp();
+        crate::ui::reload_theme();
+    }
+
+    /// Read the routing table and the up VPN interfaces' addresses off the UI loop
+    async fn refresh_routing_info(&mut self) {
+        let interfaces: Vec<String> = self.vpn_status.all_interfaces()
+            .filter_map(|s| s.interface.clone())
+            .collect();
+        self.routing_info = tokio::task::spawn_blocking(move || crate::vpn::wireguard::routing_info(&interfaces))
+            .await
+            .unwrap_or_default();
+    }
+
+    /// Lines in the longer pane of the routing popup (each interface has a header line)
+    fn routing_lines(&self) -> usize {
+        (self.routing_info.routes.len() + self.routing_info.rules.len() + 2)
+            .max(self.routing_info.addresses.iter().map(|(_, lines)| lines.len() + 1).sum())
+    }
+
+    /// Open the history popup, reloading so daemon events are included
+    fn open_history(&mut self) {
+        self.history = history::load_recent(HISTORY_CAP).into();
+        self.history_scroll = 0;
+        self.history_utc_offset = history::local_utc_offset();
+        self.popup = Popup::History;
+    }
+
+    /// Start the pending change once nothing else is running
     async fn apply_pending_change(&mut self) -> Result<()> {
+        if self.operation.is_some() {
+            // Applied once the running change is done
+            return Ok(());
+        }
         if let Some(change) = self.pending_change.take() {
             self.countdown_start = None;
             self.countdown_seconds = 0;
+            self.start_change(change).await?;
+        }
+        Ok(())
+    }
 
-            match change.action {
-                PendingAction::Connect => {
-                    if let Some(tunnel) = &change.tunnel_name {
-                        self.set_status(format!("Connecting to {}...", tunnel));
-                        match crate::vpn::wireguard::connect(tunnel).await {
-                            Ok(_) => {
-                                // Save last connected tunnel for auto-reconnect
-                                self.config.last_connected = Some(tunnel.clone());
-                                let _ = self.config.save();
-                                
-                                // Apply tunnel's kill switch setting
-                                let tunnel_ks = self.get_tunnel_info(tunnel)
-                                    .map(|t| t.kill_switch)
-                                    .unwrap_or(false);
-                                if tunnel_ks {
-                                    if let Ok(_) = crate::vpn::killswitch::enable().await {
-                                        self.kill_switch_enabled = true;
-                                        self.set_status(format!("Connected to {} (kill switch on)", tunnel));
-                                    } else {
-                                        self.set_status(format!("Connected to {}", tunnel));
-                                    }
-                                } else {
-                                    self.set_status(format!("Connected to {}", tunnel));
-                                }
-                            }
-                            Err(e) => {
-                                self.set_status(format!("Error: {}", e));
-                            }
-                        }
-                    }
+    /// Run a change's helper calls off the UI loop; `tick` finishes it
+    async fn start_change(&mut self, change: PendingChange) -> Result<()> {
+        // Replacing the running operation would lose its outcome and kill switch restore
+        if self.operation_in_progress() {
+            self.set_status(OPERATION_BUSY);
+            return Ok(());
+        }
+        let before = UndoState {
+            action: change.action.clone(),
+            tunnel: self.vpn_status.interface.clone().filter(|_| self.vpn_status.connected),
+            kill_switch: self.kill_switch_enabled,
+        };
+
+        // Check the target tunnel before tearing anything down
+        let warnings = match (&change.action, &change.tunnel_name) {
+            (PendingAction::Connect | PendingAction::Reconnect | PendingAction::Restart { .. }, Some(tunnel)) => {
+                let Some(warnings) = self.preflight_tunnel(tunnel).await else {
+                    self.refresh().await?;
+                    return Ok(());
+                };
+                warnings
+            }
+            (PendingAction::Connect | PendingAction::Reconnect | PendingAction::DisconnectTunnel, None) => return Ok(()),
+            _ => Vec::new(),
+        };
+
+        match (&change.action, &change.tunnel_name) {
+            (PendingAction::Connect, Some(tunnel)) => self.set_status(format!("Connecting to {}...", tunnel)),
+            (PendingAction::Reconnect, Some(tunnel)) => self.set_status(format!("Switching to {}...", tunnel)),
+            (PendingAction::Disconnect, _) => self.set_status("Disconnecting..."),
+            (PendingAction::DisconnectTunnel, Some(tunnel)) => self.set_status(format!("Disconnecting {}...", tunnel)),
+            (PendingAction::KillSwitchOn, _) => self.set_status("Enabling kill switch..."),
+            (PendingAction::KillSwitchOff, _) => self.set_status("Disabling kill switch..."),
+            (PendingAction::Undo { .. }, _) => self.set_status("Undoing..."),
+            (PendingAction::TestConnect { .. }, Some(tunnel)) => self.set_status(format!("Testing {}...", tunnel)),
+            (PendingAction::Restart { .. }, Some(tunnel)) => self.set_status(format!("Reconnecting {} to apply changes...", tunnel)),
+            _ => {}
+        }
+
+        // A rule bringing up its tunnel can override that tunnel's kill switch setting
+        let rule_ks = match (&change.action, &change.tunnel_name) {
+            (PendingAction::Connect | PendingAction::Reconnect, Some(tunnel)) => {
+                self.rule_kill_switch(&change.network_id, tunnel)
+            }
+            _ => None,
+        };
+        let tunnel_ks = match &change.action {
+            // The kill switch comes back if it was on, and moves along when the tunnel changed
+            PendingAction::Undo { state, .. } => {
+                state.kill_switch && (!self.kill_switch_enabled || before.tunnel != state.tunnel)
+            }
+            _ => rule_ks.unwrap_or_else(|| {
+                change.tunnel_name.as_deref()
+                    .and_then(|tunnel| self.get_tunnel_info(tunnel))
+                    .is_some_and(|t| t.kill_switch)
+            }),
+        };
+        let drop_kill_switch = match (&change.action, &change.tunnel_name) {
+            // A persistent kill switch that was on before stays on anyway
+            (PendingAction::Undo { state, .. }, _) => {
+                !state.kill_switch || (!self.config.persistent_kill_switch && before.tunnel != state.tunnel)
+            }
+            // Unless the rule asks for it to be off
+            _ if rule_ks == Some(false) => true,
+            _ if self.config.persistent_kill_switch => false,
+            (PendingAction::Disconnect | PendingAction::Reconnect, _) => true,
+            (PendingAction::DisconnectTunnel, Some(tunnel)) => {
+                self.vpn_status.interface.as_deref() == Some(self.tunnel_interface(tunnel))
+            }
+            _ => false,
+        };
+        let task = tokio::spawn(run_change(
+            change.action.clone(),
+            change.tunnel_name.clone(),
+            self.kill_switch_enabled,
+            drop_kill_switch,
+            tunnel_ks,
+        ));
+        self.operation = Some(RunningChange { change, before, warnings, started: Instant::now(), task });
+        Ok(())
+    }
+
+    /// Connect/disconnect running off the UI loop (spinner on the info line)
+    pub fn operation_in_progress(&self) -> bool {
+        self.operation.is_some()
+    }
+
+    /// Spinner frame for the running operation, advancing every 100 ms
+    pub fn spinner_frame(&self) -> Option<char> {
+        let started = self.operation.as_ref()?.started;
+        Some(SPINNER[(started.elapsed().as_millis() / 100) as usize % SPINNER.len()])
+    }
+
+    /// Finish a change whose helper calls have returned
+    async fn poll_operation(&mut self) -> Result<()> {
+        if !self.operation.as_ref().is_some_and(|op| op.task.is_finished()) {
+            return Ok(());
+        }
+        self.finish_operation().await
+    }
+
+    /// Wait for the running change and record its result (also used on quit)
+    pub async fn finish_operation(&mut self) -> Result<()> {
+        let Some(RunningChange { change, before, warnings, task, .. }) = self.operation.take() else {
+            return Ok(());
+        };
+        let outcome = match task.await {
+            Ok(outcome) => outcome,
+            Err(e) => ChangeOutcome {
+                result: Err(anyhow::anyhow!("{}", e)),
+                kill_switch: self.kill_switch_enabled,
+                disconnected: false,
+            },
+        };
+        self.kill_switch_enabled = outcome.kill_switch;
+        if outcome.disconnected {
+            let gone = match &change.action {
+                PendingAction::DisconnectTunnel => change.tunnel_name.clone(),
+                PendingAction::Undo { take_down, .. } => take_down.clone(),
+                _ => before.tunnel.clone(),
+            };
+            if let Some(gone) = gone {
+                self.run_tunnel_hook(&gone, false).await;
+            }
+        }
+
+        match (&change.action, &change.tunnel_name, outcome.result) {
+            (PendingAction::Connect | PendingAction::Reconnect, Some(tunnel), Ok(())) => {
+                if change.action == PendingAction::Connect {
+                    self.log_event(EventKind::Connect, tunnel.clone());
+                } else {
+                    self.log_event(EventKind::Reconnect, format!("Switched to {}", tunnel));
                 }
-                PendingAction::Disconnect => {
-                    self.set_status("Disconnecting...");
-                    // Disable kill switch when disconnecting
-                    if self.kill_switch_enabled {
-                        let _ = crate::vpn::killswitch::disable().await;
-                        self.kill_switch_enabled = false;
-                    }
-                    match crate::vpn::wireguard::disconnect().await {
-                        Ok(_) => {
-                            self.set_status("Disconnected");
-                        }
-                        Err(e) => {
-                            self.set_status(format!("Error: {}", e));
-                        }
-                    }
+                // Save last connected tunnel for auto-reconnect
+                self.config.last_connected = Some(tunnel.clone());
+                let _ = self.config.save();
+
+                if self.kill_switch_enabled {
+                    self.set_status(format!("Connected to {} (kill switch on)", tunnel));
+                } else {
+                    self.set_status(format!("Connected to {}", tunnel));
                 }
-                PendingAction::Reconnect => {
-                    if let Some(tunnel) = &change.tunnel_name {
-                        self.set_status(format!("Switching to {}...", tunnel));
-                        // Disable old kill switch before switching
-                        if self.kill_switch_enabled {
-                            let _ = crate::vpn::killswitch::disable().await;
-                            self.kill_switch_enabled = false;
-                        }
-                        let _ = crate::vpn::wireguard::disconnect().await;
-                        match crate::vpn::wireguard::connect(tunnel).await {
-                            Ok(_) => {
-                                // Save last connected tunnel for auto-reconnect
-                                self.config.last_connected = Some(tunnel.clone());
-                                let _ = self.config.save();
-                                
-                                // Apply new tunnel's kill switch setting
-                                let tunnel_ks = self.get_tunnel_info(tunnel)
-                                    .map(|t| t.kill_switch)
-                                    .unwrap_or(false);
-                                if tunnel_ks {
-                                    if let Ok(_) = crate::vpn::killswitch::enable().await {
-                                        self.kill_switch_enabled = true;
-                                        self.set_status(format!("Connected to {} (kill switch on)", tunnel));
-                                    } else {
-                                        self.set_status(format!("Connected to {}", tunnel));
-                                    }
-                                } else {
-                                    self.set_status(format!("Connected to {}", tunnel));
-                                }
-                            }
-                            Err(e) => {
-                                self.set_status(format!("Error: {}", e));
-                            }
-                        }
-                    }
+                self.show_preflight_warning(tunnel, &warnings);
+                self.run_tunnel_hook(tunnel, true).await;
+            }
+            (PendingAction::Connect | PendingAction::Reconnect, Some(tunnel), Err(e)) => {
+                let verb = if change.action == PendingAction::Connect { "Connect to" } else { "Switch to" };
+                self.log_event(EventKind::Error, format!("{} {} failed: {}", verb, tunnel, e));
+                self.set_status(format!("Error: {}", e));
+            }
+            (PendingAction::Disconnect, _, Ok(())) => {
+                self.log_event(EventKind::Disconnect, "Disconnected from the TUI");
+                if self.kill_switch_enabled {
+                    self.set_status("Disconnected - kill switch still on, no internet until a tunnel is up");
+                } else {
+                    self.set_status("Disconnected");
                 }
-                PendingAction::KillSwitchOn => {
-                    self.set_status("Enabling kill switch...");
-                    match crate::vpn::killswitch::enable().await {
-                        Ok(_) => {
-                            self.kill_switch_enabled = true;
-                            // Save per-tunnel if connected, otherwise global
-                            if let Some(iface) = self.vpn_status.interface.clone() {
-                                self.set_tunnel_kill_switch(&iface, true);
-                                self.set_status(format!("Kill switch enabled for {}", iface));
-                            } else {
-                                self.config.kill_switch = true;
-                                let _ = self.config.save();
-                                self.set_status("Kill switch enabled");
-                            }
-                        }
-                        Err(e) => {
-                            self.set_status(format!("Error: {}", e));
-                        }
-                    }
+            }
+            (PendingAction::DisconnectTunnel, Some(tunnel), Ok(())) => {
+                self.log_event(EventKind::Disconnect, format!("Disconnected {} from the TUI", tunnel));
+                if self.kill_switch_enabled && before.tunnel.as_deref() == Some(self.tunnel_interface(tunnel)) {
+                    self.set_status(format!("Disconnected {} - kill switch still on", tunnel));
+                } else {
+                    self.set_status(format!("Disconnected {}", tunnel));
                 }
-                PendingAction::KillSwitchOff => {
-                    self.set_status("Disabling kill switch...");
-                    match crate::vpn::killswitch::disable().await {
-                        Ok(_) => {
-                            self.kill_switch_enabled = false;
-                            // Save per-tunnel if connected, otherwise global
-                            if let Some(iface) = self.vpn_status.interface.clone() {
-                                self.set_tunnel_kill_switch(&iface, false);
-                                self.set_status(format!("Kill switch disabled for {}", iface));
-                            } else {
-                                self.config.kill_switch = false;
-                                let _ = self.config.save();
-                                self.set_status("Kill switch disabled");
-                            }
-                        }
-                        Err(e) => {
-                            self.set_status(format!("Error: {}", e));
+            }
+            (PendingAction::Disconnect | PendingAction::DisconnectTunnel, _, Err(e)) => {
+                self.log_event(EventKind::Error, format!("Disconnect failed: {}", e));
+                self.set_status(format!("Error: {}", e));
+            }
+            (PendingAction::KillSwitchOn | PendingAction::KillSwitchOff, _, Ok(())) => {
+                let enabled = change.action == PendingAction::KillSwitchOn;
+                let state = if enabled { "enabled" } else { "disabled" };
+                // Save per-tunnel if connected, otherwise global
+                if let Some(iface) = self.vpn_status.interface.clone() {
+                    self.set_tunnel_kill_switch(&iface, enabled);
+                    self.set_status(format!("Kill switch {} for {}", state, iface));
+                } else {
+                    self.config.kill_switch = enabled;
+                    let _ = self.config.save();
+                    self.set_status(format!("Kill switch {}", state));
+                }
+            }
+            (PendingAction::Undo { state, .. }, tunnel, Ok(())) => {
+                if let Some(tunnel) = tunnel {
+                    self.config.last_connected = Some(tunnel.clone());
+                    let _ = self.config.save();
+                    self.run_tunnel_hook(tunnel, true).await;
+                }
+                // A kill switch toggle was also saved as the tunnel's (or global) setting
+                if matches!(state.action, PendingAction::KillSwitchOn | PendingAction::KillSwitchOff) {
+                    match state.tunnel.as_deref() {
+                        Some(iface) => self.set_tunnel_kill_switch(iface, state.kill_switch),
+                        None => {
+                            self.config.kill_switch = state.kill_switch;
+                            let _ = self.config.save();
                         }
                     }
                 }
+
+                let (kind, restored) = match tunnel {
+                    Some(tunnel) => (EventKind::Reconnect, format!("back on {}", tunnel)),
+                    None => (EventKind::Disconnect, "no VPN".to_string()),
+                };
+                let ks = if state.kill_switch { "on" } else { "off" };
+                self.log_event(kind, format!("Undo: {}", restored));
+                self.set_status(format!("Undone - {}, kill switch {}", restored, ks));
+            }
+            (PendingAction::Restart { .. }, Some(tunnel), Ok(())) => {
+                self.log_event(EventKind::Reconnect, format!("Reconnected {} after a config edit", tunnel));
+                self.set_status(format!("Config updated & {} reconnected", tunnel));
+                self.show_preflight_warning(tunnel, &warnings);
+            }
+            (PendingAction::Restart { .. }, Some(tunnel), Err(e)) => {
+                self.log_event(EventKind::Error, format!("Reconnect {} failed: {}", tunnel, e));
+                self.set_status(format!("Reconnect failed: {}", e));
+            }
+            (PendingAction::TestConnect { previous }, Some(tunnel), Ok(())) => {
+                self.connection_test = Some(ConnectionTest {
+                    tunnel_name: tunnel.clone(),
+                    stage: TestStage::Handshake,
+                    stage_start: Instant::now(),
+                    previous_tunnel: previous.clone(),
+                });
+            }
+            (PendingAction::TestConnect { .. }, Some(tunnel), Err(e)) => {
+                self.set_status(format!("Test of {} failed: {}", tunnel, e));
             }
+            (PendingAction::TestRollBack { message, .. }, _, Ok(())) => self.set_status(message.clone()),
+            (PendingAction::TestRollBack { previous, message }, _, Err(e)) => {
+                let previous = previous.as_deref().map(|p| format!(" ({} not restored)", p)).unwrap_or_default();
+                self.set_status(format!("{} - rollback failed{}: {}", message, previous, e));
+            }
+            (PendingAction::Undo { state, .. }, _, Err(e)) => {
+                self.log_event(EventKind::Error, format!("Undo failed: {}", e));
+                self.set_status(format!("Undo failed: {} - press u to try again", e));
+                self.undo = Some((**state).clone());
+            }
+            (_, _, Err(e)) => self.set_status(format!("Error: {}", e)),
+            _ => {}
+        }
 
-            // Refresh status
-            self.refresh().await?;
+        // Refresh status
+        self.refresh().await?;
+
+        // Only offer undo when something actually changed - not for an undo itself,
+        // nor for connection tests, which roll themselves back
+        if matches!(change.action, PendingAction::Undo { .. } | PendingAction::TestConnect { .. } | PendingAction::TestRollBack { .. }) {
+            return Ok(());
+        }
+        let tunnel_now = self.vpn_status.interface.clone().filter(|_| self.vpn_status.connected);
+        if tunnel_now != before.tunnel || self.kill_switch_enabled != before.kill_switch {
+            self.undo = Some(before);
         }
         Ok(())
     }
 
+    /// Drop every tunnel and the kill switch right away - no countdown, no confirm
+    /// For a tunnel or kill switch that is blocking everything
+    async fn panic_disconnect(&mut self) -> Result<()> {
+        self.cancel_pending_change();
+        self.connection_test = None;
+        if let Some(running) = self.operation.take() {
+            running.task.abort();
+        }
+
+        let kill_switch = crate::vpn::killswitch::disable().await;
+        let tunnels = crate::vpn::wireguard::disconnect().await;
+        let kill_switch_failed = self.kill_switch_enabled && kill_switch.is_err();
+        self.kill_switch_enabled = kill_switch_failed;
+        // The daemon would bring an Always tunnel straight back
+        if let Err(e) = self.suspend_always_rule() {
+            tracing::warn!("Could not pause the network rule: {}", e);
+        }
+
+        tracing::warn!("PANIC DISCONNECT: dropping all tunnels and the kill switch");
+        self.log_event(EventKind::Disconnect, "PANIC DISCONNECT - all tunnels and the kill switch dropped");
+        match (tunnels, kill_switch_failed) {
+            (Ok(()), false) => self.set_status("PANIC DISCONNECT - all tunnels down, kill switch off, rule paused until the network changes"),
+            (Err(e), _) => {
+                self.log_event(EventKind::Error, format!("Panic disconnect: {}", e));
+                self.set_status(format!("PANIC DISCONNECT incomplete - tunnels: {}", e));
+            }
+            (Ok(()), true) => {
+                self.log_event(EventKind::Error, "Panic disconnect: kill switch still on");
+                self.set_status("PANIC DISCONNECT incomplete - the kill switch could not be removed");
+            }
+        }
+        self.refresh().await
+    }
+
+    /// Put the tunnel and kill switch back the way they were before the last applied change
+    /// Runs off the UI loop like any other change; the undo is kept if it fails
+    async fn undo_last_change(&mut self) -> Result<()> {
+        if self.pending_change.is_some() || self.operation_in_progress() {
+            return Ok(());
+        }
+        let Some(undo) = self.undo.take() else {
+            self.set_status("Nothing to undo");
+            return Ok(());
+        };
+        let tunnel_now = self.vpn_status.interface.clone().filter(|_| self.vpn_status.connected);
+
+        let (take_down, bring_up) = if tunnel_now != undo.tunnel {
+            (
+                tunnel_now.as_deref().map(|iface| self.interface_tunnel(iface).to_string()),
+                undo.tunnel.as_deref().map(|iface| self.interface_tunnel(iface).to_string()),
+            )
+        } else {
+            (None, None)
+        };
+        self.start_change(PendingChange {
+            network_id: String::new(),
+            network_name: String::new(),
+            tunnel_name: bring_up,
+            action: PendingAction::Undo { state: Box::new(undo), take_down },
+        }).await
+    }
+
     /// Schedule a pending change with countdown (resets if already pending)
     fn schedule_change(&mut self, change: PendingChange) {
         self.pending_change = Some(change);
         self.countdown_start = Some(Instant::now());
-        self.countdown_seconds = COUNTDOWN_SECONDS as u8;
+        self.countdown_seconds = self.countdown_secs();
+    }
+
+    /// Countdown length for the next change (0 with `instant_apply`)
+    fn countdown_secs(&self) -> u64 {
+        if self.config.instant_apply { 0 } else { self.config.countdown_secs }
     }
 
     /// Cancel any pending change
@@ -1516,8 +4848,20 @@ impl App {
         self.countdown_seconds = 0;
     }
 
+    /// Kill switch setting of the `network_id` rule that brings up `tunnel`, if it sets one
+    fn rule_kill_switch(&self, network_id: &str, tunnel: &str) -> Option<bool> {
+        let network = self.networks.iter().find(|n| n.identifier() == network_id)?;
+        let rule = self.get_network_rule(network).filter(|r| (r.always_vpn || r.session_vpn) && !r.suspended)?;
+        let rule_tunnel = rule.tunnel_name.as_ref().or(self.config.default_profile.as_ref())?;
+        rule.kill_switch.filter(|_| rule_tunnel == tunnel)
+    }
+
     /// Get the rule for a specific network
+    /// Open WiFi without its own rule falls back to a `match_open_networks` rule
     pub fn get_network_rule(&self, network: &NetworkInfo) -> Option<&NetworkRule> {
-        self.network_rules.iter().find(|r| r.identifier == network.identifier())
+        let identifier = network.identifier();
+        self.network_rules.iter().find(|r| r.identifier == identifier).or_else(|| {
+            self.network_rules.iter().find(|r| r.match_open_networks && network.is_open())
+        })
     }
 }
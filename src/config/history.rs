@@ -0,0 +1,184 @@
+//! Connection event history
+//!
+//! Both the daemon and the TUI append structured events to
+//! ~/.config/tonneru/history.jsonl (one JSON object per line) so the TUI's
+//! History popup can show what happened while it wasn't open.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Events kept when the file is trimmed
+const MAX_EVENTS: usize = 500;
+
+/// Trim the file once it grows past this size
+const MAX_FILE_BYTES: u64 = 128 * 1024;
+
+/// Which process recorded an event
+pub const SOURCE_DAEMON: &str = "daemon";
+pub const SOURCE_TUI: &str = "tui";
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventKind {
+    Connect,
+    Disconnect,
+    Reconnect,
+    NetworkChange,
+    HealthFailure,
+    Resume,
+    Error,
+}
+
+impl EventKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            EventKind::Connect => "connect",
+            EventKind::Disconnect => "disconnect",
+            EventKind::Reconnect => "reconnect",
+            EventKind::NetworkChange => "network",
+            EventKind::HealthFailure => "health",
+            EventKind::Resume => "resume",
+            EventKind::Error => "error",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEvent {
+    pub timestamp: u64,  // Unix seconds
+    pub kind: EventKind,
+    pub source: String,  // "daemon" or "tui"
+    pub detail: String,
+}
+
+impl HistoryEvent {
+    pub fn new(source: &str, kind: EventKind, detail: impl Into<String>) -> Self {
+        Self {
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0),
+            kind,
+            source: source.to_string(),
+            detail: detail.into(),
+        }
+    }
+}
+
+/// History file location: ~/.config/tonneru/history.jsonl
+pub fn history_path() -> Result<PathBuf> {
+    Ok(super::AppConfig::config_path()?.with_file_name("history.jsonl"))
+}
+
+/// Append an event; failures are logged, never fatal
+pub fn record(event: &HistoryEvent) {
+    if let Err(e) = append(event) {
+        tracing::debug!("Could not write history: {}", e);
+    }
+}
+
+fn append(event: &HistoryEvent) -> Result<()> {
+    let path = history_path()?;
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)?;
+    writeln!(file, "{}", serde_json::to_string(event)?)?;
+
+    if file.metadata()?.len() > MAX_FILE_BYTES {
+        let recent = load_recent(MAX_EVENTS);
+        let mut content = String::new();
+        for event in &recent {
+            content.push_str(&serde_json::to_string(event)?);
+            content.push('\n');
+        }
+        std::fs::write(&path, content)?;
+    }
+
+    Ok(())
+}
+
+/// Read the most recent `limit` events, oldest first
+/// Lines that don't parse (e.g. a partial write) are skipped
+pub fn load_recent(limit: usize) -> Vec<HistoryEvent> {
+    let Ok(content) = history_path().and_then(|p| Ok(std::fs::read_to_string(p)?)) else {
+        return Vec::new();
+    };
+
+    let events: Vec<HistoryEvent> = content
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect();
+    let skip = events.len().saturating_sub(limit);
+    events.into_iter().skip(skip).collect()
+}
+
+/// Local UTC offset in seconds, from `date +%z` (0 if unavailable)
+pub fn local_utc_offset() -> i64 {
+    std::process::Command::new("date")
+        .arg("+%z")
+        .output()
+        .ok()
+        .and_then(|o| parse_utc_offset(String::from_utf8_lossy(&o.stdout).trim()))
+        .unwrap_or(0)
+}
+
+/// Parse "+0200" / "-0530" into seconds
+fn parse_utc_offset(s: &str) -> Option<i64> {
+    let (sign, digits) = match s.as_bytes().first()? {
+        b'+' => (1, &s[1..]),
+        b'-' => (-1, &s[1..]),
+        _ => return None,
+    };
+    if digits.len() != 4 || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let hours: i64 = digits[..2].parse().ok()?;
+    let minutes: i64 = digits[2..].parse().ok()?;
+    Some(sign * (hours * 3600 + minutes * 60))
+}
+
+/// Format a Unix timestamp as "YYYY-MM-DD HH:MM:SS" shifted by `offset` seconds
+pub fn format_timestamp(timestamp: u64, offset: i64) -> String {
+    let secs = timestamp as i64 + offset;
+    let (year, month, day) = civil_date(secs.div_euclid(86_400));
+    let time = secs.rem_euclid(86_400);
+
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+        year, month, day,
+        time / 3600, (time % 3600) / 60, time % 60
+    )
+}
+
+/// Year, month and day of `days` since 1970-01-01 (Howard Hinnant's algorithm)
+pub fn civil_date(days: i64) -> (i64, i64, i64) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_timestamp() {
+        assert_eq!(format_timestamp(0, 0), "1970-01-01 00:00:00");
+        assert_eq!(format_timestamp(1_709_251_200, 0), "2024-03-01 00:00:00");
+        // 03:00 local in UTC+2 is 01:00 UTC
+        assert_eq!(format_timestamp(1_709_251_200 + 3600, 7200), "2024-03-01 03:00:00");
+        assert_eq!(parse_utc_offset("-0530"), Some(-(5 * 3600 + 30 * 60)));
+        assert_eq!(parse_utc_offset("UTC"), None);
+    }
+}
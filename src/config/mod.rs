@@ -1,6 +1,11 @@
+pub mod history;
+pub mod seen;
+
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NetworkRule {
@@ -13,14 +18,80 @@ pub struct NetworkRule {
     pub never_vpn: bool,
     #[serde(default)]
     pub session_vpn: bool,  // Only for this session (cleared on network change/sleep)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority: Option<u32>,  // Higher wins when several rules match (None = lowest)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dns_servers: Option<Vec<String>>,  // Resolvers pushed onto the tunnel while the rule is active
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kill_switch: Option<bool>,  // Kill switch on/off once the rule connects (None = the tunnel's own setting)
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub match_open_networks: bool,  // Also applies to any open WiFi that has no rule of its own
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub suspended: bool,  // Skipped by the daemon until the network changes (manual disconnect)
+}
+
+/// Bumped when `load()` has to fix up values older versions saved
+pub const CONFIG_VERSION: u32 = 1;
+
+/// Default seconds before a status message auto-clears
+pub const DEFAULT_STATUS_MESSAGE_SECS: u64 = 3;
+
+/// Default countdown seconds before a pending change is applied
+pub const DEFAULT_COUNTDOWN_SECS: u64 = 4;
+
+/// Default age after which a WireGuard handshake counts as stale
+pub const DEFAULT_HANDSHAKE_STALE_SECS: u64 = 180;
+
+/// Default seconds between latency samples while connected
+pub const DEFAULT_LATENCY_SAMPLE_SECS: u64 = 5;
+
+/// Default public IP lookup tuning: endpoints queried at once and curl timeouts
+pub const DEFAULT_IP_LOOKUP_CONCURRENCY: usize = 3;
+pub const DEFAULT_IP_LOOKUP_CONNECT_TIMEOUT_SECS: u64 = 3;
+pub const DEFAULT_IP_LOOKUP_MAX_TIME_SECS: u64 = 5;
+
+/// Default daemon timing (`[daemon]`): check cycle, health checks and reconnect backoff
+pub const DEFAULT_CHECK_INTERVAL_SECS: u64 = 5;
+pub const DEFAULT_HEALTH_CHECK_INTERVAL_SECS: u64 = 30;
+pub const DEFAULT_MAX_RECONNECT_ATTEMPTS: u32 = 3;
+pub const DEFAULT_RECONNECT_DELAY_MS: u64 = 2000;
+
+fn default_status_message_secs() -> u64 { DEFAULT_STATUS_MESSAGE_SECS }
+fn default_countdown_secs() -> u64 { DEFAULT_COUNTDOWN_SECS }
+fn default_handshake_stale_secs() -> u64 { DEFAULT_HANDSHAKE_STALE_SECS }
+fn default_latency_sample_secs() -> u64 { DEFAULT_LATENCY_SAMPLE_SECS }
+fn default_ip_lookup_concurrency() -> usize { DEFAULT_IP_LOOKUP_CONCURRENCY }
+fn default_ip_lookup_connect_timeout_secs() -> u64 { DEFAULT_IP_LOOKUP_CONNECT_TIMEOUT_SECS }
+fn default_ip_lookup_max_time_secs() -> u64 { DEFAULT_IP_LOOKUP_MAX_TIME_SECS }
+fn default_true() -> bool { true }
+fn default_connectivity_hosts() -> Vec<String> {
+    DEFAULT_CONNECTIVITY_HOSTS.iter().map(|h| h.to_string()).collect()
 }
+fn default_connectivity_http_url() -> String { DEFAULT_CONNECTIVITY_HTTP_URL.to_string() }
+
+/// Default hosts pinged by connectivity and health checks (Cloudflare, Google, Quad9)
+pub const DEFAULT_CONNECTIVITY_HOSTS: &[&str] = &[
+    "1.1.1.1", "8.8.8.8", "9.9.9.9",
+    "2606:4700:4700::1111", "2001:4860:4860::8888",
+];
+
+/// Default plain-HTTP probe that answers "success" when nothing intercepts it
+pub const DEFAULT_CONNECTIVITY_HTTP_URL: &str = "http://detectportal.firefox.com/success.txt";
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AppConfig {
+    /// Format version of the file (0 = written before versions existed), see `migrate`
+    #[serde(default)]
+    pub config_version: u32,
+
     /// Network rules for auto-connect/disconnect
     #[serde(default)]
     pub network_rules: Vec<NetworkRule>,
 
+    /// Older versions kept seen networks here; only read to move them to seen_networks.toml
+    #[serde(default, skip_serializing)]
+    pub seen_networks: Vec<seen::SeenNetwork>,
+
     /// Default VPN profile to use for auto-connect
     #[serde(skip_serializing_if = "Option::is_none")]
     pub default_profile: Option<String>,
@@ -29,34 +100,475 @@ pub struct AppConfig {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub last_connected: Option<String>,
 
+    /// Tunnel always connected when the TUI or daemon starts (takes precedence over auto_reconnect)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub startup_profile: Option<String>,
+
+    /// TUI focus when it last quit: section, selected tunnel name and network identifier
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_section: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_selected_tunnel: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_selected_network: Option<String>,
+
     /// Auto-reconnect to last tunnel on startup/wake
     #[serde(default)]
     pub auto_reconnect: bool,
 
+    /// Daemon skips all automatic connects/disconnects while set (toggled from the TUI)
+    #[serde(default)]
+    pub paused: bool,
+
     /// Kill switch enabled
     #[serde(default)]
     pub kill_switch: bool,
 
-    /// Show notifications
+    /// With the kill switch on, regularly try to get out over the physical interface
+    /// and alert when it works
     #[serde(default)]
+    pub leak_check: bool,
+
+    /// Destinations (CIDR) still reachable while the kill switch is on (printers, NAS, router)
+    #[serde(default)]
+    pub killswitch_allowlist: Vec<String>,
+
+    /// Show desktop notifications (false silences the daemon and CLI entirely)
+    #[serde(default = "default_true")]
     pub notifications: bool,
 
+    /// `[notification_style]` icons, urgency and body of desktop notifications
+    #[serde(default)]
+    pub notification_style: NotificationStyle,
+
+    /// Also log to ~/.local/state/tonneru/ (rotated daily); RUST_LOG sets the level
+    #[serde(default = "default_true")]
+    pub log_file: bool,
+
+    /// Replace PrivateKey/PresharedKey with <redacted> when copying a config (`Y`)
+    #[serde(default = "default_true")]
+    pub redact_copied_configs: bool,
+
+    /// Warn on connect, and flag in the Tunnels list, when a peer has no PresharedKey
+    #[serde(default)]
+    pub require_psk: bool,
+
+    /// Keep the kill switch on through disconnects and tunnel switches; only `k` turns it off
+    #[serde(default)]
+    pub persistent_kill_switch: bool,
+
     /// Known/imported tunnels (we track these since /etc/wireguard needs root to read)
     #[serde(default)]
     pub known_tunnels: Vec<TunnelInfo>,
+
+    /// Tunnel groups folded up in the Tunnels list
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub collapsed_groups: Vec<String>,
+
+    /// Seconds before a status message in the info line auto-clears
+    #[serde(default = "default_status_message_secs")]
+    pub status_message_secs: u64,
+
+    /// Seconds to wait before applying a pending rule/tunnel/kill switch change
+    #[serde(default = "default_countdown_secs")]
+    pub countdown_secs: u64,
+
+    /// Apply rule/tunnel/kill switch changes right away, skipping the countdown
+    #[serde(default)]
+    pub instant_apply: bool,
+
+    /// Reconnect a connected tunnel after its config was edited (false asks first)
+    #[serde(default = "default_true")]
+    pub reconnect_on_edit: bool,
+
+    /// Seconds since the last handshake before a tunnel is flagged stale (widen on slow links)
+    #[serde(default = "default_handshake_stale_secs")]
+    pub handshake_stale_secs: u64,
+
+    /// Seconds between latency pings for the health popup (0 = only the 30s health check)
+    #[serde(default = "default_latency_sample_secs")]
+    pub latency_sample_secs: u64,
+
+    /// Look up the public IP after connecting (set false to never contact lookup services)
+    #[serde(default = "default_true")]
+    pub ip_lookup_enabled: bool,
+
+    /// Custom IP lookup endpoints (plain-text responders); None uses the built-in list
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ip_lookup_endpoints: Option<Vec<String>>,
+
+    /// How many lookup endpoints to query at once (first valid answer wins)
+    #[serde(default = "default_ip_lookup_concurrency")]
+    pub ip_lookup_concurrency: usize,
+
+    /// curl --connect-timeout for each lookup
+    #[serde(default = "default_ip_lookup_connect_timeout_secs")]
+    pub ip_lookup_connect_timeout_secs: u64,
+
+    /// curl --max-time for each lookup
+    #[serde(default = "default_ip_lookup_max_time_secs")]
+    pub ip_lookup_max_time_secs: u64,
+
+    /// Hosts (IPs or hostnames) pinged to check connectivity; IPv6 addresses test IPv6
+    #[serde(default = "default_connectivity_hosts")]
+    pub connectivity_hosts: Vec<String>,
+
+    /// Plain-HTTP URL used when pings fail; must answer "success" or 204 when online
+    #[serde(default = "default_connectivity_http_url")]
+    pub connectivity_http_url: String,
+
+    /// How tunnels are brought up: "auto", "wg-quick" or "systemd"
+    #[serde(default)]
+    pub connect_backend: ConnectBackend,
+
+    /// `[daemon]` check intervals and reconnect limits
+    #[serde(default)]
+    pub daemon: DaemonSettings,
+
+    /// Tunnel configs directory under $HOME (e.g. "~/.config/wireguard") used instead of
+    /// /etc/wireguard; configs there are managed without sudo
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub config_dir: Option<String>,
+
+    /// `[theme]` color overrides, applied on top of the Omarchy theme (or defaults)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub theme: Option<ThemeColors>,
+
+    /// `[keybindings]` action name -> key or keys, replacing that action's defaults
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub keybindings: BTreeMap<String, KeyList>,
+
+    /// Why config.toml was ignored when `load` fell back to defaults (never saved)
+    #[serde(skip)]
+    pub parse_error: Option<String>,
 }
 
+/// Set once the user agreed to replace an unparseable config.toml
+static OVERWRITE_BROKEN: AtomicBool = AtomicBool::new(false);
+
+/// One key ("k") or several (["k", "Up"]) for a keybinding
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum KeyList {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl KeyList {
+    pub fn keys(&self) -> &[String] {
+        match self {
+            KeyList::One(key) => std::slice::from_ref(key),
+            KeyList::Many(keys) => keys,
+        }
+    }
+}
+
+impl Default for AppConfig {
+    fn default() -> Self {
+        Self {
+            config_version: CONFIG_VERSION,
+            network_rules: Vec::new(),
+            seen_networks: Vec::new(),
+            default_profile: None,
+            last_connected: None,
+            startup_profile: None,
+            last_section: None,
+            last_selected_tunnel: None,
+            last_selected_network: None,
+            auto_reconnect: false,
+            paused: false,
+            kill_switch: false,
+            leak_check: false,
+            killswitch_allowlist: Vec::new(),
+            notifications: true,
+            notification_style: NotificationStyle::default(),
+            log_file: true,
+            redact_copied_configs: true,
+            require_psk: false,
+            persistent_kill_switch: false,
+            known_tunnels: Vec::new(),
+            collapsed_groups: Vec::new(),
+            status_message_secs: DEFAULT_STATUS_MESSAGE_SECS,
+            countdown_secs: DEFAULT_COUNTDOWN_SECS,
+            instant_apply: false,
+            reconnect_on_edit: true,
+            handshake_stale_secs: DEFAULT_HANDSHAKE_STALE_SECS,
+            latency_sample_secs: DEFAULT_LATENCY_SAMPLE_SECS,
+            ip_lookup_enabled: true,
+            ip_lookup_endpoints: None,
+            ip_lookup_concurrency: DEFAULT_IP_LOOKUP_CONCURRENCY,
+            ip_lookup_connect_timeout_secs: DEFAULT_IP_LOOKUP_CONNECT_TIMEOUT_SECS,
+            ip_lookup_max_time_secs: DEFAULT_IP_LOOKUP_MAX_TIME_SECS,
+            connectivity_hosts: default_connectivity_hosts(),
+            connectivity_http_url: default_connectivity_http_url(),
+            connect_backend: ConnectBackend::Auto,
+            daemon: DaemonSettings::default(),
+            config_dir: None,
+            theme: None,
+            keybindings: BTreeMap::new(),
+            parse_error: None,
+        }
+    }
+}
+
+/// Hex colors ("#RRGGBB" or "#RGB") for UI roles; unset fields keep the system theme
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ThemeColors {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub accent: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub accent_bright: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub danger: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub success: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub warning: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text_dim: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bg: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bg_selected: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inactive: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub header: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct TunnelInfo {
     pub name: String,
     pub protocol: String,  // "wireguard", "openvpn", etc.
     #[serde(default)]
     pub kill_switch: bool,  // Per-tunnel kill switch setting
+    #[serde(default)]
+    pub lifetime_rx_bytes: u64,  // Bytes received over all completed connections
+    #[serde(default)]
+    pub lifetime_tx_bytes: u64,  // Bytes sent over all completed connections
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,  // Free-text reminder shown under the config viewer
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,  // Labels like "work" or "streaming-US"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub backend: Option<ConnectBackend>,  // Overrides the global `connect_backend`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,  // Folder in the Tunnels list (default: shared name prefix)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_connect: Option<String>,  // Shell command run as the user after the TUI connects it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_disconnect: Option<String>,  // Shell command run as the user after the TUI disconnects it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quota_gib: Option<f64>,  // Monthly data quota (received + sent)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quota_reset_day: Option<u8>,  // Day of month the quota period starts (1-28, default 1)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub usage_period: Option<String>,  // Month the current period started in, e.g. "2024-03"
+    #[serde(default)]
+    pub period_bytes: u64,  // Bytes over completed connections this period
+    #[serde(default)]
+    pub quota_alerted: u8,  // Highest quota alert sent this period (0, 80 or 100)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub order: Option<u32>,  // Position in the Tunnels list (Shift+j/k), before unordered ones
+}
+
+/// How wg-quick tunnels are brought up
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ConnectBackend {
+    #[default]
+    Auto,     // systemd when wg-quick@<name> is enabled or running, else wg-quick
+    WgQuick,  // wg-quick up/down through the helper
+    Systemd,  // systemctl start/stop wg-quick@<name>
+}
+
+/// How aggressively the daemon watches the network and retries a dropped tunnel
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DaemonSettings {
+    pub check_interval_secs: u64,         // Network/VPN state check cycle
+    pub health_check_interval_secs: u64,  // Handshake and routing check of the connected tunnel
+    pub max_reconnect_attempts: u32,      // Retries before giving up until the next drop
+    pub reconnect_delay_ms: u64,          // Backoff base, doubled per failed attempt (up to 16x)
+}
+
+impl Default for DaemonSettings {
+    fn default() -> Self {
+        Self {
+            check_interval_secs: DEFAULT_CHECK_INTERVAL_SECS,
+            health_check_interval_secs: DEFAULT_HEALTH_CHECK_INTERVAL_SECS,
+            max_reconnect_attempts: DEFAULT_MAX_RECONNECT_ATTEMPTS,
+            reconnect_delay_ms: DEFAULT_RECONNECT_DELAY_MS,
+        }
+    }
+}
+
+impl DaemonSettings {
+    /// Pull out-of-range values back into limits that keep the daemon responsive
+    /// without hammering the system
+    fn validate(&mut self) {
+        fn clamp<T: Copy + Ord + std::fmt::Display>(name: &str, value: &mut T, min: T, max: T) {
+            let clamped = (*value).clamp(min, max);
+            if clamped != *value {
+                tracing::warn!("daemon.{} = {} is out of range, using {}", name, value, clamped);
+                *value = clamped;
+            }
+        }
+        clamp("check_interval_secs", &mut self.check_interval_secs, 1, 300);
+        clamp("health_check_interval_secs", &mut self.health_check_interval_secs, self.check_interval_secs, 3600);
+        clamp("max_reconnect_attempts", &mut self.max_reconnect_attempts, 1, 100);
+        clamp("reconnect_delay_ms", &mut self.reconnect_delay_ms, 100, 60_000);
+    }
+}
+
+/// Icons (theme names or paths) and urgency of desktop notifications
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NotificationStyle {
+    pub icon_connected: String,
+    pub icon_disconnected: String,
+    pub icon_warning: String,     // No internet, captive portal, data quota
+    pub icon_failure: String,     // Reconnecting gave up
+    pub icon_leak: String,
+    pub quiet_connects: bool,     // Connect/disconnect at Low urgency (most daemons show them silently)
+    pub critical_failures: bool,  // Failures and leaks at Critical urgency (stay until dismissed)
+    pub transfer_stats: bool,     // Add the session's received/sent bytes to disconnect notifications
+}
+
+impl Default for NotificationStyle {
+    fn default() -> Self {
+        Self {
+            icon_connected: "network-vpn".to_string(),
+            icon_disconnected: "network-vpn-disconnected".to_string(),
+            icon_warning: "network-error".to_string(),
+            icon_failure: "network-vpn-disconnected".to_string(),
+            icon_leak: "security-low".to_string(),
+            quiet_connects: false,
+            critical_failures: true,
+            transfer_stats: false,
+        }
+    }
 }
 
 impl AppConfig {
+    /// Bring a config written by an older version up to date
+    fn migrate(&mut self) {
+        if self.config_version < 1 {
+            // Versions before 1 saved `notifications = false` into every config without
+            // reading it; now that it silences notifications it can't be trusted
+            self.notifications = true;
+        }
+        self.config_version = CONFIG_VERSION;
+    }
+
+    /// Manual position of a tunnel in the Tunnels list, if it was moved
+    pub fn tunnel_order(&self, name: &str) -> Option<u32> {
+        self.known_tunnels.iter().find(|t| t.name == name).and_then(|t| t.order)
+    }
+
+    /// Group of each tunnel in `names`: its `group` setting, else the part of the name
+    /// before the first '-' or '_' when another tunnel shares it ("mullvad-se-got")
+    pub fn tunnel_groups(&self, names: &[&str]) -> Vec<Option<String>> {
+        fn prefix(name: &str) -> Option<&str> {
+            name.split_once(['-', '_'])
+                .map(|(prefix, _)| prefix)
+                .filter(|prefix| !prefix.is_empty())
+        }
+        names
+            .iter()
+            .map(|&name| {
+                let explicit = self.known_tunnels.iter()
+                    .find(|t| t.name == name)
+                    .and_then(|t| t.group.clone());
+                explicit.or_else(|| {
+                    let own = prefix(name)?;
+                    let shared = names.iter().filter(|other| prefix(other) == Some(own)).count() > 1;
+                    shared.then(|| own.to_string())
+                })
+            })
+            .collect()
+    }
+
+    /// Point everything that names tunnel `old` at `new`: its settings, rules,
+    /// default and last-used tunnel. An existing `new` entry is replaced
+    pub fn rename_tunnel(&mut self, old: &str, new: &str) {
+        self.known_tunnels.retain(|t| t.name != new);
+        if let Some(tunnel) = self.known_tunnels.iter_mut().find(|t| t.name == old) {
+            tunnel.name = new.to_string();
+        }
+        for rule in &mut self.network_rules {
+            if rule.tunnel_name.as_deref() == Some(old) {
+                rule.tunnel_name = Some(new.to_string());
+            }
+        }
+        for name in [&mut self.default_profile, &mut self.last_connected, &mut self.startup_profile, &mut self.last_selected_tunnel] {
+            if name.as_deref() == Some(old) {
+                *name = Some(new.to_string());
+            }
+        }
+    }
+
+    /// Hand seen networks an older version saved in config.toml to seen_networks.toml,
+    /// unless that file already has some
+    fn move_seen_networks(&mut self) {
+        if self.seen_networks.is_empty() {
+            return;
+        }
+        let legacy = std::mem::take(&mut self.seen_networks);
+        let moved = seen::update(|list| {
+            let empty = list.is_empty();
+            if empty {
+                *list = legacy;
+            }
+            empty
+        });
+        if let Err(e) = moved {
+            tracing::warn!("Could not move seen networks out of config.toml: {}", e);
+        }
+    }
+
+    /// `config_dir` with "~" expanded, if set and inside the home directory
+    /// (anything else keeps using /etc/wireguard through the helper)
+    pub fn user_config_dir(&self) -> Option<PathBuf> {
+        let raw = self.config_dir.as_deref()?;
+        let home = dirs::home_dir()?;
+        let dir = match raw.strip_prefix("~/") {
+            Some(rest) => home.join(rest),
+            None => PathBuf::from(raw),
+        };
+        if dir.starts_with(&home) && !raw.contains("..") {
+            Some(dir)
+        } else {
+            tracing::warn!("config_dir {} is not under {} - using /etc/wireguard", raw, home.display());
+            None
+        }
+    }
+
+    /// Drop connectivity hosts that aren't IPs or hostnames, falling back to the
+    /// defaults when nothing usable is left (a bad entry must not read as "offline")
+    fn validate_connectivity_targets(&mut self) {
+        self.connectivity_hosts.retain(|host| {
+            let valid = is_valid_host(host);
+            if !valid {
+                tracing::warn!("Ignoring invalid connectivity host: {}", host);
+            }
+            valid
+        });
+        if self.connectivity_hosts.is_empty() {
+            self.connectivity_hosts = default_connectivity_hosts();
+        }
+
+        let url = &self.connectivity_http_url;
+        if !(url.starts_with("http://") || url.starts_with("https://")) || url.contains(char::is_whitespace) {
+            tracing::warn!("Ignoring invalid connectivity_http_url: {}", url);
+            self.connectivity_http_url = default_connectivity_http_url();
+        }
+    }
+
     /// Get the config file path
-    fn config_path() -> Result<PathBuf> {
+    pub fn config_path() -> Result<PathBuf> {
         let config_dir = dirs::config_dir()
             .ok_or_else(|| anyhow::anyhow!("Could not find config directory"))?
             .join("tonneru");
@@ -69,6 +581,8 @@ impl AppConfig {
     }
 
     /// Load config from file, or create default
+    /// An unparseable file is copied to config.toml.bak and left alone: the defaults
+    /// come back with `parse_error` set and `save` refuses to replace it
     pub fn load() -> Result<Self> {
         let path = match Self::config_path() {
             Ok(p) => p,
@@ -78,9 +592,28 @@ impl AppConfig {
         if path.exists() {
             match std::fs::read_to_string(&path) {
                 Ok(content) => {
-                    match toml::from_str(&content) {
-                        Ok(config) => return Ok(config),
-                        Err(e) => tracing::warn!("Failed to parse config: {}", e),
+                    match toml::from_str::<AppConfig>(&content) {
+                        Ok(mut config) => {
+                            // Older versions saved SSIDs unescaped
+                            for rule in &mut config.network_rules {
+                                rule.identifier = crate::network::normalize_identifier(&rule.identifier);
+                            }
+                            config.migrate();
+                            config.move_seen_networks();
+                            config.validate_connectivity_targets();
+                            config.daemon.validate();
+                            return Ok(config);
+                        }
+                        Err(e) => {
+                            tracing::warn!("Failed to parse config: {}", e);
+                            if let Err(e) = std::fs::copy(&path, path.with_extension("toml.bak")) {
+                                tracing::warn!("Could not back up config: {}", e);
+                            }
+                            return Ok(AppConfig {
+                                parse_error: Some(describe_parse_error(&content, &e)),
+                                ..AppConfig::default()
+                            });
+                        }
                     }
                 }
                 Err(e) => tracing::warn!("Failed to read config: {}", e),
@@ -92,9 +625,23 @@ impl AppConfig {
         Ok(config)
     }
 
+    /// Let `save` replace config.toml even though it doesn't parse
+    pub fn allow_overwrite() {
+        OVERWRITE_BROKEN.store(true, Ordering::Relaxed);
+    }
+
     /// Save config to file
+    /// Refuses to replace a config.toml that doesn't parse until `allow_overwrite`
     pub fn save(&self) -> Result<()> {
         let path = Self::config_path()?;
+
+        if !OVERWRITE_BROKEN.load(Ordering::Relaxed) {
+            if let Ok(existing) = std::fs::read_to_string(&path) {
+                if toml::from_str::<AppConfig>(&existing).is_err() {
+                    anyhow::bail!("config.toml failed to parse - not overwriting it until it is fixed");
+                }
+            }
+        }
         
         // Clean up the config before saving
         let mut clean_config = self.clone();
@@ -106,6 +653,15 @@ impl AppConfig {
             r.identifier.len() > 5 // Must have prefix + name
         });
         
+        // Drop allowlist entries that aren't valid CIDRs (the helper would reject them)
+        clean_config.killswitch_allowlist.retain(|cidr| {
+            let valid = is_valid_cidr(cidr);
+            if !valid {
+                tracing::warn!("Dropping invalid kill switch allowlist entry: {}", cidr);
+            }
+            valid
+        });
+        
         // Convert empty tunnel names to None
         for rule in &mut clean_config.network_rules {
             if rule.tunnel_name.as_ref().map(|s| s.is_empty()).unwrap_or(false) {
@@ -120,6 +676,52 @@ impl AppConfig {
 
 }
 
+/// "line 12: invalid type: ..." for the banner, instead of toml's multi-line report
+fn describe_parse_error(content: &str, error: &toml::de::Error) -> String {
+    match error.span() {
+        Some(span) => {
+            let line = content[..span.start.min(content.len())].matches('\n').count() + 1;
+            format!("line {}: {}", line, error.message())
+        }
+        None => error.message().to_string(),
+    }
+}
+
+/// An IP address or a DNS hostname like "connect.example.cn"
+pub fn is_valid_host(s: &str) -> bool {
+    if s.parse::<std::net::IpAddr>().is_ok() {
+        return true;
+    }
+    !s.is_empty()
+        && s.len() <= 253
+        && s.split('.').all(|label| {
+            !label.is_empty()
+                && label.len() <= 63
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        })
+}
+
+/// Validate a CIDR string like "192.168.1.0/24" or "fd00::/8"
+pub fn is_valid_cidr(s: &str) -> bool {
+    let (addr, prefix) = match s.split_once('/') {
+        Some(parts) => parts,
+        None => return false,
+    };
+
+    let prefix: u8 = match prefix.parse() {
+        Ok(p) => p,
+        Err(_) => return false,
+    };
+
+    match addr.parse::<std::net::IpAddr>() {
+        Ok(std::net::IpAddr::V4(_)) => prefix <= 32,
+        Ok(std::net::IpAddr::V6(_)) => prefix <= 128,
+        Err(_) => false,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -127,29 +729,281 @@ mod tests {
     #[test]
     fn test_config_serialization() {
         let config = AppConfig {
+            config_version: CONFIG_VERSION,
             network_rules: vec![NetworkRule {
                 identifier: "wifi:MyNetwork".to_string(),
                 tunnel_name: Some("my-vpn".to_string()),
                 always_vpn: true,
                 never_vpn: false,
                 session_vpn: false,
+                priority: None,
+                dns_servers: Some(vec!["10.0.0.53".to_string()]),
+                kill_switch: Some(true),
+                match_open_networks: false,
+                suspended: false,
+            }],
+            seen_networks: vec![seen::SeenNetwork {
+                identifier: "wifi:Office".to_string(),
+                name: "Office".to_string(),
+                network_type: "wifi".to_string(),
+                device: "wlan0".to_string(),
+                ssid: Some("Office".to_string()),
+                last_seen: 1_700_000_000,
+                times_connected: 3,
             }],
             default_profile: Some("work-vpn".to_string()),
             last_connected: None,
+            startup_profile: Some("home-vpn".to_string()),
+            last_section: None,
+            last_selected_tunnel: None,
+            last_selected_network: None,
             auto_reconnect: false,
+            paused: false,
             kill_switch: false,
+            leak_check: true,
+            killswitch_allowlist: vec!["192.168.1.0/24".to_string()],
             notifications: true,
+            notification_style: NotificationStyle {
+                icon_leak: "dialog-warning".to_string(),
+                quiet_connects: true,
+                ..Default::default()
+            },
+            log_file: false,
+            redact_copied_configs: false,
+            require_psk: true,
+            persistent_kill_switch: true,
             known_tunnels: vec![TunnelInfo {
                 name: "my-vpn".to_string(),
                 protocol: "wireguard".to_string(),
                 kill_switch: false,
+                lifetime_rx_bytes: 1024,
+                lifetime_tx_bytes: 2048,
+                note: Some("Office VPN".to_string()),
+                tags: vec!["work".to_string()],
+                backend: Some(ConnectBackend::Systemd),
+                group: Some("office".to_string()),
+                on_connect: Some("mount /mnt/office".to_string()),
+                on_disconnect: None,
+                quota_gib: Some(50.0),
+                quota_reset_day: Some(15),
+                usage_period: Some("2024-03".to_string()),
+                period_bytes: 4096,
+                quota_alerted: 80,
+                order: Some(2),
             }],
+            collapsed_groups: vec!["office".to_string()],
+            status_message_secs: 5,
+            countdown_secs: 10,
+            instant_apply: false,
+            reconnect_on_edit: false,
+            handshake_stale_secs: 300,
+            latency_sample_secs: 2,
+            ip_lookup_enabled: false,
+            ip_lookup_endpoints: Some(vec!["https://ip.example.com".to_string()]),
+            ip_lookup_concurrency: 2,
+            ip_lookup_connect_timeout_secs: 2,
+            ip_lookup_max_time_secs: 4,
+            connectivity_hosts: vec!["223.5.5.5".to_string(), "baidu.com".to_string()],
+            connectivity_http_url: "http://connect.rom.miui.com/generate_204".to_string(),
+            connect_backend: ConnectBackend::WgQuick,
+            daemon: DaemonSettings {
+                check_interval_secs: 10,
+                max_reconnect_attempts: 8,
+                ..Default::default()
+            },
+            config_dir: Some("~/.config/wireguard".to_string()),
+            theme: Some(ThemeColors {
+                accent: Some("#89b4fa".to_string()),
+                ..Default::default()
+            }),
+            keybindings: BTreeMap::from([("move_up".to_string(), KeyList::Many(vec!["k".to_string(), "Up".to_string()]))]),
+            parse_error: Some("not saved".to_string()),
         };
 
         let serialized = toml::to_string_pretty(&config).unwrap();
         let deserialized: AppConfig = toml::from_str(&serialized).unwrap();
 
         assert_eq!(config.network_rules.len(), deserialized.network_rules.len());
+        // Read from older configs, never written back
+        assert!(deserialized.seen_networks.is_empty());
+        let legacy: AppConfig = toml::from_str("[[seen_networks]]\nidentifier = \"wifi:Office\"\nname = \"Office\"\nnetwork_type = \"wifi\"\ndevice = \"wlan0\"\nlast_seen = 1\n").unwrap();
+        assert_eq!(legacy.seen_networks[0].identifier, "wifi:Office");
         assert_eq!(config.default_profile, deserialized.default_profile);
+        assert_eq!(deserialized.startup_profile.as_deref(), Some("home-vpn"));
+        assert_eq!(config.killswitch_allowlist, deserialized.killswitch_allowlist);
+        assert_eq!(config.countdown_secs, deserialized.countdown_secs);
+        assert_eq!(config.handshake_stale_secs, deserialized.handshake_stale_secs);
+        assert_eq!(config.latency_sample_secs, deserialized.latency_sample_secs);
+        assert_eq!(config.log_file, deserialized.log_file);
+        assert!(!deserialized.redact_copied_configs);
+        assert!(deserialized.require_psk);
+        assert!(deserialized.persistent_kill_switch);
+        assert_eq!(config.notification_style, deserialized.notification_style);
+        assert_eq!(config.leak_check, deserialized.leak_check);
+        assert_eq!(config.ip_lookup_endpoints, deserialized.ip_lookup_endpoints);
+        assert_eq!(config.connectivity_hosts, deserialized.connectivity_hosts);
+        assert_eq!(config.connectivity_http_url, deserialized.connectivity_http_url);
+        assert_eq!(deserialized.known_tunnels[0].tags, vec!["work".to_string()]);
+        assert_eq!(deserialized.known_tunnels[0].group.as_deref(), Some("office"));
+        assert_eq!(config.collapsed_groups, deserialized.collapsed_groups);
+        assert_eq!(deserialized.known_tunnels[0].note.as_deref(), Some("Office VPN"));
+        assert_eq!(deserialized.known_tunnels[0].backend, Some(ConnectBackend::Systemd));
+        assert_eq!(deserialized.known_tunnels[0].on_connect.as_deref(), Some("mount /mnt/office"));
+        assert_eq!(deserialized.known_tunnels[0].on_disconnect, None);
+        assert_eq!(deserialized.known_tunnels[0].quota_gib, Some(50.0));
+        assert_eq!(deserialized.known_tunnels[0].quota_reset_day, Some(15));
+        assert_eq!(deserialized.known_tunnels[0].usage_period.as_deref(), Some("2024-03"));
+        assert_eq!((deserialized.known_tunnels[0].period_bytes, deserialized.known_tunnels[0].quota_alerted), (4096, 80));
+        assert_eq!(deserialized.tunnel_order("my-vpn"), Some(2));
+        assert_eq!(deserialized.connect_backend, ConnectBackend::WgQuick);
+        assert_eq!(deserialized.daemon.check_interval_secs, 10);
+        assert_eq!(deserialized.daemon.max_reconnect_attempts, 8);
+        assert_eq!(deserialized.keybindings["move_up"].keys(), ["k", "Up"]);
+        assert_eq!(deserialized.parse_error, None);
+        assert!(serialized.contains("connect_backend = \"wg-quick\""));
+        assert_eq!(
+            deserialized.theme.and_then(|t| t.accent).as_deref(),
+            Some("#89b4fa")
+        );
+    }
+
+    #[test]
+    fn test_timing_defaults_for_old_configs() {
+        // Configs written before these fields existed must keep the old behavior
+        let config: AppConfig = toml::from_str("kill_switch = false").unwrap();
+        assert_eq!(config.status_message_secs, DEFAULT_STATUS_MESSAGE_SECS);
+        assert_eq!(config.countdown_secs, DEFAULT_COUNTDOWN_SECS);
+        assert_eq!(config.handshake_stale_secs, DEFAULT_HANDSHAKE_STALE_SECS);
+        assert_eq!(config.latency_sample_secs, DEFAULT_LATENCY_SAMPLE_SECS);
+        assert!(config.log_file);
+        assert!(config.redact_copied_configs);
+        assert!(!config.require_psk);
+        assert!(!config.persistent_kill_switch);
+        assert!(config.startup_profile.is_none());
+        assert_eq!(config.notification_style, NotificationStyle::default());
+
+        // The `notifications = false` every old save wrote means nothing; a current one does
+        let mut old: AppConfig = toml::from_str("notifications = false").unwrap();
+        old.migrate();
+        assert!(old.notifications);
+        assert_eq!(old.config_version, CONFIG_VERSION);
+        let mut current: AppConfig = toml::from_str("config_version = 1\nnotifications = false").unwrap();
+        current.migrate();
+        assert!(!current.notifications);
+        assert!(config.ip_lookup_enabled);
+        assert!(config.ip_lookup_endpoints.is_none());
+        assert_eq!(config.ip_lookup_concurrency, DEFAULT_IP_LOOKUP_CONCURRENCY);
+        assert_eq!(config.ip_lookup_max_time_secs, DEFAULT_IP_LOOKUP_MAX_TIME_SECS);
+        assert_eq!(config.connectivity_hosts.len(), DEFAULT_CONNECTIVITY_HOSTS.len());
+        assert_eq!(config.connectivity_http_url, DEFAULT_CONNECTIVITY_HTTP_URL);
+        assert_eq!(config.daemon, DaemonSettings::default());
+    }
+
+    #[test]
+    fn test_daemon_settings_validation() {
+        let mut config: AppConfig = toml::from_str(
+            "[daemon]\ncheck_interval_secs = 0\nhealth_check_interval_secs = 2\nmax_reconnect_attempts = 10",
+        ).unwrap();
+        config.daemon.validate();
+        assert_eq!(config.daemon.check_interval_secs, 1);
+        assert_eq!(config.daemon.health_check_interval_secs, 2);
+        assert_eq!(config.daemon.max_reconnect_attempts, 10);
+        assert_eq!(config.daemon.reconnect_delay_ms, DEFAULT_RECONNECT_DELAY_MS);
+
+        // A health check can't run more often than the cycle that triggers it
+        config.daemon.check_interval_secs = 20;
+        config.daemon.validate();
+        assert_eq!(config.daemon.health_check_interval_secs, 20);
+    }
+
+    #[test]
+    fn test_connectivity_target_validation() {
+        assert!(is_valid_host("223.5.5.5"));
+        assert!(is_valid_host("2400:3200::1"));
+        assert!(is_valid_host("connect.example.cn"));
+        assert!(!is_valid_host("-bad.example"));
+        assert!(!is_valid_host("1.1.1.1; rm -rf"));
+        assert!(!is_valid_host(""));
+
+        let mut config: AppConfig = toml::from_str(
+            "connectivity_hosts = [\"bad host\"]\nconnectivity_http_url = \"ftp://x\"",
+        ).unwrap();
+        config.validate_connectivity_targets();
+        assert_eq!(config.connectivity_hosts.len(), DEFAULT_CONNECTIVITY_HOSTS.len());
+        assert_eq!(config.connectivity_http_url, DEFAULT_CONNECTIVITY_HTTP_URL);
+    }
+
+    #[test]
+    fn test_tunnel_groups() {
+        let mut config = AppConfig::default();
+        config.known_tunnels.push(TunnelInfo {
+            name: "office".to_string(),
+            group: Some("work".to_string()),
+            ..Default::default()
+        });
+        let names = ["mullvad-se-got", "mullvad_de-fra", "office", "home-wg", "proton"];
+        assert_eq!(config.tunnel_groups(&names), vec![
+            Some("mullvad".to_string()),
+            Some("mullvad".to_string()),
+            Some("work".to_string()),
+            None,  // No other "home" tunnel
+            None,
+        ]);
+    }
+
+    #[test]
+    fn test_describe_parse_error() {
+        let content = "kill_switch = false\nnotifications = yes\n";
+        let error = toml::from_str::<AppConfig>(content).unwrap_err();
+        assert!(describe_parse_error(content, &error).starts_with("line 2: "));
+    }
+
+    #[test]
+    fn test_rename_tunnel() {
+        let mut config = AppConfig::default();
+        for name in ["home", "office"] {
+            config.known_tunnels.push(TunnelInfo {
+                name: name.to_string(),
+                kill_switch: name == "home",
+                ..Default::default()
+            });
+        }
+        config.network_rules.push(NetworkRule {
+            identifier: "wifi:Cafe".to_string(),
+            tunnel_name: Some("home".to_string()),
+            always_vpn: true,
+            never_vpn: false,
+            session_vpn: false,
+            priority: None,
+            dns_servers: None,
+            kill_switch: None,
+            match_open_networks: false,
+            suspended: false,
+        });
+        config.last_connected = Some("home".to_string());
+        config.default_profile = Some("office".to_string());
+        config.startup_profile = Some("home".to_string());
+
+        // Renaming over "office" replaces its entry but keeps home's settings
+        config.rename_tunnel("home", "office");
+        assert_eq!(config.known_tunnels.len(), 1);
+        assert!(config.known_tunnels[0].kill_switch);
+        assert_eq!(config.network_rules[0].tunnel_name.as_deref(), Some("office"));
+        assert_eq!(config.last_connected.as_deref(), Some("office"));
+        assert_eq!(config.default_profile.as_deref(), Some("office"));
+        assert_eq!(config.startup_profile.as_deref(), Some("office"));
+    }
+
+    #[test]
+    fn test_is_valid_cidr() {
+        assert!(is_valid_cidr("192.168.1.0/24"));
+        assert!(is_valid_cidr("10.0.0.5/32"));
+        assert!(is_valid_cidr("fd00::/8"));
+
+        assert!(!is_valid_cidr("192.168.1.0"));
+        assert!(!is_valid_cidr("192.168.1.0/33"));
+        assert!(!is_valid_cidr("300.1.1.1/24"));
+        assert!(!is_valid_cidr("fd00::/129"));
+        assert!(!is_valid_cidr("lan/24"));
     }
 }
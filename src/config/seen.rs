@@ -0,0 +1,163 @@
+//! Networks seen before
+//!
+//! Kept in ~/.config/tonneru/seen_networks.toml rather than config.toml: the
+//! daemon and the TUI both add to the list, so every change is a locked
+//! read-modify-write of this file alone and can't undo the other's edits.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Seek, Write};
+use std::path::PathBuf;
+
+use crate::network::NetworkInfo;
+
+/// Most networks kept; the least recently seen go first
+const MAX_SEEN_NETWORKS: usize = 30;
+
+/// A remembered network's `last_seen` is only written back once it is this old
+const SEEN_REFRESH_SECS: u64 = 3600;
+
+/// A network from an earlier scan, listed while out of range so rules can be set up for it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SeenNetwork {
+    pub identifier: String,  // Same key as the network's rule
+    pub name: String,
+    pub network_type: String,
+    pub device: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ssid: Option<String>,
+    pub last_seen: u64,  // Unix seconds
+    #[serde(default)]
+    pub times_connected: u32,
+}
+
+impl SeenNetwork {
+    /// Row for the Networks box; its identifier matches the real network's
+    pub fn to_network(&self) -> NetworkInfo {
+        NetworkInfo {
+            name: self.name.clone(),
+            network_type: self.network_type.clone(),
+            device: self.device.clone(),
+            connected: false,
+            ssid: self.ssid.clone(),
+            signal: None,
+            band: None,
+            security: None,
+            away: true,
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct SeenFile {
+    #[serde(default)]
+    networks: Vec<SeenNetwork>,  // Newest first
+}
+
+/// Seen networks file location: ~/.config/tonneru/seen_networks.toml
+pub fn seen_path() -> Result<PathBuf> {
+    Ok(super::AppConfig::config_path()?.with_file_name("seen_networks.toml"))
+}
+
+/// Change the list under an exclusive lock; `change` returns whether to write it back
+/// Returns the list as it is afterwards
+pub fn update(change: impl FnOnce(&mut Vec<SeenNetwork>) -> bool) -> Result<Vec<SeenNetwork>> {
+    let path = seen_path()?;
+    let mut file = std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(&path)
+        .with_context(|| format!("Could not open {}", path.display()))?;
+    file.lock()?;
+
+    let mut content = String::new();
+    file.read_to_string(&mut content)?;
+    let mut seen: SeenFile = toml::from_str(&content).unwrap_or_else(|e| {
+        tracing::warn!("Could not parse {}, starting over: {}", path.display(), e);
+        SeenFile::default()
+    });
+
+    if change(&mut seen.networks) {
+        seen.networks.truncate(MAX_SEEN_NETWORKS);
+        file.set_len(0)?;
+        file.rewind()?;
+        file.write_all(toml::to_string_pretty(&seen)?.as_bytes())?;
+    }
+    Ok(seen.networks)
+}
+
+/// Remember a scanned network; `joined` counts a new connection to it
+/// Returns true when the list changed enough to be worth saving
+pub fn remember(seen: &mut Vec<SeenNetwork>, network: &NetworkInfo, joined: bool, now: u64) -> bool {
+    let identifier = network.identifier();
+    let changed = match seen.iter().position(|s| s.identifier == identifier) {
+        Some(index) => {
+            let mut entry = seen.remove(index);
+            let changed = joined || now.saturating_sub(entry.last_seen) >= SEEN_REFRESH_SECS;
+            entry.name = network.name.clone();
+            entry.last_seen = now;
+            entry.times_connected += u32::from(joined);
+            seen.insert(0, entry);
+            changed
+        }
+        None => {
+            seen.insert(0, SeenNetwork {
+                identifier,
+                name: network.name.clone(),
+                network_type: network.network_type.clone(),
+                device: network.device.clone(),
+                ssid: network.ssid.clone(),
+                last_seen: now,
+                times_connected: u32::from(joined),
+            });
+            true
+        }
+    };
+    seen.truncate(MAX_SEEN_NETWORKS);
+    changed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_remember() {
+        let network = |ssid: &str| NetworkInfo {
+            name: ssid.to_string(),
+            network_type: "wifi".to_string(),
+            device: "wlan0".to_string(),
+            connected: false,
+            ssid: Some(ssid.to_string()),
+            signal: Some(80),
+            band: None,
+            security: None,
+            away: false,
+        };
+        let mut seen = Vec::new();
+        assert!(remember(&mut seen, &network("Office"), true, 1000));
+        assert!(remember(&mut seen, &network("Cafe"), false, 1000));
+        // Seen again shortly after: nothing worth saving, but it moves to the front
+        assert!(!remember(&mut seen, &network("Office"), false, 1100));
+        assert_eq!(seen[0].identifier, "wifi:Office");
+        assert!(remember(&mut seen, &network("Office"), true, 1200));
+        assert_eq!(seen[0].times_connected, 2);
+
+        // Away rows keep the identifier rules are keyed on
+        let away = seen[1].to_network();
+        assert!(away.away && !away.connected);
+        assert_eq!(away.identifier(), network("Cafe").identifier());
+
+        for i in 0..MAX_SEEN_NETWORKS {
+            remember(&mut seen, &network(&format!("net{}", i)), false, 2000);
+        }
+        assert_eq!(seen.len(), MAX_SEEN_NETWORKS);
+        assert!(!seen.iter().any(|s| s.identifier == "wifi:Office"));
+
+        let file = SeenFile { networks: seen };
+        let parsed: SeenFile = toml::from_str(&toml::to_string_pretty(&file).unwrap()).unwrap();
+        assert_eq!(parsed.networks.len(), MAX_SEEN_NETWORKS);
+    }
+}
@@ -0,0 +1,257 @@
+//! Key bindings for the main screen
+//!
+//! Every action has default keys; `[keybindings]` in config.toml replaces the
+//! keys of individual actions, e.g. `move_up = ["k", "Up"]`. Popups, text
+//! input, q/Ctrl+c and Esc/Enter for a pending change stay hardcoded.
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::collections::{BTreeMap, HashMap};
+
+use crate::config::KeyList;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    NextSection,
+    PrevSection,
+    MoveDown,
+    MoveUp,
+    Activate,
+    Edit,
+    View,
+    Test,
+    MtuProbe,
+    Fastest,
+    ConnectAlongside,
+    NewTunnel,
+    Peers,
+    Labels,
+    Rename,
+    Filter,
+    Search,
+    Import,
+    Export,
+    Paste,
+    Delete,
+    CaptivePortal,
+    Refresh,
+    ReloadTheme,
+    CycleRule,
+    CycleTunnel,
+    PriorityUp,
+    PriorityDown,
+    KillSwitch,
+    Allowlist,
+    Help,
+    History,
+    Latency,
+    PauseDaemon,
+    Undo,
+    CopyIp,
+    CopyConfig,
+    PanicDisconnect,
+    MoveTunnelUp,
+    MoveTunnelDown,
+    Routing,
+    Lint,
+    ReloadConfig,
+}
+
+/// Config name and default keys of every action
+const DEFAULTS: &[(&str, Action, &[&str])] = &[
+    ("next_section", Action::NextSection, &["Tab"]),
+    ("prev_section", Action::PrevSection, &["BackTab"]),
+    ("move_down", Action::MoveDown, &["j", "Down"]),
+    ("move_up", Action::MoveUp, &["Up"]),
+    ("activate", Action::Activate, &["Space", "Enter"]),
+    ("edit", Action::Edit, &["e"]),
+    ("view", Action::View, &["v"]),
+    ("test", Action::Test, &["T"]),
+    ("mtu_probe", Action::MtuProbe, &["M"]),
+    ("fastest", Action::Fastest, &["S"]),
+    ("connect_alongside", Action::ConnectAlongside, &["A"]),
+    ("new_tunnel", Action::NewTunnel, &["n"]),
+    ("peers", Action::Peers, &["P"]),
+    ("labels", Action::Labels, &["l"]),
+    ("rename", Action::Rename, &["F2"]),
+    ("filter", Action::Filter, &["/"]),
+    ("search", Action::Search, &["s"]),
+    ("import", Action::Import, &["i"]),
+    ("export", Action::Export, &["x"]),
+    ("paste", Action::Paste, &["p"]),
+    ("delete", Action::Delete, &["d", "Delete", "Backspace"]),
+    ("captive_portal", Action::CaptivePortal, &["o"]),
+    ("refresh", Action::Refresh, &["R"]),
+    ("reload_theme", Action::ReloadTheme, &["Ctrl+r"]),
+    ("cycle_rule", Action::CycleRule, &["r"]),
+    ("cycle_tunnel", Action::CycleTunnel, &["t"]),
+    ("priority_up", Action::PriorityUp, &["+", "="]),
+    ("priority_down", Action::PriorityDown, &["-"]),
+    ("kill_switch", Action::KillSwitch, &["k"]),
+    ("allowlist", Action::Allowlist, &["a"]),
+    ("help", Action::Help, &["?", "h"]),
+    ("history", Action::History, &["H"]),
+    ("latency", Action::Latency, &["L"]),
+    ("pause_daemon", Action::PauseDaemon, &["D"]),
+    ("undo", Action::Undo, &["u"]),
+    ("copy_ip", Action::CopyIp, &["y"]),
+    ("copy_config", Action::CopyConfig, &["Y"]),
+    ("panic_disconnect", Action::PanicDisconnect, &["Ctrl+k"]),
+    ("move_tunnel_up", Action::MoveTunnelUp, &["K"]),
+    ("move_tunnel_down", Action::MoveTunnelDown, &["J"]),
+    ("routing", Action::Routing, &["I"]),
+    ("lint", Action::Lint, &["C"]),
+    ("reload_config", Action::ReloadConfig, &["c"]),
+];
+
+/// Lookup table from a key press to its action
+#[derive(Debug, Clone)]
+pub struct KeyMap {
+    bindings: HashMap<(KeyCode, KeyModifiers), Action>,
+}
+
+impl Default for KeyMap {
+    fn default() -> Self {
+        Self::new(&BTreeMap::new())
+    }
+}
+
+impl KeyMap {
+    /// Defaults with the user's overrides applied
+    /// Unknown actions and keys are skipped with a warning
+    pub fn new(overrides: &BTreeMap<String, KeyList>) -> Self {
+        let mut bindings = HashMap::new();
+        for (_, action, keys) in DEFAULTS {
+            for key in keys.iter().filter_map(|k| parse_key(k)) {
+                bindings.insert(key, *action);
+            }
+        }
+
+        for (name, keys) in overrides {
+            let Some(&(_, action, _)) = DEFAULTS.iter().find(|(n, _, _)| n == name) else {
+                tracing::warn!("Ignoring unknown keybinding action: {}", name);
+                continue;
+            };
+            let keys: Vec<_> = keys.keys().iter().filter_map(|k| {
+                let parsed = parse_key(k).filter(|key| !is_reserved(key));
+                if parsed.is_none() {
+                    tracing::warn!("Ignoring key '{}' for {}", k, name);
+                }
+                Some((k, parsed?))
+            }).collect();
+            if keys.is_empty() {
+                continue;
+            }
+
+            // The action's default keys go away; the new keys move to it
+            bindings.retain(|_, bound| *bound != action);
+            for (spec, key) in keys {
+                // Taking another action's key leaves that action without it
+                if let Some(previous) = bindings.insert(key, action).filter(|p| *p != action) {
+                    tracing::warn!("Key '{}' for {} was bound to {}, which loses it", spec, name, action_name(previous));
+                }
+            }
+        }
+
+        Self { bindings }
+    }
+
+    /// Action bound to a key press, if any
+    pub fn action(&self, key: &KeyEvent) -> Option<Action> {
+        self.bindings.get(&normalize(key.code, key.modifiers)).copied()
+    }
+}
+
+fn action_name(action: Action) -> &'static str {
+    DEFAULTS.iter().find(|(_, a, _)| *a == action).map_or("?", |(name, _, _)| name)
+}
+
+/// q and Ctrl+c always quit, so they can't be rebound
+fn is_reserved(key: &(KeyCode, KeyModifiers)) -> bool {
+    matches!(key, (KeyCode::Char('q'), KeyModifiers::NONE))
+        || *key == (KeyCode::Char('c'), KeyModifiers::CONTROL)
+}
+
+/// Shift is already part of the character ('J' vs 'j') and of BackTab
+fn normalize(code: KeyCode, modifiers: KeyModifiers) -> (KeyCode, KeyModifiers) {
+    (code, modifiers & (KeyModifiers::CONTROL | KeyModifiers::ALT))
+}
+
+/// Parse "j", "Space", "Ctrl+r", "Alt+Enter", "F5", ...
+fn parse_key(spec: &str) -> Option<(KeyCode, KeyModifiers)> {
+    let mut modifiers = KeyModifiers::NONE;
+    let mut shift = false;
+    let mut rest = spec;
+    // A lone "+" is a key, not a separator
+    while let Some((prefix, tail)) = rest.split_once('+').filter(|(_, tail)| !tail.is_empty()) {
+        match prefix.to_ascii_lowercase().as_str() {
+            "ctrl" | "control" => modifiers |= KeyModifiers::CONTROL,
+            "alt" => modifiers |= KeyModifiers::ALT,
+            "shift" => shift = true,
+            _ => return None,
+        }
+        rest = tail;
+    }
+
+    let mut chars = rest.chars();
+    let code = match (chars.next(), chars.next()) {
+        (Some(c), None) => KeyCode::Char(c),
+        _ => match rest.to_ascii_lowercase().as_str() {
+            "space" => KeyCode::Char(' '),
+            "enter" => KeyCode::Enter,
+            "tab" => KeyCode::Tab,
+            "backtab" => KeyCode::BackTab,
+            "esc" => KeyCode::Esc,
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            "pageup" => KeyCode::PageUp,
+            "pagedown" => KeyCode::PageDown,
+            "delete" => KeyCode::Delete,
+            "backspace" => KeyCode::Backspace,
+            f => KeyCode::F(f.strip_prefix('f')?.parse().ok().filter(|n| (1..=12).contains(n))?),
+        },
+    };
+    let code = match code {
+        KeyCode::Tab if shift => KeyCode::BackTab,
+        KeyCode::Char(c) if shift => KeyCode::Char(c.to_ascii_uppercase()),
+        code => code,
+    };
+    Some(normalize(code, modifiers))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+        KeyEvent::new(code, modifiers)
+    }
+
+    #[test]
+    fn test_keymap_overrides() {
+        assert_eq!(parse_key("Ctrl+r"), Some((KeyCode::Char('r'), KeyModifiers::CONTROL)));
+        assert_eq!(parse_key("+"), Some((KeyCode::Char('+'), KeyModifiers::NONE)));
+        assert_eq!(parse_key("F5"), Some((KeyCode::F(5), KeyModifiers::NONE)));
+        assert_eq!(parse_key("Shift+Tab"), Some((KeyCode::BackTab, KeyModifiers::NONE)));
+        assert_eq!(parse_key("Hyper+x"), None);
+        assert_eq!(parse_key("F13"), None);
+
+        let defaults = KeyMap::default();
+        assert_eq!(defaults.action(&press(KeyCode::Char('j'), KeyModifiers::NONE)), Some(Action::MoveDown));
+        assert_eq!(defaults.action(&press(KeyCode::Char('T'), KeyModifiers::SHIFT)), Some(Action::Test));
+        assert_eq!(defaults.action(&press(KeyCode::BackTab, KeyModifiers::SHIFT)), Some(Action::PrevSection));
+
+        // vim-style k for up; the kill switch takes K from move_tunnel_up; q stays reserved
+        let overrides: BTreeMap<String, KeyList> = toml::from_str(
+            "move_up = [\"k\", \"Up\"]\nkill_switch = \"K\"\nhelp = \"q\"\nbogus = \"z\"",
+        ).unwrap();
+        let map = KeyMap::new(&overrides);
+        assert_eq!(map.action(&press(KeyCode::Char('k'), KeyModifiers::NONE)), Some(Action::MoveUp));
+        assert_eq!(map.action(&press(KeyCode::Char('K'), KeyModifiers::SHIFT)), Some(Action::KillSwitch));
+        assert_eq!(map.action(&press(KeyCode::Char('q'), KeyModifiers::NONE)), None);
+        assert_eq!(map.action(&press(KeyCode::Char('?'), KeyModifiers::NONE)), Some(Action::Help));
+    }
+}
@@ -1,6 +1,8 @@
 mod app;
 mod config;
+mod keymap;
 mod network;
+mod notify;
 mod theme;
 mod ui;
 mod vpn;
@@ -14,9 +16,11 @@ use crossterm::{
 };
 use ratatui::{backend::CrosstermBackend, Terminal};
 use std::io;
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+use std::process::ExitCode;
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter, Layer};
 
 use app::{App, Popup};
+use notify::{Kind, Notice};
 
 #[derive(Parser, Debug)]
 #[command(name = "tonneru")]
@@ -28,6 +32,11 @@ struct Args {
     #[arg(short, long)]
     daemon: bool,
 
+    /// With --daemon: log what the rules would do without connecting or disconnecting
+    /// (also enabled by TONNERU_DRY_RUN=1)
+    #[arg(long, requires = "daemon")]
+    dry_run: bool,
+
     /// Output current VPN status as JSON (for waybar)
     #[arg(short, long)]
     status: bool,
@@ -36,44 +45,189 @@ struct Args {
     #[arg(short, long)]
     connect: Option<String>,
 
+    /// With --connect: wait for a handshake and a working connection; exits non-zero on timeout
+    #[arg(long, requires = "connect")]
+    wait: bool,
+
+    /// Seconds --wait gives the tunnel before failing
+    #[arg(long, value_name = "SECS", default_value_t = 30, requires = "wait")]
+    wait_timeout: u64,
+
     /// Disconnect from VPN
     #[arg(long)]
     disconnect: bool,
+
+    /// Reconnect the active tunnel (or the last connected one)
+    #[arg(long)]
+    reconnect: bool,
+
+    /// Disconnect if connected, otherwise connect the last used (or default) tunnel
+    #[arg(long)]
+    toggle: bool,
+
+    /// Turn the kill switch on or off, or print its state; exits 0 on success (status: enabled),
+    /// 1 on failure (status: disabled), 2 without the helper
+    #[arg(long, value_name = "STATE", value_enum)]
+    killswitch: Option<KillSwitchCommand>,
+
+    /// With --connect/--disconnect/--reconnect/--toggle/--killswitch: print the result as JSON instead of notifying
+    #[arg(long)]
+    json: bool,
+
+    /// Query the running daemon; exits 0 if healthy, 1 if unhealthy, 2 if not running
+    #[arg(long)]
+    daemon_status: bool,
+
+    /// Export all tunnel configs plus config.toml to a directory (for backup/migration)
+    #[arg(long, value_name = "DIR")]
+    export: Option<std::path::PathBuf>,
 }
 
-#[tokio::main]
-async fn main() -> Result<()> {
-    // Initialize logging
-    tracing_subscriber::registry()
-        .with(tracing_subscriber::fmt::layer())
-        .with(tracing_subscriber::EnvFilter::from_default_env())
-        .init();
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum KillSwitchCommand {
+    On,
+    Off,
+    Status,
+}
 
+impl Args {
+    /// No CLI command given, so the TUI takes over the terminal
+    fn runs_tui(&self) -> bool {
+        !(self.daemon
+            || self.status
+            || self.daemon_status
+            || self.disconnect
+            || self.reconnect
+            || self.toggle
+            || self.killswitch.is_some()
+            || self.connect.is_some()
+            || self.export.is_some())
+    }
+}
+
+/// Log files kept by the daily rotation
+const LOG_FILES_KEPT: usize = 7;
+
+#[tokio::main]
+async fn main() -> Result<ExitCode> {
     let args = Args::parse();
+    // Flushes the log file on exit, so exit codes are returned rather than std::process::exit
+    let _log_guard = init_logging(!args.runs_tui());
 
     // Handle CLI-only commands
     if args.status {
-        return print_status().await;
+        return print_status().await.map(|()| ExitCode::SUCCESS);
+    }
+
+    if args.daemon_status {
+        return print_daemon_status().await;
     }
 
     if args.disconnect {
-        return disconnect_vpn().await;
+        let before = vpn::wireguard::get_tunnels().await.unwrap_or_default();
+        let result = vpn::wireguard::disconnect().await;
+        return report(ActionResult::new("disconnect", None, result).with_before(before), args.json);
+    }
+
+    if args.reconnect {
+        let (profile, result) = reconnect_vpn().await;
+        return report(ActionResult::new("reconnect", profile, result), args.json);
+    }
+
+    if args.toggle {
+        let result = toggle_vpn().await;
+        if !args.json && result.success {
+            match (result.action, &result.profile) {
+                ("connect", Some(profile)) => println!("connected ({})", profile),
+                _ => println!("disconnected"),
+            }
+        }
+        return report(result, args.json);
+    }
+
+    if let Some(command) = args.killswitch {
+        return killswitch_cli(command, args.json).await;
     }
 
-    if let Some(profile) = args.connect {
-        return connect_vpn(&profile).await;
+    if let Some(query) = args.connect {
+        let profile = match resolve_profile(&query).await {
+            Ok(profile) => profile,
+            Err(e) => return report(ActionResult::new("connect", Some(query), Err(e)), args.json),
+        };
+        let result = if args.wait {
+            connect_and_wait(&profile, args.wait_timeout).await
+        } else {
+            vpn::wireguard::connect(&profile).await
+        };
+        return report(ActionResult::new("connect", Some(profile), result), args.json);
+    }
+
+    if let Some(dir) = args.export {
+        return export_tunnels(&dir).await.map(|()| ExitCode::SUCCESS);
     }
 
     if args.daemon {
-        return run_daemon().await;
+        return run_daemon(args.dry_run || env_dry_run()).await.map(|()| ExitCode::SUCCESS);
     }
 
     // Run TUI
-    run_tui().await
+    run_tui().await.map(|()| ExitCode::SUCCESS)
+}
+
+/// stderr logging for the daemon and CLI (the TUI's alternate screen hides it),
+/// plus a daily-rotated file under ~/.local/state/tonneru unless `log_file = false`
+fn init_logging(stderr: bool) -> Option<tracing_appender::non_blocking::WorkerGuard> {
+    let stderr_layer = stderr.then(|| {
+        tracing_subscriber::fmt::layer()
+            .with_writer(io::stderr)
+            .with_filter(EnvFilter::from_default_env())
+    });
+
+    let log_dir = config::AppConfig::load()
+        .is_ok_and(|c| c.log_file)
+        .then(|| dirs::state_dir().or_else(|| dirs::home_dir().map(|h| h.join(".local/state"))))
+        .flatten()
+        .map(|dir| dir.join("tonneru"));
+    let appender = log_dir.and_then(|dir| {
+        // Pruning old files fails noisily if the directory doesn't exist yet
+        let _ = std::fs::create_dir_all(&dir);
+        tracing_appender::rolling::Builder::new()
+            .rotation(tracing_appender::rolling::Rotation::DAILY)
+            .filename_prefix("tonneru")
+            .filename_suffix("log")
+            .max_log_files(LOG_FILES_KEPT)
+            .build(&dir)
+            .map_err(|e| eprintln!("Cannot log to {}: {}", dir.display(), e))
+            .ok()
+    });
+    let (file_layer, guard) = match appender {
+        Some(appender) => {
+            let (writer, guard) = tracing_appender::non_blocking(appender);
+            // Unlike stderr, the file is worth having at info level without RUST_LOG
+            let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("tonneru=info"));
+            let layer = tracing_subscriber::fmt::layer()
+                .with_writer(writer)
+                .with_ansi(false)
+                .with_filter(filter);
+            (Some(layer), Some(guard))
+        }
+        None => (None, None),
+    };
+
+    tracing_subscriber::registry()
+        .with(stderr_layer)
+        .with(file_layer)
+        .init();
+    guard
 }
 
 async fn print_status() -> Result<()> {
-    let status = vpn::wireguard::get_status().await?;
+    // Prefer the daemon's cached view; probe directly when it isn't running
+    let snapshot = network::status_socket::query().await;
+    let status = match &snapshot {
+        Some(snapshot) => snapshot.vpn_status.clone(),
+        None => vpn::wireguard::get_status(&config::AppConfig::load()?).await?,
+    };
     
     // Determine effective state (connected AND fresh handshake)
     let is_effectively_connected = status.connected && !status.handshake_stale;
@@ -115,6 +269,24 @@ async fn print_status() -> Result<()> {
         "VPN disconnected\nClick to manage".to_string()
     };
     
+    // Daemon's automatic actions suspended from the TUI
+    let paused = config::AppConfig::load().map(|c| c.paused).unwrap_or(false);
+    let tooltip = if paused { format!("{}\n⏸ Daemon paused", tooltip) } else { tooltip };
+    
+    // Timing details for richer tooltips (null when unavailable)
+    let uptime_secs = status.interface.as_deref()
+        .filter(|_| status.connected)
+        .and_then(vpn::wireguard::get_interface_uptime);
+    let handshake_age_secs = status.latest_handshake.as_deref()
+        .and_then(vpn::wireguard::parse_handshake_age);
+    let latency_ms = if !status.connected {
+        None
+    } else if let Some(snapshot) = &snapshot {
+        snapshot.vpn_health.latency_ms
+    } else {
+        vpn::wireguard::probe_latency()
+    };
+    
     // Output waybar-compatible JSON
     let output = serde_json::json!({
         "text": if status.connected { 
@@ -128,29 +300,306 @@ async fn print_status() -> Result<()> {
         "connected": status.connected,
         "interface": status.interface,
         "endpoint": status.endpoint,
-        "healthy": is_effectively_connected && status.routing_ok
+        "healthy": is_effectively_connected && status.routing_ok,
+        "uptime_secs": uptime_secs,
+        "handshake_age_secs": handshake_age_secs,
+        "latency_ms": latency_ms,
+        "daemon_paused": paused
     });
     
     println!("{}", serde_json::to_string(&output)?);
     Ok(())
 }
 
-async fn connect_vpn(profile: &str) -> Result<()> {
+async fn print_daemon_status() -> Result<ExitCode> {
+    let Some(snapshot) = network::status_socket::fetch().await else {
+        println!("Daemon: not running (no status socket)");
+        return Ok(ExitCode::from(2));
+    };
+    let daemon = &snapshot.daemon;
+    let now = network::status_socket::now_secs();
+    let ago = |t: u64| format!("{} ago", App::format_duration(now.saturating_sub(t)));
+
+    let mut problems = Vec::new();
+    if !snapshot.is_fresh() {
+        problems.push(format!("status last refreshed {}", ago(snapshot.updated_at)));
+    }
+    if daemon.reconnect_attempts > 0 {
+        problems.push(format!("reconnecting ({}/{})", daemon.reconnect_attempts, daemon.max_reconnect_attempts));
+    }
+    // Allow one missed interval before calling the health loop stuck
+    let health_due = daemon.last_health_check_at.unwrap_or(daemon.started_at) + 2 * daemon.health_check_interval_secs;
+    if now > health_due {
+        problems.push("health check overdue".to_string());
+    }
+
+    println!("Daemon: running (pid {}, up {})", daemon.pid, App::format_duration(now.saturating_sub(daemon.started_at)));
+    println!(
+        "Last network change: {}",
+        match (&daemon.last_network_id, daemon.last_network_change_at) {
+            (Some(id), Some(t)) => format!("{} ({})", id, ago(t)),
+            (None, Some(t)) => format!("disconnected ({})", ago(t)),
+            _ => "none yet".to_string(),
+        }
+    );
+    if daemon.paused {
+        println!("Paused: yes (no automatic connects/disconnects)");
+    }
+    println!("Reconnect attempts: {}/{}", daemon.reconnect_attempts, daemon.max_reconnect_attempts);
+    println!(
+        "Last health check: {}",
+        daemon.last_health_check_at.map(ago).unwrap_or_else(|| "not yet".to_string())
+    );
+    let vpn = &snapshot.vpn_status;
+    println!(
+        "VPN: {}",
+        if vpn.connected {
+            format!("{} connected{}", vpn.interface.as_deref().unwrap_or("?"), if vpn.handshake_stale { " (handshake stale)" } else { "" })
+        } else {
+            "disconnected".to_string()
+        }
+    );
+
+    if problems.is_empty() {
+        println!("Status: healthy");
+        Ok(ExitCode::SUCCESS)
+    } else {
+        println!("Status: unhealthy - {}", problems.join(", "));
+        Ok(ExitCode::FAILURE)
+    }
+}
+
+/// Outcome of --connect/--disconnect/--reconnect/--toggle, printed with --json
+#[derive(Debug, serde::Serialize)]
+struct ActionResult {
+    action: &'static str,
+    profile: Option<String>,
+    success: bool,
+    error: Option<String>,
+    #[serde(skip)]
+    before: vpn::wireguard::WgStatus,  // Tunnel state before a disconnect, for its transfer stats
+}
+
+impl ActionResult {
+    fn new(action: &'static str, profile: Option<String>, result: Result<()>) -> Self {
+        Self {
+            action,
+            profile,
+            success: result.is_ok(),
+            error: result.err().map(|e| format!("{:#}", e)),
+            before: Default::default(),
+        }
+    }
+
+    fn with_before(mut self, before: vpn::wireguard::WgStatus) -> Self {
+        self.before = before;
+        self
+    }
+}
+
+/// Print the result as JSON (exit code 1 on failure), or notify and return the error
+fn report(result: ActionResult, json: bool) -> Result<ExitCode> {
+    if json {
+        println!("{}", serde_json::to_string(&result)?);
+        return Ok(if result.success { ExitCode::SUCCESS } else { ExitCode::FAILURE });
+    }
+
+    if let Some(error) = result.error {
+        anyhow::bail!(error);
+    }
+    let profile = result.profile.as_deref().unwrap_or("VPN");
+    let notice = match result.action {
+        "disconnect" => Notice::new(Kind::Disconnect, "VPN disconnected").transfer(&result.before),
+        "reconnect" => Notice::new(Kind::Connect, format!("Reconnected to {}", profile)),
+        _ => Notice::new(Kind::Connect, format!("Connected to {}", profile)),
+    };
+    notice.show(&config::AppConfig::load().unwrap_or_default())?;
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Outcome of --killswitch, printed with --json
+#[derive(Debug, serde::Serialize)]
+struct KillSwitchResult {
+    action: &'static str,
+    enabled: bool,
+    success: bool,
+    error: Option<String>,
+}
+
+/// --killswitch on/off/status: print the resulting state ("enabled"/"disabled", or JSON)
+/// Errors go to stderr; see the flag's doc for exit codes
+async fn killswitch_cli(command: KillSwitchCommand, json: bool) -> Result<ExitCode> {
+    let action = match command {
+        KillSwitchCommand::On => "killswitch-on",
+        KillSwitchCommand::Off => "killswitch-off",
+        KillSwitchCommand::Status => "killswitch-status",
+    };
+    let finish = |enabled: bool, error: Option<String>, code: u8| -> Result<ExitCode> {
+        let result = KillSwitchResult { action, enabled, success: error.is_none(), error };
+        if json {
+            println!("{}", serde_json::to_string(&result)?);
+        } else {
+            if let Some(error) = &result.error {
+                eprintln!("Error: {}", error);
+            }
+            println!("{}", if enabled { "enabled" } else { "disabled" });
+        }
+        Ok(ExitCode::from(code))
+    };
+
+    // is_enabled() reads a missing helper as "off", which would be a lie here
+    if !vpn::helper_installed() {
+        return finish(false, Some(format!("{} is not installed", vpn::HELPER_PATH)), 2);
+    }
+    let enabled = vpn::killswitch::is_enabled().await.unwrap_or(false);
+    let status = vpn::wireguard::get_tunnels().await.unwrap_or_default();
+    let mut config = config::AppConfig::load().unwrap_or_default();
+
+    let result = match command {
+        KillSwitchCommand::Status => return finish(enabled, None, if enabled { 0 } else { 1 }),
+        KillSwitchCommand::On if enabled => Ok(()),
+        // Without a tunnel it blocks everything; only persistent mode means that on purpose
+        KillSwitchCommand::On if !status.connected && !config.persistent_kill_switch => {
+            return finish(enabled, Some("No VPN connected - connect a tunnel first (or set persistent_kill_switch = true)".to_string()), 1);
+        }
+        KillSwitchCommand::On => vpn::killswitch::enable().await,
+        KillSwitchCommand::Off => vpn::killswitch::disable().await,
+    };
+    if let Err(e) = result {
+        return finish(enabled, Some(format!("{:#}", e)), 1);
+    }
+
+    // Remember the choice like the TUI does: per tunnel when one is up, else globally
+    let on = command == KillSwitchCommand::On;
+    match status.interface.filter(|_| status.connected) {
+        Some(iface) => match config.known_tunnels.iter_mut().find(|t| t.name == iface) {
+            Some(tunnel) => tunnel.kill_switch = on,
+            None => config.known_tunnels.push(config::TunnelInfo {
+                name: iface,
+                protocol: "wireguard".to_string(),
+                kill_switch: on,
+                ..Default::default()
+            }),
+        },
+        None => config.kill_switch = on,
+    }
+    let _ = config.save();
+    finish(on, None, 0)
+}
+
+/// Name of the tunnel `--connect` means: exact, or a unique prefix/substring match
+async fn resolve_profile(query: &str) -> Result<String> {
+    let names: Vec<String> = vpn::wireguard::list_profiles().await
+        .unwrap_or_default()
+        .into_iter()
+        .map(|p| p.name)
+        .collect();
+    if names.is_empty() {
+        // Nothing listed (no helper?) - let connect report the real problem
+        return Ok(query.to_string());
+    }
+    vpn::wireguard::resolve_profile_name(query, &names)
+}
+
+/// Bring the active tunnel (or the last connected one) down and up again
+async fn reconnect_vpn() -> (Option<String>, Result<()>) {
+    let status = vpn::wireguard::get_tunnels().await.unwrap_or_default();
+    let profile = status.interface.clone()
+        .filter(|_| status.connected)
+        .or_else(|| config::AppConfig::load().ok().and_then(|c| c.last_connected));
+    let Some(profile) = profile else {
+        return (None, Err(anyhow::anyhow!("No tunnel to reconnect")));
+    };
+
+    let result = async {
+        if status.is_up(&profile) {
+            vpn::wireguard::disconnect_interface(&profile).await?;
+        }
+        vpn::wireguard::connect(&profile).await
+    }.await;
+    (Some(profile), result)
+}
+
+/// Disconnect when connected, otherwise connect `last_connected` or `default_profile`
+/// The current network's rule is suspended so the daemon doesn't revert it
+async fn toggle_vpn() -> ActionResult {
+    let status = vpn::wireguard::get_tunnels().await.unwrap_or_default();
+    let (action, profile, result) = if status.connected {
+        ("disconnect", status.interface.clone(), vpn::wireguard::disconnect().await)
+    } else {
+        let config = config::AppConfig::load().unwrap_or_default();
+        match config.last_connected.or(config.default_profile) {
+            Some(profile) => {
+                let result = vpn::wireguard::connect(&profile).await;
+                ("connect", Some(profile), result)
+            }
+            None => ("connect", None, Err(anyhow::anyhow!("No last connected or default tunnel to connect"))),
+        }
+    };
+
+    if result.is_ok() {
+        if let Err(e) = network::monitor::suspend_conflicting_rule(action == "connect").await {
+            tracing::warn!("Could not suspend the network rule: {}", e);
+        }
+    }
+    ActionResult::new(action, profile, result).with_before(status)
+}
+
+/// Connect, then poll until the handshake is fresh and traffic flows
+/// Progress goes to stderr so stdout stays clean for scripts
+async fn connect_and_wait(profile: &str, timeout_secs: u64) -> Result<()> {
+    let deadline = std::time::Instant::now() + std::time::Duration::from_secs(timeout_secs);
+    let iface = vpn::wireguard::sanitize_profile_name(profile);
+
+    eprintln!("Connecting to {}...", profile);
     vpn::wireguard::connect(profile).await?;
-    notify("tonneru", &format!("Connected to {}", profile))?;
-    Ok(())
+
+    eprintln!("Waiting for handshake...");
+    let config = config::AppConfig::load().unwrap_or_default();
+    let mut handshake = false;
+    while std::time::Instant::now() < deadline {
+        let status = vpn::wireguard::get_status(&config).await.unwrap_or_default();
+        let fresh = status
+            .interface_status(&iface)
+            .is_some_and(|s| s.latest_handshake.is_some() && !s.handshake_stale);
+        if !handshake && fresh {
+            handshake = true;
+            eprintln!("Handshake complete, checking connectivity...");
+        }
+        if handshake && vpn::wireguard::health_check(&status).await.can_reach_internet {
+            eprintln!("Connected to {}", profile);
+            return Ok(());
+        }
+        tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+    }
+
+    if handshake {
+        anyhow::bail!("Timed out after {}s: handshake done but the internet is unreachable", timeout_secs);
+    }
+    anyhow::bail!("Timed out after {}s waiting for a handshake", timeout_secs);
 }
 
-async fn disconnect_vpn() -> Result<()> {
-    vpn::wireguard::disconnect().await?;
-    notify("tonneru", "VPN disconnected")?;
+async fn export_tunnels(dir: &std::path::Path) -> Result<()> {
+    let written = vpn::export::export_all(dir).await?;
+    if written.is_empty() {
+        anyhow::bail!("Nothing to export");
+    }
+    for path in &written {
+        println!("{}", path.display());
+    }
+    eprintln!("⚠ {}", vpn::export::PRIVATE_KEY_WARNING);
     Ok(())
 }
 
-async fn run_daemon() -> Result<()> {
+async fn run_daemon(dry_run: bool) -> Result<()> {
     // Daemon mode for auto-connect based on network rules
     tracing::info!("Starting tonneru daemon");
-    network::monitor::start_monitoring().await
+    network::monitor::start_monitoring(dry_run).await
+}
+
+/// TONNERU_DRY_RUN set to anything but "" or "0"
+fn env_dry_run() -> bool {
+    std::env::var("TONNERU_DRY_RUN").is_ok_and(|v| !v.is_empty() && v != "0")
 }
 
 async fn run_tui() -> Result<()> {
@@ -166,6 +615,17 @@ async fn run_tui() -> Result<()> {
 
     // Main loop
     let result = run_app(&mut terminal, &mut app).await;
+    // Quitting mid-test must not leave the test tunnel up
+    if let Err(e) = app.cancel_connection_test().await {
+        tracing::warn!("Could not roll back the connection test: {}", e);
+    }
+    // Let a connect/disconnect that is still running finish and be recorded
+    if let Err(e) = app.finish_operation().await {
+        tracing::warn!("Could not finish the running change: {}", e);
+    }
+    if let Err(e) = app.save_ui_state() {
+        tracing::warn!("Could not save UI state: {}", e);
+    }
 
     // Restore terminal
     disable_raw_mode()?;
@@ -187,10 +647,10 @@ async fn run_app(
         terminal.draw(|f| ui::draw(f, app))?;
 
         if event::poll(std::time::Duration::from_millis(100))? {
-            if let Event::Key(key) = event::read()? {
-                if key.kind == KeyEventKind::Press {
+            match event::read()? {
+                Event::Key(key) if key.kind == KeyEventKind::Press => {
                     match key.code {
-                        KeyCode::Char('q') if app.popup == Popup::None => return Ok(()),
+                        KeyCode::Char('q') if app.popup == Popup::None && !app.tunnel_filter_active && !app.config_search_active => return Ok(()),
                         KeyCode::Char('c') if key.modifiers.contains(event::KeyModifiers::CONTROL) => {
                             return Ok(())
                         }
@@ -202,6 +662,13 @@ async fn run_app(
                         }
                     }
                 }
+                // Click to select, double-click to connect, wheel scrolls the config
+                Event::Mouse(mouse) => {
+                    if let Err(e) = app.handle_mouse(mouse).await {
+                        app.status_message = Some(format!("Error: {}", e));
+                    }
+                }
+                _ => {}
             }
         }
 
@@ -210,12 +677,4 @@ async fn run_app(
     }
 }
 
-fn notify(summary: &str, body: &str) -> Result<()> {
-    notify_rust::Notification::new()
-        .summary(summary)
-        .body(body)
-        .icon("network-vpn")
-        .show()?;
-    Ok(())
-}
 
@@ -0,0 +1,131 @@
+//! Push notifications of network changes for the daemon
+//!
+//! Follows `ip monitor address` so rules are re-evaluated as soon as an
+//! interface gains or loses its IPv4 address (joining or leaving a network)
+//! instead of on the next poll. Polling stays as the fallback. Tunnel
+//! interfaces coming and going (often tonneru's own doing) are ignored.
+
+use anyhow::{Context, Result};
+use std::collections::HashSet;
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::process::Command;
+use tokio::sync::mpsc;
+
+/// Before restarting an `ip monitor` that exited
+const RESTART_DELAY: Duration = Duration::from_secs(30);
+
+/// Lets DHCP and NetworkManager/iwd finish before the check reads the new state
+const SETTLE_DELAY: Duration = Duration::from_millis(1500);
+
+pub struct NetworkEvents {
+    rx: mpsc::Receiver<()>,
+}
+
+impl NetworkEvents {
+    /// Start following `ip monitor` in the background
+    pub fn spawn() -> Self {
+        // One queued event is enough: a burst means a single check
+        let (tx, rx) = mpsc::channel(1);
+        tokio::spawn(async move {
+            let mut child = match start_monitor() {
+                Ok(child) => child,
+                Err(e) => {
+                    tracing::warn!("No network change events, polling only: {:#}", e);
+                    return;
+                }
+            };
+            loop {
+                if let Err(e) = follow(&mut child, &tx).await {
+                    tracing::warn!("Network change events stopped: {:#}", e);
+                }
+                if tx.is_closed() {
+                    return;
+                }
+                tokio::time::sleep(RESTART_DELAY).await;
+                match start_monitor() {
+                    Ok(restarted) => child = restarted,
+                    Err(_) => continue,
+                }
+            }
+        });
+        Self { rx }
+    }
+
+    /// Wait for the next network change, once its burst of events settled
+    /// Never returns if the monitor couldn't run
+    pub async fn changed(&mut self) {
+        if self.rx.recv().await.is_none() {
+            std::future::pending::<()>().await;
+        }
+        tokio::time::sleep(SETTLE_DELAY).await;
+        while self.rx.try_recv().is_ok() {}
+    }
+}
+
+fn start_monitor() -> Result<tokio::process::Child> {
+    Command::new("ip")
+        .args(["-o", "-4", "monitor", "address"])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .kill_on_drop(true)
+        .spawn()
+        .context("Failed to run ip monitor")
+}
+
+/// Forward address changes until `ip monitor` exits or the daemon stops listening
+async fn follow(child: &mut tokio::process::Child, tx: &mpsc::Sender<()>) -> Result<()> {
+    let stdout = child.stdout.take().context("ip monitor has no stdout")?;
+    let mut lines = BufReader::new(stdout).lines();
+    // Remembered so a tunnel's "Deleted" event is recognized after the interface is gone
+    let mut tunnels: HashSet<String> = std::fs::read_dir("/sys/class/net")
+        .map(|entries| entries
+            .filter_map(|e| e.ok()?.file_name().into_string().ok())
+            .filter(|iface| is_tunnel(iface))
+            .collect())
+        .unwrap_or_default();
+    while let Some(line) = lines.next_line().await? {
+        if tx.is_closed() {
+            return Ok(());
+        }
+        let Some(iface) = changed_interface(&line) else {
+            continue;
+        };
+        if is_tunnel(iface) {
+            tunnels.insert(iface.to_string());
+        } else if !tunnels.contains(iface) {
+            let _ = tx.try_send(());
+        }
+    }
+    anyhow::bail!("ip monitor exited")
+}
+
+/// Interface of "3: wlan0    inet 192.168.1.23/24 ..." (or the same prefixed with "Deleted"),
+/// None for other events and lo
+fn changed_interface(line: &str) -> Option<&str> {
+    let mut words = line.split_whitespace().skip_while(|w| *w == "Deleted");
+    let _index = words.next();
+    let iface = words.next().filter(|iface| *iface != "lo")?;
+    (words.next() == Some("inet")).then_some(iface)
+}
+
+/// WireGuard (and other tun-style) interfaces have no link layer: ARPHRD_NONE
+fn is_tunnel(iface: &str) -> bool {
+    std::fs::read_to_string(format!("/sys/class/net/{}/type", iface))
+        .is_ok_and(|t| t.trim() == "65534")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_changed_interface() {
+        assert_eq!(changed_interface("3: wlan0    inet 192.168.1.23/24 brd 192.168.1.255 scope global dynamic wlan0\\       valid_lft 86400sec"), Some("wlan0"));
+        assert_eq!(changed_interface("Deleted 3: wlan0    inet 192.168.1.23/24 brd 192.168.1.255 scope global wlan0"), Some("wlan0"));
+        assert_eq!(changed_interface("7: wg0    inet 10.0.0.2/32 scope global wg0"), Some("wg0"));
+        assert_eq!(changed_interface("1: lo    inet 127.0.0.1/8 scope host lo"), None);
+        assert_eq!(changed_interface("3: wlan0: <BROADCAST,MULTICAST,UP,LOWER_UP> mtu 1500 state UP"), None);
+    }
+}
@@ -1,5 +1,7 @@
+pub mod events;
 pub mod monitor;
 pub mod power;
+pub mod status_socket;
 
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
@@ -11,19 +13,143 @@ pub struct NetworkInfo {
     pub device: String,         // e.g., "wlan0", "eth0"
     pub connected: bool,
     pub ssid: Option<String>,   // For WiFi - the actual SSID
+    #[serde(default)]
+    pub signal: Option<u8>,     // WiFi signal strength in percent, if the backend reports it
+    #[serde(default)]
+    pub band: Option<String>,   // WiFi band ("2.4GHz", "5GHz", "6GHz")
+    #[serde(default)]
+    pub security: Option<String>, // WiFi security as iwd names it ("open", "psk", "8021x", "wep")
+    #[serde(default)]
+    pub away: bool,             // Remembered from an earlier scan, not in range now
 }
 
 impl NetworkInfo {
     /// Get a unique identifier for this network (for rules)
+    /// SSIDs are normalized and percent-encoded so ':' can't be mistaken for the separator
     pub fn identifier(&self) -> String {
         if let Some(ssid) = &self.ssid {
-            format!("wifi:{}", ssid)
+            format!("wifi:{}", encode_ssid(&normalize_ssid(ssid)))
         } else if !self.name.is_empty() && self.name != self.device {
             format!("network:{}", self.name)
         } else {
             format!("device:{}", self.device)
         }
     }
+
+    /// Open (unencrypted) WiFi, where a VPN matters most
+    pub fn is_open(&self) -> bool {
+        self.security.as_deref() == Some("open")
+    }
+
+    /// All rule identifiers this network can match, most specific first
+    /// (e.g. "wifi:HomeNet" and the broader "device:wlan0")
+    pub fn rule_identifiers(&self) -> Vec<String> {
+        let mut ids = vec![self.identifier()];
+        let device_id = format!("device:{}", self.device);
+        if self.device != "-" && !ids.contains(&device_id) {
+            ids.push(device_id);
+        }
+        ids
+    }
+}
+
+/// Percent-encode the characters that would make a "wifi:" identifier ambiguous
+pub fn encode_ssid(ssid: &str) -> String {
+    let mut encoded = String::with_capacity(ssid.len());
+    for c in ssid.chars() {
+        match c {
+            '%' => encoded.push_str("%25"),
+            ':' => encoded.push_str("%3A"),
+            _ => encoded.push(c),
+        }
+    }
+    encoded
+}
+
+/// Reverse `encode_ssid`; None if the text isn't a valid encoding
+/// (a raw ':' or a '%' not followed by two hex digits)
+pub fn decode_ssid(encoded: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(encoded.len());
+    let mut rest = encoded.as_bytes();
+    while let Some((&b, tail)) = rest.split_first() {
+        match b {
+            b':' => return None,
+            b'%' => {
+                let hex = std::str::from_utf8(tail.get(..2)?).ok()?;
+                bytes.push(u8::from_str_radix(hex, 16).ok()?);
+                rest = &tail[2..];
+            }
+            _ => {
+                bytes.push(b);
+                rest = tail;
+            }
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
+/// Split a rule identifier into its kind and value ("wifi", SSID), decoding SSIDs
+pub fn parse_identifier(identifier: &str) -> Option<(&str, String)> {
+    let (kind, value) = identifier.split_once(':')?;
+    if kind == "wifi" {
+        Some((kind, decode_ssid(value)?))
+    } else {
+        Some((kind, value.to_string()))
+    }
+}
+
+/// Re-encode identifiers saved before SSIDs were escaped ("wifi:My:Net")
+/// so old rules keep matching; already-encoded identifiers are unchanged
+pub fn normalize_identifier(identifier: &str) -> String {
+    match identifier.split_once(':') {
+        Some(("wifi", ssid)) if parse_identifier(identifier).is_none() => {
+            format!("wifi:{}", encode_ssid(&normalize_ssid(ssid)))
+        }
+        _ => identifier.to_string(),
+    }
+}
+
+/// Split an `nmcli -t` line into fields, undoing its "\:" and "\\" escapes
+pub(crate) fn split_terse(line: &str) -> Vec<String> {
+    let mut fields = vec![String::new()];
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                if let (Some(next), Some(field)) = (chars.next(), fields.last_mut()) {
+                    field.push(next);
+                }
+            }
+            ':' => fields.push(String::new()),
+            _ => {
+                if let Some(field) = fields.last_mut() {
+                    field.push(c);
+                }
+            }
+        }
+    }
+    fields
+}
+
+/// Security types iwctl lists in the known-networks table
+const IWD_SECURITY_TYPES: [&str; 4] = ["psk", "open", "8021x", "wep"];
+
+/// Extract the SSID and security type from one row of `iwctl known-networks list`
+/// Columns are padded by display width, so header offsets don't line up for
+/// emoji or non-ASCII names; the SSID is everything before the last
+/// whitespace-delimited security keyword instead
+fn parse_known_network_line(line: &str) -> Option<(String, String)> {
+    let (security_at, security) = IWD_SECURITY_TYPES
+        .iter()
+        .flat_map(|kw| line.match_indices(kw))
+        .filter(|&(i, kw)| {
+            line[..i].ends_with(char::is_whitespace)
+                && line[i + kw.len()..].chars().next().is_none_or(char::is_whitespace)
+        })
+        .max_by_key(|&(i, _)| i)?;
+
+    let ssid = normalize_ssid(&line[..security_at]);
+    (!ssid.is_empty() && ssid != "Name").then(|| (ssid, security.to_string()))
 }
 
 /// Get all network connections
@@ -105,6 +231,8 @@ async fn get_iwd_networks() -> Result<Vec<NetworkInfo>> {
                 let stdout = strip_ansi(&raw_stdout);
                 let mut connected_ssid: Option<String> = None;
                 let mut is_connected = false;
+                let mut signal: Option<u8> = None;
+                let mut band: Option<String> = None;
 
                 for line in stdout.lines() {
                     let line = line.trim();
@@ -114,6 +242,18 @@ async fn get_iwd_networks() -> Result<Vec<NetworkInfo>> {
                         is_connected = true;
                     }
                     
+                    // Signal and band of the current connection
+                    // Format: "RSSI   -52 dBm" / "Frequency   5180"
+                    if let Some(rest) = line.strip_prefix("RSSI") {
+                        signal = rest.split_whitespace().next()
+                            .and_then(|v| v.parse::<i32>().ok())
+                            .map(rssi_to_percent);
+                    } else if let Some(rest) = line.strip_prefix("Frequency") {
+                        band = rest.split_whitespace().next()
+                            .and_then(|v| v.parse::<u32>().ok())
+                            .and_then(band_from_freq);
+                    }
+                    
                     // Extract SSID - handle multi-word SSIDs
                     // Format: "Connected network   My WiFi Name" 
                     if line.contains("Connected network") {
@@ -140,6 +280,10 @@ async fn get_iwd_networks() -> Result<Vec<NetworkInfo>> {
                             device: device.clone(),
                             connected: is_connected,
                             ssid: Some(ssid),
+                            signal,
+                            band: band.clone(),
+                            security: None,
+                            away: false,
                         });
                     }
                 }
@@ -159,86 +303,35 @@ async fn get_iwd_networks() -> Result<Vec<NetworkInfo>> {
                 
                 // Parse iwctl known-networks output
                 // Format: "  Name                              Security     Hidden..."
-                // We need to extract just the SSID name, not the security type
-                let lines: Vec<&str> = stdout.lines().collect();
-                
-                // Find the header line to determine column positions
-                let mut ssid_start_col = 2;  // Usually starts after 2 spaces
-                let mut security_start_col = 34; // Where "Security" column typically starts
-                
-                for line in &lines {
-                    if line.contains("Name") && line.contains("Security") {
-                        // Find where columns start
-                        if let Some(name_idx) = line.find("Name") {
-                            ssid_start_col = name_idx;
-                        }
-                        if let Some(sec_idx) = line.find("Security") {
-                            security_start_col = sec_idx;
-                        }
-                        break;
-                    }
-                }
-                
-                for line in lines.iter().skip(4) { // Skip header lines
-                    if line.is_empty() || line.trim().starts_with('-') || line.contains("---") {
+                for line in stdout.lines().skip(4) { // Skip header lines
+                    if line.trim().is_empty() || line.contains("---") {
                         continue;
                     }
-                    
-                    let line_str = *line;
-                    if line_str.len() <= ssid_start_col {
+                    let Some((ssid, security)) = parse_known_network_line(line) else {
                         continue;
-                    }
-                    
-                    // Extract SSID: from name column start to just before security column
-                    // But trim trailing whitespace to get clean SSID
-                    let ssid_end = security_start_col.min(line_str.len());
-                    let raw_ssid = if ssid_start_col < ssid_end {
-                        &line_str[ssid_start_col..ssid_end]
-                        } else {
-                        line_str
-                        };
-                        
-                    // Trim the SSID properly (removes trailing spaces before Security column)
-                    let ssid = normalize_ssid(raw_ssid.trim());
-                        
-                        if ssid.is_empty() || ssid == "Name" {
-                            continue;
-                        }
-                    
-                    // Extra validation: SSIDs shouldn't contain common security type strings
-                    if ssid.ends_with("psk") || ssid.ends_with("open") || ssid.ends_with("8021x") {
-                        // Probably parsed incorrectly, try to fix
-                        let clean_ssid = ssid
-                            .trim_end_matches("psk")
-                            .trim_end_matches("open")
-                            .trim_end_matches("8021x")
-                            .trim();
-                        if !clean_ssid.is_empty() && !seen_ssids.contains(clean_ssid) {
-                            seen_ssids.insert(clean_ssid.to_string());
-                            networks.push(NetworkInfo {
-                                name: clean_ssid.to_string(),
-                                network_type: "wifi".to_string(),
-                                device: "-".to_string(),
-                                connected: false,
-                                ssid: Some(clean_ssid.to_string()),
-                            });
+                    };
+
+                    // Skip if we already have this network (connected takes priority),
+                    // but `station show` doesn't report security, so take it from here
+                    if seen_ssids.contains(&ssid) {
+                        if let Some(existing) = networks.iter_mut().find(|n| n.ssid.as_ref() == Some(&ssid)) {
+                            existing.security.get_or_insert(security);
                         }
                         continue;
                     }
-                        
-                        // Skip if we already have this network (connected takes priority)
-                        if seen_ssids.contains(&ssid) {
-                            continue;
-                        }
-                        
-                        seen_ssids.insert(ssid.clone());
-                        networks.push(NetworkInfo {
-                            name: ssid.clone(),
-                            network_type: "wifi".to_string(),
-                            device: "-".to_string(),
-                            connected: false,
-                            ssid: Some(ssid),
-                        });
+
+                    seen_ssids.insert(ssid.clone());
+                    networks.push(NetworkInfo {
+                        name: ssid.clone(),
+                        network_type: "wifi".to_string(),
+                        device: "-".to_string(),
+                        connected: false,
+                        ssid: Some(ssid),
+                        signal: None,
+                        band: None,
+                        security: Some(security),
+                        away: false,
+                    });
                 }
             }
         }
@@ -327,6 +420,10 @@ async fn get_ethernet_interfaces() -> Result<Vec<NetworkInfo>> {
                     device: device.clone(),
                     connected,
                     ssid: None,
+                    signal: None,
+                    band: None,
+                    security: None,
+                    away: false,
                 });
             }
         }
@@ -358,7 +455,8 @@ async fn get_nm_networks() -> Result<Vec<NetworkInfo>> {
         let stdout = String::from_utf8_lossy(&output.stdout);
 
         for line in stdout.lines() {
-            let parts: Vec<&str> = line.split(':').collect();
+            // Connection names may contain ':' (escaped in terse mode)
+            let parts = split_terse(line);
             if parts.len() >= 4 {
                 let conn_name = parts[0].to_string();
                 let conn_type = parts[1].to_string();
@@ -400,11 +498,25 @@ async fn get_nm_networks() -> Result<Vec<NetworkInfo>> {
                     device: if device.is_empty() { "-".to_string() } else { device },
                     connected,
                     ssid,
+                    signal: None,
+                    band: None,
+                    security: None,
+                    away: false,
                 });
             }
         }
     }
 
+    // Signal, band and security from the last scan (saved networks that are out of range keep None)
+    let scan = get_nm_wifi_scan();
+    for network in networks.iter_mut().filter(|n| n.network_type == "wifi") {
+        if let Some(bss) = network.ssid.as_ref().and_then(|ssid| scan.get(ssid)) {
+            network.signal = Some(bss.signal);
+            network.band = bss.band.clone();
+            network.security = Some(bss.security.clone());
+        }
+    }
+
     networks.sort_by(|a, b| {
         match (a.connected, b.connected) {
             (true, false) => std::cmp::Ordering::Less,
@@ -416,6 +528,81 @@ async fn get_nm_networks() -> Result<Vec<NetworkInfo>> {
     Ok(networks)
 }
 
+/// One access point from NetworkManager's scan
+struct ScanResult {
+    signal: u8,
+    band: Option<String>,
+    security: String,  // Normalized to iwd's names
+}
+
+/// Map nmcli's SECURITY column ("", "WPA2", "WPA1 WPA2 802.1X", "WEP") to iwd's names
+fn nm_security(security: &str) -> &'static str {
+    if security.is_empty() || security == "--" {
+        "open"
+    } else if security.contains("802.1X") {
+        "8021x"
+    } else if security.contains("WEP") {
+        "wep"
+    } else {
+        "psk"
+    }
+}
+
+/// Signal/band/security per SSID from NetworkManager's cached scan results
+/// The strongest BSS wins when an SSID is seen on several access points
+fn get_nm_wifi_scan() -> std::collections::HashMap<String, ScanResult> {
+    use std::process::Command;
+
+    let mut scan = std::collections::HashMap::new();
+    let Ok(output) = Command::new("nmcli")
+        .args(["-t", "-f", "SSID,SIGNAL,FREQ,SECURITY", "device", "wifi", "list", "--rescan", "no"])
+        .output()
+    else {
+        return scan;
+    };
+    if !output.status.success() {
+        return scan;
+    }
+
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        // Terse mode escapes ':' inside fields as "\:"
+        let [ssid, signal, freq, security] = split_terse(line).try_into().unwrap_or_default();
+        let Ok(signal) = signal.parse::<u8>() else {
+            continue;
+        };
+        if ssid.is_empty() {
+            continue;
+        }
+        // Format: "5180 MHz"
+        let band = freq.split_whitespace().next()
+            .and_then(|mhz| mhz.parse::<u32>().ok())
+            .and_then(band_from_freq);
+
+        let stronger = scan.get(&ssid).is_none_or(|existing: &ScanResult| signal > existing.signal);
+        if stronger {
+            let security = nm_security(&security).to_string();
+            scan.insert(ssid, ScanResult { signal, band, security });
+        }
+    }
+    scan
+}
+
+/// Map a WiFi channel frequency (MHz) to its band label
+fn band_from_freq(mhz: u32) -> Option<String> {
+    let band = match mhz {
+        2400..=2500 => "2.4GHz",
+        4900..=5900 => "5GHz",
+        5925..=7125 => "6GHz",
+        _ => return None,
+    };
+    Some(band.to_string())
+}
+
+/// Convert RSSI (dBm) to a 0-100 quality percentage (-100 dBm → 0, -50 dBm → 100)
+fn rssi_to_percent(dbm: i32) -> u8 {
+    (2 * (dbm + 100)).clamp(0, 100) as u8
+}
+
 /// Fallback: get basic network interfaces with iw for SSID
 async fn get_basic_networks() -> Result<Vec<NetworkInfo>> {
     use std::process::Command;
@@ -467,6 +654,10 @@ async fn get_basic_networks() -> Result<Vec<NetworkInfo>> {
                     device,
                     connected,
                     ssid,
+                    signal: None,
+                    band: None,
+                    security: None,
+                    away: false,
                 });
             }
         }
@@ -506,13 +697,19 @@ pub async fn get_active_connection() -> Result<Option<NetworkInfo>> {
 }
 
 /// Internet connectivity status
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ConnectivityStatus {
     pub has_interface: bool,        // Network interface is up
     pub has_ip_address: bool,       // Has an IP address assigned
     pub can_reach_gateway: bool,    // Can ping the gateway
-    pub has_internet: bool,         // Can reach external hosts
+    pub has_internet: bool,         // Can reach external hosts over either address family
+    #[serde(default)]
+    pub has_internet_v4: bool,
+    #[serde(default)]
+    pub has_internet_v6: bool,
     pub latency_ms: Option<u32>,    // Round-trip time to test host
+    #[serde(default)]
+    pub captive_portal_url: Option<String>, // Sign-in page when a portal intercepts traffic
 }
 
 impl ConnectivityStatus {
@@ -525,6 +722,91 @@ impl ConnectivityStatus {
     pub fn is_partial(&self) -> bool {
         self.has_interface && self.has_ip_address && !self.has_internet
     }
+
+    /// "IPv4 only" / "IPv6 only" when just one address family gets out
+    pub fn family_label(&self) -> Option<&'static str> {
+        family_label(self.has_internet_v4, self.has_internet_v6)
+    }
+}
+
+/// Where connectivity checks probe, from `connectivity_hosts` and `connectivity_http_url`
+/// IPv6 addresses test IPv6; IPv4 addresses and hostnames test IPv4
+pub struct ProbeTargets {
+    pub v4: Vec<String>,
+    pub v6: Vec<String>,
+    pub http_url: String,
+}
+
+impl ProbeTargets {
+    /// Targets from the current config.toml (defaults if it can't be read)
+    pub fn load() -> Self {
+        Self::from_config(&crate::config::AppConfig::load().unwrap_or_default())
+    }
+
+    pub fn from_config(config: &crate::config::AppConfig) -> Self {
+        let (v6, v4) = config.connectivity_hosts
+            .iter()
+            .cloned()
+            .partition(|host| host.parse::<std::net::Ipv6Addr>().is_ok());
+        Self { v4, v6, http_url: config.connectivity_http_url.clone() }
+    }
+
+    /// Just the first host of each family, for quick checks
+    pub fn first_v4(&self) -> &[String] {
+        &self.v4[..self.v4.len().min(1)]
+    }
+
+    pub fn first_v6(&self) -> &[String] {
+        &self.v6[..self.v6.len().min(1)]
+    }
+}
+
+/// Label for a connection that works over only one address family
+pub fn family_label(v4: bool, v6: bool) -> Option<&'static str> {
+    match (v4, v6) {
+        (true, false) => Some("IPv4 only"),
+        (false, true) => Some("IPv6 only"),
+        _ => None,
+    }
+}
+
+/// Ping `hosts` in order until one answers; returns the elapsed time in ms
+pub fn ping_any(hosts: &[String], timeout_secs: u64) -> Option<u32> {
+    let start = std::time::Instant::now();
+    let timeout = timeout_secs.to_string();
+    hosts.iter()
+        .any(|host| {
+            std::process::Command::new("ping")
+                .args(["-c", "1", "-W", &timeout, host])
+                .output()
+                .is_ok_and(|o| o.status.success())
+        })
+        .then(|| start.elapsed().as_millis() as u32)
+}
+
+/// Outcome of the captive portal probe
+#[derive(Debug, PartialEq)]
+enum ProbeResult {
+    Online,
+    Portal(String),
+    Offline,
+}
+
+/// Parse curl output of the form "<body>\n<http_code> <redirect_url>"
+/// `probe_url` is what was fetched, shown when a portal replaced its content
+fn parse_portal_probe(output: &str, probe_url: &str) -> ProbeResult {
+    let (body, meta) = output.rsplit_once('\n').unwrap_or(("", output));
+    let (code, redirect) = meta.trim().split_once(' ').unwrap_or((meta.trim(), ""));
+
+    match code {
+        "200" if body.trim() == "success" => ProbeResult::Online,
+        "204" => ProbeResult::Online,
+        // Redirected to a login page
+        c if c.starts_with('3') && !redirect.is_empty() => ProbeResult::Portal(redirect.to_string()),
+        // Probe content was replaced - opening the probe URL shows the portal
+        "200" => ProbeResult::Portal(probe_url.to_string()),
+        _ => ProbeResult::Offline,
+    }
 }
 
 /// Check internet connectivity status
@@ -566,15 +848,19 @@ pub async fn check_connectivity() -> ConnectivityStatus {
     }
     
     // Check if we have an IP address on a non-VPN interface
+    // (IPv4, or a global IPv6 address on IPv6-only networks)
     if let Ok(output) = Command::new("ip")
-        .args(["-4", "-o", "addr", "show"])
+        .args(["-o", "addr", "show"])
         .output()
     {
         if output.status.success() {
             let stdout = String::from_utf8_lossy(&output.stdout);
             for line in stdout.lines() {
+                let usable = line.contains(" inet ")
+                    || (line.contains(" inet6 ") && line.contains("scope global"));
                 // Skip loopback and VPN interfaces
-                if !line.contains(" lo ") 
+                if usable
+                   && !line.contains(" lo ") 
                    && !line.contains(" wg")
                    && !line.contains("127.0.0.1")
                 {
@@ -614,63 +900,90 @@ pub async fn check_connectivity() -> ConnectivityStatus {
         }
     }
     
-    // Check actual internet connectivity
-    // Try multiple methods for reliability
-    let start = Instant::now();
-    
+    // Check actual internet connectivity, for IPv4 and IPv6 separately
     // Method 1: Try to reach common DNS servers (fast, reliable)
-    let dns_hosts = ["1.1.1.1", "8.8.8.8", "9.9.9.9"];
-    for host in dns_hosts {
-        if let Ok(output) = Command::new("ping")
-            .args(["-c", "1", "-W", "2", host])
+    let targets = ProbeTargets::load();
+    let v4_latency = ping_any(&targets.v4, 2);
+    let v6_latency = ping_any(&targets.v6, 2);
+    status.has_internet_v4 = v4_latency.is_some();
+    status.has_internet_v6 = v6_latency.is_some();
+    status.latency_ms = v4_latency.or(v6_latency);
+    
+    // Method 2: Try HTTP connectivity check (fallback if ICMP is blocked)
+    // A captive portal either redirects the probe or answers with its own page
+    for (family, reachable) in [("-4", status.has_internet_v4), ("-6", status.has_internet_v6)] {
+        if reachable {
+            continue;
+        }
+        let start = Instant::now();
+        if let Ok(output) = Command::new("curl")
+            .args([
+                family,
+                "-s",
+                "-w", "\n%{http_code} %{redirect_url}",
+                "--connect-timeout", "3",
+                "--max-time", "5",
+                &targets.http_url,
+            ])
             .output()
         {
             if output.status.success() {
-                status.has_internet = true;
-                status.latency_ms = Some(start.elapsed().as_millis() as u32);
-                return status;
+                match parse_portal_probe(&String::from_utf8_lossy(&output.stdout), &targets.http_url) {
+                    ProbeResult::Online => {
+                        if family == "-4" {
+                            status.has_internet_v4 = true;
+                        } else {
+                            status.has_internet_v6 = true;
+                        }
+                        status.latency_ms.get_or_insert(start.elapsed().as_millis() as u32);
+                    }
+                    ProbeResult::Portal(url) => {
+                        status.captive_portal_url.get_or_insert(url);
+                    }
+                    ProbeResult::Offline => {}
+                }
             }
         }
     }
     
-    // Method 2: Try HTTP connectivity check (fallback if ICMP is blocked)
-    // Use curl with timeout to check connectivity
-    if let Ok(output) = Command::new("curl")
-        .args([
-            "-s", "-o", "/dev/null", 
-            "-w", "%{http_code}", 
-            "--connect-timeout", "3",
-            "--max-time", "5",
-            "http://detectportal.firefox.com/success.txt"
-        ])
-        .output()
-    {
-        if output.status.success() {
-            let response = String::from_utf8_lossy(&output.stdout);
-            if response.starts_with("200") || response.starts_with("204") {
-                status.has_internet = true;
-                status.latency_ms = Some(start.elapsed().as_millis() as u32);
-            }
-        }
+    status.has_internet = status.has_internet_v4 || status.has_internet_v6;
+    if status.has_internet {
+        status.captive_portal_url = None;
     }
-    
     status
 }
 
+/// Interface carrying the IPv4 default route, ignoring `exclude` (the VPN's own interface)
+pub fn default_route_interface(exclude: Option<&str>) -> Option<String> {
+    let output = std::process::Command::new("ip")
+        .args(["-4", "route", "show", "default"])
+        .output()
+        .ok()?;
+    parse_default_route_interface(&String::from_utf8_lossy(&output.stdout), exclude)
+}
+
+/// Pick the `dev` of the lowest-metric default route, as the kernel would
+fn parse_default_route_interface(output: &str, exclude: Option<&str>) -> Option<String> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let words: Vec<&str> = line.split_whitespace().collect();
+            let value = |key: &str| words.iter().position(|w| *w == key).and_then(|i| words.get(i + 1));
+            let dev = *value("dev")?;
+            let metric: u32 = value("metric").and_then(|m| m.parse().ok()).unwrap_or(0);
+            Some((metric, dev))
+        })
+        .filter(|(_, dev)| Some(*dev) != exclude)
+        .min_by_key(|(metric, _)| *metric)
+        .map(|(_, dev)| dev.to_string())
+}
+
 /// Quick connectivity check - just verifies we can reach the internet
 /// Faster than full check_connectivity()
 pub async fn has_internet() -> bool {
-    use std::process::Command;
-    
-    // Quick ping to 1.1.1.1 (Cloudflare DNS - very reliable)
-    if let Ok(output) = Command::new("ping")
-        .args(["-c", "1", "-W", "2", "1.1.1.1"])
-        .output()
-    {
-        return output.status.success();
-    }
-    
-    false
+    // Quick ping to the first configured host, then its IPv6 counterpart
+    let targets = ProbeTargets::load();
+    ping_any(targets.first_v4(), 2).is_some() || ping_any(targets.first_v6(), 2).is_some()
 }
 
 /// IP lookup endpoints - randomized to avoid rate limiting and for privacy
@@ -685,11 +998,54 @@ const IP_ENDPOINTS: &[&str] = &[
     "https://api.my-ip.io/ip",
 ];
 
-/// Fetch public IP address from a random endpoint
-/// Returns the IP as a string, or None if all attempts fail
+/// Fetch public IPv4 address from a random endpoint
+/// Returns the IP as a string, or None if all attempts fail or lookups are disabled
 pub async fn get_public_ip() -> Option<String> {
-    use std::process::Command;
+    fetch_public_ip("-4", is_valid_ipv4).await
+}
+
+/// Fetch public IPv6 address from a random endpoint
+/// Returns None if the host (or tunnel) has no IPv6 route out
+pub async fn get_public_ipv6() -> Option<String> {
+    fetch_public_ip("-6", is_valid_ipv6).await
+}
+
+/// Public IP lookup settings from config.toml
+struct IpLookupSettings {
+    endpoints: Vec<String>,  // The user's own list if configured, else the built-in one
+    concurrency: usize,
+    connect_timeout_secs: u64,
+    max_time_secs: u64,
+}
+
+/// None when public-IP lookup is disabled
+fn ip_lookup_settings() -> Option<IpLookupSettings> {
+    let config = crate::config::AppConfig::load().unwrap_or_default();
+    if !config.ip_lookup_enabled {
+        return None;
+    }
+    Some(IpLookupSettings {
+        endpoints: config.ip_lookup_endpoints.unwrap_or_else(|| {
+            IP_ENDPOINTS.iter().map(|e| e.to_string()).collect()
+        }),
+        concurrency: config.ip_lookup_concurrency.max(1),
+        connect_timeout_secs: config.ip_lookup_connect_timeout_secs,
+        max_time_secs: config.ip_lookup_max_time_secs,
+    })
+}
+
+/// Query the IP lookup endpoints in pseudo-random order for one address family
+/// Endpoints run `concurrency` at a time; the first valid answer wins and the
+/// remaining requests are cancelled. `family_flag` is passed to curl ("-4" or "-6")
+async fn fetch_public_ip(family_flag: &str, validate: fn(&str) -> bool) -> Option<String> {
     use std::time::SystemTime;
+    use tokio::task::JoinSet;
+    
+    let settings = ip_lookup_settings()?;
+    let endpoints = &settings.endpoints;
+    if endpoints.is_empty() {
+        return None;
+    }
     
     // Simple randomization using system time
     let seed = SystemTime::now()
@@ -697,40 +1053,51 @@ pub async fn get_public_ip() -> Option<String> {
         .map(|d| d.as_nanos() as usize)
         .unwrap_or(0);
     
-    // Shuffle order by starting at random position
-    let start_idx = seed % IP_ENDPOINTS.len();
+    // Shuffle order by starting at random position, wrapping around
+    let start_idx = seed % endpoints.len();
+    let mut queue = (0..endpoints.len()).map(|i| endpoints[(start_idx + i) % endpoints.len()].clone());
     
-    // Try endpoints in pseudo-random order (starting from random position, wrapping around)
-    for i in 0..IP_ENDPOINTS.len() {
-        let idx = (start_idx + i) % IP_ENDPOINTS.len();
-        let endpoint = IP_ENDPOINTS[idx];
+    let mut running = JoinSet::new();
+    loop {
+        // Keep up to `concurrency` lookups in flight
+        while running.len() < settings.concurrency {
+            let Some(endpoint) = queue.next() else {
+                break;
+            };
+            let args = [
+                family_flag.to_string(),  // Address family (-4 / -6)
+                "-s".to_string(),         // Silent
+                "-f".to_string(),         // Fail silently on HTTP errors
+                "--connect-timeout".to_string(), settings.connect_timeout_secs.to_string(),
+                "--max-time".to_string(), settings.max_time_secs.to_string(),
+                endpoint.clone(),
+            ];
+            running.spawn(async move {
+                let output = tokio::process::Command::new("curl")
+                    .args(&args)
+                    .kill_on_drop(true)  // Losing lookups are killed when the set is dropped
+                    .output()
+                    .await
+                    .ok()?;
+                output.status.success().then(|| {
+                    (endpoint, String::from_utf8_lossy(&output.stdout).trim().to_string())
+                })
+            });
+        }
         
-        if let Ok(output) = Command::new("curl")
-            .args([
-                "-4",               // IPv4 only
-                "-s",               // Silent
-                "-f",               // Fail silently on HTTP errors
-                "--connect-timeout", "3",
-                "--max-time", "5",
-                endpoint,
-            ])
-            .output()
-        {
-            if output.status.success() {
-                let ip = String::from_utf8_lossy(&output.stdout)
-                    .trim()
-                    .to_string();
-                
-                // Validate it looks like an IPv4 address
-                if is_valid_ipv4(&ip) {
-                    tracing::debug!("Got public IP {} from {}", ip, endpoint);
-                    return Some(ip);
-                }
+        let Some(result) = running.join_next().await else {
+            break;
+        };
+        // Validate it looks like an address of the requested family
+        if let Ok(Some((endpoint, ip))) = result {
+            if validate(&ip) {
+                tracing::debug!("Got public IP {} from {}", ip, endpoint);
+                return Some(ip);  // Dropping `running` aborts the rest
             }
         }
     }
     
-    tracing::warn!("Failed to fetch public IP from all endpoints");
+    tracing::warn!("Failed to fetch public IP ({}) from all endpoints", family_flag);
     None
 }
 
@@ -751,45 +1118,213 @@ fn is_valid_ipv4(s: &str) -> bool {
     true
 }
 
-/// Forget/Delete a known network connection
-pub async fn forget_network(network: &NetworkInfo) -> Result<()> {
-    use std::process::Command;
+/// IPv6 validation (accepts compressed forms like "2001:db8::1")
+fn is_valid_ipv6(s: &str) -> bool {
+    // Endpoints return plain text, so anything with a zone, prefix or brackets is bogus
+    if s.is_empty() || !s.contains(':') || s.contains(['%', '/', '[', ']']) {
+        return false;
+    }
     
-    // 1. Try to forget using iwctl (if it's a wifi network)
-    if network.network_type == "wifi" {
-        if let Some(ssid) = &network.ssid {
-            tracing::info!("Attempting to forget network '{}' using iwctl", ssid);
-            let output = Command::new("iwctl")
-                .args(["known-networks", ssid, "forget"])
-                .output();
-                
-            if let Ok(output) = output {
-                if output.status.success() {
-                    return Ok(());
-                }
-            }
+    s.parse::<std::net::Ipv6Addr>().is_ok()
+}
+
+/// What forgetting a network removed
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct ForgetResult {
+    pub iwd: bool,              // iwctl known-networks ... forget succeeded
+    pub network_manager: bool,  // nmcli connection delete succeeded
+    pub rule_removed: bool,     // Its NetworkRule was dropped
+}
+
+impl ForgetResult {
+    /// Backends that dropped the network, e.g. "iwd and NetworkManager"
+    pub fn backends(&self) -> String {
+        match (self.iwd, self.network_manager) {
+            (true, true) => "iwd and NetworkManager".to_string(),
+            (true, false) => "iwd".to_string(),
+            (false, true) => "NetworkManager".to_string(),
+            (false, false) => "no backend".to_string(),
         }
     }
-    
-    // 2. Try to forget using nmcli (NetworkManager)
-    // Works for both wifi and ethernet if managed by NM
-    let id_to_delete = if let Some(ssid) = &network.ssid {
-        ssid
-    } else {
-        &network.name
+}
+
+/// Names to forget: the iwd known network (WiFi only) and the NetworkManager connection id
+fn removal_targets(network: &NetworkInfo) -> (Option<&str>, &str) {
+    let iwd = network.ssid.as_deref().filter(|_| network.network_type == "wifi");
+    let nm = network.ssid.as_deref().unwrap_or(&network.name);
+    (iwd, nm)
+}
+
+/// Drop the rule for exactly this network (not broader device rules); true if one was removed
+fn remove_network_rules(rules: &mut Vec<crate::config::NetworkRule>, network: &NetworkInfo) -> bool {
+    let identifier = network.identifier();
+    let before = rules.len();
+    rules.retain(|r| normalize_identifier(&r.identifier) != identifier);
+    rules.len() != before
+}
+
+/// Forget (delete) a known network in iwd and NetworkManager (both are tried, since a network
+/// can be saved in each) and remove its rule from `rules`
+/// Errors only when neither backend knew it; the rule is removed either way
+pub async fn forget_network(network: &NetworkInfo, rules: &mut Vec<crate::config::NetworkRule>) -> Result<ForgetResult> {
+    use std::process::Command;
+
+    let succeeded = |cmd: &mut Command| cmd.output().is_ok_and(|o| o.status.success());
+    let (iwd_ssid, nm_id) = removal_targets(network);
+    let mut result = ForgetResult {
+        rule_removed: remove_network_rules(rules, network),
+        ..Default::default()
     };
-    
-    tracing::info!("Attempting to delete connection '{}' using nmcli", id_to_delete);
-    let output = Command::new("nmcli")
-        .args(["connection", "delete", id_to_delete])
-        .output();
-        
-    if let Ok(output) = output {
-        if output.status.success() {
-            return Ok(());
+
+    if let Some(ssid) = iwd_ssid {
+        tracing::info!("Forgetting network '{}' using iwctl", ssid);
+        result.iwd = succeeded(Command::new("iwctl").args(["known-networks", ssid, "forget"]));
+    }
+
+    // Works for both wifi and ethernet if managed by NM
+    tracing::info!("Deleting connection '{}' using nmcli", nm_id);
+    result.network_manager = succeeded(Command::new("nmcli").args(["connection", "delete", nm_id]));
+
+    if !result.iwd && !result.network_manager {
+        anyhow::bail!("Could not forget network '{}'. Is it a known network?", network.name);
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_valid_ipv6() {
+        assert!(is_valid_ipv6("2001:db8::1"));
+        assert!(is_valid_ipv6("::1"));
+        assert!(is_valid_ipv6("2001:0db8:0000:0000:0000:ff00:0042:8329"));
+        assert!(is_valid_ipv6("::ffff:192.0.2.1"));
+
+        assert!(!is_valid_ipv6(""));
+        assert!(!is_valid_ipv6("1.2.3.4"));
+        assert!(!is_valid_ipv6("2001:db8::1::2"));
+        assert!(!is_valid_ipv6("fe80::1%wlan0"));
+        assert!(!is_valid_ipv6("<html>error</html>"));
+    }
+
+    #[test]
+    fn test_parse_default_route_interface() {
+        let routes = "default via 192.168.1.1 dev wlan0 proto dhcp src 192.168.1.20 metric 600\n\
+                      default via 10.0.0.1 dev enp0s31f6 proto dhcp metric 100\n\
+                      default dev wg0 scope link\n";
+        assert_eq!(parse_default_route_interface(routes, Some("wg0")), Some("enp0s31f6".to_string()));
+        assert_eq!(parse_default_route_interface(routes, None), Some("wg0".to_string()));
+        assert_eq!(parse_default_route_interface("", None), None);
+    }
+
+    #[test]
+    fn test_parse_portal_probe() {
+        const URL: &str = crate::config::DEFAULT_CONNECTIVITY_HTTP_URL;
+        assert_eq!(parse_portal_probe("success\n\n200 ", URL), ProbeResult::Online);
+        assert_eq!(
+            parse_portal_probe("\n302 http://login.hotel.example/portal?x=1", URL),
+            ProbeResult::Portal("http://login.hotel.example/portal?x=1".to_string())
+        );
+        assert_eq!(
+            parse_portal_probe("<html>Welcome to Airport WiFi</html>\n200 ", URL),
+            ProbeResult::Portal(URL.to_string())
+        );
+        assert_eq!(parse_portal_probe("\n000 ", URL), ProbeResult::Offline);
+    }
+
+    #[test]
+    fn test_ssid_identifiers() {
+        let wifi = |ssid: &str| NetworkInfo {
+            name: ssid.to_string(),
+            network_type: "wifi".to_string(),
+            device: "wlan0".to_string(),
+            connected: true,
+            ssid: Some(ssid.to_string()),
+            signal: None,
+            band: None,
+            security: None,
+            away: false,
+        };
+
+        for ssid in ["My:Net", "100% Free", "Café ☕ 5G", "日本語の:ネット", "Home Net"] {
+            let id = wifi(ssid).identifier();
+            assert_eq!(parse_identifier(&id), Some(("wifi", ssid.to_string())));
+            assert_eq!(normalize_identifier(&id), id);
         }
+        assert_eq!(wifi("My:Net").identifier(), "wifi:My%3ANet");
+        assert_eq!(wifi("Home Net").identifier(), "wifi:Home Net");
+        // Trailing spaces/control chars differ between backends; they must not matter
+        assert_eq!(wifi("Cafe  ").identifier(), wifi("Cafe").identifier());
+
+        // Rules saved before escaping still match
+        assert_eq!(normalize_identifier("wifi:My:Net"), "wifi:My%3ANet");
+        assert_eq!(normalize_identifier("wifi:100%Free"), "wifi:100%25Free");
+        assert_eq!(normalize_identifier("device:wlan0"), "device:wlan0");
+
+        assert_eq!(split_terse("My\\:Net:802-11-wireless:wlan0:activated")[0], "My:Net");
+        assert_eq!(
+            parse_known_network_line("  Hotel  Lobby   WiFi     psk                     Oct 14, 9:12 AM"),
+            Some(("Hotel  Lobby   WiFi".to_string(), "psk".to_string()))
+        );
+        assert_eq!(
+            parse_known_network_line("  🚀 open net 🚀     open                    Oct 14, 9:12 AM"),
+            Some(("🚀 open net 🚀".to_string(), "open".to_string()))
+        );
+        assert_eq!(nm_security(""), "open");
+        assert_eq!(nm_security("WPA1 WPA2 802.1X"), "8021x");
+        assert_eq!(nm_security("WPA2 WPA3"), "psk");
+    }
+
+    #[test]
+    fn test_forget_targets() {
+        let mut network = NetworkInfo {
+            name: "Home Net".to_string(),
+            network_type: "wifi".to_string(),
+            device: "wlan0".to_string(),
+            connected: false,
+            ssid: Some("Home Net".to_string()),
+            signal: None,
+            band: None,
+            security: None,
+            away: false,
+        };
+        assert_eq!(removal_targets(&network), (Some("Home Net"), "Home Net"));
+
+        // Old-style and device rules: only the network's own rule goes
+        let rule = |identifier: &str| crate::config::NetworkRule {
+            identifier: identifier.to_string(),
+            tunnel_name: None,
+            always_vpn: true,
+            never_vpn: false,
+            session_vpn: false,
+            priority: None,
+            dns_servers: None,
+            kill_switch: None,
+            match_open_networks: false,
+            suspended: false,
+        };
+        let mut rules = vec![rule("wifi:Home Net"), rule("device:wlan0")];
+        assert!(remove_network_rules(&mut rules, &network));
+        assert_eq!(rules.len(), 1);
+        assert!(!remove_network_rules(&mut rules, &network));
+
+        network.network_type = "ethernet".to_string();
+        network.ssid = None;
+        network.name = "Wired connection 1".to_string();
+        assert_eq!(removal_targets(&network), (None, "Wired connection 1"));
+    }
+
+    #[test]
+    fn test_wifi_signal_helpers() {
+        assert_eq!(band_from_freq(2437).as_deref(), Some("2.4GHz"));
+        assert_eq!(band_from_freq(5180).as_deref(), Some("5GHz"));
+        assert_eq!(band_from_freq(5955).as_deref(), Some("6GHz"));
+        assert_eq!(band_from_freq(60000), None);
+
+        assert_eq!(rssi_to_percent(-45), 100);
+        assert_eq!(rssi_to_percent(-70), 60);
+        assert_eq!(rssi_to_percent(-110), 0);
     }
-    
-    // If we get here, we couldn't delete it
-    anyhow::bail!("Could not forget network '{}'. Is it a known network?", network.name)
 }
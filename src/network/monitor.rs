@@ -7,20 +7,62 @@
 //! - Applies network rules based on current connection
 
 use anyhow::Result;
-use std::time::Duration;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
 use tokio::time::{interval, sleep};
 
-use crate::config::AppConfig;
-use crate::network::{get_active_connection, check_connectivity, has_internet};
+use crate::config::{AppConfig, DaemonSettings, NetworkRule, NotificationStyle};
+use crate::config::history::{self, EventKind, HistoryEvent};
+use crate::network::{get_active_connection, check_connectivity, default_route_interface, has_internet, ConnectivityStatus};
+use crate::network::events::NetworkEvents;
 use crate::network::power::{PowerStateTracker, wait_for_network_ready};
-use crate::vpn::wireguard;
+use crate::network::status_socket::{self, DaemonState, SharedSnapshot};
+use crate::notify::{Kind, Notice};
+use crate::vpn::killswitch;
+use crate::vpn::wireguard::{self, WgStatus};
 
 /// Monitoring configuration
-const CHECK_INTERVAL_SECS: u64 = 5;
 const NETWORK_READY_TIMEOUT_SECS: u64 = 30;
-const VPN_RECONNECT_DELAY_MS: u64 = 2000;
-const VPN_HEALTH_CHECK_INTERVAL: u64 = 30; // Check VPN health every 30 seconds
-const MAX_RECONNECT_ATTEMPTS: u32 = 3;
+const ENDPOINT_FAILOVER_AFTER: u32 = 1; // Reconnects on the configured Endpoint before trying Endpoint-Alt
+
+/// Set by `--daemon --dry-run` or TONNERU_DRY_RUN: decide and log, but never
+/// touch the VPN, DNS, config.toml or send notifications
+static DRY_RUN: AtomicBool = AtomicBool::new(false);
+
+fn dry_run() -> bool {
+    DRY_RUN.load(Ordering::Relaxed)
+}
+
+/// Mirrors config.toml's `[notification_style]` (None with `notifications = false`),
+/// refreshed every cycle
+static NOTIFICATIONS: Mutex<Option<NotificationStyle>> = Mutex::new(None);
+
+/// Bumped whenever the daemon changes a tunnel, so results read before that are dropped
+static TUNNEL_CHANGES: AtomicU64 = AtomicU64::new(0);
+
+fn tunnel_changed() {
+    TUNNEL_CHANGES.fetch_add(1, Ordering::Relaxed);
+}
+
+/// What the cycle already read, reused by the snapshot instead of asking again
+#[derive(Default)]
+struct Observed {
+    generation: u64,  // TUNNEL_CHANGES when these were read
+    vpn_status: Option<WgStatus>,
+    connectivity: Option<ConnectivityStatus>,
+}
+
+impl Observed {
+    /// Start over when a tunnel changed since the last result
+    fn current(&mut self) -> &mut Self {
+        let generation = TUNNEL_CHANGES.load(Ordering::Relaxed);
+        if generation != self.generation {
+            *self = Self { generation, ..Default::default() };
+        }
+        self
+    }
+}
 
 /// Monitoring state
 struct MonitorState {
@@ -30,17 +72,39 @@ struct MonitorState {
     health_check_counter: u64,
     reconnect_attempts: u32,
     power_tracker: PowerStateTracker,
+    snapshot: SharedSnapshot,           // Served to clients over the status socket
+    last_snapshot_probe: Option<Instant>, // When connectivity/health were last probed
+    dns_override: Option<(String, String)>,  // (profile, interface) carrying a rule's DNS servers
+    started_at: u64,                    // Unix timestamps reported by --daemon-status
+    last_network_change_at: Option<u64>,
+    last_health_check_at: Option<u64>,
+    paused: bool,                       // Last seen config `paused`, to log transitions
+    egress_interface: Option<String>,   // Interface carrying the default route (VPN excluded)
+    active_endpoint: Option<String>,    // Endpoint-Alt in use after a failover (None = the config's Endpoint)
+    settings: DaemonSettings,           // `[daemon]` timing, refreshed with the config
+    observed: Observed,                 // This cycle's results for the snapshot
 }
 
 impl MonitorState {
-    fn new() -> Self {
+    fn new(settings: DaemonSettings) -> Self {
         Self {
             last_network_id: None,
             last_vpn_connected: false,
             last_vpn_interface: None,
             health_check_counter: 0,
             reconnect_attempts: 0,
-            power_tracker: PowerStateTracker::new(Duration::from_secs(CHECK_INTERVAL_SECS)),
+            power_tracker: PowerStateTracker::new(Duration::from_secs(settings.check_interval_secs)),
+            snapshot: SharedSnapshot::default(),
+            last_snapshot_probe: None,
+            dns_override: None,
+            started_at: status_socket::now_secs(),
+            last_network_change_at: None,
+            last_health_check_at: None,
+            paused: false,
+            egress_interface: None,
+            active_endpoint: None,
+            settings,
+            observed: Observed::default(),
         }
     }
 }
@@ -52,25 +116,78 @@ impl MonitorState {
 /// - Network changes
 /// - VPN connection drops
 /// - Internet connectivity changes
-pub async fn start_monitoring() -> Result<()> {
+pub async fn start_monitoring(dry_run: bool) -> Result<()> {
     let mut config = AppConfig::load()?;
-    let mut check_interval = interval(Duration::from_secs(CHECK_INTERVAL_SECS));
-    let mut state = MonitorState::new();
+    let mut check_interval = interval(Duration::from_secs(config.daemon.check_interval_secs));
+    let mut state = MonitorState::new(config.daemon);
 
+    DRY_RUN.store(dry_run, Ordering::Relaxed);
+    refresh_notifications(&config);
     tracing::info!("Starting tonneru daemon with resilient monitoring");
+    if dry_run {
+        tracing::info!("Dry run: actions are logged, not performed");
+    }
+
+    // Serve status to --status and the TUI; monitoring works without it
+    if let Err(e) = status_socket::spawn_server(state.snapshot.clone()) {
+        tracing::warn!("Status socket unavailable: {}", e);
+    }
 
     // Initial status check
-    let vpn_status = wireguard::get_status().await.unwrap_or_default();
+    let mut vpn_status = wireguard::get_status(&config).await.unwrap_or_default();
+
+    // Bring up the startup profile before the first check (rules still apply afterwards)
+    if let Some(profile) = config.startup_profile.as_deref().filter(|_| !vpn_status.connected && !config.paused) {
+        tracing::info!("Connecting startup profile: {}", profile);
+        match connect_vpn(profile).await {
+            Ok(()) => {
+                log_event(EventKind::Connect, format!("{} (startup profile)", profile));
+                notify_connect(profile);
+                vpn_status = wireguard::get_status(&config).await.unwrap_or_default();
+            }
+            Err(e) => {
+                tracing::error!("Startup profile {} failed: {:#}", profile, e);
+                log_event(EventKind::Error, format!("Startup profile {} failed: {}", profile, e));
+            }
+        }
+    }
     state.last_vpn_connected = vpn_status.connected;
     state.last_vpn_interface = vpn_status.interface.clone();
+    state.egress_interface = default_route_interface(vpn_status.interface.as_deref());
+
+    // Address changes wake the loop early; the interval keeps polling regardless
+    let mut events = NetworkEvents::spawn();
 
     loop {
-        check_interval.tick().await;
+        tokio::select! {
+            _ = check_interval.tick() => {}
+            _ = events.changed() => {
+                tracing::debug!("Network change reported, checking now");
+                check_interval.reset();
+            }
+        }
 
         // Reload config to pick up changes
         if let Ok(new_config) = AppConfig::load() {
             config = new_config;
         }
+        refresh_notifications(&config);
+
+        if config.daemon != state.settings {
+            tracing::info!("Daemon settings changed: {:?}", config.daemon);
+            if config.daemon.check_interval_secs != state.settings.check_interval_secs {
+                let period = Duration::from_secs(config.daemon.check_interval_secs);
+                check_interval = interval(period);
+                check_interval.reset();
+                state.power_tracker = PowerStateTracker::new(period);
+            }
+            state.settings = config.daemon;
+        }
+
+        if config.paused != state.paused {
+            state.paused = config.paused;
+            tracing::info!("Daemon {}", if config.paused { "paused" } else { "resumed" });
+        }
 
         // Check for power state changes (sleep/wake)
         let power_state = state.power_tracker.check();
@@ -81,7 +198,13 @@ pub async fn start_monitoring() -> Result<()> {
                 power_state.time_gap_ms,
                 power_state.uptime_secs
             );
-            handle_resume(&config, &mut state).await;
+            if config.paused {
+                state.power_tracker.reset_baseline();
+            } else {
+                handle_resume(&config, &mut state).await;
+            }
+            state.last_snapshot_probe = None; // Re-probe everything after resume
+            refresh_snapshot(&config, &mut state).await;
             continue; // Skip normal processing this cycle
         }
 
@@ -89,12 +212,76 @@ pub async fn start_monitoring() -> Result<()> {
         if let Err(e) = run_monitoring_cycle(&config, &mut state).await {
             tracing::error!("Monitoring cycle error: {}", e);
         }
+
+        refresh_snapshot(&config, &mut state).await;
+    }
+}
+
+/// Update the snapshot served over the status socket
+/// VPN status is refreshed every cycle; the slower connectivity and health
+/// probes follow the health check interval
+async fn refresh_snapshot(config: &AppConfig, state: &mut MonitorState) {
+    let observed = std::mem::take(state.observed.current());
+    let vpn_status = match observed.vpn_status {
+        Some(status) => status,
+        None => wireguard::get_status(config).await.unwrap_or_default(),
+    };
+
+    let probe_due = state.last_snapshot_probe
+        .is_none_or(|t| t.elapsed().as_secs() >= state.settings.health_check_interval_secs);
+    let probes = if probe_due {
+        state.last_snapshot_probe = Some(Instant::now());
+        let connectivity = match observed.connectivity {
+            Some(connectivity) => connectivity,
+            None => check_connectivity().await,
+        };
+        let health = if vpn_status.connected {
+            wireguard::health_check(&vpn_status).await
+        } else {
+            wireguard::VpnHealthCheck {
+                leak_detected: wireguard::detect_leak(None).await,
+                ..Default::default()
+            }
+        };
+        Some((connectivity, health))
+    } else {
+        None
+    };
+
+    if let Ok(mut snapshot) = state.snapshot.lock() {
+        // A tunnel going up or down invalidates the old health result
+        if snapshot.vpn_status.connected != vpn_status.connected && probes.is_none() {
+            state.last_snapshot_probe = None;
+        }
+        snapshot.vpn_status = vpn_status;
+        if let Some((connectivity, health)) = probes {
+            // Alert once per leak, not on every probe
+            if health.leak_detected && !snapshot.vpn_health.leak_detected {
+                log_event(EventKind::HealthFailure, "Traffic leaks past the kill switch");
+                notify_leak();
+            }
+            snapshot.connectivity = connectivity;
+            snapshot.vpn_health = health;
+        }
+        snapshot.updated_at = status_socket::now_secs();
+        snapshot.daemon = DaemonState {
+            pid: std::process::id(),
+            started_at: state.started_at,
+            last_network_id: state.last_network_id.clone(),
+            last_network_change_at: state.last_network_change_at,
+            reconnect_attempts: state.reconnect_attempts,
+            max_reconnect_attempts: state.settings.max_reconnect_attempts,
+            last_health_check_at: state.last_health_check_at,
+            health_check_interval_secs: state.settings.health_check_interval_secs,
+            paused: state.paused,
+        };
     }
 }
 
 /// Handle system resume from sleep
 async fn handle_resume(config: &AppConfig, state: &mut MonitorState) {
     tracing::info!("Handling system resume...");
+    log_event(EventKind::Resume, "System resumed from sleep");
     
     // Wait for network to come back up
     tracing::debug!("Waiting for network to be ready...");
@@ -102,6 +289,7 @@ async fn handle_resume(config: &AppConfig, state: &mut MonitorState) {
     
     if !network_ready {
         tracing::warn!("Network did not come up within {}s timeout", NETWORK_READY_TIMEOUT_SECS);
+        log_event(EventKind::HealthFailure, "Network not available after resume");
         notify_network_issue("Network not available after resume");
         state.power_tracker.reset_baseline();
         return;
@@ -110,10 +298,11 @@ async fn handle_resume(config: &AppConfig, state: &mut MonitorState) {
     tracing::info!("Network is ready after resume");
     
     // Small delay for network to fully stabilize
-    sleep(Duration::from_millis(VPN_RECONNECT_DELAY_MS)).await;
+    sleep(Duration::from_millis(state.settings.reconnect_delay_ms)).await;
     
     // Check internet connectivity
     let connectivity = check_connectivity().await;
+    state.observed.current().connectivity = Some(connectivity.clone());
     if !connectivity.has_internet {
         tracing::warn!("No internet connectivity after resume (has_ip: {}, gateway: {})",
             connectivity.has_ip_address, connectivity.can_reach_gateway);
@@ -128,15 +317,14 @@ async fn handle_resume(config: &AppConfig, state: &mut MonitorState) {
     
     // Get current network and VPN status
     let current_network = get_active_connection().await.ok().flatten();
-    let vpn_status = wireguard::get_status().await.unwrap_or_default();
+    let vpn_status = observe_status(config, state).await;
     
     // Update last known network
     state.last_network_id = current_network.as_ref().map(|n| n.identifier());
     
     // Determine what VPN state we should be in
     if let Some(network) = &current_network {
-        let rule = config.network_rules.iter()
-            .find(|r| r.identifier == network.identifier());
+        let rule = find_rule(config, network);
         
         match rule {
             Some(r) if r.always_vpn => {
@@ -147,11 +335,11 @@ async fn handle_resume(config: &AppConfig, state: &mut MonitorState) {
                     // Check if we need to reconnect
                     if !vpn_status.connected || vpn_status.interface.as_ref() != Some(tunnel) {
                         tracing::info!("Reconnecting VPN after resume (Always rule): {}", tunnel);
-                        reconnect_vpn(tunnel, state).await;
+                        reconnect_vpn(config, r, tunnel, state).await;
                     } else if !verify_vpn_health(&vpn_status).await {
                         // Connected but unhealthy
                         tracing::warn!("VPN connected but unhealthy after resume - reconnecting");
-                        reconnect_vpn(tunnel, state).await;
+                        reconnect_vpn(config, r, tunnel, state).await;
                     } else {
                         tracing::info!("VPN {} verified working after resume", tunnel);
                         notify_resume_ok(tunnel);
@@ -162,37 +350,35 @@ async fn handle_resume(config: &AppConfig, state: &mut MonitorState) {
                 // User requested: Session ends on sleep/hibernation
                 tracing::info!("Ending Session VPN after resume (sleep ended session)");
                 // Clear the session flag so it doesn't try to reconnect later
-                clear_session_rule(&network.identifier()).await;
+                clear_session_rule(state, &network.identifier()).await;
                 if vpn_status.connected {
-                    let _ = wireguard::disconnect().await;
-                    notify_session_ended();
+                    let before = disconnect_vpn().await.unwrap_or_default();
+                    notify_session_ended(&before);
+                    log_event(EventKind::Disconnect, "Session VPN ended by sleep");
                 }
             }
             Some(r) if r.never_vpn => {
                 // Should NOT be connected
                 if vpn_status.connected {
                     tracing::info!("Disconnecting VPN per 'never' rule after resume");
-                    if let Err(e) = wireguard::disconnect().await {
-                        tracing::error!("Failed to disconnect: {}", e);
-                    } else {
-                        notify_disconnect();
+                    match disconnect_vpn().await {
+                        Ok(before) => notify_disconnect(&before),
+                        Err(e) => tracing::error!("Failed to disconnect: {}", e),
                     }
                 }
             }
             _ => {
                 // No rule - leave VPN in current state but verify if connected
-                if vpn_status.connected {
-                    if !verify_vpn_health(&vpn_status).await {
-                        tracing::warn!("VPN unhealthy after resume, disconnecting");
-                        let _ = wireguard::disconnect().await;
-                    }
+                if vpn_status.connected && !verify_vpn_health(&vpn_status).await {
+                    tracing::warn!("VPN unhealthy after resume, disconnecting");
+                    let _ = disconnect_vpn().await;
                 }
             }
         }
     }
     
     // Update state
-    let new_status = wireguard::get_status().await.unwrap_or_default();
+    let new_status = observe_status(config, state).await;
     state.last_vpn_connected = new_status.connected;
     state.last_vpn_interface = new_status.interface.clone();
     state.reconnect_attempts = 0;
@@ -205,15 +391,42 @@ async fn run_monitoring_cycle(config: &AppConfig, state: &mut MonitorState) -> R
     let current_network = get_active_connection().await.ok().flatten();
     let current_id = current_network.as_ref().map(|n| n.identifier());
 
+    // Paused: keep tracking state so resuming doesn't replay what happened meanwhile
+    if config.paused {
+        if current_id != state.last_network_id {
+            state.last_network_id = current_id;
+            state.last_network_change_at = Some(status_socket::now_secs());
+        }
+        let vpn_status = observe_status(config, state).await;
+        state.last_vpn_connected = vpn_status.connected;
+        state.egress_interface = default_route_interface(vpn_status.interface.as_deref());
+        state.last_vpn_interface = vpn_status.interface;
+        return Ok(());
+    }
+
     // Check if network changed
-    if current_id != state.last_network_id {
+    let network_changed = current_id != state.last_network_id;
+    if network_changed {
         handle_network_change(config, state, &current_network, &current_id).await?;
     }
 
-    // Periodic VPN health check (every VPN_HEALTH_CHECK_INTERVAL seconds)
-    state.health_check_counter += CHECK_INTERVAL_SECS;
-    if state.health_check_counter >= VPN_HEALTH_CHECK_INTERVAL {
+    // Default route moved to another interface (e.g. WiFi -> dock ethernet)
+    let egress = default_route_interface(state.last_vpn_interface.as_deref());
+    if egress != state.egress_interface {
+        let previous = std::mem::replace(&mut state.egress_interface, egress.clone());
+        if let (Some(from), Some(to), Some(iface)) = (previous, egress, state.last_vpn_interface.clone()) {
+            // A network change already (re)connected the tunnel on the new path
+            if state.last_vpn_connected && !network_changed {
+                rebind_vpn(&iface, &from, &to).await;
+            }
+        }
+    }
+
+    // Periodic VPN health check (every `health_check_interval_secs`)
+    state.health_check_counter += state.settings.check_interval_secs;
+    if state.health_check_counter >= state.settings.health_check_interval_secs {
         state.health_check_counter = 0;
+        state.last_health_check_at = Some(status_socket::now_secs());
         check_vpn_health(config, state, &current_network).await?;
     }
 
@@ -228,27 +441,38 @@ async fn handle_network_change(
     current_id: &Option<String>,
 ) -> Result<()> {
     tracing::info!("Network changed: {:?} -> {:?}", state.last_network_id, current_id);
+    log_event(EventKind::NetworkChange, match current_network {
+        Some(network) => format!("Joined {}", network.name),
+        None => "Network disconnected".to_string(),
+    });
 
     // Clear session rules for the OLD network
-    if let Some(old_id) = &state.last_network_id {
-        clear_session_rule(old_id).await;
+    if let Some(old_id) = state.last_network_id.clone() {
+        clear_session_rule(state, &old_id).await;
     }
+    let lifted = lift_suspended_rules(config);
+    let config = lifted.as_ref().unwrap_or(config);
 
     if let Some(network) = current_network {
+        remember_joined_network(network);
+
         // Find matching rule
-        let rule = config.network_rules.iter()
-            .find(|r| r.identifier == network.identifier());
+        let rule = find_rule(config, network);
 
         match rule {
             Some(r) if r.always_vpn => {
                 tracing::info!("Auto-connecting VPN for network: {}", network.name);
                 let tunnel = r.tunnel_name.as_ref().or(config.default_profile.as_ref());
                 if let Some(profile) = tunnel {
-                    if let Err(e) = wireguard::connect(profile).await {
+                    if let Err(e) = connect_vpn(profile).await {
                         tracing::error!("Failed to auto-connect VPN: {}", e);
+                        log_event(EventKind::Error, format!("Auto-connect to {} failed: {}", profile, e));
                     } else {
                         notify_connect(profile);
+                        log_event(EventKind::Connect, format!("{} (Always rule on {})", profile, network.name));
                         state.reconnect_attempts = 0;
+                        apply_rule_dns(state, r, profile).await;
+                        apply_rule_kill_switch(r).await;
                     }
                 }
             }
@@ -256,20 +480,26 @@ async fn handle_network_change(
                 tracing::info!("Session VPN for network: {}", network.name);
                 let tunnel = r.tunnel_name.as_ref().or(config.default_profile.as_ref());
                 if let Some(profile) = tunnel {
-                    if let Err(e) = wireguard::connect(profile).await {
+                    if let Err(e) = connect_vpn(profile).await {
                         tracing::error!("Failed to connect session VPN: {}", e);
+                        log_event(EventKind::Error, format!("Session connect to {} failed: {}", profile, e));
                     } else {
                         notify_connect_session(profile);
+                        log_event(EventKind::Connect, format!("{} (Session rule on {})", profile, network.name));
                         state.reconnect_attempts = 0;
+                        apply_rule_dns(state, r, profile).await;
+                        apply_rule_kill_switch(r).await;
                     }
                 }
             }
             Some(r) if r.never_vpn => {
                 tracing::info!("Auto-disconnecting VPN for network: {}", network.name);
-                if let Err(e) = wireguard::disconnect().await {
-                    tracing::error!("Failed to auto-disconnect VPN: {}", e);
-                } else {
-                    notify_disconnect();
+                match disconnect_vpn().await {
+                    Ok(before) => {
+                        notify_disconnect(&before);
+                        log_event(EventKind::Disconnect, format!("Never rule on {}", network.name));
+                    }
+                    Err(e) => tracing::error!("Failed to auto-disconnect VPN: {}", e),
                 }
             }
             _ => {
@@ -281,9 +511,10 @@ async fn handle_network_change(
     }
 
     state.last_network_id = current_id.clone();
+    state.last_network_change_at = Some(status_socket::now_secs());
     
     // Update VPN state
-    let vpn_status = wireguard::get_status().await.unwrap_or_default();
+    let vpn_status = observe_status(config, state).await;
     state.last_vpn_connected = vpn_status.connected;
     state.last_vpn_interface = vpn_status.interface.clone();
     
@@ -296,19 +527,22 @@ async fn check_vpn_health(
     state: &mut MonitorState,
     current_network: &Option<crate::network::NetworkInfo>,
 ) -> Result<()> {
-    let vpn_status = wireguard::get_status().await.unwrap_or_default();
+    let vpn_status = observe_status(config, state).await;
     
     // Check for unexpected disconnection
     if state.last_vpn_connected && !vpn_status.connected {
         tracing::warn!("VPN disconnected unexpectedly!");
+        log_event(EventKind::HealthFailure, format!(
+            "{} dropped unexpectedly",
+            state.last_vpn_interface.as_deref().unwrap_or("VPN")
+        ));
         
         // Check if we should reconnect based on rules
         if let Some(network) = current_network {
-            let rule = config.network_rules.iter()
-                .find(|r| r.identifier == network.identifier());
+            let rule = find_rule(config, network);
             
             if let Some(r) = rule {
-                if (r.always_vpn || r.session_vpn) && state.reconnect_attempts < MAX_RECONNECT_ATTEMPTS {
+                if (r.always_vpn || r.session_vpn) && state.reconnect_attempts < state.settings.max_reconnect_attempts {
                     let tunnel = r.tunnel_name.clone()
                         .or_else(|| config.default_profile.clone())
                         .or_else(|| state.last_vpn_interface.clone());
@@ -316,10 +550,11 @@ async fn check_vpn_health(
                     if let Some(profile) = tunnel {
                         tracing::info!("Attempting to reconnect VPN: {} (attempt {})", 
                             profile, state.reconnect_attempts + 1);
-                        reconnect_vpn(&profile, state).await;
+                        reconnect_vpn(config, r, &profile, state).await;
                     }
-                } else if state.reconnect_attempts >= MAX_RECONNECT_ATTEMPTS {
+                } else if state.reconnect_attempts >= state.settings.max_reconnect_attempts {
                     tracing::error!("Max reconnect attempts reached, giving up");
+                    log_event(EventKind::Error, "Max reconnect attempts reached, giving up");
                     notify_vpn_failed("Max reconnect attempts reached");
                     state.reconnect_attempts = 0;
                 }
@@ -328,22 +563,23 @@ async fn check_vpn_health(
     }
     
     // Check VPN health if connected
-    if vpn_status.connected {
-        if !verify_vpn_health(&vpn_status).await {
-            tracing::warn!("VPN appears unhealthy (handshake stale: {}, routing ok: {})",
-                vpn_status.handshake_stale, vpn_status.routing_ok);
+    if vpn_status.connected && !verify_vpn_health(&vpn_status).await {
+        tracing::warn!("VPN appears unhealthy (handshake stale: {}, routing ok: {})",
+            vpn_status.handshake_stale, vpn_status.routing_ok);
+        log_event(EventKind::HealthFailure, format!(
+            "Unhealthy (handshake stale: {}, routing ok: {})",
+            vpn_status.handshake_stale, vpn_status.routing_ok
+        ));
+        
+        // Only try to fix if we should be connected
+        if let Some(network) = current_network {
+            let rule = find_rule(config, network);
             
-            // Only try to fix if we should be connected
-            if let Some(network) = current_network {
-                let rule = config.network_rules.iter()
-                    .find(|r| r.identifier == network.identifier());
-                
-                if let Some(r) = rule {
-                    if (r.always_vpn || r.session_vpn) && state.reconnect_attempts < MAX_RECONNECT_ATTEMPTS {
-                        if let Some(iface) = &vpn_status.interface {
-                            tracing::info!("Attempting VPN health recovery: {}", iface);
-                            reconnect_vpn(iface, state).await;
-                        }
+            if let Some(r) = rule {
+                if (r.always_vpn || r.session_vpn) && state.reconnect_attempts < state.settings.max_reconnect_attempts {
+                    if let Some(iface) = &vpn_status.interface {
+                        tracing::info!("Attempting VPN health recovery: {}", iface);
+                        reconnect_vpn(config, r, iface, state).await;
                     }
                 }
             }
@@ -357,6 +593,34 @@ async fn check_vpn_health(
     Ok(())
 }
 
+/// Find the rule that applies to a network
+/// When several rules match (SSID rule and device rule), the highest priority wins;
+/// ties go to the most specific identifier
+fn find_rule<'a>(config: &'a AppConfig, network: &crate::network::NetworkInfo) -> Option<&'a NetworkRule> {
+    let mut candidates: Vec<(usize, &NetworkRule)> = network.rule_identifiers()
+        .iter()
+        .enumerate()
+        .filter_map(|(specificity, id)| {
+            config.network_rules.iter()
+                .find(|r| &r.identifier == id)
+                .map(|r| (specificity, r))
+        })
+        .collect();
+
+    // Sort by priority descending (None is lowest), then most specific first
+    candidates.sort_by(|(spec_a, a), (spec_b, b)| {
+        b.priority.cmp(&a.priority).then(spec_a.cmp(spec_b))
+    });
+
+    // Any open network without a rule of its own gets the open-network policy
+    // A suspended rule still wins the match but takes no action
+    candidates.first().map(|(_, r)| *r).or_else(|| {
+        network.is_open()
+            .then(|| config.network_rules.iter().find(|r| r.match_open_networks))
+            .flatten()
+    }).filter(|r| !r.suspended)
+}
+
 /// Verify VPN is actually working (not just interface up)
 async fn verify_vpn_health(status: &wireguard::WgStatus) -> bool {
     if !status.connected {
@@ -383,45 +647,123 @@ async fn verify_vpn_health(status: &wireguard::WgStatus) -> bool {
 }
 
 /// Reconnect to VPN with exponential backoff
-async fn reconnect_vpn(profile: &str, state: &mut MonitorState) {
+/// `rule` is the Always/Session rule asking for the tunnel; its DNS servers and kill switch are put back
+async fn reconnect_vpn(config: &AppConfig, rule: &NetworkRule, profile: &str, state: &mut MonitorState) {
+    if dry_run() {
+        tracing::info!("[dry-run] Would reconnect {}", profile);
+        return;
+    }
+
     state.reconnect_attempts += 1;
     
     // Exponential backoff: 2s, 4s, 8s, etc.
-    let delay_ms = VPN_RECONNECT_DELAY_MS * (1 << state.reconnect_attempts.min(4));
+    let delay_ms = state.settings.reconnect_delay_ms * (1 << state.reconnect_attempts.min(4));
     
     // First disconnect cleanly
-    let _ = wireguard::disconnect().await;
+    let _ = disconnect_vpn().await;
     sleep(Duration::from_millis(500)).await;
     
     // Try to connect
-    match wireguard::connect(profile).await {
+    match connect_vpn(profile).await {
         Ok(_) => {
+            // Connecting resets the config's Endpoint; once that has failed, move down the alternates
+            state.active_endpoint = None;
+            if state.reconnect_attempts > ENDPOINT_FAILOVER_AFTER {
+                use_alternate_endpoint(profile, state).await;
+            }
+            apply_rule_dns(state, rule, profile).await;
+            apply_rule_kill_switch(rule).await;
+
             // Verify the connection actually works
             sleep(Duration::from_millis(1000)).await;
-            let status = wireguard::get_status().await.unwrap_or_default();
+            let status = observe_status(config, state).await;
             
             if status.connected && verify_vpn_health(&status).await {
                 tracing::info!("VPN reconnected successfully: {}", profile);
+                let via = state.active_endpoint.as_ref().map(|e| format!(" via {}", e)).unwrap_or_default();
+                log_event(EventKind::Reconnect, format!("{} reconnected{}", profile, via));
                 notify_reconnect(profile);
                 state.reconnect_attempts = 0;
             } else {
                 tracing::warn!("VPN connected but health check failed");
-                if state.reconnect_attempts < MAX_RECONNECT_ATTEMPTS {
+                log_event(EventKind::HealthFailure, format!("{} reconnected but health check failed", profile));
+                if state.reconnect_attempts < state.settings.max_reconnect_attempts {
                     sleep(Duration::from_millis(delay_ms)).await;
                 }
             }
         }
         Err(e) => {
             tracing::error!("VPN reconnect failed: {}", e);
-            if state.reconnect_attempts < MAX_RECONNECT_ATTEMPTS {
+            log_event(EventKind::Error, format!("Reconnect to {} failed: {}", profile, e));
+            if state.reconnect_attempts < state.settings.max_reconnect_attempts {
                 sleep(Duration::from_millis(delay_ms)).await;
             }
         }
     }
 }
 
+/// Point a freshly reconnected tunnel at the next `# Endpoint-Alt` of its config
+/// Cycles through the list (configured Endpoint included) with each further attempt
+async fn use_alternate_endpoint(profile: &str, state: &mut MonitorState) {
+    let Ok(config) = wireguard::read_config(profile).await else {
+        return;
+    };
+    let Some((public_key, endpoints)) = wireguard::failover_endpoints(&config) else {
+        return;
+    };
+    let index = (state.reconnect_attempts - ENDPOINT_FAILOVER_AFTER) as usize % endpoints.len();
+    if index == 0 {
+        return;  // Back to the configured Endpoint, which connect already set
+    }
+
+    let endpoint = &endpoints[index];
+    let interface = wireguard::sanitize_profile_name(profile);
+    tunnel_changed();
+    match wireguard::set_endpoint(&interface, &public_key, endpoint).await {
+        Ok(()) => {
+            tracing::info!("Failing over {} to {}", profile, endpoint);
+            log_event(EventKind::Reconnect, format!("{} failing over to {}", profile, endpoint));
+            state.active_endpoint = Some(endpoint.clone());
+        }
+        Err(e) => tracing::warn!("Failover of {} to {} failed: {}", profile, endpoint, e),
+    }
+}
+
+/// Make the tunnel pick a new source address after the egress interface changed
+/// Without this the UDP socket can keep using the old address until the handshake goes stale
+async fn rebind_vpn(interface: &str, from: &str, to: &str) {
+    tracing::info!("Default route moved {} -> {}, rebinding {}", from, to, interface);
+    log_event(EventKind::NetworkChange, format!("Roamed {} -> {}, rebound {}", from, to, interface));
+    if dry_run() {
+        tracing::info!("[dry-run] Would rebind {}", interface);
+        return;
+    }
+    tunnel_changed();
+    if let Err(e) = wireguard::rebind(interface).await {
+        tracing::warn!("Rebind of {} failed: {}", interface, e);
+        log_event(EventKind::Error, format!("Rebind of {} failed: {}", interface, e));
+    }
+}
+
 /// Clear session rule for a network (called when network changes/disconnects)
-async fn clear_session_rule(network_id: &str) {
+async fn clear_session_rule(state: &mut MonitorState, network_id: &str) {
+    if dry_run() {
+        tracing::info!("[dry-run] Would end any session rule for {}", network_id);
+        return;
+    }
+
+    // The old network's rule no longer applies - undo its DNS override,
+    // back to the tunnel's own servers if it stays up
+    if let Some((profile, iface)) = state.dns_override.take() {
+        let tunnel_dns = wireguard::read_config(&profile).await
+            .map(|config| wireguard::parse_dns_servers(&config))
+            .unwrap_or_default();
+        match wireguard::revert_dns(&iface, &tunnel_dns).await {
+            Ok(()) => tracing::info!("Restored DNS on {}", iface),
+            Err(e) => tracing::warn!("{:#}", e),
+        }
+    }
+
     if let Ok(mut config) = AppConfig::load() {
         let had_session = config.network_rules.iter().any(|r| 
             r.identifier == network_id && r.session_vpn
@@ -437,79 +779,215 @@ async fn clear_session_rule(network_id: &str) {
             } else {
                 tracing::info!("Cleared session rule for network: {}", network_id);
                 
-                if let Err(e) = wireguard::disconnect().await {
-                    tracing::error!("Failed to disconnect session VPN: {}", e);
-                } else {
-                    notify_session_ended();
+                match disconnect_vpn().await {
+                    Ok(before) => {
+                        notify_session_ended(&before);
+                        log_event(EventKind::Disconnect, "Session VPN ended by network change");
+                    }
+                    Err(e) => tracing::error!("Failed to disconnect session VPN: {}", e),
                 }
             }
         }
     }
 }
 
+/// Count a connection in the seen-networks history the TUI lists
+fn remember_joined_network(network: &crate::network::NetworkInfo) {
+    if dry_run() {
+        return;
+    }
+    let now = status_socket::now_secs();
+    if let Err(e) = crate::config::seen::update(|seen| crate::config::seen::remember(seen, network, true, now)) {
+        tracing::warn!("Could not record network {}: {}", network.name, e);
+    }
+}
+
+/// Suspend the current network's rule if it would undo a manual connect/disconnect (--toggle)
+/// Like a disconnect from the TUI, it lasts until the network changes
+pub async fn suspend_conflicting_rule(connected: bool) -> Result<()> {
+    let Some(network) = get_active_connection().await.ok().flatten() else {
+        return Ok(());
+    };
+    let mut config = AppConfig::load()?;
+    let Some(identifier) = find_rule(&config, &network)
+        .filter(|r| if connected { r.never_vpn } else { r.always_vpn || r.session_vpn })
+        .map(|r| r.identifier.clone())
+    else {
+        return Ok(());
+    };
+
+    if let Some(rule) = config.network_rules.iter_mut().find(|r| r.identifier == identifier) {
+        rule.suspended = true;
+    }
+    config.save()
+}
+
+/// Re-enable rules suspended by a manual disconnect - they only last until the network changes
+/// Returns the updated config when anything was suspended
+fn lift_suspended_rules(config: &AppConfig) -> Option<AppConfig> {
+    if !config.network_rules.iter().any(|r| r.suspended) {
+        return None;
+    }
+
+    if dry_run() {
+        tracing::info!("[dry-run] Would re-enable suspended network rules");
+        let mut config = config.clone();
+        for rule in &mut config.network_rules {
+            rule.suspended = false;
+        }
+        return Some(config);
+    }
+
+    // Start from disk: the held copy may predate edits made in the TUI
+    let mut config = match AppConfig::load() {
+        Ok(disk) if disk.parse_error.is_none() => disk,
+        Ok(_) => return None,
+        Err(e) => {
+            tracing::error!("Failed to re-enable suspended rules: {}", e);
+            return None;
+        }
+    };
+    for rule in &mut config.network_rules {
+        rule.suspended = false;
+    }
+    match config.save() {
+        Ok(()) => tracing::info!("Re-enabled suspended network rules"),
+        Err(e) => tracing::error!("Failed to re-enable suspended rules: {}", e),
+    }
+    Some(config)
+}
+
+/// Push an Always/Session rule's DNS servers onto the tunnel it just brought up
+/// Also after a reconnect, since bringing the tunnel up resets its DNS
+async fn apply_rule_dns(state: &mut MonitorState, rule: &NetworkRule, profile: &str) {
+    let Some(servers) = rule.dns_servers.as_ref().filter(|s| !s.is_empty()) else {
+        return;
+    };
+    if dry_run() {
+        tracing::info!("[dry-run] Would set DNS for {} to {}", profile, servers.join(", "));
+        return;
+    }
+
+    let interface = wireguard::profile_interface(profile).await;
+    match wireguard::set_dns(&interface, servers).await {
+        Ok(()) => {
+            tracing::info!("DNS for {} set to {}", interface, servers.join(", "));
+            state.dns_override = Some((profile.to_string(), interface));
+        }
+        Err(e) => tracing::error!("{:#}", e),
+    }
+}
+
+/// Turn the kill switch on or off as an Always/Session rule asks, once its tunnel is up
+/// Re-enabling also moves it onto the new interface after a reconnect
+async fn apply_rule_kill_switch(rule: &NetworkRule) {
+    let Some(on) = rule.kill_switch else {
+        return;
+    };
+    if dry_run() {
+        tracing::info!("[dry-run] Would turn the kill switch {}", if on { "on" } else { "off" });
+        return;
+    }
+
+    let result = if on { killswitch::enable().await } else { killswitch::disable().await };
+    match result {
+        Ok(()) => tracing::info!("Kill switch {} per rule for {}", if on { "on" } else { "off" }, rule.identifier),
+        Err(e) => {
+            tracing::error!("{:#}", e);
+            log_event(EventKind::Error, format!("Kill switch for {} rule: {:#}", rule.identifier, e));
+        }
+    }
+}
+
+/// `wg show` for this cycle, remembered for the snapshot
+async fn observe_status(config: &AppConfig, state: &mut MonitorState) -> WgStatus {
+    let status = wireguard::get_status(config).await.unwrap_or_default();
+    state.observed.current().vpn_status = Some(status.clone());
+    status
+}
+
+/// Record a daemon event in the shared history file
+fn log_event(kind: EventKind, detail: impl Into<String>) {
+    let detail = detail.into();
+    let detail = if dry_run() { format!("[dry-run] {}", detail) } else { detail };
+    history::record(&HistoryEvent::new(history::SOURCE_DAEMON, kind, detail));
+}
+
+/// Connect a tunnel, or only log it in dry-run mode
+async fn connect_vpn(profile: &str) -> Result<()> {
+    if dry_run() {
+        tracing::info!("[dry-run] Would connect {}", profile);
+        return Ok(());
+    }
+    tunnel_changed();
+    wireguard::connect(profile).await
+}
+
+/// Disconnect every tunnel, or only log it in dry-run mode
+/// Returns the tunnel state from just before, for the notification's transfer stats
+async fn disconnect_vpn() -> Result<WgStatus> {
+    if dry_run() {
+        tracing::info!("[dry-run] Would disconnect the VPN");
+        return Ok(WgStatus::default());
+    }
+    let before = wireguard::get_tunnels().await.unwrap_or_default();
+    tunnel_changed();
+    wireguard::disconnect().await?;
+    Ok(before)
+}
+
 // Notification helpers
+
+fn refresh_notifications(config: &AppConfig) {
+    if let Ok(mut style) = NOTIFICATIONS.lock() {
+        *style = config.notifications.then(|| config.notification_style.clone());
+    }
+}
+
+/// Send a desktop notification (suppressed in dry-run mode or when disabled)
+fn show(notice: Notice) {
+    if dry_run() {
+        return;
+    }
+    let style = NOTIFICATIONS.lock().ok().and_then(|style| style.clone());
+    if let Some(style) = style {
+        let _ = notice.build(&style).show();
+    }
+}
+
 fn notify_connect(profile: &str) {
-    let _ = notify_rust::Notification::new()
-        .summary("tonneru")
-        .body(&format!("Connected to {}", profile))
-        .icon("network-vpn")
-        .show();
+    show(Notice::new(Kind::Connect, format!("Connected to {}", profile)));
 }
 
 fn notify_connect_session(profile: &str) {
-    let _ = notify_rust::Notification::new()
-        .summary("tonneru")
-        .body(&format!("Session VPN: {}", profile))
-        .icon("network-vpn")
-        .show();
+    show(Notice::new(Kind::Connect, format!("Session VPN: {}", profile)));
 }
 
-fn notify_disconnect() {
-    let _ = notify_rust::Notification::new()
-        .summary("tonneru")
-        .body("VPN disconnected")
-        .icon("network-vpn-disconnected")
-        .show();
+fn notify_disconnect(before: &WgStatus) {
+    show(Notice::new(Kind::Disconnect, "VPN disconnected").transfer(before));
 }
 
-fn notify_session_ended() {
-    let _ = notify_rust::Notification::new()
-        .summary("tonneru")
-        .body("Session ended, VPN disconnected")
-        .icon("network-vpn-disconnected")
-        .show();
+fn notify_session_ended(before: &WgStatus) {
+    show(Notice::new(Kind::Disconnect, "Session ended, VPN disconnected").transfer(before));
 }
 
 fn notify_reconnect(profile: &str) {
-    let _ = notify_rust::Notification::new()
-        .summary("tonneru")
-        .body(&format!("VPN reconnected: {}", profile))
-        .icon("network-vpn")
-        .show();
+    show(Notice::new(Kind::Connect, format!("VPN reconnected: {}", profile)));
 }
 
 fn notify_resume_ok(profile: &str) {
-    let _ = notify_rust::Notification::new()
-        .summary("tonneru")
-        .body(&format!("VPN {} active after resume", profile))
-        .icon("network-vpn")
-        .show();
+    show(Notice::new(Kind::Connect, format!("VPN {} active after resume", profile)));
 }
 
 fn notify_network_issue(message: &str) {
-    let _ = notify_rust::Notification::new()
-        .summary("tonneru")
-        .body(message)
-        .icon("network-error")
-        .urgency(notify_rust::Urgency::Normal)
-        .show();
+    show(Notice::new(Kind::Warning, message));
+}
+
+fn notify_leak() {
+    show(Notice::new(Kind::Leak, "The internet is reachable outside the VPN although the kill switch is on")
+        .summary("tonneru: traffic is leaking"));
 }
 
 fn notify_vpn_failed(message: &str) {
-    let _ = notify_rust::Notification::new()
-        .summary("tonneru")
-        .body(&format!("VPN failed: {}", message))
-        .icon("network-vpn-disconnected")
-        .urgency(notify_rust::Urgency::Critical)
-        .show();
+    show(Notice::new(Kind::Failure, format!("VPN failed: {}", message)));
 }
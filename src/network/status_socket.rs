@@ -0,0 +1,157 @@
+//! Status socket for the daemon
+//!
+//! While `tonneru --daemon` runs it keeps the latest VPN status, connectivity
+//! and health results and serves them as JSON over a Unix socket, so
+//! `--status` (waybar) and the TUI can reuse them instead of probing again.
+//!
+//! Protocol: connect, send `status\n`, read one JSON document until EOF.
+//! The document also carries the daemon's own state for `--daemon-status`.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{UnixListener, UnixStream};
+use tokio::time::timeout;
+
+use crate::network::ConnectivityStatus;
+use crate::vpn::wireguard::{VpnHealthCheck, WgStatus};
+
+/// Give up on the daemon quickly so callers can fall back to direct probing
+const QUERY_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Snapshots older than this are ignored (daemon hung or stopped mid-cycle)
+const MAX_SNAPSHOT_AGE_SECS: u64 = 30;
+
+/// Everything the daemon knows about the current connection
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StatusSnapshot {
+    pub vpn_status: WgStatus,
+    pub connectivity: ConnectivityStatus,
+    pub vpn_health: VpnHealthCheck,
+    pub updated_at: u64,  // Unix timestamp of the last refresh
+    #[serde(default)]
+    pub daemon: DaemonState,
+}
+
+impl StatusSnapshot {
+    /// False when the daemon stopped refreshing (hung or stopped mid-cycle)
+    pub fn is_fresh(&self) -> bool {
+        now_secs().saturating_sub(self.updated_at) <= MAX_SNAPSHOT_AGE_SECS
+    }
+}
+
+/// The daemon's own bookkeeping (timestamps are Unix seconds)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DaemonState {
+    pub pid: u32,
+    pub started_at: u64,
+    pub last_network_id: Option<String>,
+    pub last_network_change_at: Option<u64>,
+    pub reconnect_attempts: u32,
+    pub max_reconnect_attempts: u32,
+    pub last_health_check_at: Option<u64>,
+    pub health_check_interval_secs: u64,
+    #[serde(default)]
+    pub paused: bool,  // Automatic actions suspended (config.toml `paused`)
+}
+
+/// Shared between the monitoring loop (writer) and the socket server (reader)
+pub type SharedSnapshot = Arc<Mutex<StatusSnapshot>>;
+
+/// Socket location: $XDG_RUNTIME_DIR/tonneru.sock
+pub fn socket_path() -> Option<PathBuf> {
+    std::env::var_os("XDG_RUNTIME_DIR").map(|dir| PathBuf::from(dir).join("tonneru.sock"))
+}
+
+/// Current Unix time in seconds
+pub fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Bind the socket and answer status requests in the background
+pub fn spawn_server(snapshot: SharedSnapshot) -> Result<()> {
+    let path = socket_path().context("XDG_RUNTIME_DIR is not set")?;
+
+    // A previous daemon may have left its socket behind
+    let _ = std::fs::remove_file(&path);
+    let listener = UnixListener::bind(&path)
+        .with_context(|| format!("Failed to bind {}", path.display()))?;
+
+    // Only the owning user may query the daemon
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600))?;
+    }
+
+    tracing::info!("Status socket listening on {}", path.display());
+
+    tokio::spawn(async move {
+        loop {
+            match listener.accept().await {
+                Ok((stream, _)) => {
+                    let snapshot = snapshot.clone();
+                    tokio::spawn(async move {
+                        if let Err(e) = serve_client(stream, snapshot).await {
+                            tracing::debug!("Status socket client error: {}", e);
+                        }
+                    });
+                }
+                Err(e) => {
+                    tracing::warn!("Status socket accept failed: {}", e);
+                }
+            }
+        }
+    });
+
+    Ok(())
+}
+
+/// Answer a single request
+async fn serve_client(stream: UnixStream, snapshot: SharedSnapshot) -> Result<()> {
+    let (reader, mut writer) = stream.into_split();
+    let mut request = String::new();
+    timeout(QUERY_TIMEOUT, BufReader::new(reader).read_line(&mut request)).await??;
+
+    if request.trim() != "status" {
+        writer.write_all(b"{\"error\":\"unknown request\"}\n").await?;
+        return Ok(());
+    }
+
+    let json = {
+        let snapshot = snapshot.lock().map_err(|_| anyhow::anyhow!("Snapshot lock poisoned"))?;
+        serde_json::to_vec(&*snapshot)?
+    };
+    writer.write_all(&json).await?;
+    writer.shutdown().await?;
+    Ok(())
+}
+
+/// Ask a running daemon for its latest snapshot
+/// Returns None if no daemon is listening or the data is stale
+pub async fn query() -> Option<StatusSnapshot> {
+    fetch().await.filter(StatusSnapshot::is_fresh)
+}
+
+/// Ask a running daemon for its latest snapshot, however old
+pub async fn fetch() -> Option<StatusSnapshot> {
+    let path = socket_path()?;
+    let result = timeout(QUERY_TIMEOUT, async {
+        let mut stream = UnixStream::connect(&path).await?;
+        stream.write_all(b"status\n").await?;
+        let mut response = Vec::new();
+        stream.read_to_end(&mut response).await?;
+        anyhow::Ok(serde_json::from_slice::<StatusSnapshot>(&response)?)
+    })
+    .await;
+
+    match result {
+        Ok(Ok(snapshot)) => Some(snapshot),
+        _ => None,
+    }
+}
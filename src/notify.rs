@@ -0,0 +1,138 @@
+//! Desktop notifications
+//!
+//! Every notification the daemon, CLI and TUI send is built here, so icons and
+//! urgency follow `[notification_style]` in config.toml.
+
+use anyhow::Result;
+use notify_rust::{Notification, Urgency};
+
+use crate::config::{AppConfig, NotificationStyle};
+use crate::vpn::wireguard::{self, WgStatus};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    Connect,     // A tunnel came up (connect, reconnect, resume)
+    Disconnect,  // A tunnel went down on purpose
+    Warning,     // Worth a look, nothing broke (no internet, quota)
+    Failure,     // The VPN is down and won't come back by itself
+    Leak,        // Traffic gets past the kill switch
+}
+
+/// One notification, before `[notification_style]` is applied
+#[derive(Debug, Clone)]
+pub struct Notice {
+    kind: Kind,
+    summary: String,
+    body: String,
+    transfer: Option<String>,
+}
+
+impl Notice {
+    pub fn new(kind: Kind, body: impl Into<String>) -> Self {
+        Self { kind, summary: "tonneru".to_string(), body: body.into(), transfer: None }
+    }
+
+    pub fn summary(mut self, summary: impl Into<String>) -> Self {
+        self.summary = summary.into();
+        self
+    }
+
+    /// Traffic of the tunnels in `status` (taken before they went down),
+    /// added to the body when `transfer_stats` is on
+    pub fn transfer(mut self, status: &WgStatus) -> Self {
+        self.transfer = transfer_line(status);
+        self
+    }
+
+    /// The notification as `style` wants it
+    pub fn build(&self, style: &NotificationStyle) -> Notification {
+        let body = match &self.transfer {
+            Some(line) if style.transfer_stats => format!("{}\n{}", self.body, line),
+            _ => self.body.clone(),
+        };
+        let mut notification = Notification::new();
+        notification
+            .summary(&self.summary)
+            .body(&body)
+            .icon(icon(self.kind, style))
+            .urgency(urgency(self.kind, style));
+        notification
+    }
+
+    /// Show it, unless `notifications = false`
+    pub fn show(&self, config: &AppConfig) -> Result<()> {
+        if config.notifications {
+            self.build(&config.notification_style).show()?;
+        }
+        Ok(())
+    }
+}
+
+fn icon(kind: Kind, style: &NotificationStyle) -> &str {
+    match kind {
+        Kind::Connect => &style.icon_connected,
+        Kind::Disconnect => &style.icon_disconnected,
+        Kind::Warning => &style.icon_warning,
+        Kind::Failure => &style.icon_failure,
+        Kind::Leak => &style.icon_leak,
+    }
+}
+
+fn urgency(kind: Kind, style: &NotificationStyle) -> Urgency {
+    match kind {
+        Kind::Connect | Kind::Disconnect if style.quiet_connects => Urgency::Low,
+        Kind::Failure | Kind::Leak if style.critical_failures => Urgency::Critical,
+        _ => Urgency::Normal,
+    }
+}
+
+/// "↓ 1.20 GiB ↑ 80.00 MiB" over all tunnels in `status`, None without traffic
+fn transfer_line(status: &WgStatus) -> Option<String> {
+    let (rx, tx) = std::iter::once(status).chain(&status.additional).fold((0, 0), |(rx, tx), s| {
+        let bytes = |t: &Option<String>| t.as_deref().map_or(0, wireguard::parse_transfer_bytes);
+        (rx + bytes(&s.transfer_rx), tx + bytes(&s.transfer_tx))
+    });
+    (rx + tx > 0).then(|| format!(
+        "↓ {} ↑ {}",
+        wireguard::format_transfer_bytes(rx),
+        wireguard::format_transfer_bytes(tx)
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_notice_style() {
+        let status = WgStatus {
+            transfer_rx: Some("1.50 MiB received".to_string()),
+            transfer_tx: Some("512 B sent".to_string()),
+            ..Default::default()
+        };
+        let notice = Notice::new(Kind::Disconnect, "VPN disconnected").transfer(&status);
+
+        let default = NotificationStyle::default();
+        let plain = notice.build(&default);
+        assert_eq!(plain.body, "VPN disconnected");
+        assert_eq!(plain.icon, "network-vpn-disconnected");
+        assert_eq!(urgency(Kind::Disconnect, &default), Urgency::Normal);
+        assert_eq!(urgency(Kind::Leak, &default), Urgency::Critical);
+
+        let custom = NotificationStyle {
+            icon_disconnected: "my-vpn-off".to_string(),
+            quiet_connects: true,
+            critical_failures: false,
+            transfer_stats: true,
+            ..Default::default()
+        };
+        let styled = notice.build(&custom);
+        assert_eq!(styled.body, "VPN disconnected\n↓ 1.50 MiB ↑ 512 B");
+        assert_eq!(styled.icon, "my-vpn-off");
+        assert_eq!(urgency(Kind::Connect, &custom), Urgency::Low);
+        assert_eq!(urgency(Kind::Failure, &custom), Urgency::Normal);
+
+        // Nothing moved, nothing to add
+        assert_eq!(transfer_line(&WgStatus::default()), None);
+    }
+}
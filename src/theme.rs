@@ -1,9 +1,17 @@
 //! Theme colors loaded from Omarchy/Hyprland system theme
-//! Reads colors from ~/.config/omarchy/current/theme/kitty.conf
+//! Reads colors from ~/.config/omarchy/current/theme/kitty.conf,
+//! then applies any `[theme]` overrides from config.toml
 
 use ratatui::style::Color;
 use std::collections::HashMap;
 use std::fs;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use crate::config::{AppConfig, ThemeColors};
+
+/// Resolved kitty.conf path and mtime, plus config.toml's mtime
+pub type ThemeStamp = (Option<(PathBuf, SystemTime)>, Option<SystemTime>);
 
 /// Theme colors for the UI
 #[derive(Debug, Clone)]
@@ -56,11 +64,61 @@ impl Theme {
         Self::default()
     }
 
+    /// System theme with the `[theme]` section of config.toml applied on top
+    pub fn from_config(config: &AppConfig) -> Self {
+        let mut theme = Self::load();
+        if let Some(colors) = &config.theme {
+            theme.apply_overrides(colors);
+        }
+        theme
+    }
+
+    /// Replace colors set in config; malformed values keep the current color
+    fn apply_overrides(&mut self, colors: &ThemeColors) {
+        let fields = [
+            ("accent", &colors.accent, &mut self.accent),
+            ("accent_bright", &colors.accent_bright, &mut self.accent_bright),
+            ("danger", &colors.danger, &mut self.danger),
+            ("success", &colors.success, &mut self.success),
+            ("warning", &colors.warning, &mut self.warning),
+            ("text", &colors.text, &mut self.text),
+            ("text_dim", &colors.text_dim, &mut self.text_dim),
+            ("bg", &colors.bg, &mut self.bg),
+            ("bg_selected", &colors.bg_selected, &mut self.bg_selected),
+            ("inactive", &colors.inactive, &mut self.inactive),
+            ("header", &colors.header, &mut self.header),
+        ];
+
+        for (name, value, target) in fields {
+            let Some(value) = value else {
+                continue;
+            };
+            match Self::parse_hex_color(value) {
+                Some(color) => *target = color,
+                None => tracing::warn!("Ignoring invalid [theme] color {} = {:?}", name, value),
+            }
+        }
+    }
+
+    /// Omarchy's current theme file (current/theme is a symlink swapped on theme change)
+    fn omarchy_theme_path() -> Option<PathBuf> {
+        Some(dirs::home_dir()?.join(".config/omarchy/current/theme/kitty.conf"))
+    }
+
+    /// Identifies the theme sources on disk; changes when the Omarchy theme is
+    /// switched or config.toml (and its [theme] section) is edited
+    pub fn source_stamp() -> ThemeStamp {
+        let modified = |path: &PathBuf| fs::metadata(path).and_then(|m| m.modified()).ok();
+        let omarchy = Self::omarchy_theme_path()
+            .and_then(|p| fs::canonicalize(p).ok())
+            .and_then(|p| modified(&p).map(|t| (p, t)));
+        let config = AppConfig::config_path().ok().and_then(|p| modified(&p));
+        (omarchy, config)
+    }
+
     /// Load colors from Omarchy kitty.conf theme file
     fn load_omarchy_theme() -> Option<Self> {
-        let home = dirs::home_dir()?;
-        let theme_path = home
-            .join(".config/omarchy/current/theme/kitty.conf");
+        let theme_path = Self::omarchy_theme_path()?;
 
         let content = fs::read_to_string(&theme_path).ok()?;
         let colors = Self::parse_kitty_conf(&content);
@@ -152,6 +210,9 @@ impl Theme {
     /// Parse a hex color string (#RRGGBB or #RGB)
     fn parse_hex_color(s: &str) -> Option<Color> {
         let s = s.trim().trim_start_matches('#');
+        if !s.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return None;
+        }
         
         if s.len() == 6 {
             let r = u8::from_str_radix(&s[0..2], 16).ok()?;
@@ -169,4 +230,24 @@ impl Theme {
     }
 }
 
-
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_overrides() {
+        let mut theme = Theme::default();
+        let original_danger = theme.danger;
+        theme.apply_overrides(&ThemeColors {
+            accent: Some("#89b4fa".to_string()),
+            text: Some("#fff".to_string()),
+            danger: Some("not-a-color".to_string()),
+            ..Default::default()
+        });
+
+        assert_eq!(theme.accent, Color::Rgb(0x89, 0xb4, 0xfa));
+        assert_eq!(theme.text, Color::Rgb(255, 255, 255));
+        assert_eq!(theme.danger, original_danger);
+        assert_eq!(Theme::parse_hex_color("#aé1bc"), None);
+    }
+}
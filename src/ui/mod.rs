@@ -1,22 +1,44 @@
 mod components;
 
-use std::sync::OnceLock;
+use std::sync::RwLock;
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect, Alignment},
     style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, Clear, Paragraph, Row, Table, Wrap},
+    widgets::{Block, Borders, Clear, Paragraph, Row, Sparkline, Table, TableState, Wrap},
     Frame,
 };
 
-use crate::app::{App, Popup, Section};
+use crate::app::{App, LayoutAreas, Popup, Section, TunnelRow};
+use crate::config::AppConfig;
 use crate::theme::Theme;
+use crate::vpn::wireguard::{PeerStatus, ProfileSource, TunnelScope};
 
-// Load theme colors from system (Omarchy/Hyprland) once at startup
-static THEME: OnceLock<Theme> = OnceLock::new();
+// Theme colors: config.toml [theme] over the system (Omarchy/Hyprland) theme
+// Loaded on first use and replaced by reload_theme() when the theme changes
+static THEME: RwLock<Option<Theme>> = RwLock::new(None);
 
-fn theme() -> &'static Theme {
-    THEME.get_or_init(Theme::load)
+fn load_theme() -> Theme {
+    Theme::from_config(&AppConfig::load().unwrap_or_default())
+}
+
+fn theme() -> Theme {
+    if let Some(theme) = THEME.read().ok().and_then(|t| t.clone()) {
+        return theme;
+    }
+    let theme = load_theme();
+    if let Ok(mut cached) = THEME.write() {
+        *cached = Some(theme.clone());
+    }
+    theme
+}
+
+/// Re-read the theme so the next frame uses the new colors
+pub fn reload_theme() {
+    let theme = load_theme();
+    if let Ok(mut cached) = THEME.write() {
+        *cached = Some(theme);
+    }
 }
 
 // Helper functions to get theme colors
@@ -31,8 +53,19 @@ fn text_dim() -> Color { theme().text_dim }
 fn bg_selected() -> Color { theme().bg_selected }
 fn header() -> Color { theme().header }
 
+/// Below this height the boxes don't fit; draw the compact status line instead
+const COMPACT_HEIGHT: u16 = 10;
+
 pub fn draw(f: &mut Frame, app: &App) {
     let area = f.area();
+
+    if area.height < COMPACT_HEIGHT {
+        // No boxes to click on
+        app.layout.set(LayoutAreas::default());
+        draw_compact(f, app, area);
+        draw_popup(f, app);
+        return;
+    }
     
     // Responsive layout based on terminal height
     // Networks, Tunnels, and Kill Switch boxes
@@ -48,6 +81,7 @@ pub fn draw(f: &mut Frame, app: &App) {
         .direction(Direction::Vertical)
         .margin(0)
         .constraints([
+            Constraint::Length(u16::from(app.banner_text().is_some())), // Setup or leak banner
             Constraint::Length(1),               // Info line
             networks_height,                     // Networks box
             tunnels_height,                      // Tunnels box
@@ -56,13 +90,26 @@ pub fn draw(f: &mut Frame, app: &App) {
         ])
         .split(area);
 
-    draw_info_line(f, app, chunks[0]);
-    draw_networks_box(f, app, chunks[1]);
-    draw_tunnels_box(f, app, chunks[2]);
-    draw_killswitch_box(f, app, chunks[3]);
-    draw_footer(f, app, chunks[4]);
+    // Remember widget areas for mouse hit-testing
+    let tunnel_chunks = tunnels_box_layout(chunks[3]);
+    app.layout.set(LayoutAreas {
+        networks: chunks[2],
+        tunnels: tunnel_chunks[0],
+        config: tunnel_chunks[1],
+        killswitch: chunks[4],
+    });
+
+    draw_banner(f, app, chunks[0]);
+    draw_info_line(f, app, chunks[1]);
+    draw_networks_box(f, app, chunks[2]);
+    draw_tunnels_box(f, app, chunks[3]);
+    draw_killswitch_box(f, app, chunks[4]);
+    draw_footer(f, app, chunks[5]);
+    draw_popup(f, app);
+}
 
-    // Draw popups on top
+/// Draw the open popup on top of everything
+fn draw_popup(f: &mut Frame, app: &App) {
     match app.popup {
         Popup::None => {}
         Popup::FileBrowser => draw_file_browser(f, app),
@@ -70,7 +117,69 @@ pub fn draw(f: &mut Frame, app: &App) {
         Popup::ManualConfig => draw_manual_config(f, app),
         Popup::Help => draw_help_popup(f),
         Popup::Confirm => draw_confirm_popup(f, app),
+        Popup::Allowlist => draw_allowlist_popup(f, app),
+        Popup::History => draw_history_popup(f, app),
+        Popup::Routing => draw_routing_popup(f, app),
+        Popup::Lint => draw_lint_popup(f, app),
+        Popup::KillSwitchPreview => draw_killswitch_preview_popup(f, app),
+        Popup::Fastest => draw_fastest_popup(f, app),
+        Popup::Latency => draw_latency_popup(f, app),
+        Popup::Label => draw_label_popup(f, app),
+        Popup::Rename => draw_rename_popup(f, app),
+        Popup::EditRule => draw_rule_popup(f, app),
+    }
+}
+
+/// Tiny terminals (e.g. a small tmux pane): a status line plus a short key legend
+/// Every key still works; the selection is shown so Space/Enter stay predictable
+fn draw_compact(f: &mut Frame, app: &App, area: Rect) {
+    let status = &app.vpn_status;
+    let mut spans = match status.interface.as_deref().filter(|_| status.connected) {
+        Some(iface) => vec![
+            Span::styled("󰒘 ", Style::default().fg(success())),
+            Span::styled(format!("{} UP", iface), Style::default().fg(success()).add_modifier(Modifier::BOLD)),
+        ],
+        None => vec![
+            Span::styled("󰒙 ", Style::default().fg(text_dim())),
+            Span::styled("VPN down", Style::default().fg(text_dim())),
+        ],
+    };
+    spans.push(Span::styled(" │ ", Style::default().fg(inactive())));
+    if app.kill_switch_enabled {
+        spans.push(Span::styled("KS on", Style::default().fg(danger())));
+    } else {
+        spans.push(Span::styled("KS off", Style::default().fg(text_dim())));
+    }
+    if let Some(msg) = &app.status_message {
+        spans.push(Span::styled(" │ ", Style::default().fg(inactive())));
+        spans.push(Span::styled(msg.as_str(), Style::default().fg(warning())));
+    }
+
+    let selected = match app.section {
+        Section::Networks => format!(
+            "Networks ▸ {}",
+            app.networks.get(app.selected_network).map(|n| n.name.as_str()).unwrap_or("-")
+        ),
+        Section::Tunnels => format!(
+            "Tunnels ▸ {}",
+            app.selected_group.as_deref()
+                .or(app.tunnels.get(app.selected_tunnel).map(|t| t.name.as_str()))
+                .unwrap_or("-")
+        ),
+        Section::KillSwitch => "Kill switch".to_string(),
+    };
+    let mut legend = vec![
+        Span::styled(selected, Style::default().fg(accent())),
+        Span::styled(" │ ", Style::default().fg(inactive())),
+    ];
+    for (key, action) in [("Tab", "Section"), ("↑↓", "Nav"), ("Space", "Toggle"), ("?", "Help"), ("q", "Quit")] {
+        legend.push(Span::styled(key, Style::default().fg(accent())));
+        legend.push(Span::styled(format!(" {} │ ", action), Style::default().fg(text_dim())));
     }
+    legend.push(Span::styled("enlarge for full view", Style::default().fg(inactive())));
+
+    let lines = vec![Line::from(spans), Line::from(legend)];
+    f.render_widget(Paragraph::new(lines).wrap(Wrap { trim: true }), area);
 }
 
 fn draw_killswitch_box(f: &mut Frame, app: &App, area: Rect) {
@@ -88,56 +197,130 @@ fn draw_killswitch_box(f: &mut Frame, app: &App, area: Rect) {
         .border_style(Style::default().fg(border_color));
 
     // Kill switch status
-    let (status_icon, status_text, status_color) = if app.kill_switch_enabled {
+    let (status_icon, status_text, status_color) = if app.kill_switch_enabled && app.config.persistent_kill_switch {
+        ("󰯄", "ENABLED (persistent) - All traffic blocked except VPN", danger())
+    } else if app.kill_switch_enabled {
         ("󰯄", "ENABLED - All traffic blocked except VPN", danger())
     } else {
         ("󰒙", "Disabled - Traffic allowed without VPN", text_dim())
     };
 
+    // Allowlisted destinations stay reachable while enabled
+    let allow_count = app.config.killswitch_allowlist.len();
+    let allow_text = match allow_count {
+        0 => String::new(),
+        1 => " │ 1 allowed subnet".to_string(),
+        n => format!(" │ {} allowed subnets", n),
+    };
+
     // Action hint
     let action_hint = if is_active {
         vec![
             Span::styled("  ", Style::default()),
             Span::styled(status_icon, Style::default().fg(status_color)),
-            Span::styled(format!(" {} ", status_text), Style::default().fg(status_color)),
+            Span::styled(format!(" {}", status_text), Style::default().fg(status_color)),
+            Span::styled(format!("{} ", allow_text), Style::default().fg(text_dim())),
             Span::styled("│ ", Style::default().fg(inactive())),
             Span::styled("Space", Style::default().fg(accent())),
             Span::styled("/", Style::default().fg(inactive())),
             Span::styled("k", Style::default().fg(accent())),
-            Span::styled(" toggle", Style::default().fg(text_dim())),
+            Span::styled(" toggle ", Style::default().fg(text_dim())),
+            Span::styled("a", Style::default().fg(accent())),
+            Span::styled(" allowlist", Style::default().fg(text_dim())),
         ]
     } else {
         vec![
             Span::styled("  ", Style::default()),
             Span::styled(status_icon, Style::default().fg(status_color)),
             Span::styled(format!(" {}", status_text), Style::default().fg(status_color)),
+            Span::styled(allow_text, Style::default().fg(text_dim())),
         ]
     };
 
-    let content = Paragraph::new(Line::from(action_hint))
-        .block(block);
+    // Recent tunnel throughput on the right, when there's room and traffic to show
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+    let show_sparkline = app.vpn_status.connected && app.rate_history.len() > 1 && inner.width > 90;
+    let chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Min(0), Constraint::Length(if show_sparkline { 32 } else { 0 })])
+        .split(inner);
+
+    f.render_widget(Paragraph::new(Line::from(action_hint)), chunks[0]);
+
+    if show_sparkline {
+        // Newest sample at the right edge
+        let data: Vec<u64> = app.rate_history.iter().rev().take(chunks[1].width as usize).rev().copied().collect();
+        let sparkline = Sparkline::default()
+            .data(&data)
+            .style(Style::default().fg(accent()));
+        f.render_widget(sparkline, chunks[1]);
+    }
+}
 
-    f.render_widget(content, area);
+/// Folder line in the Tunnels list: arrow, name, member count and how many are up
+fn group_header_row<'a>(app: &App, name: &'a str, members: &[usize], collapsed: bool, show_protocol: bool) -> Row<'a> {
+    let up = members.iter()
+        .filter(|&&i| app.vpn_status.is_up(app.tunnels[i].interface()))
+        .count();
+    let arrow = if collapsed { "▸" } else { "▾" };
+
+    let mut cells = vec![
+        Line::from(Span::styled(arrow, Style::default().fg(accent()))),
+        Line::from(vec![
+            Span::styled(name, Style::default().fg(header()).add_modifier(Modifier::BOLD)),
+            Span::styled(format!(" ({})", members.len()), Style::default().fg(text_dim())),
+        ]),
+    ];
+    if show_protocol {
+        cells.push(Line::from(""));
+    }
+    if up > 0 {
+        cells.push(Line::from(Span::styled(format!("{} UP", up), Style::default().fg(success()))));
+    }
+
+    let selected = app.selected_group.as_deref() == Some(name) && app.section == Section::Tunnels;
+    let style = if selected {
+        Style::default().bg(bg_selected()).fg(text())
+    } else {
+        Style::default()
+    };
+    Row::new(cells).style(style)
+}
+
+fn draw_banner(f: &mut Frame, app: &App, area: Rect) {
+    // Setup problems that won't fix themselves - never auto-cleared
+    if let Some(banner) = app.banner_text() {
+        let line = Line::from(Span::styled(
+            banner,
+            Style::default().fg(danger()).add_modifier(Modifier::BOLD),
+        ));
+        f.render_widget(Paragraph::new(line).alignment(Alignment::Center), area);
+    }
 }
 
 fn draw_info_line(f: &mut Frame, app: &App, area: Rect) {
-    // Priority: pending change countdown > status message > info message > ready
+    // Priority: pending change countdown > connection test > status message > info message > ready
     let line = if let Some(ref pending) = app.pending_change {
         // Show countdown with action description
         let action_text = match pending.action {
             crate::app::PendingAction::Connect => format!("Connect to {}", pending.tunnel_name.as_deref().unwrap_or("?")),
             crate::app::PendingAction::Disconnect => "Disconnect VPN".to_string(),
+            crate::app::PendingAction::DisconnectTunnel => format!("Disconnect {}", pending.tunnel_name.as_deref().unwrap_or("?")),
             crate::app::PendingAction::Reconnect => format!("Switch to {}", pending.tunnel_name.as_deref().unwrap_or("?")),
             crate::app::PendingAction::KillSwitchOn => "Enable kill switch".to_string(),
             crate::app::PendingAction::KillSwitchOff => "Disable kill switch".to_string(),
+            crate::app::PendingAction::Undo { .. } => "Undo the last change".to_string(),
+            crate::app::PendingAction::Restart { .. } => format!("Reconnect {}", pending.tunnel_name.as_deref().unwrap_or("?")),
+            crate::app::PendingAction::TestConnect { .. } => format!("Test {}", pending.tunnel_name.as_deref().unwrap_or("?")),
+            crate::app::PendingAction::TestRollBack { .. } => "End the connection test".to_string(),
         };
         
+        // Colors escalate over the last two seconds, whatever the configured duration
         let countdown_color = match app.countdown_seconds {
-            4 => accent(),
-            3 => accent(),
+            0 | 1 => danger(),
             2 => warning(),
-            1 => danger(),
-            _ => danger(),
+            _ => accent(),
         };
         
         Line::from(vec![
@@ -146,28 +329,79 @@ fn draw_info_line(f: &mut Frame, app: &App, area: Rect) {
             Span::styled(" │ ", Style::default().fg(text_dim())),
             Span::styled(action_text, Style::default().fg(text())),
             Span::styled(" │ ", Style::default().fg(text_dim())),
+            Span::styled("(Enter applies, Esc cancels)", Style::default().fg(text_dim())),
+        ])
+    } else if let Some(ref test) = app.connection_test {
+        // Show connection test progress
+        let (stage_text, stage_color) = match test.stage {
+            crate::app::TestStage::Handshake => ("Handshake…".to_string(), warning()),
+            crate::app::TestStage::Routing => ("Handshake ✓ │ Routing…".to_string(), warning()),
+            crate::app::TestStage::Reachable { latency_ms } => {
+                let latency = latency_ms.map(|ms| format!(" {} ms", ms)).unwrap_or_default();
+                let left = crate::app::TEST_KEEP_SECS.saturating_sub(test.stage_start.elapsed().as_secs());
+                (format!("Reachable ✓{} │ Enter keeps ({}s)", latency, left), success())
+            }
+        };
+        
+        Line::from(vec![
+            Span::styled("󰙨 ", Style::default().fg(accent())),
+            Span::styled(format!("Testing {}", test.tunnel_name), Style::default().fg(text())),
+            Span::styled(" │ ", Style::default().fg(text_dim())),
+            Span::styled(stage_text, Style::default().fg(stage_color)),
+            Span::styled(" │ ", Style::default().fg(text_dim())),
             Span::styled("(Esc cancels)", Style::default().fg(text_dim())),
         ])
     } else if let Some(ref status) = app.status_message {
         // Show status/action feedback (e.g., "Connected to wg0", "Config saved")
-        Line::from(vec![
-            Span::styled(status, Style::default().fg(warning())),
-        ])
+        let mut spans = vec![Span::styled(status, Style::default().fg(warning()))];
+        if let Some(frame) = app.spinner_frame() {
+            spans.insert(0, Span::styled(format!("{} ", frame), Style::default().fg(accent())));
+        }
+        Line::from(spans)
     } else if let Some(ref info) = app.info_message {
-        // Show VPN status/traffic info
-        Line::from(vec![
-            Span::styled(info, Style::default().fg(text_dim())),
-        ])
+        // Show VPN status/traffic info, then the live handshake age
+        let mut spans = vec![Span::styled(info, Style::default().fg(text_dim()))];
+        if let Some(age) = app.handshake_age {
+            let stale = app.config.handshake_stale_secs;
+            let color = if age >= stale {
+                danger()
+            } else if age >= crate::app::HANDSHAKE_WARN_SECS.min(stale) {
+                warning()
+            } else {
+                text_dim()
+            };
+            spans.push(Span::styled(" │ ", Style::default().fg(text_dim())));
+            spans.push(Span::styled(
+                format!("handshake {} ago", App::format_clock(age)),
+                Style::default().fg(color),
+            ));
+        }
+        Line::from(spans)
     } else {
         Line::from(vec![
             Span::styled("Ready", Style::default().fg(text_dim())),
         ])
     };
 
+    // Paused daemon is easy to forget, so it stays on the info line
+    let mut line = line;
+    if app.config.paused {
+        line.spans.insert(0, Span::styled("⏸ DAEMON PAUSED", Style::default().fg(danger()).add_modifier(Modifier::BOLD)));
+        line.spans.insert(1, Span::styled(" │ ", Style::default().fg(text_dim())));
+    }
+
     let info = Paragraph::new(line).alignment(Alignment::Center);
     f.render_widget(info, area);
 }
 
+/// Four-step signal meter; the first `level` bars are drawn lit
+const SIGNAL_BARS: &str = "▂▄▆█";
+
+/// Map signal percentage to a 0-4 bar count
+fn signal_level(signal: u8) -> usize {
+    (signal as usize).div_ceil(25).min(4)
+}
+
 fn draw_networks_box(f: &mut Frame, app: &App, area: Rect) {
     let is_active = app.section == Section::Networks;
     let border_color = if is_active { accent() } else { inactive() };
@@ -177,8 +411,12 @@ fn draw_networks_box(f: &mut Frame, app: &App, area: Rect) {
         Style::default().fg(inactive())
     };
 
+    let mut title = vec![Span::styled(" Networks ", title_style)];
+    if app.is_scanning_networks() {
+        title.push(Span::styled("scanning… ", Style::default().fg(text_dim())));
+    }
     let block = Block::default()
-        .title(Span::styled(" Networks ", title_style))
+        .title(Line::from(title))
         .borders(Borders::ALL)
         .border_style(Style::default().fg(border_color));
 
@@ -211,20 +449,67 @@ fn draw_networks_box(f: &mut Frame, app: &App, area: Rect) {
             .iter()
             .enumerate()
             .map(|(i, network)| {
+                // Remembered networks that are out of range get a history icon
                 let icon = match network.network_type.as_str() {
+                    _ if network.away => "󰋚",
                     "wifi" => "󰖩",
                     "ethernet" => "󰈀",
                     _ => "󰛳",
                 };
                 let icon_color = if network.connected { success() } else { text_dim() };
                 
+                // WiFi signal bars next to the icon (blank when the backend doesn't report it)
+                let icon_cell = match network.signal {
+                    Some(signal) => {
+                        let level = signal_level(signal);
+                        Line::from(vec![
+                            Span::styled(format!("{} ", icon), Style::default().fg(icon_color)),
+                            Span::styled(&SIGNAL_BARS[..level * 3], Style::default().fg(icon_color)),
+                            Span::styled(&SIGNAL_BARS[level * 3..], Style::default().fg(inactive())),
+                        ])
+                    }
+                    None => Line::from(Span::styled(icon, Style::default().fg(icon_color))),
+                };
+                let mut type_text = match &network.band {
+                    Some(band) => format!("{} {}", network.network_type, band),
+                    None => network.network_type.clone(),
+                };
+                if network.is_open() {
+                    type_text.push_str(" open");
+                }
+                if network.away {
+                    type_text.push_str(" away");
+                }
+                let name_color = if network.away { text_dim() } else { text() };
+                
                 let rule = app.get_network_rule(network);
-                let (rule_text, rule_color) = match rule {
+                let (rule_label, rule_color) = match rule {
                     Some(r) if r.always_vpn => ("Always", success()),
                     Some(r) if r.never_vpn => ("Never", danger()),
                     Some(r) if r.session_vpn => ("Session", accent_bright()),
                     _ => ("-", text_dim()),
                 };
+                // Append priority when set (e.g. "Always ↑2")
+                let mut rule_text = match rule.and_then(|r| r.priority) {
+                    Some(p) => format!("{} ↑{}", rule_label, p),
+                    None => rule_label.to_string(),
+                };
+                // Mark rules inherited from the open-network policy
+                if rule.is_some_and(|r| r.identifier != network.identifier()) {
+                    rule_text.push_str(" (open)");
+                }
+                // Paused by a manual disconnect until the network changes
+                if rule.is_some_and(|r| r.suspended) {
+                    rule_text.push_str(" (paused)");
+                }
+                // Append the DNS override (e.g. "Always DNS 10.0.0.53 +1")
+                let dns_servers = rule.and_then(|r| r.dns_servers.as_deref()).unwrap_or_default();
+                if let Some(first) = dns_servers.first() {
+                    rule_text.push_str(&format!(" DNS {}", first));
+                    if dns_servers.len() > 1 {
+                        rule_text.push_str(&format!(" +{}", dns_servers.len() - 1));
+                    }
+                }
 
                 // Get tunnel name from the rule
                 let tunnel_name = rule
@@ -246,19 +531,19 @@ fn draw_networks_box(f: &mut Frame, app: &App, area: Rect) {
 
                 if show_type {
                     Row::new(vec![
-                        Span::styled(icon, Style::default().fg(icon_color)),
-                        Span::styled(format!("{}{}", network.name, connected_indicator), Style::default().fg(text())),
-                        Span::styled(&network.network_type, Style::default().fg(text_dim())),
-                        Span::styled(rule_text, Style::default().fg(rule_color)),
-                        Span::styled(tunnel_name, Style::default().fg(tunnel_color)),
+                        icon_cell,
+                        Line::from(Span::styled(format!("{}{}", network.name, connected_indicator), Style::default().fg(name_color))),
+                        Line::from(Span::styled(type_text, Style::default().fg(text_dim()))),
+                        Line::from(Span::styled(rule_text.clone(), Style::default().fg(rule_color))),
+                        Line::from(Span::styled(tunnel_name, Style::default().fg(tunnel_color))),
                     ])
                     .style(row_style)
                 } else {
                     Row::new(vec![
-                        Span::styled(icon, Style::default().fg(icon_color)),
-                        Span::styled(format!("{}{}", network.name, connected_indicator), Style::default().fg(text())),
-                        Span::styled(rule_text, Style::default().fg(rule_color)),
-                        Span::styled(tunnel_name, Style::default().fg(tunnel_color)),
+                        icon_cell,
+                        Line::from(Span::styled(format!("{}{}", network.name, connected_indicator), Style::default().fg(name_color))),
+                        Line::from(Span::styled(rule_text.clone(), Style::default().fg(rule_color))),
+                        Line::from(Span::styled(tunnel_name, Style::default().fg(tunnel_color))),
                     ])
                     .style(row_style)
                 }
@@ -268,15 +553,15 @@ fn draw_networks_box(f: &mut Frame, app: &App, area: Rect) {
 
     let widths = if show_type {
         vec![
-            Constraint::Length(3),
-            Constraint::Percentage(35),
-            Constraint::Percentage(12),
+            Constraint::Length(7),  // Icon + signal bars
+            Constraint::Percentage(32),
+            Constraint::Percentage(15),
             Constraint::Percentage(15),
             Constraint::Percentage(33),
         ]
     } else {
         vec![
-            Constraint::Length(3),
+            Constraint::Length(7),
             Constraint::Percentage(40),
             Constraint::Percentage(18),
             Constraint::Percentage(37),
@@ -290,15 +575,20 @@ fn draw_networks_box(f: &mut Frame, app: &App, area: Rect) {
     f.render_widget(table, area);
 }
 
-fn draw_tunnels_box(f: &mut Frame, app: &App, area: Rect) {
+/// Split the tunnels box into the list and the config viewer
+fn tunnels_box_layout(area: Rect) -> std::rc::Rc<[Rect]> {
     // Always show config panel alongside tunnels list
-    let chunks = Layout::default()
+    Layout::default()
         .direction(Direction::Horizontal)
         .constraints([
             Constraint::Percentage(35),  // Tunnels list
             Constraint::Percentage(65),  // Config viewer
         ])
-        .split(area);
+        .split(area)
+}
+
+fn draw_tunnels_box(f: &mut Frame, app: &App, area: Rect) {
+    let chunks = tunnels_box_layout(area);
 
     draw_tunnels_list(f, app, chunks[0]);
     draw_config_viewer(f, app, chunks[1]);
@@ -313,16 +603,36 @@ fn draw_tunnels_list(f: &mut Frame, app: &App, area: Rect) {
         Style::default().fg(inactive())
     };
 
+    // Show the filter in the title while set (cursor while typing)
+    let title = match &app.tunnel_filter {
+        Some(filter) if app.tunnel_filter_active => format!(" Tunnels /{}_ ", filter),
+        Some(filter) => format!(" Tunnels /{} ", filter),
+        None => " Tunnels ".to_string(),
+    };
+
     let block = Block::default()
-        .title(Span::styled(" Tunnels ", title_style))
+        .title(Span::styled(title, title_style))
         .borders(Borders::ALL)
         .border_style(Style::default().fg(border_color));
 
-    let header = Row::new(vec![
+    // Responsive columns based on width - narrow boxes drop protocol and uptime
+    let show_protocol = area.width > 44;
+    let show_since = area.width > 56;
+
+    let mut header_cells = vec![
         Span::styled("", Style::default().fg(header())),
         Span::styled("Name", Style::default().fg(header())),
-        Span::styled("Status", Style::default().fg(header())),
-    ]);
+    ];
+    if show_protocol {
+        header_cells.push(Span::styled("Proto", Style::default().fg(header())));
+    }
+    header_cells.push(Span::styled("Status", Style::default().fg(header())));
+    if show_since {
+        header_cells.push(Span::styled("Since", Style::default().fg(header())));
+    }
+    let header = Row::new(header_cells);
+
+    let tunnel_rows = app.tunnel_rows();
 
     let rows: Vec<Row> = if app.tunnels.is_empty() {
         vec![
@@ -333,20 +643,33 @@ fn draw_tunnels_list(f: &mut Frame, app: &App, area: Rect) {
                 Span::styled("  Press 'f' to import", Style::default().fg(accent())),
             ]),
         ]
+    } else if tunnel_rows.is_empty() {
+        vec![Row::new(vec![
+            Span::styled("  No tunnels match filter", Style::default().fg(text_dim())),
+        ])]
     } else {
-        app.tunnels
+        tunnel_rows
             .iter()
-            .enumerate()
-            .map(|(i, tunnel)| {
-                let is_connected = tunnel.connected || (app.vpn_status.connected
-                    && app.vpn_status.interface.as_deref() == Some(&tunnel.name));
+            .map(|row| {
+                let (i, grouped) = match row {
+                    TunnelRow::Group { name, members, collapsed } => {
+                        return group_header_row(app, name, members, *collapsed, show_protocol);
+                    }
+                    TunnelRow::Tunnel { index, grouped } => (*index, *grouped),
+                };
+                let tunnel = &app.tunnels[i];
+                // Each active tunnel has its own status; only the primary needs the default route
+                let iface_status = app.vpn_status.interface_status(tunnel.interface());
+                let is_connected = tunnel.connected || iface_status.is_some();
+                let is_primary = app.vpn_status.interface.as_deref() == Some(tunnel.interface());
+                let status_src = iface_status.unwrap_or(&app.vpn_status);
 
                 // Determine status based on connection AND routing health
                 let (icon, icon_color, status, status_color) = if is_connected {
-                    if !app.vpn_status.routing_ok {
+                    if is_primary && !status_src.routing_ok {
                         // Interface up but routing broken
                         ("󰒙", warning(), "UP ⚠", warning())
-                    } else if app.vpn_status.handshake_stale {
+                    } else if status_src.handshake_stale {
                         // Routing OK but handshake stale
                         ("󰒘", warning(), "UP ?", warning())
                     } else {
@@ -357,7 +680,7 @@ fn draw_tunnels_list(f: &mut Frame, app: &App, area: Rect) {
                     ("󰒙", text_dim(), "DOWN", text_dim())
                 };
 
-                let row_style = if i == app.selected_tunnel && app.section == Section::Tunnels {
+                let row_style = if i == app.selected_tunnel && app.selected_group.is_none() && app.section == Section::Tunnels {
                     Style::default()
                         .bg(bg_selected())
                         .fg(text())
@@ -366,21 +689,62 @@ fn draw_tunnels_list(f: &mut Frame, app: &App, area: Rect) {
                     Style::default()
                 };
 
-                Row::new(vec![
-                    Span::styled(icon, Style::default().fg(icon_color)),
-                    Span::styled(&tunnel.name, Style::default().fg(text())),
-                    Span::styled(status, Style::default().fg(status_color)),
-                ])
-                .style(row_style)
+                // Tags follow the name as colored chips; group members are indented
+                let mut name_spans = vec![Span::styled(
+                    format!("{}{}", if grouped { "  " } else { "" }, tunnel.name),
+                    Style::default().fg(text()),
+                )];
+                if tunnel.source == ProfileSource::NetworkManager {
+                    name_spans.push(Span::styled(" (NM)", Style::default().fg(text_dim())));
+                }
+                // Lock when every peer has a PresharedKey; open lock only when `require_psk` asks
+                match app.tunnel_psk(&tunnel.name) {
+                    Some(true) => name_spans.push(Span::styled(" 󰌾", Style::default().fg(text_dim()))),
+                    Some(false) if app.config.require_psk => {
+                        name_spans.push(Span::styled(" 󰌿", Style::default().fg(warning())));
+                    }
+                    _ => {}
+                }
+                for tag in app.tunnel_tags(&tunnel.name) {
+                    name_spans.push(Span::raw(" "));
+                    name_spans.push(Span::styled(
+                        format!(" {} ", tag),
+                        Style::default().fg(tag_color(tag)).add_modifier(Modifier::REVERSED),
+                    ));
+                }
+
+                let mut cells = vec![
+                    Line::from(Span::styled(icon, Style::default().fg(icon_color))),
+                    Line::from(name_spans),
+                ];
+                if show_protocol {
+                    // NetworkManager's "vpn" covers OpenVPN and the other plugins
+                    let proto = if tunnel.protocol == "wireguard" { "󰖂 WG" } else { "󰦝 OVPN" };
+                    cells.push(Line::from(Span::styled(proto, Style::default().fg(text_dim()))));
+                }
+                cells.push(Line::from(Span::styled(status, Style::default().fg(status_color))));
+                if show_since {
+                    let since = is_connected
+                        .then(|| crate::vpn::wireguard::get_interface_uptime(tunnel.interface()))
+                        .flatten()
+                        .map(App::format_duration)
+                        .unwrap_or_else(|| "-".to_string());
+                    cells.push(Line::from(Span::styled(since, Style::default().fg(text_dim()))));
+                }
+
+                Row::new(cells).style(row_style)
             })
             .collect()
     };
 
-    let widths = vec![
-        Constraint::Length(3),
-        Constraint::Percentage(60),
-        Constraint::Percentage(35),
-    ];
+    let mut widths = vec![Constraint::Length(3), Constraint::Fill(1)];
+    if show_protocol {
+        widths.push(Constraint::Length(7));
+    }
+    widths.push(Constraint::Length(6));
+    if show_since {
+        widths.push(Constraint::Length(8));
+    }
 
     let table = Table::new(rows, widths)
         .header(header.style(Style::default()))
@@ -389,15 +753,109 @@ fn draw_tunnels_list(f: &mut Frame, app: &App, area: Rect) {
     f.render_widget(table, area);
 }
 
+/// Stable color per tag so the same label always looks the same
+fn tag_color(tag: &str) -> Color {
+    let palette = [accent(), success(), warning(), accent_bright(), danger()];
+    let hash = tag.bytes().fold(0usize, |h, b| h.wrapping_mul(31).wrapping_add(b as usize));
+    palette[hash % palette.len()]
+}
+
 fn draw_config_viewer(f: &mut Frame, app: &App, area: Rect) {
+    // The selected tunnel's note sits on its own line under the viewer
+    let note = app.tunnels.get(app.selected_tunnel).and_then(|t| app.tunnel_note(&t.name));
+    let area = if let Some(note) = note {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(3), Constraint::Length(1)])
+            .split(area);
+        let note_line = Paragraph::new(Line::from(vec![
+            Span::styled(" 󰏫 ", Style::default().fg(accent())),
+            Span::styled(note, Style::default().fg(text_dim())),
+        ]));
+        f.render_widget(note_line, chunks[1]);
+        chunks[0]
+    } else {
+        area
+    };
+
+    // Data quota as a bar on the line above the note
+    let quota = app.tunnels.get(app.selected_tunnel).and_then(|t| app.quota_usage(&t.name));
+    let area = if let Some((fraction, summary)) = quota {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(3), Constraint::Length(1)])
+            .split(area);
+        let color = if fraction >= 1.0 {
+            danger()
+        } else if fraction >= f64::from(crate::vpn::quota::WARN_PERCENT) / 100.0 {
+            warning()
+        } else {
+            success()
+        };
+        const BAR_WIDTH: usize = 20;
+        let filled = ((fraction.min(1.0) * BAR_WIDTH as f64).round() as usize).min(BAR_WIDTH);
+        let quota_line = Paragraph::new(Line::from(vec![
+            Span::styled(" 󰓅 ", Style::default().fg(accent())),
+            Span::styled("━".repeat(filled), Style::default().fg(color)),
+            Span::styled("━".repeat(BAR_WIDTH - filled), Style::default().fg(inactive())),
+            Span::styled(format!(" {}", summary), Style::default().fg(text_dim())),
+        ]));
+        f.render_widget(quota_line, chunks[1]);
+        chunks[0]
+    } else {
+        area
+    };
+
+    // Multi-peer tunnels get a peer section above the config ('P' expands it)
+    let peers = app.tunnels
+        .get(app.selected_tunnel)
+        .and_then(|t| app.vpn_status.interface_status(t.interface()))
+        .map(|s| s.peers.as_slice())
+        .unwrap_or(&[]);
+    let area = if peers.len() > 1 {
+        let height = if app.show_peers { peers.len() as u16 + 1 } else { 1 };
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(height.min(area.height / 2)), Constraint::Min(3)])
+            .split(area);
+        draw_peer_section(f, app, peers, chunks[0]);
+        chunks[1]
+    } else {
+        area
+    };
+
     // Config viewer is always visible but not separately active
     let border_color = inactive();
     let title_style = Style::default().fg(inactive());
 
-    let block = Block::default()
-        .title(Span::styled(" Config ", title_style))
+    // Show the search query in the title while set (cursor while typing),
+    // otherwise what the config's AllowedIPs route through the tunnel
+    let title = match &app.config_search {
+        Some(query) if app.config_search_active => format!(" Config s/{}_ ", query),
+        Some(query) => format!(" Config s/{} ", query),
+        None => {
+            let psk = match crate::vpn::wireguard::config_uses_psk(&app.tunnel_config_content) {
+                Some(true) => " · 󰌾 PSK",
+                Some(false) if app.config.require_psk => " · 󰌿 no PSK",
+                _ => "",
+            };
+            match TunnelScope::from_config(&app.tunnel_config_content) {
+                Some(scope) => format!(" Config · {}{} ", scope.label(), psk),
+                None => format!(" Config{} ", psk),
+            }
+        }
+    };
+
+    let mut block = Block::default()
+        .title(Span::styled(title, title_style))
         .borders(Borders::ALL)
         .border_style(Style::default().fg(border_color));
+    if app.tunnel_config_stale {
+        block = block.title_bottom(Span::styled(
+            " config changed on disk - press c to reload ",
+            Style::default().fg(warning()),
+        ));
+    }
 
     if app.tunnels.is_empty() {
         let help = Paragraph::new("No tunnel selected")
@@ -409,8 +867,9 @@ fn draw_config_viewer(f: &mut Frame, app: &App, area: Rect) {
 
     // Get the visible lines based on scroll offset
     let inner_height = area.height.saturating_sub(2) as usize; // Account for borders
+    app.config_viewer_height.set(inner_height);
     let lines: Vec<&str> = app.tunnel_config_content.lines().collect();
-    let start = app.tunnel_config_scroll;
+    let start = app.tunnel_config_scroll.min(lines.len());
     let end = (start + inner_height).min(lines.len());
     
     let visible_lines: Vec<Line> = lines[start..end]
@@ -418,7 +877,9 @@ fn draw_config_viewer(f: &mut Frame, app: &App, area: Rect) {
         .enumerate()
         .map(|(i, line)| {
             let line_num = start + i + 1;
-            let style = if line.starts_with('[') {
+            let style = if app.config_line_matches(line) {
+                Style::default().fg(warning()).bg(bg_selected()).add_modifier(Modifier::BOLD)
+            } else if line.starts_with('[') {
                 Style::default().fg(accent_bright())
             } else if line.starts_with('#') {
                 Style::default().fg(text_dim())
@@ -442,12 +903,43 @@ fn draw_config_viewer(f: &mut Frame, app: &App, area: Rect) {
     f.render_widget(content, area);
 }
 
+fn draw_peer_section(f: &mut Frame, app: &App, peers: &[PeerStatus], area: Rect) {
+    let arrow = if app.show_peers { "▾" } else { "▸" };
+    let fresh = peers.iter().filter(|p| !p.handshake_stale).count();
+    let mut lines = vec![Line::from(vec![
+        Span::styled(format!(" {} {} peers", arrow, peers.len()), Style::default().fg(accent_bright())),
+        Span::styled(format!(" ({} with recent handshake, P to {})", fresh, if app.show_peers { "collapse" } else { "expand" }), Style::default().fg(text_dim())),
+    ])];
+
+    if app.show_peers {
+        for peer in peers {
+            let key: String = peer.public_key.chars().take(8).collect();
+            let handshake_color = if peer.handshake_stale { warning() } else { success() };
+            lines.push(Line::from(vec![
+                Span::styled(format!("   {}… ", key), Style::default().fg(text())),
+                Span::styled(peer.endpoint.as_deref().unwrap_or("(no endpoint)"), Style::default().fg(text_dim())),
+                Span::styled(format!("  {}", peer.latest_handshake.as_deref().unwrap_or("no handshake")), Style::default().fg(handshake_color)),
+                Span::styled(
+                    format!("  ↓{} ↑{}", peer.transfer_rx.as_deref().unwrap_or("0 B"), peer.transfer_tx.as_deref().unwrap_or("0 B"))
+                        .replace(" received", "")
+                        .replace(" sent", ""),
+                    Style::default().fg(text_dim()),
+                ),
+            ]));
+        }
+    }
+
+    f.render_widget(Paragraph::new(lines), area);
+}
+
 fn draw_footer(f: &mut Frame, app: &App, area: Rect) {
-    let hints: Vec<(&str, &str)> = match app.section {
+    let mut hints: Vec<(&str, &str)> = match app.section {
         Section::Networks => vec![
             ("↑↓", "Nav"),
+            ("Enter", "Edit"),
             ("r", "Rule"),
             ("t", "Tunnel"),
+            ("+/-", "Prio"),
             ("d", "Del"),
             ("Tab", "Next"),
             ("h", "Help"),
@@ -456,17 +948,31 @@ fn draw_footer(f: &mut Frame, app: &App, area: Rect) {
             ("↑↓", "Nav"),
             ("Space", "Connect"),
             ("e", "Edit"),
+            ("v", "View"),
             ("n", "New"),
             ("i", "Import"),
+            ("p", "Paste"),
+            ("x", "Export"),
+            ("/", "Filter"),
+            ("l", "Label"),
+            ("s", "Search"),
+            ("PgUp/PgDn", "Scroll"),
             ("d", "Del"),
         ],
         Section::KillSwitch => vec![
             ("Space", "Toggle"),
             ("k", "Toggle"),
+            ("a", "Allowlist"),
             ("Tab", "Next"),
             ("h", "Help"),
         ],
     };
+    if app.connectivity.captive_portal_url.is_some() {
+        hints.insert(0, ("o", "Portal"));
+    }
+    if app.undo.is_some() {
+        hints.insert(0, ("u", "Undo"));
+    }
 
     // Responsive: show fewer hints on narrow terminals
     let max_hints = if area.width < 60 { 4 } else if area.width < 80 { 5 } else { hints.len() };
@@ -499,8 +1005,13 @@ fn draw_file_browser(f: &mut Frame, app: &App) {
 
     f.render_widget(Clear, popup_area);
 
+    let title = match &app.export_tunnel {
+        Some(name) => format!(" 󰈔 Export {} - choose a folder ", name),
+        None if app.browser_recursive => " 󰈔 Select WireGuard Config (all subfolders) ".to_string(),
+        None => " 󰈔 Select WireGuard Config ".to_string(),
+    };
     let block = Block::default()
-        .title(Span::styled(" 󰈔 Select WireGuard Config ", Style::default().fg(accent())))
+        .title(Span::styled(title, Style::default().fg(accent())))
         .borders(Borders::ALL)
         .border_style(Style::default().fg(accent()));
 
@@ -526,7 +1037,10 @@ fn draw_file_browser(f: &mut Frame, app: &App) {
 
     let rows: Vec<Row> = if app.browser_entries.is_empty() {
         vec![Row::new(vec![
-            Span::styled("  No .conf files in this directory", Style::default().fg(text_dim())),
+            Span::styled(
+                if app.browser_recursive { "  No .conf files below this directory" } else { "  No .conf files in this directory" },
+                Style::default().fg(text_dim()),
+            ),
         ])]
     } else {
         app.browser_entries
@@ -554,20 +1068,39 @@ fn draw_file_browser(f: &mut Frame, app: &App) {
             .collect()
     };
 
+    // Keep the selection visible in long (e.g. find mode) lists
     let widths = [Constraint::Length(5), Constraint::Percentage(90)];
     let table = Table::new(rows, widths);
-    f.render_widget(table, inner[1]);
+    let mut state = TableState::default().with_selected(Some(app.browser_selected));
+    f.render_stateful_widget(table, inner[1], &mut state);
 
-    let hint = Paragraph::new(Line::from(vec![
+    let action_hint = if app.export_tunnel.is_some() {
+        vec![
+            Span::styled("Enter", Style::default().fg(accent())),
+            Span::raw(" open │ "),
+            Span::styled("s", Style::default().fg(accent())),
+            Span::raw(" save here │ "),
+        ]
+    } else {
+        vec![
+            Span::styled("Enter", Style::default().fg(accent())),
+            Span::raw(" select │ "),
+            Span::styled("F", Style::default().fg(accent())),
+            Span::raw(if app.browser_recursive { " browse │ " } else { " find in subfolders │ " }),
+        ]
+    };
+    let mut hint_spans = vec![
         Span::styled("j/k", Style::default().fg(accent())),
         Span::raw(" nav │ "),
-        Span::styled("Enter", Style::default().fg(accent())),
-        Span::raw(" select │ "),
+    ];
+    hint_spans.extend(action_hint);
+    hint_spans.extend([
         Span::styled("Backspace", Style::default().fg(accent())),
         Span::raw(" up │ "),
         Span::styled("Esc", Style::default().fg(accent())),
         Span::raw(" cancel"),
-    ]))
+    ]);
+    let hint = Paragraph::new(Line::from(hint_spans))
     .alignment(Alignment::Center)
     .style(Style::default().fg(text_dim()));
     f.render_widget(hint, inner[2]);
@@ -720,27 +1253,46 @@ fn draw_manual_config(f: &mut Frame, app: &App) {
     let content_border = if app.preview_field == 1 { accent() } else { inactive() };
     let content_cursor = if app.preview_field == 1 { "█" } else { "" };
     
-    let content_lines: Vec<Line> = app.config_preview
-        .lines()
-        .map(|line| {
-            if line.starts_with('[') {
-                Line::styled(line, Style::default().fg(accent()).add_modifier(Modifier::BOLD))
-            } else if line.contains('=') {
-                let parts: Vec<&str> = line.splitn(2, '=').collect();
-                if parts.len() == 2 {
-                    Line::from(vec![
-                        Span::styled(parts[0], Style::default().fg(header())),
-                        Span::styled("=", Style::default().fg(text_dim())),
-                        Span::styled(parts[1], Style::default().fg(text())),
-                    ])
+    // Dim skeleton until something is typed or pasted
+    let content_lines: Vec<Line> = if app.config_preview.is_empty() {
+        crate::vpn::wireguard::CONFIG_TEMPLATE
+            .lines()
+            .map(|line| Line::styled(line, Style::default().fg(text_dim())))
+            .collect()
+    } else {
+        // The line still being typed isn't judged until Enter
+        let typing = (app.preview_field == 1 && !app.config_preview.ends_with('\n'))
+            .then(|| app.config_preview.lines().count().saturating_sub(1));
+        app.config_preview
+            .lines()
+            .enumerate()
+            .map(|(i, line)| {
+                if line.starts_with('[') {
+                    Line::styled(line, Style::default().fg(accent()).add_modifier(Modifier::BOLD))
+                } else if let Some((key, value)) = line.split_once('=') {
+                    // Flag values wg-quick would reject on connect
+                    let value_only = value.split('#').next().unwrap_or("").trim();
+                    let problem = crate::vpn::wireguard::check_config_value(key.trim(), value_only)
+                        .filter(|_| typing != Some(i));
+                    match problem {
+                        Some(problem) => Line::from(vec![
+                            Span::styled(key, Style::default().fg(danger())),
+                            Span::styled("=", Style::default().fg(text_dim())),
+                            Span::styled(value, Style::default().fg(danger())),
+                            Span::styled(format!("  ← {}", problem), Style::default().fg(text_dim())),
+                        ]),
+                        None => Line::from(vec![
+                            Span::styled(key, Style::default().fg(header())),
+                            Span::styled("=", Style::default().fg(text_dim())),
+                            Span::styled(value, Style::default().fg(text())),
+                        ]),
+                    }
                 } else {
                     Line::styled(line, Style::default().fg(text()))
                 }
-            } else {
-                Line::styled(line, Style::default().fg(text()))
-            }
-        })
-        .collect();
+            })
+            .collect()
+    };
 
     // Add cursor to last line if in content field
     let mut display_lines = content_lines;
@@ -768,6 +1320,8 @@ fn draw_manual_config(f: &mut Frame, app: &App) {
         Span::styled("  [ ", Style::default().fg(text_dim())),
         Span::styled("F2 = Save", Style::default().fg(success()).add_modifier(Modifier::BOLD)),
         Span::styled(" ]  [ ", Style::default().fg(text_dim())),
+        Span::styled("F3 = New Keypair", Style::default().fg(accent())),
+        Span::styled(" ]  [ ", Style::default().fg(text_dim())),
         Span::styled("Tab = Switch Field", Style::default().fg(accent())),
         Span::styled(" ]  [ ", Style::default().fg(text_dim())),
         Span::styled("Esc = Cancel", Style::default().fg(danger())),
@@ -808,20 +1362,92 @@ fn draw_help_popup(f: &mut Frame) {
             Span::styled("  Space     ", Style::default().fg(accent())),
             Span::raw("Connect/Disconnect selected tunnel"),
         ]),
+        Line::from(vec![
+            Span::styled("  A         ", Style::default().fg(accent())),
+            Span::raw("Connect alongside tunnels already up"),
+        ]),
+        Line::from(vec![
+            Span::styled("  T         ", Style::default().fg(accent())),
+            Span::raw("Test tunnel (connect, verify, roll back)"),
+        ]),
+        Line::from(vec![
+            Span::styled("  M         ", Style::default().fg(accent())),
+            Span::raw("Probe MTU of connected tunnel, offer to save it"),
+        ]),
+        Line::from(vec![
+            Span::styled("  S         ", Style::default().fg(accent())),
+            Span::raw("Rank tunnels by endpoint ping, connect the fastest"),
+        ]),
         Line::from(vec![
             Span::styled("  f         ", Style::default().fg(accent())),
             Span::raw("Import .conf file from file browser"),
         ]),
+        Line::from(vec![
+            Span::styled("  p         ", Style::default().fg(accent())),
+            Span::raw("Import config from clipboard"),
+        ]),
+        Line::from(vec![
+            Span::styled("  x         ", Style::default().fg(accent())),
+            Span::raw("Export config to a folder (contains private key)"),
+        ]),
         Line::from(vec![
             Span::styled("  c         ", Style::default().fg(accent())),
             Span::raw("View/edit tunnel config"),
         ]),
+        Line::from(vec![
+            Span::styled("  v         ", Style::default().fg(accent())),
+            Span::raw("View config read-only in $PAGER (no reconnect)"),
+        ]),
         Line::from(vec![
             Span::styled("  d         ", Style::default().fg(accent())),
             Span::raw("Delete selected tunnel"),
         ]),
+        Line::from(vec![
+            Span::styled("  /         ", Style::default().fg(accent())),
+            Span::raw("Filter tunnels by name or tag (Enter keeps, Esc clears)"),
+        ]),
+        Line::from(vec![
+            Span::styled("  l         ", Style::default().fg(accent())),
+            Span::raw("Edit tags, note and group for selected tunnel"),
+        ]),
+        Line::from(vec![
+            Span::styled("  F2        ", Style::default().fg(accent())),
+            Span::raw("Rename selected tunnel (reconnects if it is up)"),
+        ]),
+        Line::from(vec![
+            Span::styled("  K / J     ", Style::default().fg(accent())),
+            Span::raw("Move selected tunnel up/down in the list"),
+        ]),
+        Line::from(vec![
+            Span::styled("  C         ", Style::default().fg(accent())),
+            Span::raw("Check selected tunnel's config for common mistakes"),
+        ]),
+        Line::from(vec![
+            Span::styled("  c         ", Style::default().fg(accent())),
+            Span::raw("Reload config after it changed on disk"),
+        ]),
+        Line::from(vec![
+            Span::styled("  Enter     ", Style::default().fg(accent())),
+            Span::raw("On a group header: collapse/expand the group"),
+        ]),
+        Line::from(vec![
+            Span::styled("  s         ", Style::default().fg(accent())),
+            Span::raw("Search config (↑↓ jump between matches)"),
+        ]),
+        Line::from(vec![
+            Span::styled("  PgUp/PgDn ", Style::default().fg(accent())),
+            Span::raw("Scroll config (Ctrl+u/d half page, Home/End)"),
+        ]),
+        Line::from(vec![
+            Span::styled("  P         ", Style::default().fg(accent())),
+            Span::raw("Expand/collapse peers (multi-peer tunnels)"),
+        ]),
         Line::from(""),
         Line::from(Span::styled("═══ Network Rules ═══", Style::default().fg(header()).add_modifier(Modifier::BOLD))),
+        Line::from(vec![
+            Span::styled("  Enter     ", Style::default().fg(accent())),
+            Span::raw("Edit every setting of the network's rule at once"),
+        ]),
         Line::from(vec![
             Span::styled("  r         ", Style::default().fg(accent())),
             Span::raw("Cycle rule: Always → Never → Session → None"),
@@ -830,6 +1456,14 @@ fn draw_help_popup(f: &mut Frame) {
             Span::styled("  t         ", Style::default().fg(accent())),
             Span::raw("Cycle tunnel assignment for network"),
         ]),
+        Line::from(vec![
+            Span::styled("  +/-       ", Style::default().fg(accent())),
+            Span::raw("Raise/lower rule priority (highest wins)"),
+        ]),
+        Line::from(vec![
+            Span::styled("  o         ", Style::default().fg(accent())),
+            Span::raw("Open captive portal sign-in page (when detected)"),
+        ]),
         Line::from(""),
         Line::from(Span::styled("═══ Kill Switch ═══", Style::default().fg(header()).add_modifier(Modifier::BOLD))),
         Line::from(vec![
@@ -839,6 +1473,48 @@ fn draw_help_popup(f: &mut Frame) {
         Line::from(vec![
             Span::raw("            Blocks all traffic except through VPN"),
         ]),
+        Line::from(vec![
+            Span::styled("  a         ", Style::default().fg(accent())),
+            Span::raw("Edit allowlist (LAN subnets reachable with kill switch on)"),
+        ]),
+        Line::from(""),
+        Line::from(Span::styled("═══ General ═══", Style::default().fg(header()).add_modifier(Modifier::BOLD))),
+        Line::from(vec![
+            Span::styled("  H         ", Style::default().fg(accent())),
+            Span::raw("Event history (connects, drops, network changes)"),
+        ]),
+        Line::from(vec![
+            Span::styled("  L         ", Style::default().fg(accent())),
+            Span::raw("Latency history: min/avg/max, jitter, loss"),
+        ]),
+        Line::from(vec![
+            Span::styled("  I         ", Style::default().fg(accent())),
+            Span::raw("Routing table and VPN addresses (r refreshes)"),
+        ]),
+        Line::from(vec![
+            Span::styled("  D         ", Style::default().fg(accent())),
+            Span::raw("Pause/resume daemon auto-connect (keeps current connection)"),
+        ]),
+        Line::from(vec![
+            Span::styled("  u         ", Style::default().fg(accent())),
+            Span::raw("Undo last applied change (tunnel, kill switch)"),
+        ]),
+        Line::from(vec![
+            Span::styled("  Ctrl+k    ", Style::default().fg(danger())),
+            Span::raw("Panic: drop all tunnels and the kill switch now"),
+        ]),
+        Line::from(vec![
+            Span::styled("  y         ", Style::default().fg(accent())),
+            Span::raw("Copy public IP (or tunnel endpoint) to clipboard"),
+        ]),
+        Line::from(vec![
+            Span::styled("  Y         ", Style::default().fg(accent())),
+            Span::raw("Copy tunnel config to clipboard (keys redacted)"),
+        ]),
+        Line::from(vec![
+            Span::styled("  Ctrl+r    ", Style::default().fg(accent())),
+            Span::raw("Reload theme colors"),
+        ]),
         Line::from(""),
         Line::from(Span::styled("═══ Quick Start ═══", Style::default().fg(header()).add_modifier(Modifier::BOLD))),
         Line::from(vec![
@@ -900,17 +1576,207 @@ fn draw_help_popup(f: &mut Frame) {
     f.render_widget(help, popup_area);
 }
 
-fn draw_confirm_popup(f: &mut Frame, app: &App) {
-    let popup_area = centered_rect(40, 20, f.area());
+fn draw_label_popup(f: &mut Frame, app: &App) {
+    let area = f.area();
+    let popup_area = centered_rect(if area.width < 80 { 90 } else { 60 }, 40, area);
 
     f.render_widget(Clear, popup_area);
 
-    let message = app.status_message.as_deref().unwrap_or("Confirm?");
+    let block = Block::default()
+        .title(Span::styled(format!(" 󰓹 Labels: {} ", app.label_tunnel), Style::default().fg(accent())))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(accent()));
 
-    let confirm = Paragraph::new(vec![
-        Line::from(""),
-        Line::from(Span::styled(message, Style::default().fg(warning()))),
-        Line::from(""),
+    f.render_widget(block, popup_area);
+
+    let inner = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(1)
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Min(3),
+            Constraint::Length(3),
+            Constraint::Length(1),
+        ])
+        .split(popup_area);
+
+    let fields = [
+        (" Tags (comma-separated) ", &app.label_tags),
+        (" Note ", &app.label_note),
+        (" Group (empty = by name prefix) ", &app.label_group),
+    ];
+    for (i, (title, value)) in fields.iter().enumerate() {
+        let active = app.preview_field == i;
+        let cursor = if active { "_" } else { "" };
+        let input = Paragraph::new(format!("{}{}", value, cursor))
+            .style(Style::default().fg(text()))
+            .wrap(Wrap { trim: false })
+            .block(
+                Block::default()
+                    .title(Span::styled(*title, Style::default().fg(if active { accent() } else { header() })))
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(if active { accent() } else { inactive() })),
+            );
+        f.render_widget(input, inner[i]);
+    }
+
+    let hint = Paragraph::new(Line::from(vec![
+        Span::styled("Enter", Style::default().fg(accent())),
+        Span::styled(" Save │ ", Style::default().fg(text_dim())),
+        Span::styled("Tab", Style::default().fg(accent())),
+        Span::styled(" Switch field │ ", Style::default().fg(text_dim())),
+        Span::styled("Esc", Style::default().fg(accent())),
+        Span::styled(" Cancel", Style::default().fg(text_dim())),
+    ]))
+    .alignment(Alignment::Center);
+    f.render_widget(hint, inner[3]);
+}
+
+fn draw_rename_popup(f: &mut Frame, app: &App) {
+    let area = f.area();
+    let popup_area = centered_rect(if area.width < 80 { 90 } else { 50 }, 20, area);
+
+    f.render_widget(Clear, popup_area);
+
+    let block = Block::default()
+        .title(Span::styled(format!(" Rename: {} ", app.rename_from), Style::default().fg(accent())))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(accent()));
+
+    f.render_widget(block, popup_area);
+
+    let inner = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(1)
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Length(1),
+            Constraint::Min(0),
+            Constraint::Length(1),
+        ])
+        .split(popup_area);
+
+    let input = Paragraph::new(format!("{}_", app.input_buffer))
+        .style(Style::default().fg(text()))
+        .block(
+            Block::default()
+                .title(Span::styled(" New name ", Style::default().fg(accent())))
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(accent())),
+        );
+    f.render_widget(input, inner[0]);
+
+    // Characters the interface name can't hold are dropped on save
+    let safe_name = crate::vpn::wireguard::sanitize_profile_name(&app.input_buffer);
+    if safe_name != app.input_buffer {
+        let saved_as = Paragraph::new(format!("Saved as: {}", safe_name))
+            .style(Style::default().fg(text_dim()));
+        f.render_widget(saved_as, inner[1]);
+    }
+
+    let hint = Paragraph::new(Line::from(vec![
+        Span::styled("Enter", Style::default().fg(accent())),
+        Span::styled(" Rename │ ", Style::default().fg(text_dim())),
+        Span::styled("Esc", Style::default().fg(accent())),
+        Span::styled(" Cancel", Style::default().fg(text_dim())),
+    ]))
+    .alignment(Alignment::Center);
+    f.render_widget(hint, inner[3]);
+}
+
+fn draw_rule_popup(f: &mut Frame, app: &App) {
+    let Some(form) = app.rule_form.as_ref() else {
+        return;
+    };
+    let area = f.area();
+    let popup_area = centered_rect(if area.width < 80 { 90 } else { 60 }, 40, area);
+
+    f.render_widget(Clear, popup_area);
+
+    let block = Block::default()
+        .title(Span::styled(format!(" Rule: {} ", form.network_name), Style::default().fg(accent())))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(accent()));
+
+    f.render_widget(block, popup_area);
+
+    let inner = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(1)
+        .constraints([Constraint::Min(5), Constraint::Length(1)])
+        .split(popup_area);
+
+    let tunnel = match (&form.tunnel, &app.config.default_profile) {
+        (Some(name), _) => name.clone(),
+        (None, Some(default)) => format!("Default ({})", default),
+        (None, None) => "Default (none set)".to_string(),
+    };
+    let priority = form.priority.map_or_else(|| "Default (lowest)".to_string(), |p| p.to_string());
+    let dns = if form.dns.is_empty() && form.field != 3 {
+        "Tunnel's own".to_string()
+    } else {
+        form.dns.clone()
+    };
+    let kill_switch = match form.kill_switch {
+        None => "Tunnel's own setting",
+        Some(true) => "On",
+        Some(false) => "Off",
+    };
+    let fields = [
+        ("Rule", form.mode.label().to_string()),
+        ("Tunnel", tunnel),
+        ("Priority", priority),
+        ("DNS servers", dns),
+        ("Kill switch", kill_switch.to_string()),
+        ("Open WiFi", if form.match_open_networks { "Also applies to open WiFi without a rule" } else { "No" }.to_string()),
+    ];
+
+    let lines: Vec<Line> = fields
+        .into_iter()
+        .enumerate()
+        .map(|(i, (label, value))| {
+            let selected = i == form.field;
+            // Text field gets a cursor, choice fields get arrows
+            let value = match (selected, i) {
+                (true, 3) => format!("{}_", value),
+                (true, _) => format!("‹ {} ›", value),
+                (false, _) => value,
+            };
+            let style = if selected { Style::default().bg(bg_selected()) } else { Style::default() };
+            Line::from(vec![
+                Span::styled(format!("  {:<13}", label), Style::default().fg(header())),
+                Span::styled(value, Style::default().fg(if selected { accent() } else { text() })),
+            ])
+            .style(style)
+        })
+        .collect();
+    f.render_widget(Paragraph::new(lines), inner[0]);
+
+    let hint = Paragraph::new(Line::from(vec![
+        Span::styled("↑↓", Style::default().fg(accent())),
+        Span::styled(" Field │ ", Style::default().fg(text_dim())),
+        Span::styled("←→", Style::default().fg(accent())),
+        Span::styled(" Change │ ", Style::default().fg(text_dim())),
+        Span::styled("Enter", Style::default().fg(accent())),
+        Span::styled(" Save │ ", Style::default().fg(text_dim())),
+        Span::styled("Esc", Style::default().fg(accent())),
+        Span::styled(" Cancel", Style::default().fg(text_dim())),
+    ]))
+    .alignment(Alignment::Center);
+    f.render_widget(hint, inner[1]);
+}
+
+fn draw_confirm_popup(f: &mut Frame, app: &App) {
+    let popup_area = centered_rect(40, 20, f.area());
+
+    f.render_widget(Clear, popup_area);
+
+    let message = app.status_message.as_deref().unwrap_or("Confirm?");
+
+    let confirm = Paragraph::new(vec![
+        Line::from(""),
+        Line::from(Span::styled(message, Style::default().fg(warning()))),
+        Line::from(""),
         Line::from(vec![
             Span::styled("  y", Style::default().fg(success()).add_modifier(Modifier::BOLD)),
             Span::raw(" Yes   "),
@@ -924,11 +1790,539 @@ fn draw_confirm_popup(f: &mut Frame, app: &App) {
             .borders(Borders::ALL)
             .border_style(Style::default().fg(warning())),
     )
-    .alignment(Alignment::Center);
+    .alignment(Alignment::Center)
+    .wrap(Wrap { trim: true });
 
     f.render_widget(confirm, popup_area);
 }
 
+fn draw_allowlist_popup(f: &mut Frame, app: &App) {
+    let area = f.area();
+    let popup_area = centered_rect(
+        if area.width < 80 { 90 } else { 60 },
+        if area.height < 30 { 80 } else { 60 },
+        area
+    );
+
+    f.render_widget(Clear, popup_area);
+
+    let block = Block::default()
+        .title(Span::styled(" 󰯄 Kill Switch Allowlist ", Style::default().fg(accent())))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(accent()));
+
+    f.render_widget(block, popup_area);
+
+    let inner = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(1)
+        .constraints([
+            Constraint::Min(3),
+            Constraint::Length(3),
+            Constraint::Length(1),
+        ])
+        .split(popup_area);
+
+    // Current entries
+    let rows: Vec<Row> = if app.config.killswitch_allowlist.is_empty() {
+        vec![Row::new(vec![
+            Span::styled("  No allowed subnets - everything except the VPN is blocked", Style::default().fg(text_dim())),
+        ])]
+    } else {
+        app.config.killswitch_allowlist
+            .iter()
+            .enumerate()
+            .map(|(i, cidr)| {
+                let row_style = if i == app.allowlist_selected {
+                    Style::default()
+                        .bg(bg_selected())
+                        .fg(text())
+                } else {
+                    Style::default()
+                };
+
+                Row::new(vec![
+                    Span::styled("  󰛳 ", Style::default().fg(success())),
+                    Span::styled(cidr.as_str(), Style::default().fg(text())),
+                ])
+                .style(row_style)
+            })
+            .collect()
+    };
+
+    let widths = [Constraint::Length(5), Constraint::Percentage(90)];
+    let table = Table::new(rows, widths);
+    f.render_widget(table, inner[0]);
+
+    // New entry input
+    let input = Paragraph::new(format!("{}_", app.input_buffer))
+        .style(Style::default().fg(text()))
+        .block(
+            Block::default()
+                .title(Span::styled(" Add CIDR (e.g. 192.168.1.0/24) ", Style::default().fg(accent())))
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(accent())),
+        );
+    f.render_widget(input, inner[1]);
+
+    let hint = Paragraph::new(Line::from(vec![
+        Span::styled("↑↓", Style::default().fg(accent())),
+        Span::raw(" nav │ "),
+        Span::styled("Enter", Style::default().fg(accent())),
+        Span::raw(" add │ "),
+        Span::styled("Del", Style::default().fg(accent())),
+        Span::raw(" remove │ "),
+        Span::styled("Esc", Style::default().fg(accent())),
+        Span::raw(" close"),
+    ]))
+    .alignment(Alignment::Center)
+    .style(Style::default().fg(text_dim()));
+    f.render_widget(hint, inner[2]);
+}
+
+fn draw_history_popup(f: &mut Frame, app: &App) {
+    use crate::config::history::{format_timestamp, EventKind};
+
+    let area = f.area();
+    let popup_area = centered_rect(
+        if area.width < 100 { 95 } else { 75 },
+        if area.height < 30 { 90 } else { 70 },
+        area
+    );
+
+    f.render_widget(Clear, popup_area);
+
+    let block = Block::default()
+        .title(Span::styled(" 󰋚 Event History ", Style::default().fg(accent())))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(accent()));
+
+    f.render_widget(block, popup_area);
+
+    let inner = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(1)
+        .constraints([
+            Constraint::Min(3),
+            Constraint::Length(1),
+        ])
+        .split(popup_area);
+
+    // Newest first
+    let rows: Vec<Row> = if app.history.is_empty() {
+        vec![Row::new(vec![
+            Span::styled("  No events recorded yet", Style::default().fg(text_dim())),
+        ])]
+    } else {
+        app.history
+            .iter()
+            .rev()
+            .skip(app.history_scroll)
+            .map(|event| {
+                let color = match event.kind {
+                    EventKind::Connect | EventKind::Reconnect => success(),
+                    EventKind::HealthFailure | EventKind::Error => danger(),
+                    EventKind::Disconnect => warning(),
+                    EventKind::NetworkChange | EventKind::Resume => text_dim(),
+                };
+                Row::new(vec![
+                    Span::styled(
+                        format_timestamp(event.timestamp, app.history_utc_offset),
+                        Style::default().fg(text_dim()),
+                    ),
+                    Span::styled(event.kind.label(), Style::default().fg(color)),
+                    Span::styled(event.source.as_str(), Style::default().fg(text_dim())),
+                    Span::styled(event.detail.as_str(), Style::default().fg(text())),
+                ])
+            })
+            .collect()
+    };
+
+    let widths = [
+        Constraint::Length(20),
+        Constraint::Length(11),
+        Constraint::Length(7),
+        Constraint::Min(20),
+    ];
+    let table = Table::new(rows, widths);
+    f.render_widget(table, inner[0]);
+
+    let hint = Paragraph::new(Line::from(vec![
+        Span::styled("j/k", Style::default().fg(accent())),
+        Span::raw(" scroll │ "),
+        Span::styled("Esc", Style::default().fg(accent())),
+        Span::raw(" close"),
+    ]))
+    .alignment(Alignment::Center)
+    .style(Style::default().fg(text_dim()));
+    f.render_widget(hint, inner[1]);
+}
+
+/// Routing table next to the VPN interfaces' addresses, routes via a VPN interface highlighted
+fn draw_routing_popup(f: &mut Frame, app: &App) {
+    let info = &app.routing_info;
+    let area = f.area();
+    let popup_area = centered_rect(
+        if area.width < 100 { 95 } else { 80 },
+        if area.height < 30 { 90 } else { 75 },
+        area
+    );
+
+    f.render_widget(Clear, popup_area);
+
+    // Whether the primary tunnel carries the default route, the check behind the status line
+    let (verdict, color) = match app.vpn_status.interface.as_deref().filter(|_| app.vpn_status.connected) {
+        Some(iface) if app.vpn_status.routing_ok => (format!(" default route via {} ", iface), success()),
+        Some(iface) => (format!(" default route bypasses {} ", iface), warning()),
+        None => (" no tunnel up ".to_string(), text_dim()),
+    };
+    let block = Block::default()
+        .title(Span::styled(" 󰑪 Routing ", Style::default().fg(accent())))
+        .title(Line::from(Span::styled(verdict, Style::default().fg(color))).right_aligned())
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(accent()));
+
+    f.render_widget(block, popup_area);
+
+    let inner = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(1)
+        .constraints([
+            Constraint::Min(3),
+            Constraint::Length(1),
+        ])
+        .split(popup_area);
+    let panes = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
+        .split(inner[0]);
+
+    let vpn_route = |line: &str| {
+        info.addresses.iter().any(|(iface, _)| crate::vpn::wireguard::route_uses_interface(line, iface))
+    };
+    let mut routes: Vec<Line> = if info.routes.is_empty() {
+        vec![Line::from(Span::styled("No routes (is `ip` installed?)", Style::default().fg(text_dim())))]
+    } else {
+        info.routes
+            .iter()
+            .map(|route| {
+                let color = if vpn_route(route) { accent() } else { text_dim() };
+                Line::from(Span::styled(route.as_str(), Style::default().fg(color)))
+            })
+            .collect()
+    };
+    // Policy rules decide which table a packet uses; wg-quick's full tunnels live in their own
+    if !info.rules.is_empty() {
+        routes.push(Line::from(""));
+        routes.push(Line::from(Span::styled("ip rule", Style::default().fg(text_dim()).add_modifier(Modifier::BOLD))));
+        routes.extend(info.rules.iter().map(|rule| {
+            let color = if crate::vpn::wireguard::rule_uses_custom_table(rule) { accent() } else { text_dim() };
+            Line::from(Span::styled(rule.as_str(), Style::default().fg(color)))
+        }));
+    }
+    let routes = Paragraph::new(routes)
+        .block(Block::default()
+            .title(Span::styled(" ip route (all tables) ", Style::default().fg(text_dim())))
+            .borders(Borders::RIGHT)
+            .border_style(Style::default().fg(inactive())))
+        .scroll((app.routing_scroll as u16, 0));
+    f.render_widget(routes, panes[0]);
+
+    let mut addresses: Vec<Line> = Vec::new();
+    for (iface, lines) in &info.addresses {
+        addresses.push(Line::from(Span::styled(
+            iface.as_str(),
+            Style::default().fg(accent()).add_modifier(Modifier::BOLD),
+        )));
+        addresses.extend(lines.iter().map(|line| Line::from(Span::styled(line.as_str(), Style::default().fg(text())))));
+    }
+    if addresses.is_empty() {
+        addresses.push(Line::from(Span::styled("No VPN interface up", Style::default().fg(text_dim()))));
+    }
+    let addresses = Paragraph::new(addresses)
+        .block(Block::default()
+            .title(Span::styled(" ip addr ", Style::default().fg(text_dim())))
+            .padding(ratatui::widgets::Padding::left(1)))
+        .scroll((app.routing_scroll as u16, 0));
+    f.render_widget(addresses, panes[1]);
+
+    let hint = Paragraph::new(Line::from(vec![
+        Span::styled("j/k", Style::default().fg(accent())),
+        Span::raw(" scroll │ "),
+        Span::styled("r", Style::default().fg(accent())),
+        Span::raw(" refresh │ "),
+        Span::styled("Esc", Style::default().fg(accent())),
+        Span::raw(" close"),
+    ]))
+    .alignment(Alignment::Center)
+    .style(Style::default().fg(text_dim()));
+    f.render_widget(hint, inner[1]);
+}
+
+/// What the kill switch will allow, then the raw ruleset (scrollable), before confirming
+fn draw_killswitch_preview_popup(f: &mut Frame, app: &App) {
+    let Some(preview) = app.killswitch_preview.as_ref() else {
+        return;
+    };
+
+    let area = f.area();
+    let popup_area = centered_rect(
+        if area.width < 100 { 95 } else { 70 },
+        if area.height < 30 { 90 } else { 75 },
+        area
+    );
+
+    f.render_widget(Clear, popup_area);
+
+    let block = Block::default()
+        .title(Span::styled(
+            format!(" 󰯄 Kill Switch Preview ({}) ", preview.interface),
+            Style::default().fg(warning()),
+        ))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(warning()));
+
+    f.render_widget(block, popup_area);
+
+    // Everything not listed here is dropped
+    let mut summary = vec![Line::from(Span::styled(
+        "All other traffic will be blocked. Still allowed:",
+        Style::default().fg(text()),
+    ))];
+    summary.extend(preview.allowed().into_iter().map(|entry| {
+        Line::from(vec![
+            Span::styled("  • ", Style::default().fg(success())),
+            Span::styled(entry, Style::default().fg(text())),
+        ])
+    }));
+    if !app.vpn_status.connected {
+        summary.push(Line::from(Span::styled(
+            "No VPN is connected - this will block all internet",
+            Style::default().fg(danger()).add_modifier(Modifier::BOLD),
+        )));
+    }
+
+    let inner = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(1)
+        .constraints([
+            Constraint::Length(summary.len() as u16 + 1),
+            Constraint::Min(3),
+            Constraint::Length(1),
+        ])
+        .split(popup_area);
+
+    f.render_widget(Paragraph::new(summary), inner[0]);
+
+    let ruleset = Paragraph::new(preview.ruleset.as_str())
+        .style(Style::default().fg(text_dim()))
+        .scroll((app.killswitch_preview_scroll as u16, 0))
+        .block(
+            Block::default()
+                .title(Span::styled(" nftables ruleset ", Style::default().fg(text_dim())))
+                .borders(Borders::TOP)
+                .border_style(Style::default().fg(text_dim())),
+        );
+    f.render_widget(ruleset, inner[1]);
+
+    let hint = Paragraph::new(Line::from(vec![
+        Span::styled("j/k", Style::default().fg(accent())),
+        Span::raw(" scroll │ "),
+        Span::styled("y", Style::default().fg(success())),
+        Span::raw(" enable │ "),
+        Span::styled("n", Style::default().fg(danger())),
+        Span::raw(" cancel"),
+    ]))
+    .alignment(Alignment::Center)
+    .style(Style::default().fg(text_dim()));
+    f.render_widget(hint, inner[2]);
+}
+
+fn draw_fastest_popup(f: &mut Frame, app: &App) {
+    let area = f.area();
+    let height = (app.latency_ranking.len() as u16 + 5).min(area.height);
+    let width = 56.min(area.width);
+    let popup_area = Rect::new(
+        area.x + (area.width - width) / 2,
+        area.y + (area.height - height) / 2,
+        width,
+        height,
+    );
+
+    f.render_widget(Clear, popup_area);
+
+    let block = Block::default()
+        .title(Span::styled(" 󰓅 Fastest Tunnel ", Style::default().fg(accent())))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(accent()));
+
+    let rows: Vec<Row> = app.latency_ranking
+        .iter()
+        .enumerate()
+        .map(|(i, entry)| {
+            let (latency, color) = match entry.latency_ms {
+                Some(ms) => (format!("{} ms", ms), if i == 0 { success() } else { text() }),
+                None => ("no reply".to_string(), text_dim()),
+            };
+            let row_style = if i == app.latency_selected {
+                Style::default().bg(bg_selected()).fg(text())
+            } else {
+                Style::default()
+            };
+            Row::new(vec![
+                Line::from(Span::styled(entry.tunnel.as_str(), Style::default().fg(text()))),
+                Line::from(Span::styled(entry.endpoint.as_deref().unwrap_or("-"), Style::default().fg(text_dim()))),
+                Line::from(Span::styled(latency, Style::default().fg(color))),
+            ])
+            .style(row_style)
+        })
+        .collect();
+
+    let inner = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(1)
+        .constraints([Constraint::Min(1), Constraint::Length(1)])
+        .split(popup_area);
+    f.render_widget(block, popup_area);
+
+    let header = Row::new(vec![
+        Span::styled("Tunnel", Style::default().fg(header())),
+        Span::styled("Endpoint", Style::default().fg(header())),
+        Span::styled("Ping", Style::default().fg(header())),
+    ]);
+    let widths = [Constraint::Percentage(35), Constraint::Percentage(45), Constraint::Percentage(20)];
+    f.render_widget(Table::new(rows, widths).header(header), inner[0]);
+
+    let hint = Paragraph::new(Line::from(vec![
+        Span::styled("j/k", Style::default().fg(accent())),
+        Span::raw(" select │ "),
+        Span::styled("Enter", Style::default().fg(success())),
+        Span::raw(" connect │ "),
+        Span::styled("Esc", Style::default().fg(danger())),
+        Span::raw(" close"),
+    ]))
+    .alignment(Alignment::Center)
+    .style(Style::default().fg(text_dim()));
+    f.render_widget(hint, inner[1]);
+}
+
+/// Findings of the config check, most severe first
+fn draw_lint_popup(f: &mut Frame, app: &App) {
+    use crate::vpn::lint::Severity;
+
+    let area = f.area();
+    let popup_area = centered_rect(
+        if area.width < 100 { 95 } else { 70 },
+        if area.height < 30 { 80 } else { 50 },
+        area
+    );
+
+    f.render_widget(Clear, popup_area);
+
+    let block = Block::default()
+        .title(Span::styled(format!(" 󰄬 Config check: {} ", app.lint_tunnel), Style::default().fg(accent())))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(accent()));
+
+    let mut lines = vec![Line::from("")];
+    if app.lint_findings.is_empty() {
+        lines.push(Line::from(Span::styled("  ✓ No problems found", Style::default().fg(success()))));
+    }
+    for finding in &app.lint_findings {
+        let color = match finding.severity {
+            Severity::Error => danger(),
+            Severity::Warning => warning(),
+            Severity::Info => text_dim(),
+        };
+        lines.push(Line::from(vec![
+            Span::styled(format!("  {:<6}", finding.severity.label()), Style::default().fg(color).add_modifier(Modifier::BOLD)),
+            Span::styled(finding.message.as_str(), Style::default().fg(text())),
+        ]));
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from(vec![
+        Span::styled("Esc", Style::default().fg(accent())),
+        Span::styled(" close", Style::default().fg(text_dim())),
+    ]).alignment(Alignment::Center));
+
+    let paragraph = Paragraph::new(lines)
+        .block(block)
+        .wrap(Wrap { trim: false });
+    f.render_widget(paragraph, popup_area);
+}
+
+/// Latency stats of the connection, graded for calls and games
+fn draw_latency_popup(f: &mut Frame, app: &App) {
+    use crate::vpn::latency::{LatencyStats, Quality};
+
+    let area = f.area();
+    let height = 11.min(area.height);
+    let width = 48.min(area.width);
+    let popup_area = Rect::new(
+        area.x + (area.width - width) / 2,
+        area.y + (area.height - height) / 2,
+        width,
+        height,
+    );
+
+    f.render_widget(Clear, popup_area);
+
+    let block = Block::default()
+        .title(Span::styled(" 󰓅 Latency ", Style::default().fg(accent())))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(accent()));
+
+    let row = |label: &str, value: String, color: Color| {
+        Line::from(vec![
+            Span::styled(format!("  {:<10}", label), Style::default().fg(text_dim())),
+            Span::styled(value, Style::default().fg(color)),
+        ])
+    };
+
+    let mut lines = vec![Line::from("")];
+    match LatencyStats::from_samples(&app.latency_samples) {
+        Some(stats) => {
+            let quality = stats.quality();
+            let color = match quality {
+                Quality::Good => success(),
+                Quality::Fair => warning(),
+                Quality::Poor => danger(),
+            };
+            lines.push(row("Quality", quality.label().to_string(), color));
+            lines.push(row("Min", format!("{} ms", stats.min_ms), text()));
+            lines.push(row("Avg", format!("{} ms", stats.avg_ms), color));
+            lines.push(row("Max", format!("{} ms", stats.max_ms), text()));
+            lines.push(row("Jitter", format!("{} ms", stats.jitter_ms), color));
+            lines.push(row("Loss", format!("{}% of {} pings", stats.loss_percent, stats.samples), color));
+        }
+        None if !app.vpn_status.connected => {
+            lines.push(Line::from(Span::styled("  Not connected", Style::default().fg(text_dim()))));
+        }
+        None if app.latency_samples.is_empty() => {
+            lines.push(Line::from(Span::styled("  Collecting samples...", Style::default().fg(text_dim()))));
+        }
+        None => {
+            lines.push(row("Loss", format!("100% of {} pings", app.latency_samples.len()), danger()));
+        }
+    }
+
+    let inner = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(1)
+        .constraints([Constraint::Min(1), Constraint::Length(1)])
+        .split(popup_area);
+    f.render_widget(block, popup_area);
+    f.render_widget(Paragraph::new(lines), inner[0]);
+
+    let hint = Paragraph::new(Line::from(vec![
+        Span::styled("Esc", Style::default().fg(danger())),
+        Span::raw(" close"),
+    ]))
+    .alignment(Alignment::Center)
+    .style(Style::default().fg(text_dim()));
+    f.render_widget(hint, inner[1]);
+}
+
 fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
     let popup_layout = Layout::default()
         .direction(Direction::Vertical)
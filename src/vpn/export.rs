@@ -0,0 +1,111 @@
+//! Tunnel export and backup
+//!
+//! Copies tunnel configs out of /etc/wireguard (via the helper's config-read)
+//! so they can be backed up or moved to another machine. Exported configs
+//! contain private keys, so files are written owner-only.
+
+use anyhow::{Context, Result};
+use std::io::Write;
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::{Path, PathBuf};
+
+use crate::config::AppConfig;
+
+/// Shown wherever configs are exported
+pub const PRIVATE_KEY_WARNING: &str =
+    "Exported configs contain private keys - store them somewhere safe";
+
+/// Stands in for key values in configs copied for sharing
+pub const REDACTED: &str = "<redacted>";
+
+/// Replace PrivateKey and PresharedKey values so a config can be pasted in a forum
+/// Public keys, endpoints and everything else are kept for troubleshooting
+pub fn redact_keys(config: &str) -> String {
+    let mut out: Vec<String> = Vec::new();
+    for line in config.lines() {
+        let secret = line.split_once('=').filter(|(key, _)| {
+            let key = key.trim();
+            key.eq_ignore_ascii_case("PrivateKey") || key.eq_ignore_ascii_case("PresharedKey")
+        });
+        match secret {
+            Some((key, _)) => out.push(format!("{} = {}", key.trim_end(), REDACTED)),
+            None => out.push(line.to_string()),
+        }
+    }
+    let mut redacted = out.join("\n");
+    if config.ends_with('\n') {
+        redacted.push('\n');
+    }
+    redacted
+}
+
+/// Default export directory: ~/tonneru-backup
+pub fn default_export_dir() -> PathBuf {
+    dirs::home_dir()
+        .unwrap_or_else(|| PathBuf::from("/tmp"))
+        .join("tonneru-backup")
+}
+
+/// Write one tunnel's config to `<dir>/<name>.conf`
+pub async fn export_profile(name: &str, dir: &Path) -> Result<PathBuf> {
+    let content = super::wireguard::read_config(name).await?;
+    std::fs::create_dir_all(dir)
+        .with_context(|| format!("Cannot create {}", dir.display()))?;
+
+    let path = dir.join(format!("{}.conf", name));
+    write_private_file(&path, content.as_bytes())?;
+    Ok(path)
+}
+
+/// Export every known tunnel plus config.toml for migrating to another machine
+/// Tunnels that can't be read are skipped with a warning
+pub async fn export_all(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut written = Vec::new();
+
+    for profile in super::wireguard::list_profiles().await? {
+        match export_profile(&profile.name, dir).await {
+            Ok(path) => written.push(path),
+            Err(e) => tracing::warn!("Skipping {}: {:#}", profile.name, e),
+        }
+    }
+
+    let config_path = AppConfig::config_path()?;
+    if config_path.exists() {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("Cannot create {}", dir.display()))?;
+        let dest = dir.join("config.toml");
+        let content = std::fs::read(&config_path)
+            .with_context(|| format!("Cannot read {}", config_path.display()))?;
+        write_private_file(&dest, &content)?;
+        written.push(dest);
+    }
+
+    Ok(written)
+}
+
+/// Create or replace a file readable only by the current user
+pub(crate) fn write_private_file(path: &Path, content: &[u8]) -> Result<()> {
+    let mut file = std::fs::OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(0o600)
+        .open(path)
+        .with_context(|| format!("Cannot write {}", path.display()))?;
+    file.write_all(content)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redact_keys() {
+        let config = "[Interface]\nPrivateKey = aaa=\nAddress = 10.0.0.2/32\n\n[Peer]\nPublicKey = bbb=\npresharedkey=ccc=\n";
+        assert_eq!(
+            redact_keys(config),
+            "[Interface]\nPrivateKey = <redacted>\nAddress = 10.0.0.2/32\n\n[Peer]\nPublicKey = bbb=\npresharedkey = <redacted>\n"
+        );
+    }
+}
@@ -0,0 +1,86 @@
+//! Rank tunnels by endpoint latency
+//!
+//! Pings each tunnel's configured `Endpoint` from the physical interface -
+//! nothing is connected - so a provider's servers imported as separate
+//! tunnels can be compared before picking one.
+
+use super::wireguard;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EndpointLatency {
+    pub tunnel: String,
+    pub endpoint: Option<String>,  // Host from the config, None if it couldn't be read
+    pub latency_ms: Option<u32>,   // None when the endpoint didn't answer
+}
+
+/// Ping every tunnel's endpoint in parallel, fastest first
+/// `exclude` is the active VPN interface so pings leave through the real network
+pub async fn rank(tunnels: Vec<String>, exclude: Option<String>) -> Vec<EndpointLatency> {
+    let via = crate::network::default_route_interface(exclude.as_deref());
+
+    let mut pings = Vec::new();
+    for tunnel in tunnels {
+        let endpoint = wireguard::read_config(&tunnel).await
+            .ok()
+            .and_then(|config| wireguard::config_endpoint(&config));
+        let via = via.clone();
+        pings.push(tokio::spawn(async move {
+            let latency_ms = match &endpoint {
+                Some((host, port)) => ping_endpoint(host, *port, via).await,
+                None => None,
+            };
+            EndpointLatency {
+                tunnel,
+                endpoint: endpoint.map(|(host, _)| host),
+                latency_ms,
+            }
+        }));
+    }
+
+    let mut ranked = Vec::new();
+    for ping in pings {
+        if let Ok(result) = ping.await {
+            ranked.push(result);
+        }
+    }
+    sort_by_latency(&mut ranked);
+    ranked
+}
+
+/// Resolve the endpoint (hostnames are common) and ping its first address
+async fn ping_endpoint(host: &str, port: u16, via: Option<String>) -> Option<u32> {
+    let addr = tokio::net::lookup_host((host, port)).await.ok()?.next()?;
+    let ip = addr.ip().to_string();
+    tokio::task::spawn_blocking(move || wireguard::ping_ms(&ip, via.as_deref()))
+        .await
+        .ok()
+        .flatten()
+}
+
+/// Answering endpoints by latency, then the unreachable ones by name
+fn sort_by_latency(ranked: &mut [EndpointLatency]) {
+    ranked.sort_by(|a, b| match (a.latency_ms, b.latency_ms) {
+        (Some(x), Some(y)) => x.cmp(&y),
+        (Some(_), None) => std::cmp::Ordering::Less,
+        (None, Some(_)) => std::cmp::Ordering::Greater,
+        (None, None) => a.tunnel.cmp(&b.tunnel),
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sort_by_latency() {
+        let entry = |tunnel: &str, latency_ms| EndpointLatency {
+            tunnel: tunnel.to_string(),
+            endpoint: None,
+            latency_ms,
+        };
+        let mut ranked = vec![entry("se", None), entry("de", Some(40)), entry("at", None), entry("nl", Some(12))];
+        sort_by_latency(&mut ranked);
+        let order: Vec<&str> = ranked.iter().map(|e| e.tunnel.as_str()).collect();
+        assert_eq!(order, vec!["nl", "de", "at", "se"]);
+    }
+}
@@ -2,22 +2,103 @@ use anyhow::Result;
 
 use super::run_helper;
 
+/// What enabling the kill switch would install, for review before confirming
+pub struct Preview {
+    pub interface: String,
+    pub allowlist: Vec<String>,
+    pub ruleset: String,  // nftables ruleset as the helper would apply it
+}
+
+impl Preview {
+    /// Human-readable list of what stays reachable, taken from the output chain's accept rules
+    pub fn allowed(&self) -> Vec<String> {
+        let mut allowed: Vec<String> = Vec::new();
+        let mut in_output = false;
+
+        for line in self.ruleset.lines().map(str::trim) {
+            if line.starts_with("chain ") {
+                in_output = line.starts_with("chain output");
+                continue;
+            }
+            let Some(rule) = line.strip_suffix(" accept") else { continue };
+            if !in_output || rule.starts_with("ct state") {
+                continue;
+            }
+
+            let words: Vec<&str> = rule.split_whitespace().collect();
+            let entry = match words.as_slice() {
+                ["oif", "lo"] => "loopback".to_string(),
+                ["oif", iface] => format!("interface {}", iface),
+                ["udp", "dport", "67"] | ["udp", "sport", "68"] => "DHCP".to_string(),
+                [_, "dport", "53"] => "DNS (port 53)".to_string(),
+                ["ip" | "ip6", "daddr", addr, "udp", "dport", port] => {
+                    format!("VPN endpoint {}:{}", addr, port)
+                }
+                ["ip" | "ip6", "daddr", cidr] if self.allowlist.iter().any(|a| a == cidr) => {
+                    format!("{} (allowlist)", cidr)
+                }
+                ["ip" | "ip6", "daddr", cidr] => format!("{} (LAN)", cidr),
+                _ => rule.to_string(),
+            };
+            if !allowed.contains(&entry) {
+                allowed.push(entry);
+            }
+        }
+        allowed
+    }
+}
+
+/// Interface the kill switch is built around, plus the user's allowlist
+async fn target() -> Result<(String, Vec<String>)> {
+    // Get the current WireGuard interface
+    let status = super::wireguard::get_tunnels().await?;
+    let interface = status.interface.unwrap_or_else(|| "wg0".to_string());
+
+    // Extra destinations the user wants reachable (LAN printers, NAS, router)
+    let allowlist = crate::config::AppConfig::load()
+        .map(|c| c.killswitch_allowlist)
+        .unwrap_or_default();
+
+    Ok((interface, allowlist))
+}
+
+/// Ask the helper for the ruleset `enable` would apply, without applying it
+pub async fn preview() -> Result<Preview> {
+    let (interface, allowlist) = target().await?;
+
+    let mut args: Vec<&str> = vec!["killswitch-preview", &interface];
+    args.extend(allowlist.iter().map(|s| s.as_str()));
+    let output = run_helper(&args).await?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("Failed to preview kill switch: {}", stderr.trim());
+    }
+
+    Ok(Preview {
+        interface,
+        allowlist,
+        ruleset: String::from_utf8_lossy(&output.stdout).into_owned(),
+    })
+}
+
 /// Enable the kill switch using the secure helper
 /// This blocks all traffic except through the VPN interface
 pub async fn enable() -> Result<()> {
-    // Get the current WireGuard interface
-    let status = super::wireguard::get_status().await?;
-    let interface = status.interface.unwrap_or_else(|| "wg0".to_string());
+    let (interface, allowlist) = target().await?;
 
     // Use the secure helper to enable kill switch
-    let output = run_helper(&["killswitch-on", &interface]).await?;
+    // Form: killswitch-on <iface> [cidr...]
+    let mut args: Vec<&str> = vec!["killswitch-on", &interface];
+    args.extend(allowlist.iter().map(|s| s.as_str()));
+    let output = run_helper(&args).await?;
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
         anyhow::bail!("Failed to enable kill switch: {}", stderr);
     }
 
-    tracing::info!("Kill switch enabled for interface: {}", interface);
+    tracing::info!("Kill switch enabled for interface: {} ({} allowlisted)", interface, allowlist.len());
     Ok(())
 }
 
@@ -72,3 +153,33 @@ pub async fn is_enabled() -> Result<bool> {
     }
     Ok(false)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_preview_allowed() {
+        let preview = Preview {
+            interface: "wg0".to_string(),
+            allowlist: vec!["192.168.1.50/32".to_string()],
+            ruleset: "table inet tonneru_killswitch {\n\
+                      chain input {\n        iif lo accept\n        ip saddr 10.0.0.0/8 accept\n    }\n\
+                      chain output {\n        oif lo accept\n        oif wg0 accept\n\
+                      ct state established,related accept\n        udp dport 67 accept\n\
+                      udp sport 68 accept\n        ip daddr 10.0.0.0/8 accept\n\
+                      ip daddr 192.168.1.50/32 accept\n        udp dport 53 accept\n\
+                      tcp dport 53 accept\n        ip daddr 1.2.3.4 udp dport 51820 accept\n    }\n}\n"
+                .to_string(),
+        };
+        assert_eq!(preview.allowed(), vec![
+            "loopback",
+            "interface wg0",
+            "DHCP",
+            "10.0.0.0/8 (LAN)",
+            "192.168.1.50/32 (allowlist)",
+            "DNS (port 53)",
+            "VPN endpoint 1.2.3.4:51820",
+        ]);
+    }
+}
@@ -0,0 +1,95 @@
+//! Latency history for the connected tunnel
+//!
+//! Summarizes recent ping samples as min/avg/max, jitter and loss, and grades
+//! the connection for latency-sensitive use (calls, games).
+
+/// Summary of a window of ping samples (None = no reply)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LatencyStats {
+    pub min_ms: u32,
+    pub avg_ms: u32,
+    pub max_ms: u32,
+    pub jitter_ms: u32,  // Mean difference between consecutive replies
+    pub loss_percent: u32,
+    pub samples: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Quality {
+    Good,  // Fine for calls and games
+    Fair,  // Browsing is fine, real-time traffic may stutter
+    Poor,
+}
+
+impl Quality {
+    pub fn label(self) -> &'static str {
+        match self {
+            Quality::Good => "Good",
+            Quality::Fair => "Fair",
+            Quality::Poor => "Poor",
+        }
+    }
+}
+
+impl LatencyStats {
+    /// Stats over `samples`, None until at least one reply arrived
+    pub fn from_samples<'a>(samples: impl IntoIterator<Item = &'a Option<u32>>) -> Option<Self> {
+        let samples: Vec<Option<u32>> = samples.into_iter().copied().collect();
+        let replies: Vec<u32> = samples.iter().flatten().copied().collect();
+        if replies.is_empty() {
+            return None;
+        }
+
+        let sum: u64 = replies.iter().map(|&ms| u64::from(ms)).sum();
+        let diffs: Vec<u32> = replies.windows(2).map(|w| w[0].abs_diff(w[1])).collect();
+        let jitter_ms = if diffs.is_empty() {
+            0
+        } else {
+            (diffs.iter().map(|&d| u64::from(d)).sum::<u64>() / diffs.len() as u64) as u32
+        };
+
+        Some(Self {
+            min_ms: *replies.iter().min()?,
+            avg_ms: (sum / replies.len() as u64) as u32,
+            max_ms: *replies.iter().max()?,
+            jitter_ms,
+            loss_percent: ((samples.len() - replies.len()) * 100 / samples.len()) as u32,
+            samples: samples.len(),
+        })
+    }
+
+    /// Rough grade using common VoIP limits (150 ms latency, 30 ms jitter, 1% loss)
+    pub fn quality(&self) -> Quality {
+        if self.avg_ms <= 80 && self.jitter_ms <= 15 && self.loss_percent == 0 {
+            Quality::Good
+        } else if self.avg_ms <= 150 && self.jitter_ms <= 30 && self.loss_percent <= 1 {
+            Quality::Fair
+        } else {
+            Quality::Poor
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_latency_stats() {
+        let samples = [Some(20), Some(30), None, Some(25), Some(45)];
+        let stats = LatencyStats::from_samples(&samples).unwrap();
+        assert_eq!((stats.min_ms, stats.avg_ms, stats.max_ms), (20, 30, 45));
+        // |20-30| + |30-25| + |25-45| = 35 over 3 pairs
+        assert_eq!(stats.jitter_ms, 11);
+        assert_eq!(stats.loss_percent, 20);
+        assert_eq!(stats.quality(), Quality::Poor);
+
+        let steady = LatencyStats::from_samples(&[Some(30), Some(32), Some(31)]).unwrap();
+        assert_eq!(steady.quality(), Quality::Good);
+        // One lost ping in 50 is past the 1% VoIP limit
+        let mut lossy = vec![Some(30); 49];
+        lossy.push(None);
+        assert_eq!(LatencyStats::from_samples(&lossy).unwrap().quality(), Quality::Poor);
+        assert_eq!(LatencyStats::from_samples(&[None, None]), None);
+    }
+}
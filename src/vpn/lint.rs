@@ -0,0 +1,227 @@
+//! Sanity checks for a WireGuard config
+//!
+//! Catches mistakes wg-quick accepts without complaint but that make a tunnel
+//! misbehave: DNS leaking past a full tunnel, idle NAT mappings expiring,
+//! peers fighting over the same AllowedIPs and endpoints nobody can reach.
+
+use std::net::{IpAddr, Ipv4Addr};
+
+use super::wireguard;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Error,    // The tunnel won't work as intended
+    Warning,  // Works, but leaks or breaks in common setups
+    Info,
+}
+
+impl Severity {
+    pub fn label(self) -> &'static str {
+        match self {
+            Severity::Error => "ERROR",
+            Severity::Warning => "WARN",
+            Severity::Info => "INFO",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Finding {
+    pub severity: Severity,
+    pub message: String,
+}
+
+/// Keepalive wg-quick users usually pick for NAT
+const SUGGESTED_KEEPALIVE: u32 = 25;
+
+/// Findings for `config`, most severe first
+/// `local_addrs` are this machine's LAN addresses (private ones mean we're behind NAT)
+pub fn lint(config: &str, local_addrs: &[Ipv4Addr]) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    let mut add = |severity, message: String| findings.push(Finding { severity, message });
+
+    let sections = sections(config);
+    let interface = sections.iter().find(|(name, _)| name == "interface").map(|(_, e)| e.as_slice()).unwrap_or(&[]);
+    let peers: Vec<&[(String, String)]> = sections.iter()
+        .filter(|(name, _)| name == "peer")
+        .map(|(_, e)| e.as_slice())
+        .collect();
+    if peers.is_empty() {
+        add(Severity::Error, "No [Peer] section - there is nothing to connect to".to_string());
+    }
+
+    // Full tunnel without DNS: lookups keep going to the LAN's resolver
+    let allowed = wireguard::allowed_ips(config);
+    let full = allowed.iter().any(|cidr| cidr == "0.0.0.0/0" || cidr == "::/0");
+    if full && values(interface, "dns").next().is_none() {
+        add(Severity::Warning, "Full tunnel without DNS - name lookups bypass the VPN (add DNS = under [Interface])".to_string());
+    }
+
+    let behind_nat = local_addrs.iter().any(|addr| addr.is_private() || is_shared(*addr));
+    for (i, peer) in peers.iter().enumerate() {
+        let label = format!("Peer {}", i + 1);
+        match values(peer, "endpoint").next() {
+            Some(endpoint) => {
+                let host = endpoint.rsplit_once(':').map_or(endpoint, |(host, _)| host);
+                if let Ok(ip) = host.trim_start_matches('[').trim_end_matches(']').parse::<IpAddr>() {
+                    if let Some((severity, why)) = endpoint_problem(ip) {
+                        add(severity, format!("{}: Endpoint {} is {}", label, ip, why));
+                    }
+                }
+                let keepalive = values(peer, "persistentkeepalive").next()
+                    .and_then(|v| v.parse::<u32>().ok())
+                    .unwrap_or(0);
+                if behind_nat && keepalive == 0 {
+                    add(Severity::Warning, format!(
+                        "{}: no PersistentKeepalive - behind NAT the tunnel stops receiving when idle (try {})",
+                        label, SUGGESTED_KEEPALIVE
+                    ));
+                }
+            }
+            None => add(Severity::Info, format!("{}: no Endpoint - it has to contact you first", label)),
+        }
+    }
+
+    // The same range on two peers: WireGuard routes it to only one of them
+    // Nested ranges are fine, the longest prefix wins
+    let peer_ranges: Vec<Vec<(String, (IpAddr, u8))>> = peers.iter()
+        .map(|peer| values(peer, "allowedips")
+            .flat_map(|v| v.split(','))
+            .map(str::trim)
+            .filter_map(|cidr| Some((cidr.to_string(), parse_cidr(cidr)?)))
+            .collect())
+        .collect();
+    for (i, ranges) in peer_ranges.iter().enumerate() {
+        for (j, others) in peer_ranges.iter().enumerate().skip(i + 1) {
+            for (cidr, net) in ranges {
+                for (other, o) in others.iter().filter(|(_, o)| overlaps(*net, *o)) {
+                    if net.1 == o.1 {
+                        add(Severity::Error, format!(
+                            "AllowedIPs {} (peer {}) is also on peer {} - only one peer gets that traffic",
+                            cidr, i + 1, j + 1
+                        ));
+                    } else {
+                        let (inner, outer) = if net.1 > o.1 { ((cidr, i), (other, j)) } else { ((other, j), (cidr, i)) };
+                        add(Severity::Info, format!(
+                            "AllowedIPs {} (peer {}) is inside {} (peer {}) - peer {} gets that part",
+                            inner.0, inner.1 + 1, outer.0, outer.1 + 1, inner.1 + 1
+                        ));
+                    }
+                }
+            }
+        }
+    }
+
+    // Address and AllowedIPs clashing with the LAN this machine is on
+    for address in values(interface, "address").flat_map(|v| v.split(',')).map(str::trim) {
+        let Some(net) = parse_cidr(address) else {
+            continue;
+        };
+        if let Some(local) = local_addrs.iter().find(|a| overlaps(net, (IpAddr::V4(**a), 32))) {
+            add(Severity::Warning, format!("Address {} overlaps your LAN address {}", address, local));
+        }
+    }
+    for warning in wireguard::lan_overlap_warnings(config, local_addrs) {
+        add(Severity::Warning, warning);
+    }
+
+    findings.sort_by_key(|f| f.severity);
+    findings
+}
+
+/// (lowercased section name, (lowercased key, value) entries) in file order
+fn sections(config: &str) -> Vec<(String, Vec<(String, String)>)> {
+    let mut sections: Vec<(String, Vec<(String, String)>)> = Vec::new();
+    for line in config.lines().map(str::trim) {
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            sections.push((name.trim().to_ascii_lowercase(), Vec::new()));
+        } else if let (Some((_, entries)), Some((key, value))) = (sections.last_mut(), line.split_once('=')) {
+            if !line.starts_with('#') {
+                entries.push((key.trim().to_ascii_lowercase(), value.trim().to_string()));
+            }
+        }
+    }
+    sections
+}
+
+fn values<'a>(entries: &'a [(String, String)], key: &'a str) -> impl Iterator<Item = &'a str> {
+    entries.iter().filter(move |(k, _)| k == key).map(|(_, v)| v.as_str())
+}
+
+/// Why an endpoint IP can't be reached from the internet, if it can't
+fn endpoint_problem(ip: IpAddr) -> Option<(Severity, &'static str)> {
+    if ip.is_loopback() || ip.is_unspecified() || ip.is_multicast() {
+        return Some((Severity::Error, "not a reachable server address"));
+    }
+    let local = match ip {
+        IpAddr::V4(v4) => v4.is_private() || v4.is_link_local() || is_shared(v4),
+        // Unique local (fc00::/7) and link-local (fe80::/10)
+        IpAddr::V6(v6) => (v6.segments()[0] & 0xfe00) == 0xfc00 || (v6.segments()[0] & 0xffc0) == 0xfe80,
+    };
+    local.then_some((Severity::Warning, "a private address - only reachable from inside that network"))
+}
+
+/// 100.64.0.0/10, carrier-grade NAT
+fn is_shared(ip: Ipv4Addr) -> bool {
+    let [a, b, ..] = ip.octets();
+    a == 100 && (64..128).contains(&b)
+}
+
+/// "10.0.0.0/8" or a bare address (a single host)
+fn parse_cidr(cidr: &str) -> Option<(IpAddr, u8)> {
+    let (addr, prefix) = match cidr.split_once('/') {
+        Some((addr, prefix)) => (addr.parse::<IpAddr>().ok()?, prefix.parse().ok()?),
+        None => {
+            let addr = cidr.parse::<IpAddr>().ok()?;
+            (addr, if addr.is_ipv4() { 32 } else { 128 })
+        }
+    };
+    let max = if addr.is_ipv4() { 32 } else { 128 };
+    (prefix <= max).then_some((addr, prefix))
+}
+
+/// Whether two ranges share any address
+fn overlaps((a, a_len): (IpAddr, u8), (b, b_len): (IpAddr, u8)) -> bool {
+    let (a, b, bits) = match (a, b) {
+        (IpAddr::V4(a), IpAddr::V4(b)) => (u128::from(u32::from(a)), u128::from(u32::from(b)), 32),
+        (IpAddr::V6(a), IpAddr::V6(b)) => (u128::from(a), u128::from(b), 128),
+        _ => return false,
+    };
+    // The shorter prefix decides; a /0 covers everything
+    let prefix = u32::from(a_len.min(b_len));
+    if prefix == 0 {
+        return true;
+    }
+    let shift = bits - prefix;
+    (a >> shift) == (b >> shift)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lint() {
+        let lan = [Ipv4Addr::new(192, 168, 1, 23)];
+        let clean = "[Interface]\nAddress = 10.0.0.2/32\nDNS = 10.0.0.1\n\n[Peer]\nEndpoint = 203.0.113.5:51820\nAllowedIPs = 0.0.0.0/0\nPersistentKeepalive = 25\n";
+        assert!(lint(clean, &lan).is_empty());
+
+        let broken = "[Interface]\nAddress = 192.168.1.50/24\n\n[Peer]\nEndpoint = 10.1.2.3:51820\nAllowedIPs = 0.0.0.0/0\n\n[Peer]\nAllowedIPs = 10.0.0.0/24\n\n[Peer]\nAllowedIPs = 10.0.0.0/24\n";
+        let findings = lint(broken, &lan);
+        let messages: Vec<&str> = findings.iter().map(|f| f.message.as_str()).collect();
+        assert_eq!(findings[0].severity, Severity::Error);
+        assert_eq!(messages[0], "AllowedIPs 10.0.0.0/24 (peer 2) is also on peer 3 - only one peer gets that traffic");
+        assert_eq!(findings.iter().filter(|f| f.severity == Severity::Error).count(), 1);
+        // Nesting is how split routing works, not a mistake
+        assert!(findings.iter().any(|f| f.severity == Severity::Info
+            && f.message.starts_with("AllowedIPs 10.0.0.0/24 (peer 2) is inside 0.0.0.0/0 (peer 1)")));
+        assert!(messages.iter().any(|m| m.starts_with("Full tunnel without DNS")));
+        assert!(messages.iter().any(|m| m.contains("Endpoint 10.1.2.3 is a private address")));
+        assert!(messages.iter().any(|m| m.contains("no PersistentKeepalive")));
+        assert!(messages.iter().any(|m| m.starts_with("Address 192.168.1.50/24 overlaps")));
+        assert_eq!(findings.last().unwrap().severity, Severity::Info);
+
+        // Nothing to keep alive for on a public address
+        assert!(lint(clean.replace("PersistentKeepalive = 25\n", "").as_str(), &[Ipv4Addr::new(203, 0, 113, 9)]).is_empty());
+    }
+}
@@ -1,4 +1,11 @@
+pub mod export;
+pub mod fastest;
 pub mod killswitch;
+pub mod latency;
+pub mod lint;
+pub mod mtu;
+pub mod nm;
+pub mod quota;
 pub mod wireguard;
 
 use anyhow::{Context, Result};
@@ -9,12 +16,33 @@ use tokio::time::timeout;
 /// Timeout for privileged operations
 pub const SUDO_TIMEOUT: Duration = Duration::from_secs(5);
 
+/// Longest a tunnel's on_connect/on_disconnect command may run
+pub const HOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
 /// Path to the secure helper script
-const HELPER_PATH: &str = "/usr/lib/tonneru/tonneru-sudo";
+pub const HELPER_PATH: &str = "/usr/lib/tonneru/tonneru-sudo";
+
+/// Check that the helper script is installed and executable
+pub fn helper_installed() -> bool {
+    use std::os::unix::fs::PermissionsExt;
+
+    std::fs::metadata(HELPER_PATH)
+        .map(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+/// Fail early with a clear message instead of a confusing sudo error
+fn ensure_helper() -> Result<()> {
+    if !helper_installed() {
+        anyhow::bail!("Helper not installed: {} is missing or not executable", HELPER_PATH);
+    }
+    Ok(())
+}
 
 /// Run the tonneru-sudo helper with the given command and arguments
 /// This is the single entry point for all privileged operations
 pub async fn run_helper(args: &[&str]) -> Result<std::process::Output> {
+    ensure_helper()?;
     let args: Vec<String> = args.iter().map(|s| s.to_string()).collect();
     
     let result = timeout(SUDO_TIMEOUT, tokio::task::spawn_blocking(move || {
@@ -27,12 +55,13 @@ pub async fn run_helper(args: &[&str]) -> Result<std::process::Output> {
     match result {
         Ok(Ok(output)) => output.context("Helper execution failed"),
         Ok(Err(e)) => anyhow::bail!("Task failed: {}", e),
-        Err(_) => anyhow::bail!("Command timed out (sudo may need password or user not in tonneru group)"),
+        Err(_) => anyhow::bail!("Helper timed out after {}s (sudo may need password or user not in tonneru group)", SUDO_TIMEOUT.as_secs()),
     }
 }
 
 /// Run the tonneru-sudo helper with stdin input
 pub async fn run_helper_with_stdin(args: &[&str], stdin_data: &str) -> Result<std::process::Output> {
+    ensure_helper()?;
     let args: Vec<String> = args.iter().map(|s| s.to_string()).collect();
     let stdin_data = stdin_data.to_string();
     
@@ -57,7 +86,36 @@ pub async fn run_helper_with_stdin(args: &[&str], stdin_data: &str) -> Result<st
     match result {
         Ok(Ok(output)) => output.context("Helper execution failed"),
         Ok(Err(e)) => anyhow::bail!("Task failed: {}", e),
-        Err(_) => anyhow::bail!("Command timed out (sudo may need password or user not in tonneru group)"),
+        Err(_) => anyhow::bail!("Helper timed out after {}s (sudo may need password or user not in tonneru group)", SUDO_TIMEOUT.as_secs()),
+    }
+}
+
+/// Run a tunnel hook through `sh -c` as the current user - never through the helper
+/// TONNERU_TUNNEL and TONNERU_EVENT ("connect" or "disconnect") describe what happened
+pub async fn run_hook(command: &str, tunnel: &str, event: &str) -> Result<()> {
+    let output = tokio::process::Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .env("TONNERU_TUNNEL", tunnel)
+        .env("TONNERU_EVENT", event)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .output();
+
+    match timeout(HOOK_TIMEOUT, output).await {
+        Ok(Ok(output)) if output.status.success() => Ok(()),
+        Ok(Ok(output)) => {
+            // The last stderr line usually says what went wrong
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            match stderr.lines().rev().find(|l| !l.trim().is_empty()) {
+                Some(line) => anyhow::bail!("{} ({})", line.trim(), output.status),
+                None => anyhow::bail!("{}", output.status),
+            }
+        }
+        Ok(Err(e)) => Err(e).context("Failed to run hook"),
+        Err(_) => anyhow::bail!("timed out after {}s", HOOK_TIMEOUT.as_secs()),
     }
 }
 
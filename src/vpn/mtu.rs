@@ -0,0 +1,144 @@
+//! Path MTU discovery for tunnels
+//!
+//! Binary-searches the largest ping that reaches the peer's endpoint with
+//! fragmentation disabled, then subtracts WireGuard's encapsulation overhead
+//! to get the MTU the tunnel interface should use.
+
+use anyhow::Result;
+use std::net::SocketAddr;
+use std::process::Command;
+
+/// Outer IP + UDP + WireGuard header overhead, per endpoint family
+const WG_OVERHEAD_V4: u32 = 60;
+const WG_OVERHEAD_V6: u32 = 80;
+
+/// IP + ICMP header bytes that ping's payload size doesn't include
+const ICMP_HEADERS_V4: u32 = 28;
+const ICMP_HEADERS_V6: u32 = 48;
+
+/// Smallest ping payload tried and the largest packet (Ethernet MTU)
+const MIN_PAYLOAD: u32 = 548;
+const MAX_PACKET: u32 = 1500;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MtuProbe {
+    pub path_mtu: u32,     // Largest unfragmented packet to the endpoint
+    pub recommended: u32,  // Tunnel MTU that fits inside it
+}
+
+/// Find the path MTU to a peer endpoint ("1.2.3.4:51820" or "[2001:db8::1]:51820")
+/// `via` pins the pings to the physical interface so they don't go through the tunnel
+/// Blocking - runs up to ten one-second pings
+pub fn probe(endpoint: &str, via: Option<&str>) -> Result<MtuProbe> {
+    let addr: SocketAddr = endpoint.parse()
+        .map_err(|_| anyhow::anyhow!("Unexpected endpoint address: {}", endpoint))?;
+    let ip = addr.ip().to_string();
+    let (headers, overhead) = if addr.is_ipv4() {
+        (ICMP_HEADERS_V4, WG_OVERHEAD_V4)
+    } else {
+        (ICMP_HEADERS_V6, WG_OVERHEAD_V6)
+    };
+
+    let fits = |payload: u32| {
+        let mut cmd = Command::new("ping");
+        cmd.args(["-c", "1", "-W", "1", "-M", "do", "-s", &payload.to_string()]);
+        if let Some(iface) = via {
+            cmd.args(["-I", iface]);
+        }
+        cmd.arg(&ip)
+            .output()
+            .is_ok_and(|o| o.status.success())
+    };
+
+    if !fits(MIN_PAYLOAD) {
+        anyhow::bail!("{} doesn't answer ping - cannot probe the MTU", ip);
+    }
+
+    let largest = largest_passing(MIN_PAYLOAD, MAX_PACKET - headers, fits);
+    let path_mtu = largest + headers;
+    Ok(MtuProbe {
+        path_mtu,
+        recommended: path_mtu.saturating_sub(overhead),
+    })
+}
+
+/// Binary search for the largest size in `low..=high` that passes; `low` must pass
+fn largest_passing(mut low: u32, mut high: u32, mut passes: impl FnMut(u32) -> bool) -> u32 {
+    while low < high {
+        let mid = low + (high - low).div_ceil(2);
+        if passes(mid) {
+            low = mid;
+        } else {
+            high = mid - 1;
+        }
+    }
+    low
+}
+
+/// The `MTU =` value from a config's [Interface] section
+pub fn config_mtu(config: &str) -> Option<u32> {
+    let mut in_interface = false;
+    for line in config.lines().map(str::trim) {
+        if line.starts_with('[') {
+            in_interface = line.eq_ignore_ascii_case("[Interface]");
+        } else if let Some((key, value)) = line.split_once('=') {
+            if in_interface && key.trim().eq_ignore_ascii_case("MTU") {
+                return value.trim().parse().ok();
+            }
+        }
+    }
+    None
+}
+
+/// Set `MTU = <mtu>` in the [Interface] section, replacing an existing value
+pub fn set_config_mtu(config: &str, mtu: u32) -> String {
+    let mut lines: Vec<String> = Vec::new();
+    let mut in_interface = false;
+    let mut done = false;
+
+    for line in config.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with('[') {
+            // Leaving [Interface] without an MTU line - add one at its end
+            if in_interface && !done {
+                let at = lines.iter().rposition(|l| !l.trim().is_empty()).map_or(0, |i| i + 1);
+                lines.insert(at, format!("MTU = {}", mtu));
+                done = true;
+            }
+            in_interface = trimmed.eq_ignore_ascii_case("[Interface]");
+        } else if in_interface
+            && trimmed.split_once('=').is_some_and(|(key, _)| key.trim().eq_ignore_ascii_case("MTU"))
+        {
+            lines.push(format!("MTU = {}", mtu));
+            done = true;
+            continue;
+        }
+        lines.push(line.to_string());
+    }
+    if in_interface && !done {
+        lines.push(format!("MTU = {}", mtu));
+    }
+
+    let mut out = lines.join("\n");
+    out.push('\n');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mtu_search_and_config() {
+        // Path MTU 1420 -> 1392 byte payload passes, 1393 doesn't
+        assert_eq!(largest_passing(MIN_PAYLOAD, 1472, |size| size <= 1392), 1392);
+        assert_eq!(largest_passing(MIN_PAYLOAD, 1472, |_| true), 1472);
+
+        let config = "[Interface]\nPrivateKey = x\nAddress = 10.0.0.2/32\n\n[Peer]\nPublicKey = y\n";
+        let updated = set_config_mtu(config, 1380);
+        assert_eq!(updated, "[Interface]\nPrivateKey = x\nAddress = 10.0.0.2/32\nMTU = 1380\n\n[Peer]\nPublicKey = y\n");
+        assert_eq!(config_mtu(&updated), Some(1380));
+        assert_eq!(config_mtu(&set_config_mtu(&updated, 1280)), Some(1280));
+        assert_eq!(config_mtu(config), None);
+    }
+}
@@ -0,0 +1,117 @@
+//! NetworkManager VPN connections
+//!
+//! Some setups manage WireGuard (or OpenVPN) through NetworkManager instead
+//! of wg-quick configs in /etc/wireguard. Those connections are listed
+//! alongside the wg-quick tunnels and brought up/down with nmcli, so the two
+//! don't compete over the same tunnel.
+
+use anyhow::{Context, Result};
+use tokio::process::Command;
+
+/// A VPN connection NetworkManager knows about
+#[derive(Debug, Clone, PartialEq)]
+pub struct NmConnection {
+    pub name: String,
+    pub protocol: String,  // "wireguard" or "vpn" (OpenVPN and other plugins)
+    pub active: bool,
+    pub device: Option<String>,  // Interface of an active WireGuard connection (may differ from the name)
+}
+
+/// VPN connections from `nmcli connection show` (empty without NetworkManager)
+pub async fn list_connections() -> Vec<NmConnection> {
+    let Ok(output) = Command::new("nmcli")
+        .args(["-t", "-f", "NAME,TYPE,STATE,DEVICE", "connection", "show"])
+        .output()
+        .await
+    else {
+        return Vec::new();
+    };
+    if !output.status.success() {
+        return Vec::new();
+    }
+    parse_connections(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Parse terse `NAME:TYPE:STATE:DEVICE` lines, keeping VPN and WireGuard connections
+fn parse_connections(output: &str) -> Vec<NmConnection> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let fields = crate::network::split_terse(line);
+            let [name, kind, state, rest @ ..] = fields.as_slice() else {
+                return None;
+            };
+            // Plugin VPNs report their parent device (e.g. wlan0), not a tunnel interface
+            let device = rest.first()
+                .filter(|d| !d.is_empty() && kind == "wireguard")
+                .cloned();
+            let protocol = match kind.as_str() {
+                "wireguard" => "wireguard",
+                "vpn" => "vpn",
+                _ => return None,
+            };
+            Some(NmConnection {
+                name: name.clone(),
+                protocol: protocol.to_string(),
+                active: state == "activated",
+                device,
+            })
+        })
+        .collect()
+}
+
+/// Look up one NetworkManager VPN connection by name
+pub async fn find_connection(name: &str) -> Option<NmConnection> {
+    list_connections().await.into_iter().find(|c| c.name == name)
+}
+
+/// `nmcli connection up id <name>`
+pub async fn up(name: &str) -> Result<()> {
+    run_nmcli(&["connection", "up", "id", name]).await
+        .with_context(|| format!("Failed to connect {}", name))
+}
+
+/// `nmcli connection down id <name>`
+pub async fn down(name: &str) -> Result<()> {
+    run_nmcli(&["connection", "down", "id", name]).await
+        .with_context(|| format!("Failed to disconnect {}", name))
+}
+
+/// `nmcli connection delete id <name>`
+pub async fn delete(name: &str) -> Result<()> {
+    run_nmcli(&["connection", "delete", "id", name]).await
+        .with_context(|| format!("Failed to delete {}", name))
+}
+
+async fn run_nmcli(args: &[&str]) -> Result<()> {
+    let output = Command::new("nmcli")
+        .args(args)
+        .output()
+        .await
+        .context("nmcli not available")?;
+
+    if !output.status.success() {
+        anyhow::bail!("{}", String::from_utf8_lossy(&output.stderr).trim());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_connections() {
+        let output = "Home WiFi:802-11-wireless:activated\n\
+                      office:wireguard:activated:wg0\n\
+                      client\\:eu:vpn:\n\
+                      lo:loopback:activated\n";
+        assert_eq!(
+            parse_connections(output),
+            vec![
+                NmConnection { name: "office".into(), protocol: "wireguard".into(), active: true, device: Some("wg0".into()) },
+                NmConnection { name: "client:eu".into(), protocol: "vpn".into(), active: false, device: None },
+            ]
+        );
+    }
+}
@@ -0,0 +1,85 @@
+//! Monthly data quotas per tunnel
+//!
+//! Traffic (received + sent) is counted per period. A period starts on the
+//! tunnel's `quota_reset_day` each month, local time, and is named after the
+//! month it starts in ("2024-03").
+
+use crate::config::history::civil_date;
+use crate::config::TunnelInfo;
+
+/// Share of the quota that triggers the first warning
+pub const WARN_PERCENT: u8 = 80;
+
+/// Reset days are capped so every month has one
+pub const MAX_RESET_DAY: u8 = 28;
+
+/// Period that Unix time `now` falls in, `offset` seconds east of UTC
+pub fn current_period(now: u64, offset: i64, reset_day: u8) -> String {
+    let (year, month, day) = civil_date((now as i64 + offset).div_euclid(86_400));
+    let reset_day = i64::from(reset_day.clamp(1, MAX_RESET_DAY));
+    // Before the reset day we're still in the period that began last month
+    let (year, month) = match (day < reset_day, month) {
+        (true, 1) => (year - 1, 12),
+        (true, m) => (year, m - 1),
+        (false, m) => (year, m),
+    };
+    format!("{:04}-{:02}", year, month)
+}
+
+/// Start a new period for `tunnel` if the current one is over
+/// Returns true when a previous period ended (not on the first one)
+pub fn roll_period(tunnel: &mut TunnelInfo, now: u64, offset: i64) -> bool {
+    let period = current_period(now, offset, tunnel.quota_reset_day.unwrap_or(1));
+    if tunnel.usage_period.as_deref() == Some(period.as_str()) {
+        return false;
+    }
+    let ended = tunnel.usage_period.is_some();
+    tunnel.usage_period = Some(period);
+    tunnel.period_bytes = 0;
+    tunnel.quota_alerted = 0;
+    ended
+}
+
+/// Bytes in a quota given in GiB
+pub fn quota_bytes(gib: f64) -> u64 {
+    (gib.max(0.0) * 1024.0 * 1024.0 * 1024.0) as u64
+}
+
+/// Highest alert level reached: 0, `WARN_PERCENT` or 100
+pub fn alert_level(used: u64, quota: u64) -> u8 {
+    if quota == 0 {
+        return 0;
+    }
+    let percent = used as f64 * 100.0 / quota as f64;
+    if percent >= 100.0 {
+        100
+    } else if percent >= f64::from(WARN_PERCENT) {
+        WARN_PERCENT
+    } else {
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_current_period() {
+        // 2024-03-01 00:00 UTC
+        let march_first = 1_709_251_200;
+        assert_eq!(current_period(march_first, 0, 1), "2024-03");
+        // Still February's period until the 15th; an hour west of UTC it's still Feb 29
+        assert_eq!(current_period(march_first, 0, 15), "2024-02");
+        assert_eq!(current_period(march_first, -3600, 1), "2024-02");
+        assert_eq!(current_period(march_first + 14 * 86_400, 0, 15), "2024-03");
+        // January before the reset day belongs to last year's December
+        assert_eq!(current_period(1_704_067_200, 0, 5), "2023-12");
+
+        let quota = quota_bytes(10.0);
+        assert_eq!(alert_level(quota / 2, quota), 0);
+        assert_eq!(alert_level(quota * 4 / 5, quota), WARN_PERCENT);
+        assert_eq!(alert_level(quota, quota), 100);
+        assert_eq!(alert_level(1, 0), 0);
+    }
+}
@@ -1,15 +1,37 @@
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::process::Command;
 
+use crate::config::{AppConfig, ConnectBackend, DEFAULT_HANDSHAKE_STALE_SECS};
+
 use super::run_helper;
 use super::run_helper_with_stdin;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WgProfile {
     pub name: String,
-    pub protocol: String,  // "wireguard"
+    pub protocol: String,  // "wireguard", or "vpn" for NetworkManager plugin VPNs
     pub connected: bool,
+    #[serde(default)]
+    pub source: ProfileSource,
+    #[serde(default)]
+    pub interface: Option<String>,  // Kernel interface when it differs from the name (NetworkManager)
+}
+
+impl WgProfile {
+    /// Interface the tunnel runs on - wg-quick always names it after the config file
+    pub fn interface(&self) -> &str {
+        self.interface.as_deref().unwrap_or(&self.name)
+    }
+}
+
+/// Who manages a tunnel - decides how it is brought up, down and deleted
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum ProfileSource {
+    #[default]
+    WgQuick,         // /etc/wireguard/<name>.conf via the helper
+    NetworkManager,  // nmcli connection up/down
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -18,11 +40,65 @@ pub struct WgStatus {
     pub interface: Option<String>,
     pub endpoint: Option<String>,
     pub latest_handshake: Option<String>,
+    #[serde(default)]
+    pub handshake_epoch: Option<u64>,  // Unix time of the newest handshake, for a live age
     pub transfer_rx: Option<String>,
     pub transfer_tx: Option<String>,
-    pub handshake_stale: bool,       // True if handshake is too old (>3 min)
+    pub handshake_stale: bool,       // True if handshake is older than handshake_stale_secs
     pub has_traffic: bool,           // True if there's been any data transfer
     pub routing_ok: bool,            // True if default route goes through VPN
+    #[serde(default)]
+    pub additional: Vec<WgStatus>,   // Other tunnels up at the same time (e.g. split tunnels)
+    #[serde(default)]
+    pub peers: Vec<PeerStatus>,      // Every peer block; the fields above summarize them
+    #[serde(default)]
+    pub managed_by: Option<String>,  // Tool that brought the interface up (MANAGER_*), None = not checked
+}
+
+/// Values of `WgStatus::managed_by`
+pub const MANAGER_NM: &str = "NetworkManager";
+pub const MANAGER_SYSTEMD: &str = "systemd";
+pub const MANAGER_WG_QUICK: &str = "wg-quick";
+pub const MANAGER_UNKNOWN: &str = "unknown";  // Up, but not from a config, unit or NM connection
+
+/// Whether `managed_by` is a tool tonneru wouldn't use to take down a tunnel of this source
+/// (e.g. NetworkManager running an interface tonneru knows as a wg-quick config)
+pub fn is_foreign_manager(source: ProfileSource, managed_by: &str) -> bool {
+    match source {
+        ProfileSource::WgQuick => managed_by != MANAGER_WG_QUICK && managed_by != MANAGER_SYSTEMD,
+        ProfileSource::NetworkManager => managed_by != MANAGER_NM,
+    }
+}
+
+/// One `peer:` block of `wg show` output
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PeerStatus {
+    pub public_key: String,
+    pub endpoint: Option<String>,
+    pub allowed_ips: Option<String>,
+    pub latest_handshake: Option<String>,
+    pub transfer_rx: Option<String>,
+    pub transfer_tx: Option<String>,
+    pub handshake_stale: bool,
+}
+
+impl WgStatus {
+    /// The primary interface followed by any additional ones
+    pub fn all_interfaces(&self) -> impl Iterator<Item = &WgStatus> {
+        std::iter::once(self)
+            .filter(|s| s.connected)
+            .chain(self.additional.iter())
+    }
+
+    /// Status for a specific interface, if it is up
+    pub fn interface_status(&self, name: &str) -> Option<&WgStatus> {
+        self.all_interfaces().find(|s| s.interface.as_deref() == Some(name))
+    }
+
+    /// Whether the given interface is up (primary or additional)
+    pub fn is_up(&self, name: &str) -> bool {
+        self.interface_status(name).is_some()
+    }
 }
 
 /// List all available WireGuard profiles
@@ -32,25 +108,28 @@ pub async fn list_profiles() -> Result<Vec<WgProfile>> {
     let mut valid_configs = std::collections::HashSet::new();
     let mut could_read_config_dir = false;
 
-    // Get current connection status
-    let status = get_status().await.unwrap_or_default();
-    let active_interface = status.interface.clone();
+    // Get current connection status (any number of tunnels may be up)
+    let status = get_tunnels().await.unwrap_or_default();
 
-    // Get list of config files using helper
-    if let Ok(output) = run_helper(&["config-list"]).await {
-        if output.status.success() {
-            could_read_config_dir = true;
-            let stdout = String::from_utf8_lossy(&output.stdout);
-            for line in stdout.lines() {
-                let name = line.trim();
-                if !name.is_empty() {
-                    valid_configs.insert(name.to_string());
-                }
-            }
-        }
+    // Get list of config files (helper, or the user's config_dir)
+    if let Some(names) = config_names().await {
+        could_read_config_dir = true;
+        valid_configs.extend(names);
     }
 
+    // NetworkManager VPNs - a wg-quick config of the same name takes precedence
+    let nm_connections: Vec<super::nm::NmConnection> = super::nm::list_connections().await
+        .into_iter()
+        .filter(|c| !valid_configs.contains(&c.name))
+        .collect();
+    let nm_active = |name: &str| nm_connections.iter().find(|c| c.name == name).map(|c| c.active);
+    let nm_interface = |name: &str| nm_connections.iter()
+        .find(|c| c.name == name)
+        .and_then(|c| c.device.clone())
+        .filter(|device| device != name);
+
     // Also check for active interfaces (even if no .conf file - might be manually configured)
+    // Interfaces of NetworkManager connections are listed under the connection instead
     let output = Command::new("ip")
         .args(["link", "show", "type", "wireguard"])
         .output();
@@ -61,7 +140,8 @@ pub async fn list_profiles() -> Result<Vec<WgProfile>> {
             for line in stdout.lines() {
                 if let Some(name) = line.split(':').nth(1) {
                     let name = name.trim().split('@').next().unwrap_or("").to_string();
-                    if !name.is_empty() {
+                    let nm_owned = nm_connections.iter().any(|c| c.device.as_deref() == Some(name.as_str()));
+                    if !name.is_empty() && !nm_owned {
                         valid_configs.insert(name);
                     }
                 }
@@ -70,14 +150,15 @@ pub async fn list_profiles() -> Result<Vec<WgProfile>> {
     }
 
     // Load our config
-    if let Ok(mut config) = crate::config::AppConfig::load() {
+    let mut config = crate::config::AppConfig::load().ok();
+    if let Some(config) = config.as_mut() {
         // Only clean up orphaned entries if we could actually read the config directory
         if could_read_config_dir {
             let original_len = config.known_tunnels.len();
             
             // Only keep tunnels that have valid configs
             config.known_tunnels.retain(|t| {
-                t.protocol != "wireguard" || valid_configs.contains(&t.name)
+                t.protocol != "wireguard" || valid_configs.contains(&t.name) || nm_active(&t.name).is_some()
             });
             
             if config.known_tunnels.len() != original_len {
@@ -88,11 +169,16 @@ pub async fn list_profiles() -> Result<Vec<WgProfile>> {
         // Add profiles from our config
         for tunnel in &config.known_tunnels {
             if tunnel.protocol == "wireguard" && !seen_names.contains(&tunnel.name) {
-                let connected = active_interface.as_ref() == Some(&tunnel.name);
+                let (connected, source) = match nm_active(&tunnel.name) {
+                    Some(active) => (active, ProfileSource::NetworkManager),
+                    None => (status.is_up(&tunnel.name), ProfileSource::WgQuick),
+                };
                 profiles.push(WgProfile {
                     name: tunnel.name.clone(),
                     protocol: "wireguard".to_string(),
                     connected,
+                    source,
+                    interface: nm_interface(&tunnel.name),
                 });
                 seen_names.insert(tunnel.name.clone());
             }
@@ -102,28 +188,140 @@ pub async fn list_profiles() -> Result<Vec<WgProfile>> {
     // Add any configs that aren't in our known_tunnels
     for name in &valid_configs {
         if !seen_names.contains(name) {
-            let connected = active_interface.as_ref().map(|s| s.as_str()) == Some(name.as_str());
+            let connected = status.is_up(name);
             profiles.push(WgProfile {
                 name: name.clone(),
                 protocol: "wireguard".to_string(),
                 connected,
+                source: ProfileSource::WgQuick,
+                interface: None,
             });
             seen_names.insert(name.clone());
         }
     }
 
-    profiles.sort_by(|a, b| a.name.cmp(&b.name));
+    for conn in &nm_connections {
+        if !seen_names.contains(&conn.name) {
+            profiles.push(WgProfile {
+                name: conn.name.clone(),
+                protocol: conn.protocol.clone(),
+                connected: conn.active,
+                source: ProfileSource::NetworkManager,
+                interface: nm_interface(&conn.name),
+            });
+            seen_names.insert(conn.name.clone());
+        }
+    }
+
+    sort_profiles(&mut profiles, &config.unwrap_or_default());
     Ok(profiles)
 }
 
-/// Get current WireGuard connection status
-pub async fn get_status() -> Result<WgStatus> {
+/// Tunnels with a manual `order` first (lowest first), the rest by name
+pub fn sort_profiles(profiles: &mut [WgProfile], config: &crate::config::AppConfig) {
+    profiles.sort_by_cached_key(|p| (config.tunnel_order(&p.name).unwrap_or(u32::MAX), p.name.clone()));
+}
+
+/// Resolve a tunnel name typed on the command line against `names`
+/// An exact name always wins, then a unique case-insensitive prefix, substring
+/// or in-order match ("wow" for "work-office-wg"); ties list the candidates
+pub fn resolve_profile_name(query: &str, names: &[String]) -> Result<String> {
+    if let Some(name) = names.iter().find(|n| *n == query) {
+        return Ok(name.clone());
+    }
+
+    let lower = query.to_lowercase();
+    let in_order = |name: &str| {
+        let mut chars = name.chars();
+        lower.chars().all(|q| chars.any(|c| c == q))
+    };
+    let tiers: [&dyn Fn(&str) -> bool; 4] = [
+        &|name| name == lower,
+        &|name| name.starts_with(&lower),
+        &|name| name.contains(&lower),
+        &in_order,
+    ];
+    for matches in tiers {
+        let candidates: Vec<&String> = names.iter().filter(|n| matches(&n.to_lowercase())).collect();
+        match candidates.as_slice() {
+            [] => continue,
+            [name] => return Ok((*name).clone()),
+            _ => anyhow::bail!(
+                "'{}' matches several tunnels: {}",
+                query,
+                candidates.iter().map(|n| n.as_str()).collect::<Vec<_>>().join(", ")
+            ),
+        }
+    }
+    anyhow::bail!("No tunnel matches '{}' (available: {})", query, names.join(", "))
+}
+
+/// Get current WireGuard connection status (`managed_by` unset, see `detect_managers`)
+/// Handshakes older than the config's `handshake_stale_secs` count as stale
+pub async fn get_status(config: &AppConfig) -> Result<WgStatus> {
+    read_status(config.handshake_stale_secs).await
+}
+
+/// `get_status` for callers that only need which tunnels are up and their counters
+/// Staleness uses the default threshold, so config.toml isn't read
+pub async fn get_tunnels() -> Result<WgStatus> {
+    read_status(DEFAULT_HANDSHAKE_STALE_SECS).await
+}
+
+/// Fill in who manages each interface in `status`, reusing what `known` (an earlier
+/// status) found for interfaces that stayed up; only new interfaces cost lookups
+pub async fn detect_managers(status: &mut WgStatus, known: &WgStatus) -> Result<()> {
+    let carried = |iface: &str| known.interface_status(iface).and_then(|k| k.managed_by.clone());
+    let new = status.all_interfaces()
+        .filter_map(|s| s.interface.as_deref())
+        .any(|iface| carried(iface).is_none());
+    let (nm, configs) = if new {
+        let configs = config_names().await
+            .context("Can't list tunnel configs to tell who manages the interface")?;
+        (super::nm::list_connections().await, configs)
+    } else {
+        Default::default()
+    };
+
+    let manager_of = |iface: &str| carried(iface).unwrap_or_else(|| interface_manager(iface, &nm, &configs));
+    if status.connected {
+        status.managed_by = status.interface.as_deref().map(manager_of);
+    }
+    for other in &mut status.additional {
+        other.managed_by = other.interface.as_deref().map(manager_of);
+    }
+    Ok(())
+}
+
+/// Which tool brought `interface` up: an active NetworkManager connection on it,
+/// a running wg-quick@ unit, or a config of that name (wg-quick)
+fn interface_manager(
+    interface: &str,
+    nm: &[super::nm::NmConnection],
+    configs: &std::collections::HashSet<String>,
+) -> String {
+    let manager = if nm.iter().any(|c| c.active && c.device.as_deref() == Some(interface)) {
+        MANAGER_NM
+    } else if systemctl_ok(&["is-active", "--quiet", &format!("wg-quick@{}.service", interface)]) {
+        MANAGER_SYSTEMD
+    } else if configs.contains(interface) {
+        MANAGER_WG_QUICK
+    } else {
+        MANAGER_UNKNOWN
+    };
+    manager.to_string()
+}
+
+/// `wg show` through the helper, or interface names from `ip link` without it
+async fn read_status(stale_secs: u64) -> Result<WgStatus> {
     // Use helper to get status
     if let Ok(output) = run_helper(&["status"]).await {
         if output.status.success() {
             let stdout = String::from_utf8_lossy(&output.stdout);
-            if !stdout.trim().is_empty() {
-                return parse_wg_show_output(&stdout);
+            // The epoch section is printed even when no tunnel is up
+            let wg_show = stdout.split(EPOCH_HANDSHAKES_MARKER).next().unwrap_or("");
+            if !wg_show.trim().is_empty() {
+                return parse_wg_show_output(&stdout, stale_secs);
             }
         }
     }
@@ -136,17 +334,23 @@ pub async fn get_status() -> Result<WgStatus> {
     if let Ok(output) = output {
         if output.status.success() {
             let stdout = String::from_utf8_lossy(&output.stdout);
-            if !stdout.trim().is_empty() {
-                for line in stdout.lines() {
-                    if let Some(name) = line.split(':').nth(1) {
-                        let name = name.trim().split('@').next().unwrap_or(name.trim());
-                        return Ok(WgStatus {
-                            connected: true,
-                            interface: Some(name.to_string()),
-                            ..Default::default()
-                        });
-                    }
-                }
+            // Interface lines look like "5: wg0: <POINTOPOINT,...>"; skip the link/ lines
+            let mut found: Vec<WgStatus> = stdout
+                .lines()
+                .filter(|line| !line.starts_with(char::is_whitespace))
+                .filter_map(|line| line.split(':').nth(1))
+                .map(|name| name.trim().split('@').next().unwrap_or("").to_string())
+                .filter(|name| !name.is_empty())
+                .map(|name| WgStatus {
+                    connected: true,
+                    interface: Some(name),
+                    ..Default::default()
+                })
+                .collect();
+            if !found.is_empty() {
+                let mut status = found.remove(0);
+                status.additional = found;
+                return Ok(status);
             }
         }
     }
@@ -154,7 +358,79 @@ pub async fn get_status() -> Result<WgStatus> {
     Ok(WgStatus::default())
 }
 
-fn parse_wg_show_output(stdout: &str) -> Result<WgStatus> {
+/// Marker the helper prints before `wg show all latest-handshakes` output
+const EPOCH_HANDSHAKES_MARKER: &str = "latest-handshakes:";
+
+/// Parse `wg show` output, which has one `interface:` block per active tunnel
+/// The tunnel carrying the default route becomes the primary status
+fn parse_wg_show_output(stdout: &str, stale_secs: u64) -> Result<WgStatus> {
+    // Newer helpers append epoch handshake times; older ones only print `wg show`
+    let (human, epochs, peer_epochs) = match stdout.split_once(EPOCH_HANDSHAKES_MARKER) {
+        Some((human, epochs)) => (human, parse_epoch_handshakes(epochs), parse_peer_epochs(epochs)),
+        None => (stdout, HashMap::new(), HashMap::new()),
+    };
+    let epochs = (&epochs, &peer_epochs);
+
+    let mut blocks: Vec<Vec<&str>> = Vec::new();
+    for line in human.lines() {
+        if line.trim().starts_with("interface:") || blocks.is_empty() {
+            blocks.push(Vec::new());
+        }
+        if let Some(block) = blocks.last_mut() {
+            block.push(line);
+        }
+    }
+
+    let mut all: Vec<WgStatus> = blocks
+        .iter()
+        .map(|block| parse_interface_block(block, stale_secs, epochs))
+        .filter(|status| status.interface.is_some())
+        .collect();
+    if all.is_empty() {
+        return Ok(parse_interface_block(&human.lines().collect::<Vec<_>>(), stale_secs, epochs));
+    }
+
+    let primary = all.iter().position(|s| s.routing_ok).unwrap_or(0);
+    let mut status = all.remove(primary);
+    status.additional = all;
+    Ok(status)
+}
+
+/// Parse `wg show all latest-handshakes` lines ("iface<TAB>peer<TAB>epoch")
+/// into the newest handshake per interface (0 = never)
+fn parse_epoch_handshakes(output: &str) -> HashMap<String, u64> {
+    let mut latest: HashMap<String, u64> = HashMap::new();
+    for line in output.lines() {
+        let fields: Vec<&str> = line.split('\t').collect();
+        if let [iface, _peer, epoch] = fields.as_slice() {
+            if let Ok(epoch) = epoch.trim().parse::<u64>() {
+                let entry = latest.entry(iface.trim().to_string()).or_insert(0);
+                *entry = (*entry).max(epoch);
+            }
+        }
+    }
+    latest
+}
+
+/// Parse `wg show all latest-handshakes` lines into the epoch per peer public key
+fn parse_peer_epochs(output: &str) -> HashMap<String, u64> {
+    output
+        .lines()
+        .filter_map(|line| match line.split('\t').collect::<Vec<_>>().as_slice() {
+            [_iface, peer, epoch] => Some((peer.trim().to_string(), epoch.trim().parse().ok()?)),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Parse a single `interface:` block of `wg show` output
+/// `epochs` holds the newest handshake per interface and the handshake per peer
+fn parse_interface_block(
+    lines: &[&str],
+    stale_secs: u64,
+    epochs: (&HashMap<String, u64>, &HashMap<String, u64>),
+) -> WgStatus {
+    let (iface_epochs, peer_epochs) = epochs;
     let mut status = WgStatus {
         connected: true,
         routing_ok: false,
@@ -163,62 +439,313 @@ fn parse_wg_show_output(stdout: &str) -> Result<WgStatus> {
         ..Default::default()
     };
 
-    for line in stdout.lines() {
+    for line in lines {
         let line = line.trim();
 
-        if line.starts_with("interface:") {
-            status.interface = Some(line.replace("interface:", "").trim().to_string());
-        } else if line.starts_with("endpoint:") {
-            status.endpoint = Some(line.replace("endpoint:", "").trim().to_string());
-        } else if line.starts_with("latest handshake:") {
-            let handshake = line.replace("latest handshake:", "").trim().to_string();
-            status.handshake_stale = is_handshake_stale(&handshake);
-            status.latest_handshake = Some(handshake);
-        } else if line.starts_with("transfer:") {
-            let transfer = line.replace("transfer:", "").trim().to_string();
-            let parts: Vec<&str> = transfer.split(',').collect();
-            if parts.len() >= 2 {
-                status.transfer_rx = Some(parts[0].trim().to_string());
-                status.transfer_tx = Some(parts[1].trim().to_string());
-                // Check if there's been any meaningful traffic
-                status.has_traffic = has_meaningful_traffic(parts[0], parts[1]);
+        if let Some(iface) = line.strip_prefix("interface:") {
+            status.interface = Some(iface.trim().to_string());
+        } else if let Some(key) = line.strip_prefix("peer:") {
+            status.peers.push(PeerStatus {
+                public_key: key.trim().to_string(),
+                handshake_stale: true,
+                ..Default::default()
+            });
+        } else if let Some(peer) = status.peers.last_mut() {
+            if let Some(endpoint) = line.strip_prefix("endpoint:") {
+                peer.endpoint = Some(endpoint.trim().to_string());
+            } else if let Some(allowed) = line.strip_prefix("allowed ips:") {
+                peer.allowed_ips = Some(allowed.trim().to_string());
+            } else if let Some(handshake) = line.strip_prefix("latest handshake:") {
+                let handshake = handshake.trim().to_string();
+                peer.handshake_stale = is_handshake_stale(&handshake, stale_secs);
+                peer.latest_handshake = Some(handshake);
+            } else if let Some(transfer) = line.strip_prefix("transfer:") {
+                if let Some((rx, tx)) = transfer.split_once(',') {
+                    peer.transfer_rx = Some(rx.trim().to_string());
+                    peer.transfer_tx = Some(tx.trim().to_string());
+                }
             }
         }
     }
 
+    // Epoch times are exact and locale-independent, so they win over the text
+    for peer in &mut status.peers {
+        if let Some(&epoch) = peer_epochs.get(&peer.public_key) {
+            peer.handshake_stale = is_epoch_handshake_stale(epoch, now_secs(), stale_secs);
+        }
+    }
+
+    summarize_peers(&mut status);
+
+    // Without epochs, anchor the text age to now so it can keep counting
+    status.handshake_epoch = status.latest_handshake.as_deref()
+        .and_then(parse_handshake_age)
+        .map(|age| now_secs().saturating_sub(age));
+
+    // Helpers that only print per-interface epochs still decide staleness
+    if let Some(&epoch) = status.interface.as_ref().and_then(|iface| iface_epochs.get(iface)) {
+        status.handshake_stale = is_epoch_handshake_stale(epoch, now_secs(), stale_secs);
+        status.handshake_epoch = Some(epoch).filter(|&e| e > 0);
+    }
+
     // Check if routing goes through VPN
     if let Some(ref iface) = status.interface {
         status.routing_ok = check_vpn_routing(iface);
     }
 
-    Ok(status)
+    status
+}
+
+/// Fill the interface-level fields from the peers: the freshest handshake
+/// decides staleness, transfer is summed, and the first endpoint is shown
+fn summarize_peers(status: &mut WgStatus) {
+    status.endpoint = status.peers.iter().find_map(|p| p.endpoint.clone());
+
+    let freshest = status.peers
+        .iter()
+        .filter(|p| p.latest_handshake.is_some())
+        .min_by_key(|p| (p.handshake_stale, p.latest_handshake.as_deref().and_then(parse_handshake_age).unwrap_or(u64::MAX)));
+    if let Some(peer) = freshest {
+        status.latest_handshake = peer.latest_handshake.clone();
+    }
+    status.handshake_stale = status.peers.iter().all(|p| p.handshake_stale);
+
+    match status.peers.as_slice() {
+        [] => {}
+        // Keep wg's own text for the common single-peer case
+        [peer] => {
+            status.transfer_rx = peer.transfer_rx.clone();
+            status.transfer_tx = peer.transfer_tx.clone();
+        }
+        peers => {
+            let total = |f: fn(&PeerStatus) -> Option<&String>| -> u64 {
+                peers.iter().filter_map(f).map(|s| parse_transfer_bytes(s)).sum()
+            };
+            let rx = total(|p| p.transfer_rx.as_ref());
+            let tx = total(|p| p.transfer_tx.as_ref());
+            if peers.iter().any(|p| p.transfer_rx.is_some()) {
+                status.transfer_rx = Some(format!("{} received", format_transfer_bytes(rx)));
+                status.transfer_tx = Some(format!("{} sent", format_transfer_bytes(tx)));
+            }
+        }
+    }
+
+    if let (Some(rx), Some(tx)) = (&status.transfer_rx, &status.transfer_tx) {
+        // Check if there's been any meaningful traffic
+        status.has_traffic = has_meaningful_traffic(rx, tx);
+    }
+}
+
+/// Parse a `wg show` transfer string like "1.23 GiB" or "1.23 GiB received" to bytes
+pub fn parse_transfer_bytes(s: &str) -> u64 {
+    let parts: Vec<&str> = s.split_whitespace().collect();
+    if parts.len() < 2 {
+        return 0;
+    }
+    
+    let value: f64 = parts[0].parse().unwrap_or(0.0);
+    let unit = parts[1].to_lowercase();
+    
+    let multiplier: u64 = match unit.as_str() {
+        "b" => 1,
+        "kib" => 1024,
+        "mib" => 1024 * 1024,
+        "gib" => 1024 * 1024 * 1024,
+        "tib" => 1024 * 1024 * 1024 * 1024,
+        // Also handle SI units
+        "kb" => 1000,
+        "mb" => 1000 * 1000,
+        "gb" => 1000 * 1000 * 1000,
+        "tb" => 1000 * 1000 * 1000 * 1000,
+        _ => 1,
+    };
+    
+    (value * multiplier as f64) as u64
+}
+
+/// Format bytes the way `wg show` does ("1.50 MiB")
+pub fn format_transfer_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.2} {}", value, UNITS[unit])
+}
+
+/// Check an epoch handshake time against the threshold (0 means no handshake yet)
+fn is_epoch_handshake_stale(epoch: u64, now: u64, stale_secs: u64) -> bool {
+    epoch == 0 || now.saturating_sub(epoch) >= stale_secs
+}
+
+/// Fallback for helpers without epoch output: parse the human-readable age
+/// Unparseable strings (e.g. "(none)") count as stale
+fn is_handshake_stale(handshake: &str, stale_secs: u64) -> bool {
+    parse_handshake_age(handshake).is_none_or(|age| age >= stale_secs)
+}
+
+fn now_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
 }
 
-/// Check if handshake is stale (older than 3 minutes)
-fn is_handshake_stale(handshake: &str) -> bool {
+/// Parse a `wg show` handshake string into an age in seconds
+/// e.g. "1 hour, 2 minutes, 3 seconds ago" -> 3723, "Now" -> 0
+pub fn parse_handshake_age(handshake: &str) -> Option<u64> {
     let handshake_lower = handshake.to_lowercase();
     
-    // If it says "hour" or "day", definitely stale
-    if handshake_lower.contains("hour") || handshake_lower.contains("day") {
-        return true;
+    if handshake_lower.trim() == "now" {
+        return Some(0);
     }
     
-    // Parse minutes
-    if handshake_lower.contains("minute") {
-        for part in handshake_lower.split_whitespace() {
-            if let Ok(mins) = part.parse::<u32>() {
-                return mins >= 3;
-            }
+    let mut total: u64 = 0;
+    let mut found = false;
+    let words: Vec<&str> = handshake_lower
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|w| !w.is_empty())
+        .collect();
+    
+    for pair in words.windows(2) {
+        if let Ok(value) = pair[0].parse::<u64>() {
+            let multiplier = match pair[1].trim_end_matches('s') {
+                "second" => 1,
+                "minute" => 60,
+                "hour" => 3600,
+                "day" => 86400,
+                "week" => 604800,
+                "year" => 31536000,
+                _ => continue,
+            };
+            total += value * multiplier;
+            found = true;
         }
     }
     
-    // If it's only seconds, it's fresh
-    if handshake_lower.contains("second") && !handshake_lower.contains("minute") {
-        return false;
+    found.then_some(total)
+}
+
+/// Quick round-trip probe (single ping, 1s timeout) for status output
+/// Returns the latency reported by ping in milliseconds
+pub fn probe_latency() -> Option<u32> {
+    let targets = crate::network::ProbeTargets::load();
+    let host = targets.v4.first().or(targets.v6.first())?;
+    ping_ms(host, None)
+}
+
+/// Single ping (1s timeout), optionally pinned to an interface
+/// Returns the latency reported by ping in milliseconds
+pub fn ping_ms(host: &str, via: Option<&str>) -> Option<u32> {
+    let mut cmd = Command::new("ping");
+    cmd.args(["-c", "1", "-W", "1"]);
+    if let Some(iface) = via {
+        cmd.args(["-I", iface]);
+    }
+    let output = cmd.arg(host).output().ok()?;
+    
+    if !output.status.success() {
+        return None;
     }
     
-    // Default to stale if we can't parse
-    true
+    // Format: "64 bytes from 1.1.1.1: icmp_seq=1 ttl=57 time=12.3 ms"
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    stdout
+        .split_whitespace()
+        .find_map(|w| w.strip_prefix("time="))
+        .and_then(|t| t.parse::<f64>().ok())
+        .map(|ms| ms.round() as u32)
+}
+
+/// First peer `Endpoint` in a config, split into host and port (brackets removed)
+pub fn config_endpoint(config: &str) -> Option<(String, u16)> {
+    let mut in_peer = false;
+    for line in config.lines().map(str::trim) {
+        if line.starts_with('[') {
+            in_peer = line.eq_ignore_ascii_case("[Peer]");
+        } else if let Some((key, value)) = line.split_once('=') {
+            if in_peer && key.trim().eq_ignore_ascii_case("Endpoint") {
+                let (host, port) = value.trim().rsplit_once(':')?;
+                let host = host.trim_start_matches('[').trim_end_matches(']');
+                return Some((host.to_string(), port.parse().ok()?));
+            }
+        }
+    }
+    None
+}
+
+/// Whether every [Peer] has a PresharedKey (None for a config without peers)
+pub fn config_uses_psk(config: &str) -> Option<bool> {
+    let mut in_peer = false;
+    let (mut peers, mut with_psk) = (0, 0);
+    for line in config.lines().map(str::trim) {
+        if line.starts_with('[') {
+            in_peer = line.eq_ignore_ascii_case("[Peer]");
+            peers += usize::from(in_peer);
+        } else if let Some((key, value)) = line.split_once('=') {
+            if in_peer && key.trim().eq_ignore_ascii_case("PresharedKey") && !value.trim().is_empty() {
+                with_psk += 1;
+            }
+        }
+    }
+    (peers > 0).then_some(with_psk >= peers)
+}
+
+/// Failover endpoints of the first peer that lists `# Endpoint-Alt = host:port` comments
+/// Returns the peer's public key and its endpoints, the configured `Endpoint` first
+pub fn failover_endpoints(config: &str) -> Option<(String, Vec<String>)> {
+    let mut peers: Vec<(Option<String>, Vec<String>, Vec<String>)> = Vec::new();
+    for line in config.lines().map(str::trim) {
+        if line.starts_with('[') {
+            if line.eq_ignore_ascii_case("[Peer]") {
+                peers.push((None, Vec::new(), Vec::new()));
+            }
+            continue;
+        }
+        let Some((public_key, primary, alternates)) = peers.last_mut() else {
+            continue;
+        };
+        let (key, value) = match line.strip_prefix('#') {
+            Some(comment) => match comment.split_once('=') {
+                Some((key, value)) if key.trim().eq_ignore_ascii_case("Endpoint-Alt") => (key, value),
+                _ => continue,
+            },
+            None => match line.split_once('=') {
+                Some(pair) => pair,
+                None => continue,
+            },
+        };
+        let value = value.trim().to_string();
+        match key.trim().to_ascii_lowercase().as_str() {
+            "publickey" => *public_key = Some(value),
+            "endpoint" => primary.push(value),
+            "endpoint-alt" if !value.is_empty() => alternates.push(value),
+            _ => {}
+        }
+    }
+
+    peers.into_iter().find_map(|(public_key, mut endpoints, alternates)| {
+        if alternates.is_empty() {
+            return None;
+        }
+        endpoints.extend(alternates);
+        Some((public_key?, endpoints))
+    })
+}
+
+/// Point a connected peer at another endpoint without reconnecting
+pub async fn set_endpoint(interface: &str, public_key: &str, endpoint: &str) -> Result<()> {
+    let output = run_helper(&["set-endpoint", interface, public_key, endpoint]).await?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("Setting endpoint failed: {}", stderr.trim());
+    }
+    Ok(())
 }
 
 /// Check if there's been meaningful traffic (not just handshake bytes)
@@ -250,45 +777,112 @@ fn has_meaningful_traffic(rx: &str, tx: &str) -> bool {
 /// Check if the default route goes through the VPN interface
 fn check_vpn_routing(vpn_interface: &str) -> bool {
     // Check default route
-    let output = Command::new("ip")
-        .args(["route", "show", "default"])
-        .output();
-    
-    if let Ok(output) = output {
-        if output.status.success() {
-            let stdout = String::from_utf8_lossy(&output.stdout);
-            if stdout.contains(vpn_interface) {
-                return true;
-            }
-        }
+    if ip_output(&["route", "show", "default"]).is_some_and(|routes| routes.contains(vpn_interface)) {
+        return true;
     }
-    
+
     // Also check for WireGuard's split default routes (0.0.0.0/1 and 128.0.0.0/1)
-    let output = Command::new("ip")
-        .args(["route", "show"])
-        .output();
-    
-    if let Ok(output) = output {
-        if output.status.success() {
-            let stdout = String::from_utf8_lossy(&output.stdout);
-            for line in stdout.lines() {
-                if line.contains(vpn_interface) && 
-                   (line.starts_with("0.0.0.0/1") || line.starts_with("128.0.0.0/1") || line.starts_with("default")) {
-                    return true;
-                }
-            }
+    // and wg-quick's default route in its own table (51820, chosen by `ip rule`)
+    ip_output(&["route", "show", "table", "all"])
+        .is_some_and(|routes| routes.lines().any(|line| is_vpn_default_route(line, vpn_interface)))
+}
+
+/// Route line sending default traffic (or one of its /1 halves) through `iface`
+fn is_vpn_default_route(line: &str, iface: &str) -> bool {
+    line.contains(iface)
+        && (line.starts_with("0.0.0.0/1") || line.starts_with("128.0.0.0/1") || line.starts_with("default"))
+}
+
+/// True if a route line goes out through `iface` ("... dev wg0 ...")
+pub fn route_uses_interface(line: &str, iface: &str) -> bool {
+    let mut words = line.split_whitespace();
+    while let Some(word) = words.next() {
+        if word == "dev" && words.next() == Some(iface) {
+            return true;
+        }
+    }
+    false
+}
+
+/// Everything but the kernel's local/broadcast entries, which every machine has
+fn shown_route(line: &str) -> bool {
+    let kind = line.split_whitespace().next().unwrap_or("");
+    !matches!(kind, "local" | "broadcast" | "multicast") && !line.contains(" table local")
+}
+
+/// A rule sending traffic to a table other than the standard ones (e.g. wg-quick's 51820)
+pub fn rule_uses_custom_table(line: &str) -> bool {
+    let mut words = line.split_whitespace();
+    while let Some(word) = words.next() {
+        if word == "lookup" || word == "table" {
+            return !matches!(words.next(), Some("main" | "local" | "default"));
         }
     }
-    
     false
 }
 
+/// stdout of `ip <args>`, None if it failed
+fn ip_output(args: &[&str]) -> Option<String> {
+    let output = Command::new("ip").args(args).output().ok()?;
+    output.status.success().then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Routing tables, policy rules and VPN interface addresses, as `ip` prints them
+#[derive(Debug, Clone, Default)]
+pub struct RoutingInfo {
+    pub routes: Vec<String>,                    // `ip route show table all`, without the local table
+    pub rules: Vec<String>,                     // `ip rule show` (wg-quick full tunnels route via table 51820)
+    pub addresses: Vec<(String, Vec<String>)>,  // `ip addr show dev <iface>` per VPN interface
+}
+
+/// Read the routes, rules and the given interfaces' addresses (blocking, runs `ip`)
+pub fn routing_info(interfaces: &[String]) -> RoutingInfo {
+    let lines = |output: Option<String>| -> Vec<String> {
+        output.map(|o| o.lines().map(str::to_string).collect()).unwrap_or_default()
+    };
+    RoutingInfo {
+        routes: lines(ip_output(&["route", "show", "table", "all"])).into_iter().filter(|r| shown_route(r)).collect(),
+        rules: lines(ip_output(&["rule", "show"])),
+        addresses: interfaces.iter()
+            .map(|iface| (iface.clone(), lines(ip_output(&["addr", "show", "dev", iface]))))
+            .collect(),
+    }
+}
+
 /// Connect to a WireGuard profile using the secure helper
 pub async fn connect(profile_name: &str) -> Result<()> {
+    // Refuse a broken config before taking the current tunnel down
+    log_preflight_warnings(profile_name, preflight(profile_name).await?);
+    connect_checked(profile_name).await
+}
+
+/// `connect` for a profile the caller already ran `preflight` on
+pub async fn connect_checked(profile_name: &str) -> Result<()> {
     // First disconnect any existing connection
     let _ = disconnect().await;
 
-    let output = run_helper(&["connect", profile_name]).await
+    bring_up(profile_name).await
+}
+
+/// Bring up a profile alongside any tunnels that are already up
+pub async fn connect_additional(profile_name: &str) -> Result<()> {
+    log_preflight_warnings(profile_name, preflight(profile_name).await?);
+    bring_up(profile_name).await
+}
+
+/// Run wg-quick up via the helper (or nmcli for NetworkManager connections)
+/// Other tunnels are left alone and no preflight runs - callers check first
+pub async fn bring_up(profile_name: &str) -> Result<()> {
+    if nm_connection(profile_name).await.is_some() {
+        return super::nm::up(profile_name).await;
+    }
+
+    // The helper copies user-directory configs somewhere root-owned before wg-quick reads them
+    let output = match user_config_path(profile_name) {
+        Some(path) => run_helper(&["connect-file", &path.to_string_lossy()]).await,
+        None if use_systemd(profile_name) => run_helper(&["connect-systemd", profile_name]).await,
+        None => run_helper(&["connect", profile_name]).await,
+    }
         .context("Failed to execute connect")?;
 
     if !output.status.success() {
@@ -299,10 +893,144 @@ pub async fn connect(profile_name: &str) -> Result<()> {
     Ok(())
 }
 
-/// Disconnect from current WireGuard connection
+/// Whether to start the profile as wg-quick@<name>.service (per-tunnel setting, then global)
+/// Only for /etc/wireguard configs - the unit can't read a user `config_dir`
+fn use_systemd(profile_name: &str) -> bool {
+    let config = AppConfig::load().unwrap_or_default();
+    let backend = config.known_tunnels.iter()
+        .find(|t| t.name == profile_name)
+        .and_then(|t| t.backend)
+        .unwrap_or(config.connect_backend);
+    let unit = format!("wg-quick@{}.service", profile_name);
+
+    match backend {
+        ConnectBackend::WgQuick => false,
+        // An enabled or running unit means systemd already manages this tunnel
+        ConnectBackend::Auto => ["is-enabled", "is-active"].iter().any(|check| systemctl_ok(&[check, "--quiet", &unit])),
+        ConnectBackend::Systemd => {
+            let available = systemctl_ok(&["cat", "wg-quick@.service"]);
+            if !available {
+                tracing::warn!("wg-quick@.service not found - using wg-quick for {}", profile_name);
+            }
+            available
+        }
+    }
+}
+
+/// Run an unprivileged systemctl query; false if it fails or systemd is absent
+fn systemctl_ok(args: &[&str]) -> bool {
+    Command::new("systemctl")
+        .args(args)
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .status()
+        .is_ok_and(|s| s.success())
+}
+
+/// Disconnect every active WireGuard connection
+/// NetworkManager connections are only taken down when they run one of those interfaces
 pub async fn disconnect() -> Result<()> {
-    // Helper will auto-detect the active interface
-    match run_helper(&["disconnect"]).await {
+    let status = get_tunnels().await.unwrap_or_default();
+    let interfaces: Vec<String> = status
+        .all_interfaces()
+        .filter_map(|s| s.interface.clone())
+        .collect();
+
+    if interfaces.is_empty() {
+        // Helper will auto-detect the active interface
+        run_disconnect(&["disconnect"]).await;
+    }
+    // NetworkManager owns its tunnels - wg-quick down would fail on them
+    let nm = if interfaces.is_empty() { Vec::new() } else { super::nm::list_connections().await };
+    for iface in &interfaces {
+        match nm.iter().find(|c| c.active && c.device.as_deref() == Some(iface.as_str())) {
+            Some(conn) => {
+                if let Err(e) = super::nm::down(&conn.name).await {
+                    tracing::warn!("{:#}", e);
+                }
+            }
+            None => run_disconnect(&["disconnect", iface]).await,
+        }
+    }
+
+    Ok(())
+}
+
+/// The NetworkManager connection behind `name`, unless a wg-quick config of that name exists
+async fn nm_connection(name: &str) -> Option<super::nm::NmConnection> {
+    let conn = super::nm::find_connection(name).await?;
+    let has_config = config_names().await.is_some_and(|names| names.contains(name));
+    (!has_config).then_some(conn)
+}
+
+/// The user-writable `config_dir` from config.toml, when one is set
+fn user_config_dir() -> Option<std::path::PathBuf> {
+    AppConfig::load().ok()?.user_config_dir()
+}
+
+/// Path of a profile in the user's `config_dir` (None when configs live in /etc/wireguard)
+pub fn user_config_path(profile_name: &str) -> Option<std::path::PathBuf> {
+    user_config_dir().map(|dir| dir.join(format!("{}.conf", profile_name)))
+}
+
+/// Profile names with a config file, or None if the directory can't be read
+async fn config_names() -> Option<std::collections::HashSet<String>> {
+    if let Some(dir) = user_config_dir() {
+        let entries = std::fs::read_dir(&dir).ok()?;
+        return Some(entries
+            .filter_map(|e| e.ok())
+            .filter_map(|e| e.file_name().to_str()?.strip_suffix(".conf").map(String::from))
+            .collect());
+    }
+
+    let output = run_helper(&["config-list"]).await.ok().filter(|o| o.status.success())?;
+    Some(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(String::from)
+        .collect())
+}
+
+/// Interface a connected profile runs on: the device NetworkManager reports,
+/// else the wg-quick interface named after the config
+pub async fn profile_interface(profile_name: &str) -> String {
+    match nm_connection(profile_name).await.and_then(|c| c.device) {
+        Some(device) => device,
+        None => sanitize_profile_name(profile_name),
+    }
+}
+
+/// Disconnect a single interface (or the tunnel of that name), leaving any others up
+pub async fn disconnect_interface(interface: &str) -> Result<()> {
+    if nm_connection(interface).await.is_some_and(|c| c.active) {
+        return super::nm::down(interface).await;
+    }
+    // A NetworkManager connection running on a device named differently
+    let on_device = super::nm::list_connections().await
+        .into_iter()
+        .find(|c| c.active && c.device.as_deref() == Some(interface));
+    if let Some(conn) = on_device {
+        return super::nm::down(&conn.name).await;
+    }
+    run_disconnect(&["disconnect", interface]).await;
+    Ok(())
+}
+
+/// Re-set each peer's endpoint so the kernel drops its cached source address
+/// Used after roaming to another interface without a reconnect
+pub async fn rebind(interface: &str) -> Result<()> {
+    let output = run_helper(&["rebind", interface]).await?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("Rebind failed: {}", stderr.trim());
+    }
+    Ok(())
+}
+
+/// Run the helper's disconnect command, logging (not returning) failures
+async fn run_disconnect(args: &[&str]) {
+    match run_helper(args).await {
         Ok(output) => {
             if !output.status.success() {
                 let stderr = String::from_utf8_lossy(&output.stderr);
@@ -313,50 +1041,506 @@ pub async fn disconnect() -> Result<()> {
             tracing::warn!("Disconnect command failed: {}", e);
         }
     }
+}
+
+/// Check a profile for problems wg-quick would only report cryptically
+/// Errors on missing or non-executable hook scripts; returns warnings
+/// (e.g. AllowedIPs swallowing the local LAN) that don't block connecting
+pub async fn preflight(profile_name: &str) -> Result<Vec<String>> {
+    // Can't read it (old helper, permissions) - let wg-quick have its say
+    let Ok(config) = read_config(profile_name).await else {
+        return Ok(Vec::new());
+    };
+
+    check_hook_scripts(&config, profile_name)?;
+    let mut warnings = lan_overlap_warnings(&config, &local_ipv4_addrs());
+    if config_uses_psk(&config) == Some(false) && crate::config::AppConfig::load().is_ok_and(|c| c.require_psk) {
+        warnings.push("not every peer has a PresharedKey (require_psk)".to_string());
+    }
+    Ok(warnings)
+}
+
+fn log_preflight_warnings(profile_name: &str, warnings: Vec<String>) {
+    for warning in warnings {
+        tracing::warn!("{}: {}", profile_name, warning);
+    }
+}
+
+/// Verify that absolute script paths in PreUp/PostUp/PreDown/PostDown exist
+/// Paths we aren't allowed to stat (e.g. inside /etc/wireguard) are skipped
+fn check_hook_scripts(config: &str, interface: &str) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    const HOOKS: [&str; 4] = ["PreUp", "PostUp", "PreDown", "PostDown"];
+    const SHELLS: [&str; 6] = ["sh", "bash", "zsh", "/bin/sh", "/bin/bash", "/usr/bin/bash"];
+
+    for line in config.lines() {
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let Some(hook) = HOOKS.iter().find(|h| h.eq_ignore_ascii_case(key.trim())) else {
+            continue;
+        };
+
+        // wg-quick substitutes %i with the interface name
+        let value = value.replace("%i", interface);
+        for command in value.split([';', '&', '|']) {
+            let mut words = command.split_whitespace();
+            let Some(first) = words.next() else {
+                continue;
+            };
+            // "bash /path/script" only needs the script to exist
+            let (path, needs_exec) = if SHELLS.contains(&first) {
+                match words.find(|w| !w.starts_with('-')) {
+                    Some(script) => (script, false),
+                    None => continue,
+                }
+            } else {
+                (first, true)
+            };
+            if !path.starts_with('/') {
+                continue;
+            }
+
+            match std::fs::metadata(path) {
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                    anyhow::bail!("{} script {} not found", hook, path);
+                }
+                Ok(meta) if needs_exec && meta.permissions().mode() & 0o111 == 0 => {
+                    anyhow::bail!("{} script {} is not executable", hook, path);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Warn when an AllowedIPs range covers one of this machine's LAN addresses
+/// 0.0.0.0/0 is fine: wg-quick keeps the directly connected LAN routes
+pub fn lan_overlap_warnings(config: &str, local_addrs: &[std::net::Ipv4Addr]) -> Vec<String> {
+    let mut warnings = Vec::new();
+
+    for cidr in allowed_ips(config) {
+        let Some((net, prefix)) = cidr.split_once('/') else {
+            continue;
+        };
+        let (Ok(net), Ok(prefix)) = (net.parse::<std::net::Ipv4Addr>(), prefix.parse::<u32>()) else {
+            continue;
+        };
+        if prefix == 0 || prefix > 32 {
+            continue;
+        }
+
+        let mask = u32::MAX << (32 - prefix);
+        if let Some(local) = local_addrs.iter().find(|a| u32::from(**a) & mask == u32::from(net) & mask) {
+            warnings.push(format!(
+                "AllowedIPs {} covers your LAN address {} - local devices may be unreachable",
+                cidr, local
+            ));
+        }
+    }
+
+    warnings
+}
+
+/// Every AllowedIPs entry in a config, across all peers
+pub fn allowed_ips(config: &str) -> Vec<String> {
+    config
+        .lines()
+        .filter_map(|line| line.split_once('='))
+        .filter(|(key, _)| key.trim().eq_ignore_ascii_case("AllowedIPs"))
+        .flat_map(|(_, value)| value.split(','))
+        .map(|cidr| cidr.trim().to_string())
+        .filter(|cidr| !cidr.is_empty())
+        .collect()
+}
+
+/// What a tunnel's config sends through it, independent of the current routes
+#[derive(Debug, Clone, PartialEq)]
+pub enum TunnelScope {
+    Full(Vec<String>),  // The default routes it claims (0.0.0.0/0, ::/0)
+    Split(usize),       // Number of specific subnets
+}
+
+impl TunnelScope {
+    /// Read the scope from a config's AllowedIPs; None if it has none
+    pub fn from_config(config: &str) -> Option<Self> {
+        let cidrs = allowed_ips(config);
+        if cidrs.is_empty() {
+            return None;
+        }
+        let defaults: Vec<String> = cidrs
+            .iter()
+            .filter(|c| *c == "0.0.0.0/0" || *c == "::/0")
+            .cloned()
+            .collect();
+        Some(if defaults.is_empty() {
+            TunnelScope::Split(cidrs.len())
+        } else {
+            TunnelScope::Full(defaults)
+        })
+    }
+
+    pub fn is_full(&self) -> bool {
+        matches!(self, TunnelScope::Full(_))
+    }
+
+    /// "Full tunnel (0.0.0.0/0)" or "Split tunnel (3 subnets)"
+    pub fn label(&self) -> String {
+        match self {
+            TunnelScope::Full(defaults) => format!("Full tunnel ({})", defaults.join(", ")),
+            TunnelScope::Split(1) => "Split tunnel (1 subnet)".to_string(),
+            TunnelScope::Split(n) => format!("Split tunnel ({} subnets)", n),
+        }
+    }
+}
+
+/// IPv4 addresses on physical interfaces (not loopback or WireGuard)
+pub fn local_ipv4_addrs() -> Vec<std::net::Ipv4Addr> {
+    let Ok(output) = Command::new("ip").args(["-4", "-o", "addr", "show"]).output() else {
+        return Vec::new();
+    };
+
+    // Lines look like "2: wlan0    inet 192.168.1.23/24 brd ... scope global wlan0"
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter(|line| !line.contains(" lo ") && !line.contains(" wg"))
+        .filter_map(|line| {
+            let mut words = line.split_whitespace();
+            words.find(|w| *w == "inet")?;
+            words.next()?.split('/').next()?.parse().ok()
+        })
+        .collect()
+}
+
+/// Read a profile's config from /etc/wireguard via the helper (or straight from `config_dir`)
+pub async fn read_config(profile_name: &str) -> Result<String> {
+    if let Some(path) = user_config_path(profile_name) {
+        return std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()));
+    }
+
+    let output = run_helper(&["config-read", profile_name]).await
+        .context("Failed to read config")?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("Failed to read {}: {}", profile_name, stderr.trim());
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// When a profile's config was last modified (Unix seconds)
+/// None if unknown, e.g. NetworkManager connections or a helper without config-mtime
+pub async fn config_mtime(profile_name: &str) -> Option<u64> {
+    if let Some(path) = user_config_path(profile_name) {
+        let modified = std::fs::metadata(&path).and_then(|meta| meta.modified()).ok()?;
+        return modified.duration_since(std::time::UNIX_EPOCH).ok().map(|d| d.as_secs());
+    }
+
+    let output = run_helper(&["config-mtime", profile_name]).await.ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8_lossy(&output.stdout).trim().parse().ok()
+}
+
+/// Point an interface at specific DNS servers via the helper
+/// (systemd-resolved when running, resolvconf otherwise)
+pub async fn set_dns(interface: &str, servers: &[String]) -> Result<()> {
+    let mut args = vec!["dns-set", interface];
+    args.extend(servers.iter().map(String::as_str));
+    let output = run_helper(&args).await
+        .context("Failed to execute dns-set")?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("Failed to set DNS on {}: {}", interface, stderr.trim());
+    }
+
+    Ok(())
+}
+
+/// Drop DNS servers previously pushed with set_dns
+/// `tunnel_dns` (the config's `DNS =`) goes back on the interface while it is still up,
+/// so lookups don't fall back to the LAN's resolver
+pub async fn revert_dns(interface: &str, tunnel_dns: &[std::net::IpAddr]) -> Result<()> {
+    let servers: Vec<String> = tunnel_dns.iter().map(|ip| ip.to_string()).collect();
+    let mut args = vec!["dns-revert", interface];
+    args.extend(servers.iter().map(String::as_str));
+    let output = run_helper(&args).await
+        .context("Failed to execute dns-revert")?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("Failed to restore DNS on {}: {}", interface, stderr.trim());
+    }
+
+    Ok(())
+}
+
+/// Check that a config has the fields wg-quick needs and that keys are well-formed
+/// Errors name the offending line so the user can find it
+pub fn validate_wg_config(content: &str) -> Result<()> {
+    let mut section = "";
+    let mut interface_count = 0;
+    let mut has_private_key = false;
+    let mut peers = 0;
+    let mut peers_with_key = 0;
+    let mut has_endpoint = false;
+
+    for (index, raw) in content.lines().enumerate() {
+        let line_no = index + 1;
+        let line = raw.split('#').next().unwrap_or("").trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        if line.starts_with('[') {
+            section = if line.eq_ignore_ascii_case("[Interface]") {
+                interface_count += 1;
+                "Interface"
+            } else if line.eq_ignore_ascii_case("[Peer]") {
+                peers += 1;
+                "Peer"
+            } else {
+                anyhow::bail!("Line {}: unknown section {}", line_no, line);
+            };
+            continue;
+        }
+
+        let Some((key, value)) = line.split_once('=') else {
+            anyhow::bail!("Line {}: expected 'Key = Value'", line_no);
+        };
+        let key = key.trim();
+        // Base64 values end in '=', so split_once leaves the padding on the value
+        let value = value.trim();
+
+        match (section, key.to_ascii_lowercase().as_str()) {
+            ("", _) => anyhow::bail!("Line {}: {} appears before any section", line_no, key),
+            ("Interface", "privatekey") => {
+                check_wg_key(value).with_context(|| format!("Line {}: invalid PrivateKey", line_no))?;
+                has_private_key = true;
+            }
+            ("Peer", "publickey") => {
+                check_wg_key(value).with_context(|| format!("Line {}: invalid PublicKey", line_no))?;
+                peers_with_key += 1;
+            }
+            ("Peer", "presharedkey") => {
+                check_wg_key(value).with_context(|| format!("Line {}: invalid PresharedKey", line_no))?;
+            }
+            ("Peer", "endpoint") => {
+                if value.is_empty() {
+                    anyhow::bail!("Line {}: Endpoint is empty", line_no);
+                }
+                has_endpoint = true;
+            }
+            ("Interface", "address" | "dns") | ("Peer", "allowedips") => {
+                if let Some(problem) = check_config_value(key, value) {
+                    anyhow::bail!("Line {}: invalid {} - {}", line_no, key, problem);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if interface_count != 1 {
+        anyhow::bail!("Config must have exactly one [Interface] section");
+    }
+    if !has_private_key {
+        anyhow::bail!("[Interface] is missing PrivateKey");
+    }
+    if peers == 0 {
+        anyhow::bail!("Config has no [Peer] section");
+    }
+    if peers_with_key < peers {
+        anyhow::bail!("Every [Peer] needs a PublicKey");
+    }
+    if !has_endpoint {
+        anyhow::bail!("[Peer] is missing Endpoint");
+    }
+    Ok(())
+}
 
+/// A WireGuard key is 32 bytes, base64-encoded: 43 characters plus one '=' pad
+fn check_wg_key(key: &str) -> Result<()> {
+    let valid = key.len() == 44
+        && key
+            .strip_suffix('=')
+            .is_some_and(|body| body.chars().all(|c| c.is_ascii_alphanumeric() || c == '+' || c == '/'));
+    if !valid {
+        anyhow::bail!("expected a 44-character base64 key, got {} characters", key.len());
+    }
     Ok(())
 }
 
+/// What's wrong with an Address, DNS or AllowedIPs value, None if it looks fine
+/// Other keys aren't checked
+pub fn check_config_value(key: &str, value: &str) -> Option<&'static str> {
+    let mut items = value.split(',').map(str::trim);
+    match key.to_ascii_lowercase().as_str() {
+        // A bare address means a single host (/32 or /128)
+        "address" | "allowedips" => (!items.all(|item| {
+            item.parse::<std::net::IpAddr>().is_ok() || crate::config::is_valid_cidr(item)
+        }))
+        .then_some("expected addresses like 10.0.0.2/32"),
+        // Entries that aren't IPs become search domains
+        "dns" => (!items.all(crate::config::is_valid_host)).then_some("expected servers like 1.1.1.1"),
+        _ => None,
+    }
+}
+
+/// Skeleton shown in the empty manual config editor
+pub const CONFIG_TEMPLATE: &str = "\
+[Interface]
+PrivateKey = <F3 generates one>
+Address = <tunnel IP from your provider, e.g. 10.0.0.2/32>
+DNS = <optional, e.g. 1.1.1.1>
+
+[Peer]
+PublicKey = <server's public key>
+Endpoint = <host:port>
+AllowedIPs = <0.0.0.0/0, ::/0 sends everything through the tunnel>";
+
+/// Generate a fresh (private, public) keypair with `wg genkey` / `wg pubkey`
+/// Unprivileged - neither command touches the system
+pub fn generate_keypair() -> Result<(String, String)> {
+    use std::io::Write;
+    use std::process::Stdio;
+
+    let genkey = Command::new("wg").arg("genkey").output().map_err(|e| {
+        if e.kind() == std::io::ErrorKind::NotFound {
+            anyhow::anyhow!("wg not found - install wireguard-tools")
+        } else {
+            anyhow::anyhow!("Failed to run wg genkey: {}", e)
+        }
+    })?;
+    if !genkey.status.success() {
+        anyhow::bail!("wg genkey failed: {}", String::from_utf8_lossy(&genkey.stderr).trim());
+    }
+    let private_key = String::from_utf8_lossy(&genkey.stdout).trim().to_string();
+
+    let mut child = Command::new("wg")
+        .arg("pubkey")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("Failed to run wg pubkey")?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(private_key.as_bytes())?;
+    }
+    let pubkey = child.wait_with_output()?;
+    if !pubkey.status.success() {
+        anyhow::bail!("wg pubkey failed: {}", String::from_utf8_lossy(&pubkey.stderr).trim());
+    }
+    let public_key = String::from_utf8_lossy(&pubkey.stdout).trim().to_string();
+
+    check_wg_key(&private_key)?;
+    check_wg_key(&public_key)?;
+    Ok((private_key, public_key))
+}
+
+/// Set `PrivateKey = <key>` in the [Interface] section, replacing an existing one
+/// Starts an [Interface] section when the config doesn't have one yet
+pub fn set_private_key(config: &str, key: &str) -> String {
+    let line = format!("PrivateKey = {}", key);
+    let mut lines: Vec<String> = Vec::new();
+    let mut in_interface = false;
+    let mut done = false;
+
+    for existing in config.lines() {
+        let trimmed = existing.trim();
+        if trimmed.starts_with('[') {
+            in_interface = trimmed.eq_ignore_ascii_case("[Interface]");
+            lines.push(existing.to_string());
+            // First line of the section, so the key is easy to spot
+            if in_interface && !done {
+                lines.push(line.clone());
+                done = true;
+            }
+        } else if in_interface
+            && trimmed.split_once('=').is_some_and(|(k, _)| k.trim().eq_ignore_ascii_case("PrivateKey"))
+        {
+            continue;  // Replaced by the line added under the header
+        } else {
+            lines.push(existing.to_string());
+        }
+    }
+    if !done {
+        lines.insert(0, line);
+        lines.insert(0, "[Interface]".to_string());
+    }
+
+    let mut out = lines.join("\n");
+    out.push('\n');
+    out
+}
+
+/// Interface name as it will be saved: alphanumerics, '-' and '_' only
+pub fn sanitize_profile_name(name: &str) -> String {
+    name.chars()
+        .filter(|c| c.is_alphanumeric() || *c == '-' || *c == '_')
+        .collect()
+}
+
+/// Whether saving `name` would replace an existing tunnel
+/// (remembered in config.toml or present in /etc/wireguard)
+pub async fn profile_exists(name: &str) -> bool {
+    let safe_name = sanitize_profile_name(name);
+    let config = crate::config::AppConfig::load().unwrap_or_default();
+    if config.known_tunnels.iter().any(|t| t.name == safe_name) {
+        return true;
+    }
+    list_profiles()
+        .await
+        .map(|profiles| profiles.iter().any(|p| p.name == safe_name))
+        .unwrap_or(false)
+}
+
 /// Add a new WireGuard profile and save to our config
+/// An existing profile with the same name is replaced; callers confirm first
 pub async fn add_profile(name: &str, config_content: &str) -> Result<()> {
     // Sanitize the name (helper also validates, but we do it here too)
-    let safe_name: String = name
-        .chars()
-        .filter(|c| c.is_alphanumeric() || *c == '-' || *c == '_')
-        .collect();
+    let safe_name = sanitize_profile_name(name);
 
     if safe_name.is_empty() {
         anyhow::bail!("Invalid profile name");
     }
 
     // Validate the config
-    if !config_content.contains("[Interface]") || !config_content.contains("[Peer]") {
-        anyhow::bail!("Invalid WireGuard config: missing [Interface] or [Peer] section");
-    }
+    validate_wg_config(config_content)?;
 
-    // Write config using helper
-    let output = run_helper_with_stdin(&["config-write", &safe_name], config_content).await
-        .context("Failed to write config")?;
+    // Write config directly into the user's config_dir, or using the helper
+    if let Some(dir) = user_config_dir() {
+        std::os::unix::fs::DirBuilderExt::mode(std::fs::DirBuilder::new().recursive(true), 0o700)
+            .create(&dir)
+            .with_context(|| format!("Cannot create {}", dir.display()))?;
+        super::export::write_private_file(&dir.join(format!("{}.conf", safe_name)), config_content.as_bytes())?;
+    } else {
+        let output = run_helper_with_stdin(&["config-write", &safe_name], config_content).await
+            .context("Failed to write config")?;
 
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        anyhow::bail!("Failed to save profile: {}", stderr);
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            anyhow::bail!("Failed to save profile: {}", stderr);
+        }
     }
 
     // Save to our config so we remember it
     let mut config = crate::config::AppConfig::load().unwrap_or_default();
     
-    // Preserve kill_switch setting if tunnel existed
-    let existing_ks = config.known_tunnels.iter()
-        .find(|t| t.name == safe_name)
-        .map(|t| t.kill_switch)
-        .unwrap_or(false);
-    config.known_tunnels.retain(|t| t.name != safe_name);
-    config.known_tunnels.push(crate::config::TunnelInfo {
-        name: safe_name.clone(),
-        protocol: "wireguard".to_string(),
-        kill_switch: existing_ks,
-    });
+    // Keep existing per-tunnel settings (kill switch, transfer totals) on re-import
+    if !config.known_tunnels.iter().any(|t| t.name == safe_name) {
+        config.known_tunnels.push(crate::config::TunnelInfo {
+            name: safe_name.clone(),
+            protocol: "wireguard".to_string(),
+            ..Default::default()
+        });
+    }
     config.save()?;
 
     tracing::info!("Created WireGuard profile: {}", safe_name);
@@ -366,17 +1550,27 @@ pub async fn add_profile(name: &str, config_content: &str) -> Result<()> {
 /// Delete a WireGuard profile
 pub async fn delete_profile(name: &str) -> Result<()> {
     // Disconnect if connected
-    let status = get_status().await.unwrap_or_default();
+    let status = get_tunnels().await.unwrap_or_default();
     if status.interface.as_deref() == Some(name) {
         let _ = disconnect().await;
     }
 
-    let output = run_helper(&["config-delete", name]).await
-        .context("Failed to delete WireGuard config")?;
+    if nm_connection(name).await.is_some() {
+        super::nm::delete(name).await?;
+    } else if let Some(path) = user_config_path(name) {
+        if let Err(e) = std::fs::remove_file(&path) {
+            if e.kind() != std::io::ErrorKind::NotFound {
+                return Err(e).with_context(|| format!("Failed to delete {}", path.display()));
+            }
+        }
+    } else {
+        let output = run_helper(&["config-delete", name]).await
+            .context("Failed to delete WireGuard config")?;
 
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        anyhow::bail!("Failed to delete profile: {}", stderr);
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            anyhow::bail!("Failed to delete profile: {}", stderr);
+        }
     }
 
     // Remove from our config
@@ -388,14 +1582,25 @@ pub async fn delete_profile(name: &str) -> Result<()> {
 }
 
 /// Extended health check result
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct VpnHealthCheck {
     pub interface_exists: bool,
     pub has_peer: bool,
     pub handshake_recent: bool,
     pub routing_configured: bool,
-    pub can_reach_internet: bool,
+    pub can_reach_internet: bool,  // Either address family works
+    #[serde(default)]
+    pub has_internet_v4: bool,
+    #[serde(default)]
+    pub has_internet_v6: bool,
     pub latency_ms: Option<u32>,
+    pub dns_ok: bool,            // System resolver answers queries
+    #[serde(default)]
+    pub resolved_via_vpn: Option<bool>,  // Resolver uses the tunnel's DNS servers, None = can't tell
+    #[serde(default)]
+    pub endpoint_changed: bool,  // An Endpoint hostname now resolves to a different IP
+    #[serde(default)]
+    pub leak_detected: bool,     // Internet reachable past the kill switch (`leak_check`)
 }
 
 impl VpnHealthCheck {
@@ -413,63 +1618,257 @@ impl VpnHealthCheck {
             && self.has_peer 
             && (!self.handshake_recent || !self.routing_configured)
     }
+
+    /// "IPv4 only" / "IPv6 only" when the tunnel carries just one address family
+    pub fn family_label(&self) -> Option<&'static str> {
+        crate::network::family_label(self.has_internet_v4, self.has_internet_v6)
+    }
 }
 
 /// Perform a comprehensive health check on the VPN connection
-pub async fn health_check() -> VpnHealthCheck {
-    let mut result = VpnHealthCheck::default();
-    
-    // Get current status
-    let status = get_status().await.unwrap_or_default();
+/// `status` is a fresh `get_status`, which callers already have at hand
+pub async fn health_check(status: &WgStatus) -> VpnHealthCheck {
+    let mut result = VpnHealthCheck {
+        leak_detected: detect_leak(status.interface.as_deref().filter(|_| status.connected)).await,
+        ..Default::default()
+    };
     
     if !status.connected {
         return result;
     }
     
     result.interface_exists = true;
-    result.has_peer = status.endpoint.is_some();
+    result.has_peer = status.peers.iter().any(|p| p.endpoint.is_some());
     result.handshake_recent = !status.handshake_stale;
     result.routing_configured = status.routing_ok;
     
-    // Try to reach the internet through the VPN
-    let start = std::time::Instant::now();
-    
-    // Use ping to 1.1.1.1 with a short timeout
-    if let Ok(output) = Command::new("ping")
-        .args(["-c", "1", "-W", "3", "1.1.1.1"])
-        .output()
-    {
-        if output.status.success() {
-            result.can_reach_internet = true;
-            result.latency_ms = Some(start.elapsed().as_millis() as u32);
-        }
-    }
+    // Try to reach the internet through the VPN over IPv4 and IPv6
+    // Ping the first configured host of each family with a short timeout
+    let targets = crate::network::ProbeTargets::load();
+    let v4_latency = crate::network::ping_any(targets.first_v4(), 3);
+    let v6_latency = crate::network::ping_any(targets.first_v6(), 3);
+    result.has_internet_v4 = v4_latency.is_some();
+    result.has_internet_v6 = v6_latency.is_some();
+    result.latency_ms = v4_latency.or(v6_latency);
     
     // If ping failed, try curl as fallback (ICMP might be blocked)
-    if !result.can_reach_internet {
+    for (family, reachable) in [("-4", result.has_internet_v4), ("-6", result.has_internet_v6)] {
+        if reachable {
+            continue;
+        }
+        let start = std::time::Instant::now();
         if let Ok(output) = Command::new("curl")
             .args([
+                family,
                 "-s", "-o", "/dev/null",
                 "-w", "%{http_code}",
                 "--connect-timeout", "3",
                 "--max-time", "5",
-                "http://detectportal.firefox.com/success.txt"
+                &targets.http_url,
             ])
             .output()
         {
             if output.status.success() {
                 let response = String::from_utf8_lossy(&output.stdout);
                 if response.starts_with("200") || response.starts_with("204") {
-                    result.can_reach_internet = true;
-                    result.latency_ms = Some(start.elapsed().as_millis() as u32);
+                    if family == "-4" {
+                        result.has_internet_v4 = true;
+                    } else {
+                        result.has_internet_v6 = true;
+                    }
+                    result.latency_ms.get_or_insert(start.elapsed().as_millis() as u32);
                 }
             }
         }
     }
+    result.can_reach_internet = result.has_internet_v4 || result.has_internet_v6;
+    
+    if let Some(ref iface) = status.interface {
+        let config = read_config(iface).await.unwrap_or_default();
+
+        // DNS leak check - compare the active resolvers with the tunnel's DNS line
+        result.dns_ok = dns_resolves().await;
+        let tunnel_dns = parse_dns_servers(&config);
+        // No DNS line or no readable resolver state is no evidence of a leak
+        result.resolved_via_vpn = match system_nameservers(iface) {
+            Some(active) if !tunnel_dns.is_empty() && !active.is_empty() => {
+                Some(active.iter().all(|ns| tunnel_dns.contains(ns)))
+            }
+            _ => None,
+        };
+
+        result.endpoint_changed = endpoint_drifted(&config, &status.peers).await;
+    }
     
     result
 }
 
+/// With the kill switch on and `leak_check` set, fetch the connectivity URL bound to the
+/// physical interface; any HTTP answer means traffic gets past the kill switch
+/// Takes up to 5s
+pub async fn detect_leak(vpn_interface: Option<&str>) -> bool {
+    let config = AppConfig::load().unwrap_or_default();
+    if !(config.kill_switch && config.leak_check) {
+        return false;
+    }
+    let Some(via) = crate::network::default_route_interface(vpn_interface) else {
+        return false;
+    };
+    let url = crate::network::ProbeTargets::load().http_url;
+
+    let Ok(output) = tokio::process::Command::new("curl")
+        .args([
+            "-s", "-o", "/dev/null",
+            "-w", "%{http_code}",
+            "--interface", &via,
+            "--connect-timeout", "3",
+            "--max-time", "5",
+            &url,
+        ])
+        .output()
+        .await
+    else {
+        return false;
+    };
+    // curl prints 000 when nothing answered
+    let code = String::from_utf8_lossy(&output.stdout);
+    let leaked = !code.is_empty() && code != "000";
+    if leaked {
+        tracing::warn!("Leak: {} answered over {} with the kill switch on (HTTP {})", url, via, code);
+    }
+    leaked
+}
+
+/// True when a peer's Endpoint hostname now resolves somewhere other than the address in use
+/// wg resolves the hostname once at connect time, so dynamic-DNS moves go unnoticed
+async fn endpoint_drifted(config: &str, peers: &[PeerStatus]) -> bool {
+    for (public_key, host, port) in hostname_endpoints(config) {
+        let Some(current) = peers.iter()
+            .find(|p| p.public_key == public_key)
+            .and_then(|p| p.endpoint.as_deref())
+            .and_then(|e| e.parse::<std::net::SocketAddr>().ok())
+        else {
+            continue;
+        };
+        // Unresolvable right now (e.g. DNS only works through the tunnel) - no verdict
+        let Ok(resolved) = tokio::net::lookup_host((host.as_str(), port)).await else {
+            continue;
+        };
+        let resolved: Vec<std::net::IpAddr> = resolved.map(|addr| addr.ip()).collect();
+        if !resolved.is_empty() && !resolved.contains(&current.ip()) {
+            tracing::info!("Endpoint {} moved: {} no longer among {:?}", host, current.ip(), resolved);
+            return true;
+        }
+    }
+    false
+}
+
+/// (PublicKey, host, port) of every [Peer] whose Endpoint is a hostname rather than an IP
+fn hostname_endpoints(config: &str) -> Vec<(String, String, u16)> {
+    let mut found = Vec::new();
+    let mut peer: Option<(Option<String>, Option<String>)> = None;
+
+    let mut finish = |peer: Option<(Option<String>, Option<String>)>| {
+        if let Some((Some(key), Some(endpoint))) = peer {
+            let Some((host, port)) = endpoint.rsplit_once(':') else { return };
+            let host = host.trim_start_matches('[').trim_end_matches(']');
+            if let (Ok(port), Err(_)) = (port.parse::<u16>(), host.parse::<std::net::IpAddr>()) {
+                found.push((key, host.to_string(), port));
+            }
+        }
+    };
+
+    for line in config.lines().map(str::trim) {
+        if line.starts_with('[') {
+            finish(peer.take());
+            if line.eq_ignore_ascii_case("[Peer]") {
+                peer = Some((None, None));
+            }
+        } else if let (Some((key, value)), Some(current)) = (line.split_once('='), peer.as_mut()) {
+            let value = value.trim().to_string();
+            match key.trim() {
+                k if k.eq_ignore_ascii_case("PublicKey") => current.0 = Some(value),
+                k if k.eq_ignore_ascii_case("Endpoint") => current.1 = Some(value),
+                _ => {}
+            }
+        }
+    }
+    finish(peer);
+    found
+}
+
+/// Check that the system resolver can look up a well-known hostname
+async fn dns_resolves() -> bool {
+    let lookup = tokio::task::spawn_blocking(|| {
+        use std::net::ToSocketAddrs;
+        ("one.one.one.one", 443)
+            .to_socket_addrs()
+            .map(|mut addrs| addrs.next().is_some())
+            .unwrap_or(false)
+    });
+    matches!(
+        tokio::time::timeout(std::time::Duration::from_secs(3), lookup).await,
+        Ok(Ok(true))
+    )
+}
+
+/// Extract nameserver addresses from a config's `DNS =` line(s)
+/// Search domains share the same line and are skipped
+pub fn parse_dns_servers(config: &str) -> Vec<std::net::IpAddr> {
+    config
+        .lines()
+        .filter_map(|line| {
+            let (key, value) = line.split_once('=')?;
+            key.trim().eq_ignore_ascii_case("DNS").then_some(value)
+        })
+        .flat_map(|value| value.split(','))
+        .filter_map(|entry| entry.trim().parse().ok())
+        .collect()
+}
+
+/// Parse resolver addresses, ignoring DoT server names ("1.1.1.1#cloudflare-dns.com")
+/// and IPv6 zone ids
+fn parse_nameserver_tokens<'a>(tokens: impl Iterator<Item = &'a str>) -> Vec<std::net::IpAddr> {
+    tokens
+        .filter_map(|token| {
+            let addr = token.split('#').next()?;
+            addr.split('%').next()?.parse().ok()
+        })
+        .collect()
+}
+
+/// Nameservers the system is using for lookups while the tunnel is up
+/// With the systemd-resolved stub this is the tunnel link's own servers
+/// None when resolvectl can't tell
+fn system_nameservers(interface: &str) -> Option<Vec<std::net::IpAddr>> {
+    let resolv_conf = std::fs::read_to_string("/etc/resolv.conf").unwrap_or_default();
+    let nameservers = parse_nameserver_tokens(
+        resolv_conf
+            .lines()
+            .filter_map(|line| line.trim().strip_prefix("nameserver")),
+    );
+
+    if !nameservers.iter().any(|ns| ns.is_loopback()) {
+        return Some(nameservers);
+    }
+
+    // Local stub resolver - ask resolved which servers the tunnel link uses
+    // Output: "Link 5 (wg0): 10.2.0.1 10.2.0.2"
+    Command::new("resolvectl")
+        .args(["dns", interface])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| {
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            stdout
+                .lines()
+                .filter_map(|line| line.split_once("):").map(|(_, servers)| servers.to_string()))
+                .flat_map(|servers| parse_nameserver_tokens(servers.split_whitespace()))
+                .collect()
+        })
+}
+
 /// Get the uptime of a network interface in seconds
 /// Reads the modification time of /sys/class/net/<interface>/uevent which corresponds
 /// to when the interface was created
@@ -483,15 +1882,15 @@ pub fn get_interface_uptime(interface: &str) -> Option<u64> {
 
 /// Quick check if VPN interface exists and has recent handshake
 #[allow(dead_code)]
-pub async fn is_alive() -> bool {
-    let status = get_status().await.unwrap_or_default();
+pub async fn is_alive(config: &AppConfig) -> bool {
+    let status = get_status(config).await.unwrap_or_default();
     status.connected && !status.handshake_stale
 }
 
 /// Force a handshake refresh by sending a ping through the tunnel
 #[allow(dead_code)]
 pub async fn refresh_connection() -> Result<()> {
-    let status = get_status().await?;
+    let status = get_tunnels().await?;
     
     if !status.connected {
         anyhow::bail!("VPN not connected");
@@ -506,10 +1905,262 @@ pub async fn refresh_connection() -> Result<()> {
         }
     }
     
-    // Also ping a public IP to force handshake if needed
-    let _ = Command::new("ping")
-        .args(["-c", "1", "-W", "2", "1.1.1.1"])
-        .output();
+    // Also ping a public host to force handshake if needed
+    let targets = crate::network::ProbeTargets::load();
+    if let Some(host) = targets.v4.first().or(targets.v6.first()) {
+        let _ = Command::new("ping")
+            .args(["-c", "1", "-W", "2", host])
+            .output();
+    }
     
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_handshake_age() {
+        assert_eq!(parse_handshake_age("Now"), Some(0));
+        assert_eq!(parse_handshake_age("45 seconds ago"), Some(45));
+        assert_eq!(parse_handshake_age("1 minute, 23 seconds ago"), Some(83));
+        assert_eq!(parse_handshake_age("2 hours, 1 minute, 5 seconds ago"), Some(7265));
+        assert_eq!(parse_handshake_age("1 day, 3 hours ago"), Some(97200));
+        assert_eq!(parse_handshake_age("(none)"), None);
+    }
+
+    #[test]
+    fn test_parse_multiple_interfaces() {
+        let output = "interface: wg-full\n  public key: abc=\n\npeer: def=\n  endpoint: 1.2.3.4:51820\n  transfer: 1.00 MiB received, 2.00 MiB sent\n\ninterface: wg-split\n  public key: ghi=\n\npeer: jkl=\n  endpoint: 5.6.7.8:51820\n";
+        let status = parse_wg_show_output(output, DEFAULT_HANDSHAKE_STALE_SECS).unwrap();
+        let names: Vec<&str> = status.all_interfaces().filter_map(|s| s.interface.as_deref()).collect();
+        assert_eq!(names, vec!["wg-full", "wg-split"]);
+        assert_eq!(status.transfer_rx.as_deref(), Some("1.00 MiB received"));
+        assert_eq!(status.additional[0].endpoint.as_deref(), Some("5.6.7.8:51820"));
+        assert!(status.is_up("wg-split"));
+        assert!(!status.is_up("wg-other"));
+    }
+
+    #[test]
+    fn test_route_matching() {
+        assert!(is_vpn_default_route("0.0.0.0/1 dev wg0 scope link", "wg0"));
+        assert!(!is_vpn_default_route("10.0.0.0/24 dev wg0 scope link", "wg0"));
+        assert!(route_uses_interface("10.0.0.0/24 dev wg0 proto kernel", "wg0"));
+        assert!(route_uses_interface("default via 10.0.0.1 dev wg0", "wg0"));
+        assert!(!route_uses_interface("default via 192.168.1.1 dev wg0-backup", "wg0"));
+        assert!(!route_uses_interface("default via 192.168.1.1 dev wlan0", "wg0"));
+        assert!(shown_route("default dev wg0 table 51820 scope link"));
+        assert!(!shown_route("local 10.0.0.2 dev wg0 table local proto kernel scope host src 10.0.0.2"));
+        assert!(!shown_route("broadcast 192.168.1.255 dev wlan0 table local proto kernel scope link"));
+        assert!(rule_uses_custom_table("32765:\tnot from all fwmark 0xca6c lookup 51820"));
+        assert!(!rule_uses_custom_table("32764:\tfrom all lookup main suppress_prefixlength 0"));
+    }
+
+    #[test]
+    fn test_resolve_profile_name() {
+        let names: Vec<String> = ["work-office-wg", "work-lab", "home", "Home-backup"]
+            .iter().map(|n| n.to_string()).collect();
+        assert_eq!(resolve_profile_name("home", &names).unwrap(), "home");
+        assert_eq!(resolve_profile_name("work-o", &names).unwrap(), "work-office-wg");
+        assert_eq!(resolve_profile_name("backup", &names).unwrap(), "Home-backup");
+        assert_eq!(resolve_profile_name("wow", &names).unwrap(), "work-office-wg");
+        let ambiguous = resolve_profile_name("work", &names).unwrap_err().to_string();
+        assert!(ambiguous.contains("work-office-wg, work-lab"));
+        assert!(resolve_profile_name("mullvad", &names).is_err());
+    }
+
+    #[test]
+    fn test_handshake_staleness() {
+        // Epoch form: newest peer wins, 0 means never
+        let epochs = parse_epoch_handshakes("wg0\tpeerA=\t1700000000\nwg0\tpeerB=\t1700000100\nwg1\tpeerC=\t0\n");
+        assert_eq!(epochs.get("wg0"), Some(&1700000100));
+        assert!(!is_epoch_handshake_stale(1700000100, 1700000200, 180));
+        assert!(is_epoch_handshake_stale(1700000100, 1700000280, 180));
+        assert!(is_epoch_handshake_stale(epochs["wg1"], 1700000200, 180));
+
+        // Text fallback uses the same threshold
+        assert!(!is_handshake_stale("2 minutes, 59 seconds ago", 180));
+        assert!(is_handshake_stale("3 minutes ago", 180));
+        assert!(!is_handshake_stale("4 minutes ago", 300));
+        assert!(is_handshake_stale("(none)", 180));
+
+        // Epoch section overrides the text when both are present
+        let epoch = now_secs() - 5;
+        let output = format!(
+            "interface: wg0\n\npeer: abc=\n  latest handshake: 1 day ago\n{}\nwg0\tabc=\t{}\n",
+            EPOCH_HANDSHAKES_MARKER,
+            epoch
+        );
+        let status = parse_wg_show_output(&output, 180).unwrap();
+        assert!(!status.handshake_stale);
+        assert_eq!(status.handshake_epoch, Some(epoch));
+    }
+
+    #[test]
+    fn test_parse_multiple_peers() {
+        let output = "interface: hub
+  public key: abc=
+
+peer: a=
+  endpoint: 1.2.3.4:51820
+  allowed ips: 10.0.0.2/32
+  latest handshake: 10 minutes ago
+  transfer: 1.00 MiB received, 512 B sent
+
+peer: b=
+  allowed ips: 10.0.0.3/32
+  latest handshake: 30 seconds ago
+  transfer: 1.00 MiB received, 512 B sent
+";
+        let status = parse_wg_show_output(output, 180).unwrap();
+        assert_eq!(status.peers.len(), 2);
+        assert_eq!(status.peers[1].allowed_ips.as_deref(), Some("10.0.0.3/32"));
+        assert!(status.peers[0].handshake_stale);
+        // One fresh peer keeps the interface up
+        assert!(!status.handshake_stale);
+        assert_eq!(status.latest_handshake.as_deref(), Some("30 seconds ago"));
+        assert_eq!(status.endpoint.as_deref(), Some("1.2.3.4:51820"));
+        assert_eq!(status.transfer_rx.as_deref(), Some("2.00 MiB received"));
+        assert_eq!(status.transfer_tx.as_deref(), Some("1.00 KiB sent"));
+    }
+
+    #[test]
+    fn test_preflight_checks() {
+        let dir = std::env::temp_dir().join(format!("tonneru-preflight-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let script = dir.join("up.sh");
+        std::fs::write(&script, "#!/bin/sh\n").unwrap();
+        let script = script.display().to_string();
+
+        // Exists but not executable: fine via "bash", an error when run directly
+        assert!(check_hook_scripts(&format!("PostUp = bash {script}\n"), "wg0").is_ok());
+        let err = check_hook_scripts(&format!("PostUp = {script}\n"), "wg0").unwrap_err();
+        assert!(err.to_string().contains("not executable"));
+
+        let missing = format!("PostUp = iptables -A FORWARD -i %i -j ACCEPT; {}/%i.sh\n", dir.display());
+        let err = check_hook_scripts(&missing, "wg0").unwrap_err();
+        assert_eq!(err.to_string(), format!("PostUp script {}/wg0.sh not found", dir.display()));
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let lan = ["192.168.1.23".parse().unwrap()];
+        assert!(lan_overlap_warnings("AllowedIPs = 0.0.0.0/0, ::/0\n", &lan).is_empty());
+
+        // Scope comes from the config, whatever the routes say
+        let scope = |config: &str| TunnelScope::from_config(config).map(|s| s.label());
+        assert_eq!(scope("AllowedIPs = 0.0.0.0/0, ::/0\n").as_deref(), Some("Full tunnel (0.0.0.0/0, ::/0)"));
+        assert_eq!(
+            scope("[Peer]\nAllowedIPs = 10.0.0.0/8, 172.16.0.0/12\n[Peer]\nallowedips=192.168.5.0/24\n").as_deref(),
+            Some("Split tunnel (3 subnets)")
+        );
+        assert_eq!(scope("[Interface]\nAddress = 10.0.0.2/32\n"), None);
+        assert!(lan_overlap_warnings("AllowedIPs = 10.0.0.0/8\n", &lan).is_empty());
+        assert_eq!(lan_overlap_warnings("AllowedIPs = 10.0.0.0/8, 192.168.0.0/16\n", &lan).len(), 1);
+    }
+
+    #[test]
+    fn test_parse_dns_servers() {
+        let config = "[Interface]\nAddress = 10.2.0.2/32\nDNS = 10.2.0.1, fd00::1, corp.example\n\n[Peer]\nEndpoint = 1.2.3.4:51820\n";
+        assert_eq!(
+            parse_dns_servers(config),
+            vec!["10.2.0.1".parse::<std::net::IpAddr>().unwrap(), "fd00::1".parse().unwrap()]
+        );
+        assert!(parse_dns_servers("[Interface]\nAddress = 10.2.0.2/32\n").is_empty());
+    }
+
+    #[test]
+    fn test_hostname_endpoints() {
+        let config = "[Interface]\nAddress = 10.2.0.2/32\n\n\
+                      [Peer]\nPublicKey = AAA=\nEndpoint = vpn.example.com:51820\n\n\
+                      [Peer]\nPublicKey = BBB=\nEndpoint = 1.2.3.4:51820\n\n\
+                      [Peer]\nEndpoint = [2001:db8::1]:51820\nPublicKey = CCC=\n";
+        assert_eq!(
+            hostname_endpoints(config),
+            vec![("AAA=".to_string(), "vpn.example.com".to_string(), 51820)]
+        );
+    }
+
+    #[test]
+    fn test_config_endpoint() {
+        let config = "[Interface]\nPrivateKey = x\n\n[Peer]\nPublicKey = y\nEndpoint = vpn.example.com:51820\n";
+        assert_eq!(config_endpoint(config), Some(("vpn.example.com".to_string(), 51820)));
+        assert_eq!(config_endpoint("[Peer]\nEndpoint = [2001:db8::1]:443\n"), Some(("2001:db8::1".to_string(), 443)));
+        assert_eq!(config_endpoint("[Interface]\nEndpoint = 1.2.3.4:1\n"), None);
+    }
+
+    #[test]
+    fn test_config_uses_psk() {
+        let peer = "[Peer]\nPublicKey = y\nPresharedKey = z\n";
+        assert_eq!(config_uses_psk(&format!("[Interface]\nPrivateKey = x\n{}", peer)), Some(true));
+        // One peer without a PSK is enough to count as missing
+        assert_eq!(config_uses_psk(&format!("{}[Peer]\nPublicKey = w\n", peer)), Some(false));
+        assert_eq!(config_uses_psk("[Interface]\nPrivateKey = x\n"), None);
+    }
+
+    #[test]
+    fn test_failover_endpoints() {
+        let config = "[Interface]\nPrivateKey = x\n\n[Peer]\nPublicKey = abc=\nEndpoint = se1.example.com:51820\n\
+                      # Endpoint-Alt = se2.example.com:51820\n#Endpoint-Alt=[2001:db8::1]:51820\n";
+        assert_eq!(failover_endpoints(config), Some(("abc=".to_string(), vec![
+            "se1.example.com:51820".to_string(),
+            "se2.example.com:51820".to_string(),
+            "[2001:db8::1]:51820".to_string(),
+        ])));
+        assert_eq!(failover_endpoints("[Peer]\nPublicKey = abc=\nEndpoint = a:1\n# Other = b:2\n"), None);
+    }
+
+    #[test]
+    fn test_is_foreign_manager() {
+        assert!(!is_foreign_manager(ProfileSource::WgQuick, MANAGER_WG_QUICK));
+        assert!(!is_foreign_manager(ProfileSource::WgQuick, MANAGER_SYSTEMD));
+        assert!(is_foreign_manager(ProfileSource::WgQuick, MANAGER_NM));
+        assert!(is_foreign_manager(ProfileSource::WgQuick, MANAGER_UNKNOWN));
+        assert!(!is_foreign_manager(ProfileSource::NetworkManager, MANAGER_NM));
+        assert!(is_foreign_manager(ProfileSource::NetworkManager, MANAGER_SYSTEMD));
+    }
+
+    #[test]
+    fn test_set_private_key() {
+        let key = "a".repeat(43) + "=";
+        assert_eq!(set_private_key("", &key), format!("[Interface]\nPrivateKey = {}\n", key));
+
+        let config = "[Interface]\nAddress = 10.0.0.2/32\nPrivateKey = old=\n\n[Peer]\nPublicKey = y\n";
+        assert_eq!(
+            set_private_key(config, &key),
+            format!("[Interface]\nPrivateKey = {}\nAddress = 10.0.0.2/32\n\n[Peer]\nPublicKey = y\n", key)
+        );
+    }
+
+    #[test]
+    fn test_validate_wg_config() {
+        let key = "yAnz5TF+lXXJte14tji3zlMNq+hd2rYUIgJBgB3fBmk=";
+        let valid = format!(
+            "[Interface]\nPrivateKey = {key}\nAddress = 10.2.0.2/32\n\n[Peer]\nPublicKey = {key}\nEndpoint = 1.2.3.4:51820\nAllowedIPs = 0.0.0.0/0\n"
+        );
+        assert!(validate_wg_config(&valid).is_ok());
+
+        let missing_key = valid.replace(&format!("PrivateKey = {key}\n"), "");
+        assert!(validate_wg_config(&missing_key).is_err());
+
+        let short_key = valid.replacen(key, "abc=", 1);
+        let err = validate_wg_config(&short_key).unwrap_err();
+        assert!(err.to_string().contains("Line 2"));
+
+        let no_endpoint = valid.replace("Endpoint = 1.2.3.4:51820\n", "");
+        assert!(validate_wg_config(&no_endpoint).is_err());
+
+        let bad_dns = valid.replace("Address = 10.2.0.2/32\n", "Address = 10.2.0.2/32\nDNS = 10.2.0.1; 1.1.1.1\n");
+        assert!(validate_wg_config(&bad_dns).unwrap_err().to_string().contains("Line 4"));
+    }
+
+    #[test]
+    fn test_check_config_value() {
+        assert_eq!(check_config_value("Address", "10.2.0.2/32, fd00::2/128"), None);
+        assert_eq!(check_config_value("AllowedIPs", "0.0.0.0/0,::/0"), None);
+        assert_eq!(check_config_value("DNS", "10.2.0.1, corp.example.com"), None);
+        assert!(check_config_value("Address", "10.2.0.2/33").is_some());
+        assert!(check_config_value("AllowedIPs", "").is_some());
+        assert!(check_config_value("DNS", "1.1.1.1 8.8.8.8").is_some());
+        assert_eq!(check_config_value("MTU", "abc"), None);
+    }
+}